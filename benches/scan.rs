@@ -0,0 +1,162 @@
+//! Guardrail benchmarks for the per-scan hot paths.
+//!
+//! `scan_for_suspicious_processes` and `calculate_overall_risk` run against a
+//! synthetic `ProcessSource`, so their numbers are deterministic and comparable
+//! across machines. `get_all_processes` and `find_hidden_overlays` hit the real
+//! OS and are only meaningful when compared on the same host.
+//!
+//! Run with `cargo bench --bench scan`. Baseline numbers are recorded in the
+//! README under "Benchmarks".
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fairview::config::Config;
+use fairview::hardware_detector::HardwareSuspicion;
+use fairview::overlay_detector::OverlayDetector;
+use fairview::process_monitor::{ProcessCapabilities, ProcessMonitor, ProcessSource};
+use fairview::vm_detector::VmCheckResult;
+use fairview::{FairviewDetector, OverlayWindow, Process, ReasonCode, SuspiciousProcess};
+use std::collections::HashMap;
+
+struct SyntheticProcessSource {
+    processes: Vec<Process>,
+}
+
+impl SyntheticProcessSource {
+    /// Mix of benign workers, whitelisted browsers, and a suspicious tool every
+    /// 25th process so every branch of the scoring gate gets exercised.
+    fn new(count: usize) -> Self {
+        let processes = (0..count)
+            .map(|i| {
+                let name = match i % 25 {
+                    0 => format!("interview-ai-{}.exe", i),
+                    1..=4 => "chrome.exe".to_string(),
+                    _ => format!("worker-{}.exe", i),
+                };
+                Process {
+                    pid: 10_000 + i as u32,
+                    path: format!("C:\\Users\\candidate\\AppData\\Local\\{}", name),
+                    name,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        Self { processes }
+    }
+}
+
+impl ProcessSource for SyntheticProcessSource {
+    fn processes(&self) -> Vec<Process> {
+        self.processes.clone()
+    }
+
+    fn capabilities(&self, process: &Process) -> ProcessCapabilities {
+        ProcessCapabilities {
+            screen_capture: process.pid % 3 == 1,
+            audio_capture: process.pid % 5 == 2,
+            accessibility: process.pid % 7 == 3,
+            screen_capture_hint: process.pid % 4 == 1,
+            ..Default::default()
+        }
+    }
+}
+
+fn bench_scan_for_suspicious_processes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scan_for_suspicious_processes");
+    for size in [100, 500, 2000] {
+        let detector = FairviewDetector::with_process_source(
+            Config::default(),
+            Box::new(SyntheticProcessSource::new(size)),
+        );
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| black_box(detector.scan_for_suspicious_processes()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_calculate_overall_risk(c: &mut Criterion) {
+    let detector = FairviewDetector::with_process_source(
+        Config::default(),
+        Box::new(SyntheticProcessSource::new(0)),
+    );
+    let processes: Vec<SuspiciousProcess> = (0..50)
+        .map(|i| SuspiciousProcess {
+            pid: i,
+            name: format!("tool-{}.exe", i),
+            path: "C:\\tools".to_string(),
+            risk_score: (i as f64 % 10.0) / 10.0,
+            reasons: vec!["Suspicious process name".to_string()],
+            reason_codes: vec![ReasonCode::SuspiciousName],
+            started_during_interview: i % 2 == 0,
+            is_whitelisted: false,
+            drive_type: Default::default(),
+        })
+        .collect();
+    let overlays = vec![OverlayWindow {
+        id: String::new(),
+        handle: 1,
+        position: (0, 0),
+        size: (800, 600),
+        owner_pid: 3,
+        is_transparent: true,
+        is_topmost: true,
+        is_cloaked: false,
+        excluded_from_capture: false,
+        title: String::new(),
+        class_name: String::new(),
+        owner_flagged: false,
+        alpha: None,
+        risk_score: 0.0,
+        first_seen_scan: 0,
+    }];
+    let hardware = HardwareSuspicion {
+        risk_score: 0.45,
+        flags: vec!["Multiple displays detected: 2 displays".to_string()],
+        details: HashMap::new(),
+        remote_desktop: Default::default(),
+        cast_sessions: Vec::new(),
+    };
+    let vm = VmCheckResult {
+        is_vm: true,
+        reasons: vec!["CPUID hypervisor bit set".to_string()],
+        confidence_score: 0.9,
+        raw_confidence_score: 0.9,
+    };
+
+    c.bench_function("calculate_overall_risk", |b| {
+        b.iter(|| {
+            black_box(detector.calculate_overall_risk(
+                &processes,
+                &overlays,
+                1.0,
+                Some(&hardware),
+                Some(&vm),
+                0.0,
+            ))
+        })
+    });
+}
+
+fn bench_system_sources(c: &mut Criterion) {
+    let monitor = ProcessMonitor::new(Config::default());
+    // Listing reads the shared snapshot, so refresh it as a scan would.
+    c.bench_function("get_all_processes/system", |b| {
+        b.iter(|| {
+            fairview::snapshot::shared().refresh();
+            black_box(monitor.get_all_processes())
+        })
+    });
+
+    let overlay_detector = OverlayDetector::new();
+    c.bench_function("find_hidden_overlays/system", |b| {
+        b.iter(|| black_box(overlay_detector.find_hidden_overlays()))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_scan_for_suspicious_processes,
+    bench_calculate_overall_risk,
+    bench_system_sources
+);
+criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,216 @@
+use crate::{DetectionReport, ReasonCode, Severity, SuspiciousProcess};
+
+pub const DEFAULT_ALERT_LINE_MAX_CHARS: usize = 160;
+
+/// Shortest budget `output.alert_line_max_chars` accepts; enough for the
+/// severity, scan number and risk.
+pub const MIN_ALERT_LINE_CHARS: usize = 40;
+
+/// Builds a one-line summary of a scan for SMS or chat, e.g.
+/// `[HIGH] Scan 12: risk 0.78 — interview-ai.exe (screen+audio), VM detected`.
+///
+/// Offenders are listed highest risk first. When the line would exceed
+/// `max_chars`, trailing offenders are folded into `+N more`; the other
+/// findings are always kept.
+pub fn format_alert_line(report: &DetectionReport, max_chars: usize) -> String {
+    let mut head = format!(
+        "[{}] Scan {}: risk {:.2}",
+        severity_label(report.severity),
+        report.scan_number,
+        report.overall_risk_score
+    );
+    if report.observational {
+        head.push_str(" (observe)");
+    }
+
+    let mut offenders: Vec<_> = report.suspicious_processes.iter().collect();
+    offenders.sort_by(|a, b| b.risk_score.partial_cmp(&a.risk_score).unwrap_or(std::cmp::Ordering::Equal));
+    let offenders: Vec<String> = offenders.into_iter().map(offender).collect();
+    let total_offenders = offenders.len() + report.omitted_processes;
+    let findings = other_findings(report);
+
+    if offenders.is_empty() && findings.is_empty() {
+        return fit(format!("{} — no findings", head), max_chars);
+    }
+
+    for shown in (0..=offenders.len()).rev() {
+        let mut parts: Vec<String> = offenders[..shown].to_vec();
+        if shown < total_offenders {
+            parts.push(format!("+{} more", total_offenders - shown));
+        }
+        parts.extend(findings.iter().cloned());
+
+        let line = format!("{} — {}", head, parts.join(", "));
+        if line.chars().count() <= max_chars {
+            return line;
+        }
+    }
+
+    // Not even the findings fit; cut the shortest candidate.
+    let mut parts = Vec::new();
+    if total_offenders > 0 {
+        parts.push(format!("{} processes", total_offenders));
+    }
+    parts.extend(findings);
+    fit(format!("{} — {}", head, parts.join(", ")), max_chars)
+}
+
+fn offender(process: &SuspiciousProcess) -> String {
+    let tags: Vec<&str> = process.reason_codes.iter().filter_map(|code| tag(*code)).collect();
+    if tags.is_empty() {
+        process.name.clone()
+    } else {
+        format!("{} ({})", process.name, tags.join("+"))
+    }
+}
+
+fn tag(code: ReasonCode) -> Option<&'static str> {
+    match code {
+        ReasonCode::ScreenCapture => Some("screen"),
+        ReasonCode::AudioCapture => Some("audio"),
+        ReasonCode::Accessibility => Some("a11y"),
+        ReasonCode::SuspiciousName => Some("name"),
+        ReasonCode::StartedDuringInterview => Some("new"),
+        ReasonCode::CaptureExcludedWindow => Some("hidden window"),
+        ReasonCode::OwnsHiddenOverlay => Some("overlay"),
+        ReasonCode::LocalBrowserProxy => Some("proxy"),
+        ReasonCode::ParkedOnInactiveDesktop => Some("other desktop"),
+        ReasonCode::FocusStealing => Some("focus"),
+        ReasonCode::CorrelatedAcrossScans => Some("correlated"),
+        ReasonCode::CustomRule => Some("rule"),
+        ReasonCode::ExternalDrive => Some("external drive"),
+        ReasonCode::UnexpectedParent => Some("automated launch"),
+        ReasonCode::IdeDuringBehavioralInterview => Some("ide"),
+        ReasonCode::PermissionGrantedDuringInterview => Some("new permission"),
+        ReasonCode::SystemNameImpersonation => Some("impersonation"),
+        ReasonCode::FreshExecutable => Some("fresh binary"),
+        ReasonCode::Denylisted => Some("denylisted"),
+        ReasonCode::ScreenCaptureHint
+        | ReasonCode::QuietHelper
+        | ReasonCode::UnknownPath
+        | ReasonCode::GuiObjectOutlier => None,
+    }
+}
+
+fn other_findings(report: &DetectionReport) -> Vec<String> {
+    let mut findings = Vec::new();
+
+    if report.vm_detection.as_ref().is_some_and(|vm| vm.is_vm) {
+        findings.push("VM detected".to_string());
+    }
+    let overlays = report.hidden_overlays.len() + report.omitted_overlays;
+    if overlays > 0 {
+        findings.push(format!("{} overlay{}", overlays, if overlays == 1 { "" } else { "s" }));
+    }
+    if report.audio_monitoring_detected {
+        findings.push("audio monitoring".to_string());
+    }
+    if let Some(ref hardware) = report.hardware_suspicion {
+        if hardware.remote_desktop.active {
+            match hardware.remote_desktop.kind {
+                Some(kind) => findings.push(format!("remote desktop ({})", kind.label())),
+                None => findings.push("remote desktop".to_string()),
+            }
+        }
+        if let Some(session) = hardware.cast_sessions.first() {
+            findings.push(format!("screen streaming ({})", session.kind.label()));
+        }
+        if hardware.has_virtual_display {
+            findings.push("virtual display".to_string());
+        }
+    }
+    if !report.new_input_devices.is_empty() {
+        findings.push(format!("{} new input device(s)", report.new_input_devices.len()));
+    }
+    if !report.new_audio_inputs.is_empty() {
+        findings.push(format!("{} new audio input(s)", report.new_audio_inputs.len()));
+    }
+    if report.cloud_clipboard_sync {
+        findings.push("cloud clipboard sync".to_string());
+    }
+    if let Some(app) = report.foreground_app.as_ref().filter(|app| app.is_suspicious()) {
+        findings.push(format!("focused: {}", app.name.as_deref().unwrap_or("flagged app")));
+    }
+
+    findings
+}
+
+fn fit(line: String, max_chars: usize) -> String {
+    if line.chars().count() <= max_chars {
+        return line;
+    }
+    let mut cut: String = line.chars().take(max_chars.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+pub fn severity_label(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Low => "LOW",
+        Severity::Medium => "MEDIUM",
+        Severity::High => "HIGH",
+        Severity::Critical => "CRITICAL",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::report;
+    use crate::vm_detector::VmCheckResult;
+
+    fn flagged(name: &str, risk_score: f64, reason_codes: Vec<ReasonCode>) -> SuspiciousProcess {
+        SuspiciousProcess {
+            pid: 1,
+            name: name.to_string(),
+            path: String::new(),
+            risk_score,
+            reasons: Vec::new(),
+            reason_codes,
+            started_during_interview: false,
+            is_whitelisted: false,
+            drive_type: Default::default(),
+        }
+    }
+
+    fn breaching_scan() -> DetectionReport {
+        let mut scan = report(12, 0.78, true);
+        scan.suspicious_processes = vec![
+            flagged("helper.exe", 0.4, vec![ReasonCode::StartedDuringInterview]),
+            flagged("interview-ai.exe", 0.9, vec![ReasonCode::ScreenCapture, ReasonCode::AudioCapture]),
+        ];
+        scan.severity = Severity::High;
+        scan.vm_detection = Some(VmCheckResult {
+            is_vm: true,
+            reasons: Vec::new(),
+            confidence_score: 0.9,
+            raw_confidence_score: 0.9,
+        });
+        scan
+    }
+
+    #[test]
+    fn test_alert_line_lists_offenders_and_findings() {
+        assert_eq!(
+            format_alert_line(&breaching_scan(), DEFAULT_ALERT_LINE_MAX_CHARS),
+            "[HIGH] Scan 12: risk 0.78 — interview-ai.exe (screen+audio), helper.exe (new), VM detected"
+        );
+        assert_eq!(format_alert_line(&report(3, 0.05, false), 160), "[LOW] Scan 3: risk 0.05 — no findings");
+    }
+
+    #[test]
+    fn test_alert_line_truncates_offenders_first() {
+        let mut scan = breaching_scan();
+        scan.omitted_processes = 3;
+
+        assert_eq!(
+            format_alert_line(&scan, 90),
+            "[HIGH] Scan 12: risk 0.78 — interview-ai.exe (screen+audio), +4 more, VM detected"
+        );
+        assert_eq!(format_alert_line(&scan, 60), "[HIGH] Scan 12: risk 0.78 — +5 more, VM detected");
+
+        let line = format_alert_line(&scan, MIN_ALERT_LINE_CHARS);
+        assert_eq!(line.chars().count(), MIN_ALERT_LINE_CHARS);
+        assert!(line.ends_with('…'));
+    }
+}
\ No newline at end of file
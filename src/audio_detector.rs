@@ -1,14 +1,256 @@
-pub struct AudioCaptureDetector;
+use crate::platform::{PlatformProvider, SystemPlatform};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub struct AudioCaptureDetector {
+    platform: Arc<dyn PlatformProvider>,
+    baseline_inputs: Option<Vec<AudioInputDevice>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointFlow {
+    Render,
+    Capture,
+}
+
+/// An audio endpoint and whether a stream is running on it. `device_path`
+/// names the underlying device, which a render endpoint shares with any
+/// capture endpoint tapping it (a PulseAudio sink and its `.monitor` source,
+/// or both ends of a virtual audio cable).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointState {
+    pub device_path: String,
+    pub name: String,
+    pub flow: EndpointFlow,
+    pub active: bool,
+}
+
+/// Output on `render_name` is being recorded through `capture_name`, e.g. TTS
+/// answers or a remote helper's voice fed back into the microphone path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioLoop {
+    pub device_path: String,
+    pub render_name: String,
+    pub capture_name: String,
+}
+
+/// Pairs active render and capture endpoints on the same device path.
+pub fn find_audio_loops(endpoints: &[EndpointState]) -> Vec<AudioLoop> {
+    let active = |flow: EndpointFlow| endpoints.iter().filter(move |e| e.active && e.flow == flow);
+
+    let mut loops = Vec::new();
+    for render in active(EndpointFlow::Render) {
+        for capture in active(EndpointFlow::Capture).filter(|c| c.device_path == render.device_path) {
+            loops.push(AudioLoop {
+                device_path: render.device_path.clone(),
+                render_name: render.name.clone(),
+                capture_name: capture.name.clone(),
+            });
+        }
+    }
+    loops
+}
+
+/// An audio input, i.e. a capture endpoint other than a monitor source,
+/// which PulseAudio creates alongside every sink.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioInputDevice {
+    pub device_path: String,
+    pub name: String,
+    /// A virtual cable or loopback driver rather than a microphone.
+    pub virtual_device: bool,
+}
+
+const VIRTUAL_AUDIO_MARKERS: &[&str] = &[
+    "cable", "vb-audio", "voicemeeter", "blackhole", "soundflower",
+    "loopback", "virtual", "null", "remap",
+];
+
+pub fn is_virtual_audio_device(name: &str) -> bool {
+    let name = name.to_lowercase();
+    VIRTUAL_AUDIO_MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+pub fn input_devices(endpoints: &[EndpointState]) -> Vec<AudioInputDevice> {
+    endpoints
+        .iter()
+        .filter(|e| e.flow == EndpointFlow::Capture && !e.name.ends_with(".monitor"))
+        .map(|e| AudioInputDevice {
+            device_path: e.device_path.clone(),
+            name: e.name.clone(),
+            virtual_device: is_virtual_audio_device(&e.name) || is_virtual_audio_device(&e.device_path),
+        })
+        .collect()
+}
+
+/// Inputs in `current` whose device path isn't in `baseline`.
+pub fn new_input_devices(baseline: &[AudioInputDevice], current: &[AudioInputDevice]) -> Vec<AudioInputDevice> {
+    current
+        .iter()
+        .filter(|device| !baseline.iter().any(|known| known.device_path == device.device_path))
+        .cloned()
+        .collect()
+}
+
+/// Audio capture streams running at once and the applications behind them.
+/// Several at once, e.g. a meeting app and a separate transcriber, are a
+/// stronger signal than one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaptureStreams {
+    pub count: usize,
+    /// Distinct applications owning the streams, sorted.
+    pub applications: Vec<String>,
+}
+
+impl CaptureStreams {
+    fn from_streams(streams: Vec<Option<String>>) -> Self {
+        let mut applications: Vec<String> = streams.iter().flatten().cloned().collect();
+        applications.sort();
+        applications.dedup();
+        Self { count: streams.len(), applications }
+    }
+}
+
+/// Value of a `key = "value"` property line.
+fn property<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let (name, value) = line.trim().split_once(" = ")?;
+    (name == key).then(|| value.trim_matches('"'))
+}
+
+/// Counts the streams in `pactl list source-outputs`, leaving out corked
+/// (paused) ones. Each is attributed to its process binary, or failing that
+/// its application name.
+pub fn parse_pactl_source_outputs(output: &str) -> CaptureStreams {
+    let mut streams = Vec::new();
+    for block in output.split("Source Output #").skip(1) {
+        if block.lines().any(|line| line.trim() == "Corked: yes") {
+            continue;
+        }
+        let find = |key| block.lines().find_map(|line| property(line, key));
+        let application = find("application.process.binary").or_else(|| find("application.name"));
+        streams.push(application.map(|name| name.to_string()));
+    }
+    CaptureStreams::from_streams(streams)
+}
+
+/// Counts the audio input stream nodes (`media.class = "Stream/Input/Audio"`)
+/// in `pw-cli list-objects`, attributed by application or node name.
+pub fn parse_pw_cli_objects(output: &str) -> CaptureStreams {
+    // Each object starts with an `id 75, type PipeWire:Interface:Node/3` line.
+    let mut objects: Vec<Vec<&str>> = Vec::new();
+    for line in output.lines() {
+        if line.trim_start().starts_with("id ") {
+            objects.push(Vec::new());
+        } else if let Some(object) = objects.last_mut() {
+            object.push(line);
+        }
+    }
+
+    let streams = objects
+        .iter()
+        .filter(|object| object.iter().any(|line| property(line, "media.class") == Some("Stream/Input/Audio")))
+        .map(|object| {
+            let find = |key| object.iter().find_map(|line| property(line, key));
+            find("application.name").or_else(|| find("node.name")).map(|name| name.to_string())
+        })
+        .collect();
+    CaptureStreams::from_streams(streams)
+}
+
+/// Parses `pactl list short sinks` or `pactl list short sources`
+/// (`index<TAB>name<TAB>driver<TAB>spec<TAB>state`). A monitor source maps to
+/// the device path of the sink it taps.
+pub fn parse_pactl_short(output: &str, flow: EndpointFlow) -> Vec<EndpointState> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let name = fields.get(1)?.trim();
+            let state = fields.get(4).map(|s| s.trim()).unwrap_or("");
+            Some(EndpointState {
+                device_path: name.strip_suffix(".monitor").unwrap_or(name).to_string(),
+                name: name.to_string(),
+                flow,
+                active: state == "RUNNING",
+            })
+        })
+        .collect()
+}
+
+impl Default for AudioCaptureDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl AudioCaptureDetector {
     pub fn new() -> Self {
-        Self
+        Self::with_platform(Arc::new(SystemPlatform))
+    }
+
+    pub fn with_platform(platform: Arc<dyn PlatformProvider>) -> Self {
+        Self { platform, baseline_inputs: None }
+    }
+
+    pub fn set_baseline(&mut self) -> Result<usize, String> {
+        let inputs = input_devices(&self.audio_endpoints()?);
+        let count = inputs.len();
+        self.baseline_inputs = Some(inputs);
+        Ok(count)
+    }
+
+    /// Returns audio inputs connected since the baseline. Without a baseline
+    /// the first call records one and reports nothing.
+    pub fn detect_new_input_devices(&mut self) -> Result<Vec<AudioInputDevice>, String> {
+        let current = input_devices(&self.audio_endpoints()?);
+
+        match self.baseline_inputs {
+            Some(ref baseline) => Ok(new_input_devices(baseline, &current)),
+            None => {
+                self.baseline_inputs = Some(current);
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    /// Inputs in the baseline, 0 before one is taken.
+    pub fn baseline_input_count(&self) -> usize {
+        self.baseline_inputs.as_ref().map_or(0, |baseline| baseline.len())
     }
 
     pub fn detect_realtime_audio_processing(&self) -> bool {
+        self.platform.audio_monitoring()
+    }
+
+    /// Whether `detect_realtime_audio_processing` goes by per-process CPU
+    /// use here, which a busy machine inflates.
+    pub const REALTIME_AUDIO_USES_CPU: bool = cfg!(target_os = "windows");
+
+    /// Whether audio is being captured, with the streams counted where the
+    /// platform can tell them apart.
+    pub fn detect_capture_streams(&self) -> (bool, Option<CaptureStreams>) {
+        match self.platform.audio_capture_streams() {
+            Some(streams) => (streams.count > 0, Some(streams)),
+            None => (self.detect_realtime_audio_processing(), None),
+        }
+    }
+
+    /// Looks for output being recorded back into an input.
+    pub fn detect_audio_loops(&self) -> Result<Vec<AudioLoop>, String> {
+        Ok(find_audio_loops(&self.audio_endpoints()?))
+    }
+
+    pub fn audio_endpoints(&self) -> Result<Vec<EndpointState>, String> {
+        self.platform.audio_endpoints()
+    }
+}
+
+impl SystemPlatform {
+    pub(crate) fn detect_realtime_audio_processing(&self) -> bool {
         #[cfg(target_os = "windows")]
         {
-            self.detect_windows_audio()
+            crate::snapshot::shared().read(|system| self.detect_windows_audio(system))
         }
 
         #[cfg(target_os = "macos")]
@@ -24,14 +266,11 @@ impl AudioCaptureDetector {
 }
 
 #[cfg(target_os = "windows")]
-impl AudioCaptureDetector {
-    fn detect_windows_audio(&self) -> bool {
-        use sysinfo::System;
-
-        let mut system = System::new_all();
-        system.refresh_all();
-
-        for (_pid, process) in system.processes() {
+impl SystemPlatform {
+    /// CPU use is only meaningful across refreshes of one `System`, which
+    /// the shared snapshot provides.
+    fn detect_windows_audio(&self, system: &sysinfo::System) -> bool {
+        for process in system.processes().values() {
             let cpu_usage = process.cpu_usage();
             let name = process.name().to_lowercase();
 
@@ -53,13 +292,14 @@ impl AudioCaptureDetector {
 }
 
 #[cfg(target_os = "macos")]
-impl AudioCaptureDetector {
+impl SystemPlatform {
     fn detect_macos_audio(&self) -> bool {
+        use crate::command::LimitedOutput;
         use std::process::Command;
 
         let output = Command::new("system_profiler")
             .arg("SPAudioDataType")
-            .output();
+            .limited_output();
 
         if let Ok(output) = output {
             let result = String::from_utf8_lossy(&output.stdout);
@@ -72,41 +312,141 @@ impl AudioCaptureDetector {
 }
 
 #[cfg(target_os = "linux")]
-impl AudioCaptureDetector {
+impl SystemPlatform {
     fn detect_linux_audio(&self) -> bool {
-        self.check_pulseaudio() || self.check_pipewire()
+        self.linux_capture_streams().is_some_and(|streams| streams.count > 0)
     }
 
-    fn check_pulseaudio(&self) -> bool {
+    /// Capture streams from PulseAudio, or from PipeWire when PulseAudio has
+    /// none or isn't there. `None` when neither could be asked.
+    pub(crate) fn linux_capture_streams(&self) -> Option<CaptureStreams> {
+        match self.pulseaudio_capture_streams() {
+            Some(streams) if streams.count > 0 => Some(streams),
+            pulseaudio => self.pipewire_capture_streams().or(pulseaudio),
+        }
+    }
+
+    pub(crate) fn linux_endpoint_states(&self) -> Result<Vec<EndpointState>, String> {
+        use crate::command::LimitedOutput;
+        use std::process::Command;
+
+        let mut states = Vec::new();
+        for (kind, flow) in [("sinks", EndpointFlow::Render), ("sources", EndpointFlow::Capture)] {
+            let output = Command::new("pactl")
+                .args(["list", "short", kind])
+                .limited_output()
+                .map_err(|e| format!("Failed to run pactl: {}", e))?;
+            states.extend(parse_pactl_short(&String::from_utf8_lossy(&output.stdout), flow));
+        }
+        Ok(states)
+    }
+
+    fn pulseaudio_capture_streams(&self) -> Option<CaptureStreams> {
+        use crate::command::LimitedOutput;
         use std::process::Command;
 
         let output = Command::new("pactl")
             .arg("list")
             .arg("source-outputs")
-            .output();
-
-        if let Ok(output) = output {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            return stdout.contains("Source Output #");
-        }
-
-        false
+            .limited_output()
+            .ok()?;
+        Some(parse_pactl_source_outputs(&String::from_utf8_lossy(&output.stdout)))
     }
 
-    fn check_pipewire(&self) -> bool {
+    fn pipewire_capture_streams(&self) -> Option<CaptureStreams> {
+        use crate::command::LimitedOutput;
         use std::process::Command;
 
         let output = Command::new("pw-cli")
             .arg("list-objects")
-            .output();
+            .limited_output()
+            .ok()?;
+        Some(parse_pw_cli_objects(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
 
-        if let Ok(output) = output {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if stdout.contains("Stream") && stdout.contains("capture") {
-                return true;
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(device_path: &str, name: &str, flow: EndpointFlow, active: bool) -> EndpointState {
+        EndpointState {
+            device_path: device_path.to_string(),
+            name: name.to_string(),
+            flow,
+            active,
         }
+    }
 
-        false
+    #[test]
+    fn test_active_render_and_capture_on_same_device_is_a_loop() {
+        let endpoints = vec![
+            endpoint("cable", "CABLE Input", EndpointFlow::Render, true),
+            endpoint("cable", "CABLE Output", EndpointFlow::Capture, true),
+            endpoint("speakers", "Speakers", EndpointFlow::Render, true),
+            endpoint("mic", "Microphone", EndpointFlow::Capture, true),
+        ];
+
+        assert_eq!(find_audio_loops(&endpoints), vec![AudioLoop {
+            device_path: "cable".to_string(),
+            render_name: "CABLE Input".to_string(),
+            capture_name: "CABLE Output".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn test_idle_endpoint_is_not_a_loop() {
+        let endpoints = vec![
+            endpoint("cable", "CABLE Input", EndpointFlow::Render, true),
+            endpoint("cable", "CABLE Output", EndpointFlow::Capture, false),
+        ];
+        assert!(find_audio_loops(&endpoints).is_empty());
+    }
+
+    #[test]
+    fn test_capture_streams_are_counted_and_attributed() {
+        let pactl = parse_pactl_source_outputs(include_str!("../tests/fixtures/pactl_source_outputs.txt"));
+        assert_eq!(pactl.count, 3);
+        assert_eq!(pactl.applications, vec!["python3.11".to_string(), "zoom".to_string()]);
+
+        let pipewire = parse_pw_cli_objects(include_str!("../tests/fixtures/pw_cli_objects.txt"));
+        assert_eq!(pipewire.count, 2);
+        assert_eq!(pipewire.applications, vec!["Chromium".to_string(), "whisper-stream".to_string()]);
+
+        assert_eq!(parse_pactl_source_outputs(""), CaptureStreams::default());
+    }
+
+    #[test]
+    fn test_input_added_since_baseline_is_reported() {
+        let baseline = input_devices(&[
+            endpoint("alsa_input.pci.analog-stereo", "alsa_input.pci.analog-stereo", EndpointFlow::Capture, false),
+            endpoint("alsa_output.pci.analog-stereo", "alsa_output.pci.analog-stereo", EndpointFlow::Render, true),
+        ]);
+        let current = input_devices(&[
+            endpoint("alsa_input.pci.analog-stereo", "alsa_input.pci.analog-stereo", EndpointFlow::Capture, true),
+            endpoint("vb-cable", "CABLE Output (VB-Audio Virtual Cable)", EndpointFlow::Capture, false),
+            endpoint("alsa_output.usb-headset", "alsa_output.usb-headset.monitor", EndpointFlow::Capture, false),
+        ]);
+
+        assert_eq!(new_input_devices(&baseline, &current), vec![AudioInputDevice {
+            device_path: "vb-cable".to_string(),
+            name: "CABLE Output (VB-Audio Virtual Cable)".to_string(),
+            virtual_device: true,
+        }]);
+    }
+
+    #[test]
+    fn test_parse_pactl_monitor_source_maps_to_sink() {
+        let sinks = "0\talsa_output.pci.analog-stereo\tmodule-alsa-card.c\ts16le 2ch 48000Hz\tRUNNING\n";
+        let sources = "0\talsa_output.pci.analog-stereo.monitor\tmodule-alsa-card.c\ts16le 2ch 48000Hz\tRUNNING\n\
+                       1\talsa_input.pci.analog-stereo\tmodule-alsa-card.c\ts16le 2ch 48000Hz\tSUSPENDED\n";
+
+        let mut endpoints = parse_pactl_short(sinks, EndpointFlow::Render);
+        endpoints.extend(parse_pactl_short(sources, EndpointFlow::Capture));
+
+        let loops = find_audio_loops(&endpoints);
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].device_path, "alsa_output.pci.analog-stereo");
+        assert_eq!(loops[0].capture_name, "alsa_output.pci.analog-stereo.monitor");
     }
 }
\ No newline at end of file
@@ -0,0 +1,217 @@
+use crate::process_monitor::{normalize_name, normalize_path};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Processes and displays present when the baseline was collected, written
+/// to `monitoring.baseline_file` so two sessions can be compared later.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BaselineSnapshot {
+    pub captured_at: String,
+    pub processes: Vec<BaselineProcess>,
+    pub displays: Vec<BaselineDisplay>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaselineProcess {
+    pub name: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaselineDisplay {
+    pub id: String,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl BaselineProcess {
+    /// PIDs differ between sessions, so processes are matched by name and
+    /// path; several instances of the same executable count once.
+    fn key(&self) -> (String, String) {
+        (normalize_name(&self.name), normalize_path(&self.path))
+    }
+}
+
+impl BaselineDisplay {
+    fn key(&self) -> (String, u32, u32) {
+        (self.id.clone(), self.width, self.height)
+    }
+}
+
+impl BaselineSnapshot {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize baseline: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write baseline: {}", e))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read baseline {}: {}", path.display(), e))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse baseline {}: {}", path.display(), e))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessChange {
+    pub process: BaselineProcess,
+    /// Whitelisted processes coming and going is normal churn and does not
+    /// make the diff fail.
+    pub expected: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BaselineDiff {
+    pub added_processes: Vec<ProcessChange>,
+    pub removed_processes: Vec<ProcessChange>,
+    pub added_displays: Vec<BaselineDisplay>,
+    pub removed_displays: Vec<BaselineDisplay>,
+}
+
+/// Compares the baseline of one session (`before`) with the next (`after`).
+/// `is_expected` marks process changes that should not count against a reset.
+pub fn diff_baselines<F>(before: &BaselineSnapshot, after: &BaselineSnapshot, is_expected: F) -> BaselineDiff
+where
+    F: Fn(&BaselineProcess) -> bool,
+{
+    let before_processes: BTreeMap<_, _> = before.processes.iter().map(|p| (p.key(), p)).collect();
+    let after_processes: BTreeMap<_, _> = after.processes.iter().map(|p| (p.key(), p)).collect();
+    let before_displays: BTreeMap<_, _> = before.displays.iter().map(|d| (d.key(), d)).collect();
+    let after_displays: BTreeMap<_, _> = after.displays.iter().map(|d| (d.key(), d)).collect();
+
+    let change = |process: &BaselineProcess| ProcessChange {
+        expected: is_expected(process),
+        process: process.clone(),
+    };
+
+    BaselineDiff {
+        added_processes: after_processes
+            .iter()
+            .filter(|(key, _)| !before_processes.contains_key(*key))
+            .map(|(_, p)| change(p))
+            .collect(),
+        removed_processes: before_processes
+            .iter()
+            .filter(|(key, _)| !after_processes.contains_key(*key))
+            .map(|(_, p)| change(p))
+            .collect(),
+        added_displays: after_displays
+            .iter()
+            .filter(|(key, _)| !before_displays.contains_key(*key))
+            .map(|(_, d)| (*d).clone())
+            .collect(),
+        removed_displays: before_displays
+            .iter()
+            .filter(|(key, _)| !after_displays.contains_key(*key))
+            .map(|(_, d)| (*d).clone())
+            .collect(),
+    }
+}
+
+impl BaselineDiff {
+    /// Any display change, or any process change not marked expected.
+    pub fn has_unexpected_changes(&self) -> bool {
+        !self.added_displays.is_empty()
+            || !self.removed_displays.is_empty()
+            || self
+                .added_processes
+                .iter()
+                .chain(&self.removed_processes)
+                .any(|change| !change.expected)
+    }
+
+    /// One line per change, `+` for additions and `-` for removals.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        for (sign, changes) in [("+", &self.added_processes), ("-", &self.removed_processes)] {
+            for change in changes {
+                out.push_str(&format!("{} process {}", sign, change.process.name));
+                if !change.process.path.is_empty() {
+                    out.push_str(&format!(" ({})", change.process.path));
+                }
+                if change.expected {
+                    out.push_str(" [whitelisted]");
+                }
+                out.push('\n');
+            }
+        }
+
+        for (sign, displays) in [("+", &self.added_displays), ("-", &self.removed_displays)] {
+            for display in displays {
+                out.push_str(&format!(
+                    "{} display {} {}x{} ({})\n",
+                    sign, display.name, display.width, display.height, display.id
+                ));
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEFORE: &str = r#"{
+        "captured_at": "2024-11-18T09:00:00+00:00",
+        "processes": [
+            { "name": "explorer.exe", "path": "C:\\Windows\\explorer.exe" },
+            { "name": "svchost.exe", "path": "C:\\Windows\\System32\\svchost.exe" },
+            { "name": "svchost.exe", "path": "C:\\Windows\\System32\\svchost.exe" },
+            { "name": "Teams.exe", "path": "C:\\Program Files\\Teams\\Teams.exe" }
+        ],
+        "displays": [
+            { "id": "DISPLAY1", "name": "Dell U2720Q", "width": 2560, "height": 1440 }
+        ]
+    }"#;
+
+    const AFTER: &str = r#"{
+        "captured_at": "2024-11-18T11:00:00+00:00",
+        "processes": [
+            { "name": "EXPLORER.EXE", "path": "c:/windows/explorer.exe" },
+            { "name": "svchost.exe", "path": "C:\\Windows\\System32\\svchost.exe" },
+            { "name": "cluely.exe", "path": "C:\\Users\\kiosk\\AppData\\cluely.exe" }
+        ],
+        "displays": [
+            { "id": "DISPLAY1", "name": "Dell U2720Q", "width": 2560, "height": 1440 },
+            { "id": "DISPLAY2", "name": "Generic PnP Monitor", "width": 1920, "height": 1080 }
+        ]
+    }"#;
+
+    fn fixtures() -> (BaselineSnapshot, BaselineSnapshot) {
+        (serde_json::from_str(BEFORE).unwrap(), serde_json::from_str(AFTER).unwrap())
+    }
+
+    #[test]
+    fn test_diff_reports_additions_and_removals() {
+        let (before, after) = fixtures();
+        let diff = diff_baselines(&before, &after, |p| p.name.eq_ignore_ascii_case("teams.exe"));
+
+        assert_eq!(
+            diff.render(),
+            "+ process cluely.exe (C:\\Users\\kiosk\\AppData\\cluely.exe)\n\
+             - process Teams.exe (C:\\Program Files\\Teams\\Teams.exe) [whitelisted]\n\
+             + display Generic PnP Monitor 1920x1080 (DISPLAY2)\n"
+        );
+        assert!(diff.has_unexpected_changes());
+    }
+
+    #[test]
+    fn test_only_expected_changes_pass() {
+        let (before, _) = fixtures();
+        let mut after = before.clone();
+        after.processes.retain(|p| p.name != "Teams.exe");
+
+        let diff = diff_baselines(&before, &after, |p| p.name == "Teams.exe");
+        assert_eq!(diff.removed_processes.len(), 1);
+        assert!(!diff.has_unexpected_changes());
+        assert_eq!(diff_baselines(&before, &before, |_| false), BaselineDiff::default());
+    }
+}
\ No newline at end of file
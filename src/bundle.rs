@@ -0,0 +1,223 @@
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::denylist::sha256_file;
+use crate::observations::{Observation, ObservationPhase};
+use crate::{DetectionReport, Severity};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// What went into an evidence bundle, written last as `manifest.json` with
+/// a SHA-256 of every other file so later tampering shows.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub scan_number: usize,
+    pub session_id: Option<String>,
+    pub candidate_ref: Option<String>,
+    pub created_at: String,
+    pub files: Vec<BundledFile>,
+    /// Evidence that was looked for but isn't in the bundle, and why.
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundledFile {
+    pub name: String,
+    pub sha256: String,
+}
+
+/// A flagged process's executable as it was on disk when the bundle was
+/// made, which may be after the scan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryHash {
+    pub pid: u32,
+    pub name: String,
+    pub path: String,
+    pub sha256: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Name of the bundle directory for the scan in `report`.
+pub fn bundle_name(report: &DetectionReport) -> String {
+    format!("evidence_scan{}", report.scan_number)
+}
+
+/// Picks the report to bundle from a report file: the whole file when it is
+/// a single report, otherwise a JSON-lines file, from which `scan` picks
+/// the report, or else the last Critical one.
+pub fn select_report(contents: &str, scan: Option<usize>) -> Result<DetectionReport, String> {
+    if let Ok(report) = serde_json::from_str::<DetectionReport>(contents) {
+        return match scan {
+            Some(scan) if scan != report.scan_number => {
+                Err(format!("The report is for scan {}, not scan {}", report.scan_number, scan))
+            }
+            _ => Ok(report),
+        };
+    }
+
+    let mut chosen = None;
+    for (index, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let report: DetectionReport = serde_json::from_str(line)
+            .map_err(|e| format!("Line {} is not a report: {}", index + 1, e))?;
+        let wanted = match scan {
+            Some(scan) => report.scan_number == scan,
+            None => report.severity == Severity::Critical,
+        };
+        if wanted {
+            chosen = Some(report);
+        }
+    }
+    chosen.ok_or_else(|| match scan {
+        Some(scan) => format!("No report for scan {}", scan),
+        None => "No Critical report to bundle; pick one with --scan".to_string(),
+    })
+}
+
+/// The baseline observation and the one for `scan_number`, the raw input
+/// `rescore` needs to rebuild that scan's report.
+pub fn observations_for_scan(log: &[Observation], scan_number: usize) -> Vec<Observation> {
+    log.iter()
+        .filter(|observation| {
+            observation.phase == ObservationPhase::Baseline
+                || (observation.phase == ObservationPhase::Scan && observation.scan_number == scan_number)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Writes everything needed to adjudicate the scan in `report` into `dir`:
+/// the report, its raw observations (`None` when no observation log was
+/// kept), the hashes of the flagged executables, the overlay windows and
+/// the config in effect with secrets redacted. Screenshots aren't taken,
+/// so overlays are described by their window metadata only. The manifest is
+/// stamped from `clock`.
+pub fn write_bundle(
+    dir: &Path,
+    report: &DetectionReport,
+    observations: Option<&[Observation]>,
+    config: &Config,
+    clock: &dyn Clock,
+) -> Result<BundleManifest, String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create bundle directory {}: {}", dir.display(), e))?;
+
+    let mut files = Vec::new();
+    let mut missing = Vec::new();
+    let mut write = |name: &str, contents: String| -> Result<(), String> {
+        let path = dir.join(name);
+        std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        files.push(BundledFile { name: name.to_string(), sha256: sha256_file(&path.to_string_lossy())? });
+        Ok(())
+    };
+
+    write("report.json", to_json(report)?)?;
+
+    match observations {
+        Some(log) => {
+            let observations = observations_for_scan(log, report.scan_number);
+            if !observations.iter().any(|observation| observation.phase == ObservationPhase::Scan) {
+                missing.push(format!("observations: scan {} is not in the observation log", report.scan_number));
+            }
+            let mut lines = String::new();
+            for observation in &observations {
+                lines.push_str(
+                    &serde_json::to_string(observation).map_err(|e| format!("Failed to serialize observation: {}", e))?,
+                );
+                lines.push('\n');
+            }
+            write("observations.jsonl", lines)?;
+        }
+        None => missing.push("observations: output.observation_log was off".to_string()),
+    }
+
+    let binaries: Vec<BinaryHash> = report
+        .suspicious_processes
+        .iter()
+        .map(|process| {
+            let digest = sha256_file(&process.path);
+            BinaryHash {
+                pid: process.pid,
+                name: process.name.clone(),
+                path: process.path.clone(),
+                sha256: digest.clone().ok(),
+                error: digest.err(),
+            }
+        })
+        .collect();
+    write("binaries.json", to_json(&binaries)?)?;
+    write("overlays.json", to_json(&report.hidden_overlays)?)?;
+    if report.hidden_overlays.iter().any(|overlay| overlay.title.is_empty()) {
+        missing.push("overlays: some windows have no title; only their class name and geometry are known".to_string());
+    }
+
+    let config = toml::to_string_pretty(&config.redacted()).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    write("config.toml", config)?;
+
+    let manifest = BundleManifest {
+        scan_number: report.scan_number,
+        session_id: report.session_id.clone(),
+        candidate_ref: report.candidate_ref.clone(),
+        created_at: crate::timestamp_format::format(&clock.now().wall),
+        files,
+        missing,
+    };
+    let path = dir.join("manifest.json");
+    std::fs::write(&path, to_json(&manifest)?).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(manifest)
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize bundle file: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::test_support::report;
+    use crate::SuspiciousProcess;
+
+    #[test]
+    fn test_bundle_holds_every_artifact_for_a_critical_scan() {
+        let dir = std::env::temp_dir().join(format!("fairview_bundle_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let binary = dir.join("helper.exe");
+        std::fs::write(&binary, b"helper").unwrap();
+
+        let mut critical = report(4, 0.95, true);
+        assert_eq!(critical.severity, Severity::Critical);
+        critical.suspicious_processes = vec![SuspiciousProcess {
+            pid: 77,
+            name: "helper.exe".to_string(),
+            path: binary.to_string_lossy().into_owned(),
+            risk_score: 0.9,
+            reasons: Vec::new(),
+            reason_codes: Vec::new(),
+            started_during_interview: true,
+            is_whitelisted: false,
+            drive_type: Default::default(),
+        }];
+        let log = [
+            Observation { phase: ObservationPhase::Baseline, ..Default::default() },
+            Observation { scan_number: 3, ..Default::default() },
+            Observation { scan_number: 4, ..Default::default() },
+        ];
+
+        let bundle = dir.join(bundle_name(&critical));
+        let clock = FixedClock::deterministic();
+        let manifest = write_bundle(&bundle, &critical, Some(&log), &Config::default(), &clock).unwrap();
+        let names: Vec<&str> = manifest.files.iter().map(|file| file.name.as_str()).collect();
+        let observations = std::fs::read_to_string(bundle.join("observations.jsonl")).unwrap();
+        let binaries: Vec<BinaryHash> =
+            serde_json::from_str(&std::fs::read_to_string(bundle.join("binaries.json")).unwrap()).unwrap();
+        let written = bundle.join("manifest.json").exists();
+        let digest = sha256_file(&binary.to_string_lossy()).ok();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(names, vec!["report.json", "observations.jsonl", "binaries.json", "overlays.json", "config.toml"]);
+        assert!(written && manifest.missing.is_empty());
+        assert_eq!(manifest.created_at, "2023-11-14T22:13:20+00:00");
+        assert_eq!(observations.lines().count(), 2);
+        assert!(digest.is_some());
+        assert_eq!(binaries[0].sha256, digest);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,52 @@
+/// Runs a module every `every` scans and keeps its last result for the scans
+/// in between.
+#[derive(Debug)]
+pub struct Cadenced<T> {
+    every: usize,
+    last: Option<T>,
+}
+
+impl<T: Clone> Cadenced<T> {
+    pub fn new(every: usize) -> Self {
+        Self { every: every.max(1), last: None }
+    }
+
+    /// Whether scan `scan_number`, counted from 1, runs the module: scans 1,
+    /// 1 + every, 1 + 2 * every and so on, and any scan while there is no
+    /// earlier result to reuse (the module failed, or the session resumed
+    /// between runs).
+    pub fn is_due(&self, scan_number: usize) -> bool {
+        let since_first_run = scan_number.saturating_sub(1) % self.every;
+        self.last.is_none() || since_first_run == 0
+    }
+
+    /// Keeps `result` for the skipped scans that follow and returns it.
+    pub fn store(&mut self, result: T) -> T {
+        self.last = Some(result.clone());
+        result
+    }
+
+    /// The result of the module's last run.
+    pub fn last(&self) -> Option<T> {
+        self.last.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cadence_five_runs_on_scans_1_6_11() {
+        let mut cadence = Cadenced::new(5);
+        let mut ran = Vec::new();
+        for scan in 1..=12 {
+            if cadence.is_due(scan) {
+                cadence.store(scan);
+                ran.push(scan);
+            }
+        }
+        assert_eq!(ran, vec![1, 6, 11]);
+        assert_eq!(cadence.last(), Some(11));
+    }
+}
\ No newline at end of file
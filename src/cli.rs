@@ -0,0 +1,307 @@
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Command {
+    /// Run the scan loop in the foreground.
+    #[default]
+    Monitor,
+    /// Register Fairview with the Windows service control manager.
+    InstallService,
+    /// Stop and remove the registered Windows service.
+    UninstallService,
+    /// Entry point used by the service control manager; not meant to be run by hand.
+    Service,
+    /// Print or write a systemd unit (Linux) or launchd plist (macOS).
+    GenerateUnit,
+    /// Diff two saved baselines and fail if the machine was not reset.
+    CompareBaseline,
+    /// Score a recorded observation log again under the current config.
+    Rescore,
+    /// Load and validate the config, print the effective values and exit.
+    CheckConfig,
+    /// Follow a JSON-lines report file and print each report as it lands.
+    Watch,
+    /// Check this machine is ready to be monitored, then exit.
+    Selftest,
+    /// Package one scan's report and evidence for adjudication.
+    Bundle,
+}
+
+pub const DEFAULT_CONFIG_PATH: &str = "fairview_config.toml";
+
+#[derive(Debug, Clone)]
+pub struct CliArgs {
+    pub command: Command,
+    pub interactive: bool,
+    /// Suppress the per-scan console report; JSON reports are still written.
+    pub quiet: bool,
+    /// Fixed clock and RNG seed so reports are reproducible.
+    pub deterministic: bool,
+    /// Config file to load; `-` reads TOML from stdin.
+    pub config_path: String,
+    /// TOML passed directly with `--config-inline`.
+    pub config_inline: Option<String>,
+    /// Where `generate-unit` writes the unit, `rescore` the reports, or
+    /// `bundle` the evidence directory; stdout when unset.
+    pub output: Option<String>,
+    /// Overrides `session.session_id` from the config.
+    pub session_id: Option<String>,
+    /// Overrides `session.candidate_ref` from the config.
+    pub candidate_ref: Option<String>,
+    /// The two baseline files given to `compare-baseline`.
+    pub baseline_files: Vec<String>,
+    /// The observation log given to `rescore`.
+    pub observation_file: Option<String>,
+    /// The report file followed by `watch`.
+    pub watch_file: Option<String>,
+    /// The report file given to `bundle`.
+    pub report_file: Option<String>,
+    /// The scan `bundle` picks from a JSON-lines report file.
+    pub scan: Option<usize>,
+    /// Write each report to stdout as one JSON line; console output goes to
+    /// stderr.
+    pub stdout_jsonl: bool,
+    /// Drop every cache before the first scan.
+    pub force_fresh: bool,
+}
+
+impl Default for CliArgs {
+    fn default() -> Self {
+        Self {
+            command: Command::Monitor,
+            interactive: false,
+            quiet: false,
+            deterministic: false,
+            config_path: DEFAULT_CONFIG_PATH.to_string(),
+            config_inline: None,
+            output: None,
+            session_id: None,
+            candidate_ref: None,
+            baseline_files: Vec::new(),
+            observation_file: None,
+            watch_file: None,
+            report_file: None,
+            scan: None,
+            stdout_jsonl: false,
+            force_fresh: false,
+        }
+    }
+}
+
+impl CliArgs {
+    pub fn parse<I>(args: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut cli = CliArgs::default();
+
+        let mut args = args.into_iter().enumerate();
+        let mut config_given = false;
+
+        while let Some((index, arg)) = args.next() {
+            match arg.as_str() {
+                "--interactive" => cli.interactive = true,
+                "--quiet" => cli.quiet = true,
+                "--deterministic" => cli.deterministic = true,
+                "--stdout-jsonl" => cli.stdout_jsonl = true,
+                "--force-fresh" => cli.force_fresh = true,
+                "--config" => {
+                    cli.config_path = Self::value_for(&arg, args.next())?;
+                    config_given = true;
+                }
+                "--config-inline" => cli.config_inline = Some(Self::value_for(&arg, args.next())?),
+                "--output" => cli.output = Some(Self::value_for(&arg, args.next())?),
+                "--session-id" => cli.session_id = Some(Self::value_for(&arg, args.next())?),
+                "--candidate-ref" => cli.candidate_ref = Some(Self::value_for(&arg, args.next())?),
+                "--scan" => {
+                    let value = Self::value_for(&arg, args.next())?;
+                    cli.scan = Some(value.parse().map_err(|_| format!("--scan takes a scan number, got {}", value))?);
+                }
+                "install-service" if index == 0 => cli.command = Command::InstallService,
+                "uninstall-service" if index == 0 => cli.command = Command::UninstallService,
+                "service" if index == 0 => cli.command = Command::Service,
+                "generate-unit" if index == 0 => cli.command = Command::GenerateUnit,
+                "compare-baseline" if index == 0 => cli.command = Command::CompareBaseline,
+                "rescore" if index == 0 => cli.command = Command::Rescore,
+                "check-config" if index == 0 => cli.command = Command::CheckConfig,
+                "watch" if index == 0 => cli.command = Command::Watch,
+                "selftest" if index == 0 => cli.command = Command::Selftest,
+                "bundle" if index == 0 => cli.command = Command::Bundle,
+                file if cli.command == Command::CompareBaseline && !file.starts_with("--") => {
+                    cli.baseline_files.push(file.to_string())
+                }
+                file if cli.command == Command::Rescore && cli.observation_file.is_none() && !file.starts_with("--") => {
+                    cli.observation_file = Some(file.to_string())
+                }
+                file if cli.command == Command::Watch && cli.watch_file.is_none() && !file.starts_with("--") => {
+                    cli.watch_file = Some(file.to_string())
+                }
+                file if cli.command == Command::Bundle && cli.report_file.is_none() && !file.starts_with("--") => {
+                    cli.report_file = Some(file.to_string())
+                }
+                other => return Err(format!("Unknown argument: {}", other)),
+            }
+        }
+
+        if config_given && cli.config_inline.is_some() {
+            return Err("--config and --config-inline cannot be combined".to_string());
+        }
+
+        if cli.output.is_some() && !matches!(cli.command, Command::GenerateUnit | Command::Rescore | Command::Bundle) {
+            return Err("--output is only valid with generate-unit, rescore or bundle".to_string());
+        }
+
+        if cli.scan.is_some() && cli.command != Command::Bundle {
+            return Err("--scan is only valid with bundle".to_string());
+        }
+
+        if cli.stdout_jsonl && cli.command != Command::Monitor {
+            return Err("--stdout-jsonl is only valid when monitoring".to_string());
+        }
+
+        if cli.force_fresh && cli.command != Command::Monitor {
+            return Err("--force-fresh is only valid when monitoring".to_string());
+        }
+
+        if cli.command == Command::CompareBaseline && cli.baseline_files.len() != 2 {
+            return Err("compare-baseline takes exactly two baseline files".to_string());
+        }
+
+        if cli.command == Command::Rescore && cli.observation_file.is_none() {
+            return Err("rescore takes an observation log".to_string());
+        }
+
+        if cli.command == Command::Watch && cli.watch_file.is_none() {
+            return Err("watch takes a report file".to_string());
+        }
+
+        if cli.command == Command::Bundle && cli.report_file.is_none() {
+            return Err("bundle takes a report file".to_string());
+        }
+
+        Ok(cli)
+    }
+
+    pub fn reads_config_from_stdin(&self) -> bool {
+        self.config_inline.is_none() && self.config_path == "-"
+    }
+
+    fn value_for(flag: &str, value: Option<(usize, String)>) -> Result<String, String> {
+        match value {
+            Some((_, value)) if value == "-" || !value.starts_with("--") => Ok(value),
+            _ => Err(format!("{} requires a value", flag)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_interactive() {
+        let cli = CliArgs::parse(args(&["--interactive"])).unwrap();
+        assert!(cli.interactive);
+        assert!(!CliArgs::parse(args(&[])).unwrap().interactive);
+    }
+
+    #[test]
+    fn test_unknown_argument() {
+        assert!(CliArgs::parse(args(&["--bogus"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_service_subcommands() {
+        assert_eq!(CliArgs::parse(args(&[])).unwrap().command, Command::Monitor);
+        assert_eq!(CliArgs::parse(args(&["install-service"])).unwrap().command, Command::InstallService);
+        assert_eq!(CliArgs::parse(args(&["uninstall-service"])).unwrap().command, Command::UninstallService);
+        assert_eq!(CliArgs::parse(args(&["service"])).unwrap().command, Command::Service);
+        assert!(CliArgs::parse(args(&["--interactive", "install-service"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_generate_unit_options() {
+        let cli = CliArgs::parse(args(&[
+            "generate-unit", "--config", "/etc/fairview.toml", "--output", "fairview.service",
+        ]))
+        .unwrap();
+        assert_eq!(cli.command, Command::GenerateUnit);
+        assert_eq!(cli.config_path, "/etc/fairview.toml");
+        assert_eq!(cli.output.as_deref(), Some("fairview.service"));
+
+        assert_eq!(CliArgs::parse(args(&[])).unwrap().config_path, DEFAULT_CONFIG_PATH);
+        assert!(CliArgs::parse(args(&["--config"])).is_err());
+        assert!(CliArgs::parse(args(&["--output", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_config_sources() {
+        assert!(CliArgs::parse(args(&["--config", "-"])).unwrap().reads_config_from_stdin());
+
+        let cli = CliArgs::parse(args(&["--config-inline", "[scan]\ninterval_seconds = 5"])).unwrap();
+        assert_eq!(cli.config_inline.as_deref(), Some("[scan]\ninterval_seconds = 5"));
+        assert!(!cli.reads_config_from_stdin());
+
+        assert!(CliArgs::parse(args(&["--config", "a.toml", "--config-inline", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_session_identifiers() {
+        let cli = CliArgs::parse(args(&["--session-id", "int-2041", "--candidate-ref", "ats:88213"])).unwrap();
+        assert_eq!(cli.session_id.as_deref(), Some("int-2041"));
+        assert_eq!(cli.candidate_ref.as_deref(), Some("ats:88213"));
+        assert!(CliArgs::parse(args(&["--session-id"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_compare_baseline() {
+        let cli = CliArgs::parse(args(&["compare-baseline", "a.json", "b.json"])).unwrap();
+        assert_eq!(cli.command, Command::CompareBaseline);
+        assert_eq!(cli.baseline_files, vec!["a.json", "b.json"]);
+
+        assert!(CliArgs::parse(args(&["compare-baseline", "a.json"])).is_err());
+        assert!(CliArgs::parse(args(&["a.json", "b.json"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_rescore() {
+        let cli = CliArgs::parse(args(&["rescore", "observations.jsonl", "--output", "rescored.jsonl"])).unwrap();
+        assert_eq!(cli.command, Command::Rescore);
+        assert_eq!(cli.observation_file.as_deref(), Some("observations.jsonl"));
+        assert_eq!(cli.output.as_deref(), Some("rescored.jsonl"));
+
+        assert!(CliArgs::parse(args(&["rescore"])).is_err());
+        assert!(CliArgs::parse(args(&["rescore", "a.jsonl", "b.jsonl"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_check_config() {
+        let cli = CliArgs::parse(args(&["check-config", "--config", "tuned.toml", "--session-id", "int-7"])).unwrap();
+        assert_eq!(cli.command, Command::CheckConfig);
+        assert_eq!(cli.config_path, "tuned.toml");
+        assert_eq!(cli.session_id.as_deref(), Some("int-7"));
+
+        assert!(CliArgs::parse(args(&["check-config", "extra.toml"])).is_err());
+        assert_eq!(CliArgs::parse(args(&["selftest", "--config", "tuned.toml"])).unwrap().command, Command::Selftest);
+    }
+
+    #[test]
+    fn test_parse_watch() {
+        let cli = CliArgs::parse(args(&["watch", "reports.jsonl", "--quiet"])).unwrap();
+        assert_eq!(cli.command, Command::Watch);
+        assert_eq!(cli.watch_file.as_deref(), Some("reports.jsonl"));
+        assert!(cli.quiet);
+
+        assert!(CliArgs::parse(args(&["watch"])).is_err());
+        assert!(CliArgs::parse(args(&["watch", "a.jsonl", "b.jsonl"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_force_fresh() {
+        assert!(CliArgs::parse(args(&["--force-fresh"])).unwrap().force_fresh);
+        assert!(!CliArgs::parse(args(&[])).unwrap().force_fresh);
+        assert!(CliArgs::parse(args(&["rescore", "a.jsonl", "--force-fresh"])).is_err());
+    }
+}
\ No newline at end of file
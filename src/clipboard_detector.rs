@@ -0,0 +1,82 @@
+use crate::platform::{PlatformProvider, SystemPlatform};
+use std::sync::Arc;
+
+/// Where Windows keeps the clipboard history and Cloud Clipboard settings
+/// for the signed-in user.
+pub const CLIPBOARD_KEY: &str = "HKCU\\Software\\Microsoft\\Clipboard";
+
+/// Checks whether Windows Cloud Clipboard syncs copies across the user's
+/// devices, which lets a second signed-in device push answers straight
+/// into the interview machine's clipboard.
+pub struct ClipboardDetector {
+    platform: Arc<dyn PlatformProvider>,
+}
+
+impl ClipboardDetector {
+    pub fn with_platform(platform: Arc<dyn PlatformProvider>) -> Self {
+        Self { platform }
+    }
+
+    /// `None` where the setting can't be read (outside Windows).
+    pub fn cloud_clipboard_sync(&self) -> Option<bool> {
+        self.platform.cloud_clipboard_sync()
+    }
+}
+
+/// Whether `reg query` output for `CLIPBOARD_KEY` has Cloud Clipboard on
+/// with automatic upload, so every copy reaches the user's other devices
+/// and theirs reach this one. A missing value is Windows' default: off.
+pub fn parse_clipboard_settings(output: &str) -> bool {
+    let value = |name: &str| {
+        output.lines().find_map(|line| {
+            let mut fields = line.split_whitespace();
+            if fields.next()? != name || fields.next()? != "REG_DWORD" {
+                return None;
+            }
+            u32::from_str_radix(fields.next()?.trim_start_matches("0x"), 16).ok()
+        })
+    };
+    value("EnableCloudClipboard") != Some(0) && value("CloudClipboardAutomaticUpload") == Some(1)
+}
+
+impl SystemPlatform {
+    pub(crate) fn current_cloud_clipboard_sync(&self) -> Option<bool> {
+        #[cfg(target_os = "windows")]
+        {
+            use crate::command::LimitedOutput;
+            use std::process::Command;
+
+            let output = Command::new("reg").args(["query", CLIPBOARD_KEY]).limited_output().ok()?;
+            // The key only exists once a clipboard setting has been changed.
+            if !output.status.success() {
+                return Some(false);
+            }
+            Some(parse_clipboard_settings(&String::from_utf8_lossy(&output.stdout)))
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cloud_clipboard_upload_is_read_from_registry_output() {
+        let enabled = include_str!("../tests/fixtures/reg_query_clipboard.txt");
+        let turned_off = |name: &str| {
+            enabled.replace(&format!("{}    REG_DWORD    0x1", name), &format!("{}    REG_DWORD    0x0", name))
+        };
+        let without_upload: String =
+            enabled.lines().filter(|line| !line.contains("CloudClipboardAutomaticUpload")).collect::<Vec<_>>().join("\n");
+
+        assert!(parse_clipboard_settings(enabled));
+        assert!(!parse_clipboard_settings(&turned_off("CloudClipboardAutomaticUpload")));
+        assert!(!parse_clipboard_settings(&turned_off("EnableCloudClipboard")));
+        assert!(!parse_clipboard_settings(&without_upload));
+    }
+}
\ No newline at end of file
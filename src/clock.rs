@@ -0,0 +1,239 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Wall-clock and monotonic readings taken together. The monotonic clock can't
+/// be set by the candidate, so it is the reference the wall clock is checked
+/// against. `monotonic` is measured from the clock's own origin.
+#[derive(Debug, Clone, Copy)]
+pub struct ClockSample {
+    pub wall: SystemTime,
+    pub monotonic: Duration,
+}
+
+impl ClockSample {
+    /// Checks this sample against an earlier one.
+    pub fn compare_with(&self, previous: &ClockSample, tolerance: Duration) -> Option<String> {
+        check_clock_consistency(
+            previous.wall,
+            self.wall,
+            self.monotonic.saturating_sub(previous.monotonic),
+            tolerance,
+        )
+    }
+
+    /// Monotonic time elapsed since `earlier`.
+    pub fn since(&self, earlier: &ClockSample) -> Duration {
+        self.monotonic.saturating_sub(earlier.monotonic)
+    }
+}
+
+/// Time source for scans. Tests and `--deterministic` runs swap in a
+/// `FixedClock` so reports serialize identically on every run.
+pub trait Clock: Send {
+    fn now(&self) -> ClockSample;
+}
+
+/// Reads the monotonic time from a clock that keeps counting while the
+/// machine is suspended where there is one, so resuming a laptop doesn't
+/// look like the wall clock jumping forward.
+pub struct SystemClock {
+    origin: Instant,
+    suspend_aware_origin: Option<Duration>,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self { origin: Instant::now(), suspend_aware_origin: suspend_aware_now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> ClockSample {
+        let suspend_aware = self
+            .suspend_aware_origin
+            .and_then(|origin| Some(suspend_aware_now()?.saturating_sub(origin)));
+        ClockSample {
+            wall: SystemTime::now(),
+            monotonic: suspend_aware.unwrap_or_else(|| self.origin.elapsed()),
+        }
+    }
+}
+
+/// Time since boot including suspend, from `CLOCK_BOOTTIME`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn suspend_aware_now() -> Option<Duration> {
+    let mut time = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    if unsafe { libc::clock_gettime(libc::CLOCK_BOOTTIME, &mut time) } != 0 {
+        return None;
+    }
+    Some(Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+}
+
+/// Time since boot including sleep, from `mach_continuous_time`.
+#[cfg(target_os = "macos")]
+fn suspend_aware_now() -> Option<Duration> {
+    #[repr(C)]
+    struct MachTimebaseInfo {
+        numer: u32,
+        denom: u32,
+    }
+    extern "C" {
+        fn mach_continuous_time() -> u64;
+        fn mach_timebase_info(info: *mut MachTimebaseInfo) -> i32;
+    }
+
+    let mut timebase = MachTimebaseInfo { numer: 0, denom: 0 };
+    if unsafe { mach_timebase_info(&mut timebase) } != 0 || timebase.denom == 0 {
+        return None;
+    }
+    let ticks = unsafe { mach_continuous_time() } as u128;
+    Some(Duration::from_nanos((ticks * timebase.numer as u128 / timebase.denom as u128) as u64))
+}
+
+/// `Instant` already counts suspended time on Windows; other platforms fall
+/// back to it as well.
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+fn suspend_aware_now() -> Option<Duration> {
+    None
+}
+
+/// Start of the `--deterministic` clock: 2023-11-14T22:13:20Z.
+pub const DETERMINISTIC_START_SECS: u64 = 1_700_000_000;
+
+/// Starts at `start` and advances both readings by `step` on every call.
+pub struct FixedClock {
+    start: SystemTime,
+    step: Duration,
+    ticks: AtomicU64,
+}
+
+impl FixedClock {
+    pub fn new(start: SystemTime, step: Duration) -> Self {
+        Self {
+            start,
+            step,
+            ticks: AtomicU64::new(0),
+        }
+    }
+
+    /// The clock used by `--deterministic`: starts at `DETERMINISTIC_START_SECS`
+    /// and ticks one second per reading.
+    pub fn deterministic() -> Self {
+        Self::new(
+            SystemTime::UNIX_EPOCH + Duration::from_secs(DETERMINISTIC_START_SECS),
+            Duration::from_secs(1),
+        )
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> ClockSample {
+        let ticks = self.ticks.fetch_add(1, Ordering::Relaxed);
+        let elapsed = self.step * ticks as u32;
+        ClockSample {
+            wall: self.start + elapsed,
+            monotonic: elapsed,
+        }
+    }
+}
+
+/// Returns a reason when the wall clock moved backward, or advanced by an amount
+/// that differs from the monotonic elapsed time by more than `tolerance`.
+///
+/// `SystemClock` counts time spent suspended, so a machine resumed from
+/// sleep reads as consistent. A clock that pauses during suspend would flag
+/// every resume as a forward jump.
+pub fn check_clock_consistency(
+    previous_wall: SystemTime,
+    current_wall: SystemTime,
+    monotonic_elapsed: Duration,
+    tolerance: Duration,
+) -> Option<String> {
+    match current_wall.duration_since(previous_wall) {
+        Ok(wall_elapsed) => {
+            let drift = wall_elapsed.max(monotonic_elapsed) - wall_elapsed.min(monotonic_elapsed);
+
+            if drift > tolerance {
+                Some(format!(
+                    "Wall clock advanced {}s while {}s elapsed",
+                    wall_elapsed.as_secs(),
+                    monotonic_elapsed.as_secs()
+                ))
+            } else {
+                None
+            }
+        }
+        Err(e) => {
+            let backwards = e.duration();
+            if backwards + monotonic_elapsed > tolerance {
+                Some(format!("Wall clock moved backward by {}s", backwards.as_secs()))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: Duration = Duration::from_secs(10);
+
+    fn at(seconds: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + seconds)
+    }
+
+    #[test]
+    fn test_consistent_clocks_pass() {
+        assert!(check_clock_consistency(at(0), at(30), Duration::from_secs(31), TOLERANCE).is_none());
+    }
+
+    #[test]
+    fn test_backward_wall_clock_is_flagged() {
+        let reason = check_clock_consistency(at(600), at(0), Duration::from_secs(30), TOLERANCE).unwrap();
+        assert_eq!(reason, "Wall clock moved backward by 600s");
+    }
+
+    #[test]
+    fn test_forward_jump_is_flagged() {
+        let reason = check_clock_consistency(at(0), at(3600), Duration::from_secs(30), TOLERANCE).unwrap();
+        assert_eq!(reason, "Wall clock advanced 3600s while 30s elapsed");
+    }
+
+    #[test]
+    fn test_fixed_clock_advances_consistently() {
+        let clock = FixedClock::new(at(0), Duration::from_secs(1));
+        let first = clock.now();
+        let second = clock.now();
+
+        assert_eq!(first.wall, at(0));
+        assert_eq!(second.wall, at(1));
+        assert_eq!(second.since(&first), Duration::from_secs(1));
+        assert!(second.compare_with(&first, TOLERANCE).is_none());
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_system_clock_reads_a_suspend_aware_clock() {
+        let clock = SystemClock::new();
+        let first = clock.now();
+        std::thread::sleep(Duration::from_millis(20));
+        let second = clock.now();
+
+        assert!(clock.suspend_aware_origin.is_some());
+        assert!(second.since(&first) >= Duration::from_millis(20));
+        assert!(second.compare_with(&first, TOLERANCE).is_none());
+    }
+
+    #[test]
+    fn test_stalled_wall_clock_is_flagged() {
+        assert!(check_clock_consistency(at(0), at(0), Duration::from_secs(60), TOLERANCE).is_some());
+    }
+}
\ No newline at end of file
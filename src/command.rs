@@ -0,0 +1,118 @@
+use std::io;
+use std::process::{Command, Output};
+use std::sync::{Condvar, Mutex, MutexGuard, OnceLock};
+
+pub const DEFAULT_MAX_CONCURRENT_COMMANDS: usize = 4;
+
+/// Caps how many external commands (`pactl`, `xrandr`, `qwinsta`, ...) run at
+/// once. Detectors run synchronously, so a waiting caller blocks its thread
+/// until a slot frees up rather than awaiting.
+pub struct CommandLimiter {
+    state: Mutex<LimiterState>,
+    released: Condvar,
+}
+
+struct LimiterState {
+    max: usize,
+    running: usize,
+}
+
+/// Holds one slot until dropped.
+struct Permit<'a> {
+    limiter: &'a CommandLimiter,
+}
+
+impl CommandLimiter {
+    pub fn new(max: usize) -> Self {
+        Self {
+            state: Mutex::new(LimiterState { max: max.max(1), running: 0 }),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Changes the limit for commands started from now on.
+    pub fn set_max(&self, max: usize) {
+        self.lock().max = max.max(1);
+        self.released.notify_all();
+    }
+
+    /// Runs `f` once fewer than the maximum number of commands are running.
+    pub fn run<T>(&self, f: impl FnOnce() -> T) -> T {
+        let _permit = self.acquire();
+        f()
+    }
+
+    fn acquire(&self) -> Permit<'_> {
+        let mut state = self.lock();
+        while state.running >= state.max {
+            state = self.released.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        state.running += 1;
+        Permit { limiter: self }
+    }
+
+    // A panic while holding the lock can't leave the counts inconsistent,
+    // so a poisoned lock is still usable.
+    fn lock(&self) -> MutexGuard<'_, LimiterState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.limiter.lock().running -= 1;
+        self.limiter.released.notify_one();
+    }
+}
+
+/// The limiter shared by every detector, sized from `scan.max_concurrent_commands`.
+pub fn limiter() -> &'static CommandLimiter {
+    static LIMITER: OnceLock<CommandLimiter> = OnceLock::new();
+    LIMITER.get_or_init(|| CommandLimiter::new(DEFAULT_MAX_CONCURRENT_COMMANDS))
+}
+
+/// `Command::output` through the shared limiter.
+pub trait LimitedOutput {
+    fn limited_output(&mut self) -> io::Result<Output>;
+}
+
+impl LimitedOutput for Command {
+    fn limited_output(&mut self) -> io::Result<Output> {
+        limiter().run(|| self.output())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_limiter_caps_concurrent_commands() {
+        let limiter = Arc::new(CommandLimiter::new(2));
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let workers: Vec<_> = (0..8)
+            .map(|_| {
+                let (limiter, running, peak) = (limiter.clone(), running.clone(), peak.clone());
+                std::thread::spawn(move || {
+                    limiter.run(|| {
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(20));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+        assert_eq!(limiter.lock().running, 0);
+    }
+}
\ No newline at end of file
@@ -1,21 +1,647 @@
+use crate::{ReasonCode, Severity};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::Read;
 use std::path::Path;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
+    #[serde(default)]
+    pub mode: Mode,
     pub scan: ScanConfig,
     pub weights: WeightsConfig,
     pub thresholds: ThresholdsConfig,
     pub whitelist: WhitelistConfig,
     pub monitoring: MonitoringConfig,
+    #[serde(default)]
+    pub heuristics: HeuristicsConfig,
+    #[serde(default)]
+    pub hardware: HardwareConfig,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    #[serde(default)]
+    pub overlays: OverlayConfig,
+    #[serde(default)]
+    pub vm: VmConfig,
+    #[serde(default)]
+    pub session: SessionConfig,
+    #[serde(default)]
+    pub output: OutputConfig,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+    #[serde(default)]
+    pub denylist: DenylistConfig,
+    #[serde(default)]
+    pub correlation: CorrelationConfig,
+    #[serde(default)]
+    pub definitions: DefinitionsConfig,
+    #[serde(default)]
+    pub cadence: CadenceConfig,
+    /// Skipped when empty so saved configs can append `[[rules]]` tables.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RuleConfig>,
+}
+
+/// A custom process policy from `[[rules]]`. Every condition that is set must
+/// hold for the rule to match; its effects then apply on top of the built-in
+/// scoring.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RuleConfig {
+    pub name: String,
+    /// Matched like whitelist entries: `helper` matches any name containing
+    /// it, `helper.exe` that executable.
+    #[serde(default)]
+    pub process_name: Option<String>,
+    /// Case-insensitive fragment of the executable path.
+    #[serde(default)]
+    pub path_contains: Option<String>,
+    #[serde(default)]
+    pub capabilities: Vec<RuleCapability>,
+    /// Reason codes the built-in checks must already have given the process.
+    #[serde(default)]
+    pub reason_codes: Vec<ReasonCode>,
+    /// Added to the process's risk score; a matching process is flagged once
+    /// its score reaches `process_threshold`.
+    #[serde(default)]
+    pub risk_delta: f64,
+    /// Raise the report to at least this severity when a matching process is
+    /// flagged. `high` and `critical` also mark the scan as a breach.
+    #[serde(default)]
+    pub force_severity: Option<Severity>,
+    /// Never flag matching processes.
+    #[serde(default)]
+    pub suppress: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleCapability {
+    ScreenCapture,
+    AudioCapture,
+    Accessibility,
+}
+
+impl RuleConfig {
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("rules entries must have a name".to_string());
+        }
+
+        let has_condition = self.process_name.is_some()
+            || self.path_contains.is_some()
+            || !self.capabilities.is_empty()
+            || !self.reason_codes.is_empty();
+        if !has_condition {
+            return Err(format!("rule '{}' must have at least one condition", self.name));
+        }
+
+        if !(-1.0..=1.0).contains(&self.risk_delta) {
+            return Err(format!("rule '{}' risk_delta must be between -1.0 and 1.0", self.name));
+        }
+
+        let has_score_effect = self.risk_delta != 0.0 || self.force_severity.is_some();
+        if self.suppress && has_score_effect {
+            return Err(format!("rule '{}' can't both suppress and adjust scoring", self.name));
+        }
+        if !self.suppress && !has_score_effect {
+            return Err(format!("rule '{}' has no effect", self.name));
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether scans reach a verdict. `Observe` runs every check and writes full
+/// reports, but never marks a breach, so a pilot can collect data to
+/// calibrate thresholds before enforcing them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    #[default]
+    Enforce,
+    Observe,
+}
+
+/// How each scan is printed to the console. `Delta` prints the first report
+/// in full and then only what changed since the previous scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsoleMode {
+    #[default]
+    Full,
+    Delta,
+}
+
+/// Where scan results go besides the JSON report files.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OutputConfig {
+    /// Write one event per scan to syslog (Unix) or the Application event
+    /// log (Windows), at a level matching the report severity.
+    #[serde(default)]
+    pub system_log: bool,
+    /// Only write a report when its findings differ from the last one written.
+    #[serde(default)]
+    pub changes_only: bool,
+    /// In change-only mode, write a heartbeat after this long without output
+    /// so consumers can tell a quiet session from a dead one. 0 disables it.
+    #[serde(default = "default_heartbeat_seconds")]
+    pub heartbeat_seconds: u64,
+    /// After the first report that exceeds the threshold, write no more
+    /// report files unless the severity escalates, so the decisive report
+    /// stays the latest evidence. Scanning, heartbeats and notifications go on.
+    #[serde(default)]
+    pub freeze_reports_on_breach: bool,
+    /// Character budget for the one-line alert printed when a scan breaches;
+    /// offenders beyond it are summarized as `+N more`.
+    #[serde(default = "default_alert_line_max_chars")]
+    pub alert_line_max_chars: usize,
+    /// Decimals each report's risk scores are rounded to once the scan has
+    /// checked its thresholds.
+    #[serde(default = "default_score_precision")]
+    pub score_precision: u32,
+    /// Directory reports, heartbeats, the checkpoint and the verdict are
+    /// written to.
+    #[serde(default = "default_output_dir")]
+    pub output_dir: String,
+    /// Give every run its own subdirectory of `output_dir`, named from the
+    /// session id and launch time.
+    #[serde(default)]
+    pub per_run_directory: bool,
+    /// Add every non-whitelisted process the scan inspected to the report,
+    /// with its capability flags and risk, even when it wasn't flagged.
+    #[serde(default)]
+    pub full_capability_matrix: bool,
+    /// Also write what each scan read from the system, before scoring, to
+    /// `observations.jsonl`, so the session can be rescored later.
+    #[serde(default)]
+    pub observation_log: bool,
+    #[serde(default)]
+    pub console: ConsoleMode,
+    /// In delta mode, print the full report again every this many scans.
+    /// 0 prints it only for the first scan.
+    #[serde(default = "default_console_full_every")]
+    pub console_full_every: usize,
+    /// Every format each emitted report is written in, per-report JSON
+    /// files by default.
+    #[serde(default = "default_report_formats")]
+    pub report_formats: Vec<OutputSpec>,
+    /// Write an evidence bundle to the run directory whenever a scan turns
+    /// Critical, as `fairview bundle` would.
+    #[serde(default)]
+    pub bundle_on_critical: bool,
+}
+
+/// One output each emitted report is written to.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct OutputSpec {
+    pub format: ReportFormat,
+    /// File in the run directory, or an absolute path. Defaults per format;
+    /// `json` writes a file per report and takes none.
+    #[serde(default)]
+    pub destination: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    /// `detection_report_*.json`, pretty-printed, one per report.
+    Json,
+    /// One compact JSON line per report, appended to a single file.
+    Jsonl,
+    /// One row per report with the score, severity and alert line.
+    Csv,
+    /// A page listing every scan, ending with the session verdict.
+    Html,
+}
+
+fn default_report_formats() -> Vec<OutputSpec> {
+    vec![OutputSpec { format: ReportFormat::Json, destination: None }]
+}
+
+pub const DEFAULT_SCORE_PRECISION: u32 = 2;
+
+/// More decimals than this only exposes floating point noise.
+const MAX_SCORE_PRECISION: u32 = 6;
+
+fn default_score_precision() -> u32 {
+    DEFAULT_SCORE_PRECISION
+}
+
+fn default_output_dir() -> String {
+    ".".to_string()
+}
+
+fn default_console_full_every() -> usize {
+    20
+}
+
+fn default_heartbeat_seconds() -> u64 {
+    300
+}
+
+fn default_alert_line_max_chars() -> usize {
+    crate::alert::DEFAULT_ALERT_LINE_MAX_CHARS
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            system_log: false,
+            changes_only: false,
+            heartbeat_seconds: default_heartbeat_seconds(),
+            freeze_reports_on_breach: false,
+            alert_line_max_chars: default_alert_line_max_chars(),
+            score_precision: default_score_precision(),
+            output_dir: default_output_dir(),
+            per_run_directory: false,
+            full_capability_matrix: false,
+            observation_log: false,
+            console: ConsoleMode::default(),
+            console_full_every: default_console_full_every(),
+            report_formats: default_report_formats(),
+            bundle_on_critical: false,
+        }
+    }
+}
+
+/// Executables that force a critical verdict on sight, whatever their
+/// capabilities or score.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DenylistConfig {
+    /// Exact executable names, extension optional: `cluely` matches
+    /// `Cluely.exe` but not `cluely-helper`.
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// SHA-256 digests of executables, in hex.
+    #[serde(default)]
+    pub hashes: Vec<String>,
+    /// Executables whose digest is kept between scans; the least recently
+    /// used is dropped beyond this.
+    #[serde(default = "default_digest_cache_size")]
+    pub digest_cache_size: usize,
+}
+
+impl Default for DenylistConfig {
+    fn default() -> Self {
+        Self { names: Vec::new(), hashes: Vec::new(), digest_cache_size: default_digest_cache_size() }
+    }
+}
+
+fn default_digest_cache_size() -> usize {
+    4096
+}
+
+/// Cross-scan correlation: each scan's events (dropped executables,
+/// process starts, focus steals, overlays) are kept for `window_scans` scans
+/// and matched against the events that follow.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CorrelationConfig {
+    /// Scans an event stays eligible, counting the one it was seen in.
+    /// 0 disables correlation.
+    #[serde(default = "default_correlation_window_scans")]
+    pub window_scans: usize,
+    /// Directories watched for new executables, e.g. Downloads or a temp
+    /// folder, for the drop-then-run rule.
+    #[serde(default)]
+    pub watch_dirs: Vec<String>,
+    /// Added to the risk of a process implicated by a correlated finding.
+    #[serde(default = "default_correlation_weight")]
+    pub weight: f64,
+}
+
+fn default_correlation_window_scans() -> usize {
+    3
+}
+
+fn default_correlation_weight() -> f64 {
+    0.3
+}
+
+impl Default for CorrelationConfig {
+    fn default() -> Self {
+        Self {
+            window_scans: default_correlation_window_scans(),
+            watch_dirs: Vec::new(),
+            weight: default_correlation_weight(),
+        }
+    }
+}
+
+/// How often the heavier modules run, as "every N scans". Scans in between
+/// reuse the module's last result and list it in `carried_forward_modules`.
+/// Process and overlay monitoring run every scan, since the per-scan passes
+/// build on their output.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CadenceConfig {
+    #[serde(default = "default_cadence")]
+    pub vm_detection: usize,
+    #[serde(default = "default_cadence")]
+    pub audio: usize,
+    #[serde(default = "default_cadence")]
+    pub audio_loops: usize,
+    #[serde(default = "default_cadence")]
+    pub audio_devices: usize,
+    #[serde(default = "default_cadence")]
+    pub hardware: usize,
+    #[serde(default = "default_cadence")]
+    pub input_devices: usize,
+}
+
+fn default_cadence() -> usize {
+    1
+}
+
+impl Default for CadenceConfig {
+    fn default() -> Self {
+        Self {
+            vm_detection: default_cadence(),
+            audio: default_cadence(),
+            audio_loops: default_cadence(),
+            audio_devices: default_cadence(),
+            hardware: default_cadence(),
+            input_devices: default_cadence(),
+        }
+    }
+}
+
+/// Whitelist, denylist and suspicious-name definitions pulled from a
+/// central URL at startup and merged into the local lists. The last good
+/// copy is cached at `cache_file` for when the URL can't be reached.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DefinitionsConfig {
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default = "default_definitions_cache_file")]
+    pub cache_file: String,
+    /// Fetch again every this many minutes while monitoring.
+    #[serde(default)]
+    pub refresh_minutes: Option<u64>,
+    /// Shared key the definitions' HMAC-SHA256 signature must verify
+    /// against. Unsigned definitions are accepted when unset.
+    #[serde(default)]
+    pub signing_key: Option<String>,
+    #[serde(default = "default_definitions_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+fn default_definitions_cache_file() -> String {
+    "fairview_definitions.toml".to_string()
+}
+
+fn default_definitions_timeout_seconds() -> u64 {
+    10
+}
+
+impl Default for DefinitionsConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            cache_file: default_definitions_cache_file(),
+            refresh_minutes: None,
+            signing_key: None,
+            timeout_seconds: default_definitions_timeout_seconds(),
+        }
+    }
+}
+
+/// Push channels for scan results, each with the minimum severity that
+/// reaches it. The system log channel is `output.system_log`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NotificationConfig {
+    /// Receives the report JSON as a POST.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Program and arguments run with the one-line alert appended.
+    #[serde(default)]
+    pub command: Vec<String>,
+    #[serde(default = "default_webhook_min_severity")]
+    pub webhook_min_severity: Severity,
+    #[serde(default = "default_command_min_severity")]
+    pub command_min_severity: Severity,
+    #[serde(default)]
+    pub syslog_min_severity: Severity,
+    /// At most one webhook and one command notification per this many
+    /// seconds; critical scans are always sent.
+    #[serde(default = "default_throttle_seconds")]
+    pub throttle_seconds: u64,
+    #[serde(default = "default_notification_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// Webhook and command notifications only for scans that breached.
+    #[serde(default)]
+    pub only_on_threshold_exceeded: bool,
+    /// Webhook and command notifications only for scans at least this risky.
+    /// Neither gate holds back a critical scan.
+    #[serde(default)]
+    pub min_risk_to_alert: f64,
+}
+
+fn default_webhook_min_severity() -> Severity {
+    Severity::High
+}
+
+fn default_command_min_severity() -> Severity {
+    Severity::Critical
+}
+
+fn default_throttle_seconds() -> u64 {
+    300
+}
+
+fn default_notification_timeout_seconds() -> u64 {
+    10
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            webhook_url: None,
+            command: Vec::new(),
+            webhook_min_severity: default_webhook_min_severity(),
+            command_min_severity: default_command_min_severity(),
+            syslog_min_severity: Severity::Low,
+            throttle_seconds: default_throttle_seconds(),
+            timeout_seconds: default_notification_timeout_seconds(),
+            only_on_threshold_exceeded: false,
+            min_risk_to_alert: 0.0,
+        }
+    }
+}
+
+/// Opaque identifiers copied into every report and the session verdict so
+/// evidence can be matched to an interview. Fairview never interprets them.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SessionConfig {
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub candidate_ref: Option<String>,
+    /// Where the running session verdict is checkpointed after every scan.
+    /// Only used when `session_id` is set; a restart with the same id resumes
+    /// from it.
+    #[serde(default = "default_checkpoint_file")]
+    pub checkpoint_file: String,
+    /// Replace the OS username in reports and the verdict, since it often
+    /// names the candidate. The hostname and OS are kept.
+    #[serde(default)]
+    pub redact_os_username: bool,
+}
+
+fn default_checkpoint_file() -> String {
+    "fairview_checkpoint.json".to_string()
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            session_id: None,
+            candidate_ref: None,
+            checkpoint_file: default_checkpoint_file(),
+            redact_os_username: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ScanConfig {
     pub interval_seconds: u64,
     pub risk_threshold: f64,
+    /// `coding`, `system_design` or `behavioral`, in any case; see
+    /// `InterviewType`.
     pub interview_type: String,
+    #[serde(default = "default_min_sleep_seconds")]
+    pub min_sleep_seconds: u64,
+    #[serde(default = "default_max_interval_seconds")]
+    pub max_interval_seconds: u64,
+    #[serde(default = "default_true")]
+    pub auto_extend_interval: bool,
+    #[serde(default = "default_backoff_jitter")]
+    pub backoff_jitter: f64,
+    #[serde(default = "default_clock_tolerance_seconds")]
+    pub clock_tolerance_seconds: u64,
+    #[serde(default = "default_max_reported_processes")]
+    pub max_reported_processes: usize,
+    #[serde(default = "default_max_reported_overlays")]
+    pub max_reported_overlays: usize,
+    /// Processes and overlays scoring below this are left out of the report.
+    /// They still count toward the overall score.
+    #[serde(default)]
+    pub min_report_confidence: f64,
+    /// External commands (`pactl`, `xrandr`, `qwinsta`, ...) allowed to run
+    /// at once across all detectors.
+    #[serde(default = "default_max_concurrent_commands")]
+    pub max_concurrent_commands: usize,
+    #[serde(default)]
+    pub trigger: ScanTrigger,
+    /// With event triggers, scan at least this often even when nothing
+    /// happens.
+    #[serde(default = "default_keepalive_seconds")]
+    pub keepalive_seconds: u64,
+    /// Wait this long after monitoring starts before the first scan, so apps
+    /// the candidate opens while settling in don't all land in scan #1.
+    #[serde(default)]
+    pub startup_delay_seconds: u64,
+    /// Creating this file makes the next scan drop every cache first; the
+    /// file is removed once it has been seen.
+    #[serde(default)]
+    pub force_fresh_file: Option<String>,
+    /// Inspect at most this many processes per scan, non-whitelisted and
+    /// most recently started first. Unset inspects them all.
+    #[serde(default)]
+    pub max_processes_inspected: Option<usize>,
+    #[serde(default)]
+    pub exited_process_policy: ExitedProcessPolicy,
+}
+
+/// The kind of interview, which decides which developer tools are expected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InterviewType {
+    /// IDEs, terminals and compilers are part of the task.
+    #[default]
+    Coding,
+    /// IDEs are tolerated; terminals and compilers get no special pass.
+    SystemDesign,
+    /// No developer tools are expected, and an IDE opened mid-session is
+    /// itself a finding.
+    Behavioral,
+}
+
+impl std::str::FromStr for InterviewType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "coding" => Ok(InterviewType::Coding),
+            "system_design" => Ok(InterviewType::SystemDesign),
+            "behavioral" => Ok(InterviewType::Behavioral),
+            _ => Err(format!(
+                "Unknown interview_type '{}': expected coding, system_design or behavioral",
+                value
+            )),
+        }
+    }
+}
+
+/// What to do with a process that exits between being listed and being
+/// inspected. Either way the race is counted in the report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitedProcessPolicy {
+    /// Leave it out of the scan and log that it exited.
+    #[default]
+    Skip,
+    /// Inspect it once more, in case the failure was a transient one such
+    /// as a module list changing under the snapshot, and skip it if it has
+    /// still gone.
+    RetryOnce,
+}
+
+/// What starts a scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanTrigger {
+    /// Every `interval_seconds`.
+    #[default]
+    Interval,
+    /// On process starts and display changes, plus every `keepalive_seconds`.
+    /// Falls back to interval scans where no event source is available.
+    Events,
+}
+
+fn default_min_sleep_seconds() -> u64 {
+    5
+}
+
+fn default_max_interval_seconds() -> u64 {
+    300
+}
+
+fn default_backoff_jitter() -> f64 {
+    0.2
+}
+
+fn default_clock_tolerance_seconds() -> u64 {
+    10
+}
+
+fn default_max_reported_processes() -> usize {
+    50
+}
+
+fn default_max_reported_overlays() -> usize {
+    20
+}
+
+fn default_keepalive_seconds() -> u64 {
+    300
+}
+
+fn default_max_concurrent_commands() -> usize {
+    crate::command::DEFAULT_MAX_CONCURRENT_COMMANDS
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -25,6 +651,8 @@ pub struct WeightsConfig {
     pub audio_risk: f64,
     pub hardware_risk: f64,
     pub vm_risk: f64, 
+    #[serde(default)]
+    pub input_risk: f64,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -39,6 +667,44 @@ pub struct ThresholdsConfig {
 pub struct WhitelistConfig {
     pub processes: Vec<String>,
     pub directories: Vec<String>,
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    pub profile_switch_file: Option<String>,
+    #[serde(default)]
+    pub profiles: HashMap<String, WhitelistProfile>,
+    /// Trust whitelisted processes even when their name looks suspicious,
+    /// e.g. an internal tool called "interview-helper". Off by default.
+    #[serde(default)]
+    pub whitelist_overrides_suspicious_name: bool,
+    #[serde(default)]
+    pub conflict_resolution: WhitelistConflictMode,
+}
+
+/// What the whitelist does for a process that also trips a suspicious signal
+/// (a suspicious name, or screen, audio and accessibility access at once).
+/// Either way the conflict is listed in the report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhitelistConflictMode {
+    /// The whitelist clears the process as usual.
+    #[default]
+    Annotate,
+    /// The process is scored as if not whitelisted, at a reduced risk, and
+    /// flagged if that still reaches `process_threshold`.
+    Downgrade,
+}
+
+/// Stage-specific additions to the base whitelist, e.g. a "whiteboard" stage
+/// that allows a drawing tool but revokes the built-in IDE allowance.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct WhitelistProfile {
+    #[serde(default)]
+    pub processes: Vec<String>,
+    #[serde(default)]
+    pub directories: Vec<String>,
+    #[serde(default)]
+    pub revoked_processes: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -51,34 +717,583 @@ pub struct MonitoringConfig {
     pub collect_baseline: bool,
     pub baseline_duration_seconds: u64,
     pub continue_on_module_failure: bool,
+    #[serde(default)]
+    pub enable_input_device_monitoring: bool,
+    /// Start scanning right after the baseline instead of waiting for Enter.
+    #[serde(default)]
+    pub auto_start: bool,
+    /// Flag audio output that is being recorded back into an input.
+    #[serde(default)]
+    pub enable_audio_loop_detection: bool,
+    /// Flag audio inputs, such as a virtual cable or capture card, that
+    /// appear after the baseline.
+    #[serde(default)]
+    pub enable_audio_device_monitoring: bool,
+    /// Flag Windows Cloud Clipboard sync, which lets another signed-in
+    /// device put content on this machine's clipboard.
+    #[serde(default)]
+    pub enable_clipboard_monitoring: bool,
+    /// Inspect the TCP table for local servers the browser talks to.
+    #[serde(default)]
+    pub enable_network_monitoring: bool,
+    /// Check for windows parked on other virtual desktops / workspaces.
+    #[serde(default)]
+    pub enable_virtual_desktop_detection: bool,
+    /// Only scan processes in Fairview's own login session (Windows), leaving
+    /// out other users' sessions on the same machine.
+    #[serde(default)]
+    pub current_session_only: bool,
+    /// Where to save the collected baseline for `compare-baseline`.
+    #[serde(default)]
+    pub baseline_file: Option<String>,
+    /// Refresh the process and display baseline after this many scans, so
+    /// apps opened early in a long session stop counting as new.
+    #[serde(default)]
+    pub rebaseline_interval_scans: Option<usize>,
+    /// Same, after this much time; whichever interval passes first.
+    #[serde(default)]
+    pub rebaseline_interval_seconds: Option<u64>,
+    /// Keep processes flagged in the previous scan out of a refreshed
+    /// baseline, so an active offender isn't absorbed into it.
+    #[serde(default = "default_true")]
+    pub carry_forward_flagged: bool,
+    /// Warn once when an enabled module has run this many scans without
+    /// producing any output, a sign it doesn't work here. 0 disables it.
+    #[serde(default = "default_silent_module_scans")]
+    pub silent_module_scans: usize,
+    /// Warn at startup, and note on every report, when enforcing without a
+    /// baseline or `expected_displays` to compare against.
+    #[serde(default = "default_true")]
+    pub warn_without_baseline: bool,
+    /// Record system-wide CPU and memory use in every report.
+    #[serde(default = "default_true")]
+    pub enable_system_load: bool,
 }
 
-impl Config {
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
-        let contents = fs::read_to_string(path)
-            .map_err(|e| format!("Failed to read config file: {}", e))?;
-        
-        let config: Config = toml::from_str(&contents)
-            .map_err(|e| format!("Failed to parse config file: {}", e))?;
-        
-        config.validate()?;
-        
-        Ok(config)
+fn default_silent_module_scans() -> usize {
+    10
+}
+
+/// Opt-in anonymized session summary. Off unless `enabled` is set and an
+/// `endpoint` is given.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TelemetryConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default = "default_telemetry_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+fn default_telemetry_timeout_seconds() -> u64 {
+    10
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: None,
+            timeout_seconds: default_telemetry_timeout_seconds(),
+        }
+    }
+}
+
+/// Known-benign layered windows (toasts, flyouts, game overlays) that are
+/// dropped before scoring. Process names match exactly and titles match as a
+/// substring, both case-insensitively.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OverlayConfig {
+    #[serde(default = "default_allowed_overlay_processes")]
+    pub allowed_processes: Vec<String>,
+    #[serde(default = "default_allowed_overlay_titles")]
+    pub allowed_titles: Vec<String>,
+    /// Sample the foreground window's owner this often, in milliseconds, to
+    /// catch processes that keep stealing input focus. Unset disables it.
+    /// Windows only.
+    #[serde(default)]
+    pub focus_sample_millis: Option<u64>,
+    /// Focus gains per minute, since the previous scan, at which a process
+    /// that owns an overlay, shows no window or isn't whitelisted is flagged.
+    #[serde(default = "default_focus_changes_per_minute")]
+    pub focus_changes_per_minute: f64,
+    #[serde(default = "default_focus_steal_weight")]
+    pub focus_steal_weight: f64,
+    /// Record the foreground application and its window title in every
+    /// report. Turn off where window titles are too personal to keep.
+    #[serde(default = "default_true")]
+    pub capture_foreground_app: bool,
+}
+
+fn default_focus_changes_per_minute() -> f64 {
+    6.0
+}
+
+fn default_focus_steal_weight() -> f64 {
+    0.4
+}
+
+fn default_allowed_overlay_processes() -> Vec<String> {
+    vec![
+        "ShellExperienceHost.exe".to_string(),
+        "StartMenuExperienceHost.exe".to_string(),
+        "TextInputHost.exe".to_string(),
+        "Discord.exe".to_string(),
+    ]
+}
+
+fn default_allowed_overlay_titles() -> Vec<String> {
+    vec![
+        "Volume Control".to_string(),
+        "New notification".to_string(),
+        "Discord Overlay".to_string(),
+    ]
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            allowed_processes: default_allowed_overlay_processes(),
+            allowed_titles: default_allowed_overlay_titles(),
+            focus_sample_millis: None,
+            focus_changes_per_minute: default_focus_changes_per_minute(),
+            focus_steal_weight: default_focus_steal_weight(),
+            capture_foreground_app: true,
+        }
+    }
+}
+
+/// Smoothing of the VM verdict across scans. Each scan's confidence is
+/// blended into a moving average as `factor * sample + (1 - factor) * previous`,
+/// so 1.0 disables smoothing and lower values need a longer streak to trip.
+/// The first scan's confidence seeds the average.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct VmConfig {
+    #[serde(default = "default_vm_smoothing_factor")]
+    pub smoothing_factor: f64,
+    /// Treat a detected VM as a critical breach whatever the overall score.
+    #[serde(default)]
+    pub hard_fail: bool,
+}
+
+fn default_vm_smoothing_factor() -> f64 {
+    0.5
+}
+
+impl Default for VmConfig {
+    fn default() -> Self {
+        Self {
+            smoothing_factor: default_vm_smoothing_factor(),
+            hard_fail: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HardwareConfig {
+    /// Displays the candidate is expected to have. Used as the comparison
+    /// baseline when no baseline is collected; a collected baseline wins.
+    #[serde(default)]
+    pub expected_displays: Vec<ExpectedDisplay>,
+    /// Flag HDMI capture cards (Elgato HD60, AVerMedia, Cam Link) that can
+    /// mirror the screen to a second device. Webcams are not flagged.
+    #[serde(default = "default_true")]
+    pub enable_capture_card_detection: bool,
+    #[serde(default = "default_capture_card_weight")]
+    pub capture_card_weight: f64,
+    /// Flag processes streaming the screen to another device (Miracast,
+    /// Chromecast senders, Steam Remote Play). Needs process monitoring.
+    #[serde(default = "default_true")]
+    pub enable_cast_detection: bool,
+    /// Added to the hardware score once per kind of streaming session.
+    #[serde(default = "default_cast_session_weight")]
+    pub cast_session_weight: f64,
+    #[serde(default)]
+    pub connection_weights: ConnectionWeights,
+    /// Consecutive scans a display being added, removed or re-enumerated
+    /// must persist before it counts as a change.
+    #[serde(default = "default_display_change_scans")]
+    pub display_change_scans: u32,
+    /// Hardware checks at session start whose display changes are flagged
+    /// but not scored, while a secondary monitor may still be initializing.
+    #[serde(default)]
+    pub display_grace_scans: u32,
+    /// Read the display configuration this often between full scans, to
+    /// catch a display enabled and disabled again in between. Off when unset.
+    #[serde(default)]
+    pub display_sample_seconds: Option<u64>,
+    /// Added to the hardware score when a sampled display state was gone by
+    /// the next full scan.
+    #[serde(default = "default_transient_display_weight")]
+    pub transient_display_weight: f64,
+    /// Treat a virtual display as a critical breach whatever the overall
+    /// score. `connection_weights.virtual` still adds to the score.
+    #[serde(default)]
+    pub virtual_display_hard_fail: bool,
+    /// Treat a wireless display, or a Miracast session sending to one, as a
+    /// critical breach whatever the overall score.
+    #[serde(default)]
+    pub wireless_display_hard_fail: bool,
+}
+
+fn default_transient_display_weight() -> f64 {
+    0.4
+}
+
+fn default_display_change_scans() -> u32 {
+    2
+}
+
+/// Risk added per display by how it is connected. USB and wireless
+/// displays are always flagged; other types only when their weight is set.
+/// A weight of 0.0 keeps the flag but adds nothing to the score.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ConnectionWeights {
+    #[serde(default)]
+    pub hdmi: f64,
+    #[serde(default)]
+    pub display_port: f64,
+    #[serde(default = "default_usb_display_weight")]
+    pub usb: f64,
+    #[serde(default, rename = "virtual")]
+    pub virtual_display: f64,
+    #[serde(default = "default_wireless_display_weight")]
+    pub wireless: f64,
+    #[serde(default)]
+    pub unknown: f64,
+}
+
+fn default_usb_display_weight() -> f64 {
+    0.2
+}
+
+fn default_wireless_display_weight() -> f64 {
+    0.25
+}
+
+impl Default for ConnectionWeights {
+    fn default() -> Self {
+        Self {
+            hdmi: 0.0,
+            display_port: 0.0,
+            usb: default_usb_display_weight(),
+            virtual_display: 0.0,
+            wireless: default_wireless_display_weight(),
+            unknown: 0.0,
+        }
+    }
+}
+
+fn default_capture_card_weight() -> f64 {
+    0.6
+}
+
+fn default_cast_session_weight() -> f64 {
+    0.5
+}
+
+impl Default for HardwareConfig {
+    fn default() -> Self {
+        Self {
+            expected_displays: Vec::new(),
+            enable_capture_card_detection: true,
+            capture_card_weight: default_capture_card_weight(),
+            enable_cast_detection: true,
+            cast_session_weight: default_cast_session_weight(),
+            connection_weights: ConnectionWeights::default(),
+            display_change_scans: default_display_change_scans(),
+            display_grace_scans: 0,
+            display_sample_seconds: None,
+            transient_display_weight: default_transient_display_weight(),
+            virtual_display_hard_fail: false,
+            wireless_display_hard_fail: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ExpectedDisplay {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Composite signals layered on top of the capability-count gate.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HeuristicsConfig {
+    /// Name patterns flagged as suspicious on top of the built-in ones.
+    #[serde(default)]
+    pub suspicious_names: Vec<String>,
+    /// Off by default: a single CPU sample reads most processes as idle, so
+    /// the profile needs tuning per fleet before it is worth turning on.
+    #[serde(default)]
+    pub enable_quiet_helper: bool,
+    #[serde(default = "default_quiet_helper_weight")]
+    pub quiet_helper_weight: f64,
+    #[serde(default = "default_quiet_helper_max_memory_mb")]
+    pub quiet_helper_max_memory_mb: u64,
+    #[serde(default = "default_quiet_helper_max_cpu_percent")]
+    pub quiet_helper_max_cpu_percent: f32,
+    #[serde(default = "default_quiet_helper_max_age_seconds")]
+    pub quiet_helper_max_age_seconds: u64,
+    /// When false, processes already running at baseline are trusted: their
+    /// screen, audio and accessibility capabilities are not checked.
+    #[serde(default = "default_true")]
+    pub flag_baseline_processes: bool,
+    /// Add a small risk to capture-capable, non-whitelisted processes whose
+    /// executable path can't be resolved, instead of scoring them as usual.
+    #[serde(default)]
+    pub flag_unknown_path_processes: bool,
+    /// Add risk to non-whitelisted processes whose executable lives on a
+    /// removable or network drive.
+    #[serde(default = "default_true")]
+    pub flag_external_drive_processes: bool,
+    #[serde(default = "default_external_drive_weight")]
+    pub external_drive_weight: f64,
+    /// Flag browsers, even whitelisted ones, launched by a process other
+    /// than another browser or one of `expected_browser_parents`; python,
+    /// node or a WebDriver as the parent points at automation.
+    #[serde(default = "default_true")]
+    pub enable_browser_parent_check: bool,
+    /// Executable names, extension optional, of the shells and launchers a
+    /// browser is normally started from.
+    #[serde(default = "default_expected_browser_parents")]
+    pub expected_browser_parents: Vec<String>,
+    #[serde(default = "default_unexpected_browser_parent_weight")]
+    pub unexpected_browser_parent_weight: f64,
+    /// Added, even for whitelisted IDEs, when one starts during a
+    /// behavioral interview.
+    #[serde(default = "default_behavioral_ide_weight")]
+    pub behavioral_ide_weight: f64,
+    /// Flag processes, even whitelisted ones, granted screen recording,
+    /// microphone or accessibility access after the baseline (macOS TCC).
+    #[serde(default = "default_true")]
+    pub flag_fresh_permission_grants: bool,
+    #[serde(default = "default_fresh_permission_grant_weight")]
+    pub fresh_permission_grant_weight: f64,
+    /// Exempt processes under `system_paths` from being flagged for holding
+    /// all three capabilities, which many OS services do.
+    #[serde(default = "default_true")]
+    pub exempt_system_paths: bool,
+    #[serde(default = "default_system_paths")]
+    pub system_paths: Vec<String>,
+    /// A process started during the interview less than this many seconds
+    /// before the scan has its risk multiplied, the more the newer it is.
+    #[serde(default = "default_recent_start_window_seconds")]
+    pub recent_start_window_seconds: u64,
+    /// Multiplier for a process that started right at the scan, falling to
+    /// 1.0 at the end of the window. 1.0 turns recency weighting off.
+    #[serde(default = "default_recent_start_multiplier")]
+    pub recent_start_multiplier: f64,
+    /// Flag non-whitelisted capable processes whose executable was written
+    /// less than `fresh_executable_window_seconds` before the scan.
+    #[serde(default = "default_true")]
+    pub flag_fresh_executables: bool,
+    #[serde(default = "default_fresh_executable_window_seconds")]
+    pub fresh_executable_window_seconds: u64,
+    #[serde(default = "default_fresh_executable_weight")]
+    pub fresh_executable_weight: f64,
+    /// Only flag a process once scans have seen it for at least this many
+    /// seconds, so a one-shot helper that exits straight away isn't
+    /// reported. 0 flags on first sight.
+    #[serde(default)]
+    pub min_process_lifetime_seconds: u64,
+    /// Added to the input category score while Cloud Clipboard sync is on.
+    #[serde(default = "default_cloud_clipboard_weight")]
+    pub cloud_clipboard_weight: f64,
+    /// Flag processes, even whitelisted ones, named like a Windows system
+    /// process (`svchost.exe`, `explorer.exe`) but running from outside
+    /// `system_paths`.
+    #[serde(default = "default_true")]
+    pub flag_system_name_impersonation: bool,
+    #[serde(default = "default_system_name_impersonation_weight")]
+    pub system_name_impersonation_weight: f64,
+    /// Only exempt system-path processes whose code signature verifies
+    /// (Authenticode, Windows only). Elsewhere nothing is exempt.
+    #[serde(default)]
+    pub require_signed_system_binaries: bool,
+    /// Add a small risk to processes holding far more GDI/USER objects per
+    /// MB of memory than is typical on the machine (Windows).
+    #[serde(default = "default_true")]
+    pub enable_gui_object_outliers: bool,
+    /// How many times the median objects-per-MB a process must reach.
+    #[serde(default = "default_gui_outlier_factor")]
+    pub gui_outlier_factor: f64,
+    /// Processes holding fewer GUI objects than this are never outliers.
+    #[serde(default = "default_gui_outlier_min_objects")]
+    pub gui_outlier_min_objects: u32,
+    /// System-wide CPU use at which the machine counts as saturated.
+    #[serde(default = "default_saturated_cpu_percent")]
+    pub saturated_cpu_percent: f32,
+    /// Multiplies signals derived from per-process CPU use while the machine
+    /// is saturated, since general load then says little about any one
+    /// process. 1.0 leaves them as they are.
+    #[serde(default = "default_saturated_cpu_signal_weight")]
+    pub saturated_cpu_signal_weight: f64,
+}
+
+fn default_expected_browser_parents() -> Vec<String> {
+    [
+        // Windows shell, and the brokers that open links from other apps
+        "explorer", "sihost", "runtimebroker", "svchost", "openwith",
+        // macOS
+        "launchd",
+        // Linux session managers, desktop shells and terminals' shells
+        "systemd", "gnome-shell", "plasmashell", "kwin_x11", "kwin_wayland", "xfce4-panel", "xdg-desktop-portal",
+        "xdg-open", "gio", "bash", "zsh", "fish",
+    ]
+    .iter()
+    .map(|name| name.to_string())
+    .collect()
+}
+
+fn default_system_paths() -> Vec<String> {
+    vec!["C:\\Windows\\System32".to_string(), "C:\\Windows\\SysWOW64".to_string()]
+}
+
+fn default_recent_start_window_seconds() -> u64 {
+    120
+}
+
+fn default_recent_start_multiplier() -> f64 {
+    1.5
+}
+
+fn default_fresh_executable_window_seconds() -> u64 {
+    3600
+}
+
+fn default_cloud_clipboard_weight() -> f64 {
+    0.5
+}
+
+fn default_fresh_executable_weight() -> f64 {
+    0.3
+}
+
+fn default_system_name_impersonation_weight() -> f64 {
+    0.6
+}
+
+fn default_unexpected_browser_parent_weight() -> f64 {
+    0.6
+}
+
+fn default_behavioral_ide_weight() -> f64 {
+    0.6
+}
+
+fn default_fresh_permission_grant_weight() -> f64 {
+    0.4
+}
+
+fn default_external_drive_weight() -> f64 {
+    0.3
+}
+
+fn default_gui_outlier_factor() -> f64 {
+    5.0
+}
+
+fn default_gui_outlier_min_objects() -> u32 {
+    500
+}
+
+fn default_saturated_cpu_percent() -> f32 {
+    90.0
+}
+
+fn default_saturated_cpu_signal_weight() -> f64 {
+    1.0
+}
+
+fn default_quiet_helper_weight() -> f64 {
+    0.3
+}
+
+fn default_quiet_helper_max_memory_mb() -> u64 {
+    50
+}
+
+fn default_quiet_helper_max_cpu_percent() -> f32 {
+    1.0
+}
+
+fn default_quiet_helper_max_age_seconds() -> u64 {
+    600
+}
+
+impl Default for HeuristicsConfig {
+    fn default() -> Self {
+        Self {
+            suspicious_names: Vec::new(),
+            enable_quiet_helper: false,
+            quiet_helper_weight: default_quiet_helper_weight(),
+            quiet_helper_max_memory_mb: default_quiet_helper_max_memory_mb(),
+            quiet_helper_max_cpu_percent: default_quiet_helper_max_cpu_percent(),
+            quiet_helper_max_age_seconds: default_quiet_helper_max_age_seconds(),
+            flag_baseline_processes: true,
+            flag_unknown_path_processes: false,
+            flag_external_drive_processes: true,
+            external_drive_weight: default_external_drive_weight(),
+            enable_browser_parent_check: true,
+            expected_browser_parents: default_expected_browser_parents(),
+            unexpected_browser_parent_weight: default_unexpected_browser_parent_weight(),
+            behavioral_ide_weight: default_behavioral_ide_weight(),
+            flag_fresh_permission_grants: true,
+            fresh_permission_grant_weight: default_fresh_permission_grant_weight(),
+            exempt_system_paths: true,
+            system_paths: default_system_paths(),
+            recent_start_window_seconds: default_recent_start_window_seconds(),
+            recent_start_multiplier: default_recent_start_multiplier(),
+            flag_fresh_executables: true,
+            fresh_executable_window_seconds: default_fresh_executable_window_seconds(),
+            fresh_executable_weight: default_fresh_executable_weight(),
+            min_process_lifetime_seconds: 0,
+            cloud_clipboard_weight: default_cloud_clipboard_weight(),
+            flag_system_name_impersonation: true,
+            system_name_impersonation_weight: default_system_name_impersonation_weight(),
+            require_signed_system_binaries: false,
+            enable_gui_object_outliers: true,
+            gui_outlier_factor: default_gui_outlier_factor(),
+            gui_outlier_min_objects: default_gui_outlier_min_objects(),
+            saturated_cpu_percent: default_saturated_cpu_percent(),
+            saturated_cpu_signal_weight: default_saturated_cpu_signal_weight(),
+        }
     }
+}
 
-    pub fn default() -> Self {
+impl Default for Config {
+    fn default() -> Self {
         Self {
+            mode: Mode::Enforce,
             scan: ScanConfig {
                 interval_seconds: 30,
                 risk_threshold: 0.5,
                 interview_type: "coding".to_string(),
+                min_sleep_seconds: default_min_sleep_seconds(),
+                max_interval_seconds: default_max_interval_seconds(),
+                auto_extend_interval: true,
+                backoff_jitter: default_backoff_jitter(),
+                clock_tolerance_seconds: default_clock_tolerance_seconds(),
+                max_reported_processes: default_max_reported_processes(),
+                max_reported_overlays: default_max_reported_overlays(),
+                min_report_confidence: 0.0,
+                max_concurrent_commands: default_max_concurrent_commands(),
+                trigger: ScanTrigger::Interval,
+                keepalive_seconds: default_keepalive_seconds(),
+                startup_delay_seconds: 0,
+                force_fresh_file: None,
+                max_processes_inspected: None,
+                exited_process_policy: ExitedProcessPolicy::Skip,
             },
             weights: WeightsConfig {
                 process_risk: 0.30,
                 overlay_risk: 0.20,
                 audio_risk: 0.10,
-                hardware_risk: 0.15,
+                hardware_risk: 0.10,
                 vm_risk: 0.25,
+                input_risk: 0.05,
             },
             thresholds: ThresholdsConfig {
                 process_threshold: 0.6,
@@ -100,6 +1315,11 @@ impl Config {
                     "/usr/bin".to_string(),
                     "/Applications".to_string(),
                 ],
+                active_profile: None,
+                profile_switch_file: None,
+                profiles: HashMap::new(),
+                whitelist_overrides_suspicious_name: false,
+                conflict_resolution: WhitelistConflictMode::default(),
             },
             monitoring: MonitoringConfig {
                 enable_process_monitoring: true,
@@ -110,16 +1330,83 @@ impl Config {
                 collect_baseline: true,
                 baseline_duration_seconds: 10,
                 continue_on_module_failure: true,
+                enable_input_device_monitoring: false,
+                auto_start: false,
+                enable_audio_loop_detection: false,
+                enable_audio_device_monitoring: false,
+                enable_clipboard_monitoring: false,
+                enable_network_monitoring: false,
+                enable_virtual_desktop_detection: false,
+                current_session_only: false,
+                baseline_file: None,
+                rebaseline_interval_scans: None,
+                rebaseline_interval_seconds: None,
+                carry_forward_flagged: true,
+                silent_module_scans: default_silent_module_scans(),
+                warn_without_baseline: true,
+                enable_system_load: true,
             },
+            heuristics: HeuristicsConfig::default(),
+            hardware: HardwareConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            notifications: NotificationConfig::default(),
+            denylist: DenylistConfig::default(),
+            correlation: CorrelationConfig::default(),
+            definitions: DefinitionsConfig::default(),
+            cadence: CadenceConfig::default(),
+            overlays: OverlayConfig::default(),
+            vm: VmConfig::default(),
+            session: SessionConfig::default(),
+            output: OutputConfig::default(),
+            rules: Vec::new(),
         }
     }
+}
+
+impl Config {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file: {}", e))?;
+        
+        Self::from_toml_str(&contents)
+    }
+
+    /// Reads TOML until EOF, e.g. from stdin for `--config -`.
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, String> {
+        let mut contents = String::new();
+        reader
+            .read_to_string(&mut contents)
+            .map_err(|e| format!("Failed to read config: {}", e))?;
+
+        Self::from_toml_str(&contents)
+    }
+
+    /// A copy safe to print or hand over, with the definitions signing key
+    /// replaced by `provenance::REDACTED`.
+    pub fn redacted(&self) -> Config {
+        let mut config = self.clone();
+        if config.definitions.signing_key.is_some() {
+            config.definitions.signing_key = Some(crate::provenance::REDACTED.to_string());
+        }
+        config
+    }
+
+    pub fn from_toml_str(contents: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(contents)
+            .map_err(|e| format!("Failed to parse config: {}", e))?;
+        
+        config.validate()?;
+        
+        Ok(config)
+    }
 
     fn validate(&self) -> Result<(), String> {
         let weight_sum = self.weights.process_risk 
             + self.weights.overlay_risk 
             + self.weights.audio_risk 
             + self.weights.hardware_risk
-            + self.weights.vm_risk;
+            + self.weights.vm_risk
+            + self.weights.input_risk;
         
         if (weight_sum - 1.0).abs() > 0.01 {
             return Err(format!(
@@ -132,7 +1419,8 @@ impl Config {
             || self.weights.overlay_risk < 0.0 
             || self.weights.audio_risk < 0.0 
             || self.weights.hardware_risk < 0.0 
-            || self.weights.vm_risk < 0.0 {
+            || self.weights.vm_risk < 0.0
+            || self.weights.input_risk < 0.0 {
             return Err("All weights must be positive".to_string());
         }
 
@@ -140,6 +1428,281 @@ impl Config {
             return Err("risk_threshold must be between 0.0 and 1.0".to_string());
         }
 
+        if !(0.0..=1.0).contains(&self.scan.min_report_confidence) {
+            return Err("min_report_confidence must be between 0.0 and 1.0".to_string());
+        }
+
+        if self.scan.keepalive_seconds == 0 {
+            return Err("keepalive_seconds must be at least 1".to_string());
+        }
+
+        if self.monitoring.rebaseline_interval_scans == Some(0) {
+            return Err("rebaseline_interval_scans must be greater than 0".to_string());
+        }
+
+        if self.monitoring.rebaseline_interval_seconds == Some(0) {
+            return Err("rebaseline_interval_seconds must be greater than 0".to_string());
+        }
+
+        if self.scan.max_concurrent_commands == 0 {
+            return Err("max_concurrent_commands must be at least 1".to_string());
+        }
+
+        self.scan.interview_type.parse::<InterviewType>()?;
+
+        if self.scan.max_processes_inspected == Some(0) {
+            return Err("max_processes_inspected must be greater than 0".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.scan.backoff_jitter) {
+            return Err("backoff_jitter must be between 0.0 and 1.0".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.heuristics.quiet_helper_weight) {
+            return Err("quiet_helper_weight must be between 0.0 and 1.0".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.heuristics.external_drive_weight) {
+            return Err("external_drive_weight must be between 0.0 and 1.0".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.heuristics.unexpected_browser_parent_weight) {
+            return Err("unexpected_browser_parent_weight must be between 0.0 and 1.0".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.heuristics.behavioral_ide_weight) {
+            return Err("behavioral_ide_weight must be between 0.0 and 1.0".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.heuristics.fresh_permission_grant_weight) {
+            return Err("fresh_permission_grant_weight must be between 0.0 and 1.0".to_string());
+        }
+
+        if !(1.0..=3.0).contains(&self.heuristics.recent_start_multiplier) {
+            return Err("recent_start_multiplier must be between 1.0 and 3.0".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.heuristics.fresh_executable_weight) {
+            return Err("fresh_executable_weight must be between 0.0 and 1.0".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.heuristics.cloud_clipboard_weight) {
+            return Err("cloud_clipboard_weight must be between 0.0 and 1.0".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.heuristics.system_name_impersonation_weight) {
+            return Err("system_name_impersonation_weight must be between 0.0 and 1.0".to_string());
+        }
+
+        if self.heuristics.gui_outlier_factor < 1.0 {
+            return Err("gui_outlier_factor must be at least 1.0".to_string());
+        }
+
+        if !(self.heuristics.saturated_cpu_percent > 0.0 && self.heuristics.saturated_cpu_percent <= 100.0) {
+            return Err("saturated_cpu_percent must be above 0 and at most 100".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.heuristics.saturated_cpu_signal_weight) {
+            return Err("saturated_cpu_signal_weight must be between 0.0 and 1.0".to_string());
+        }
+
+        if self.hardware.expected_displays.iter().any(|d| d.width == 0 || d.height == 0) {
+            return Err("expected_displays entries must have a non-zero width and height".to_string());
+        }
+
+        if self.vm.smoothing_factor <= 0.0 || self.vm.smoothing_factor > 1.0 {
+            return Err("vm.smoothing_factor must be greater than 0.0 and at most 1.0".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.hardware.capture_card_weight) {
+            return Err("capture_card_weight must be between 0.0 and 1.0".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.hardware.cast_session_weight) {
+            return Err("cast_session_weight must be between 0.0 and 1.0".to_string());
+        }
+
+        if self.hardware.display_sample_seconds == Some(0) {
+            return Err("display_sample_seconds must be greater than 0".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.hardware.transient_display_weight) {
+            return Err("transient_display_weight must be between 0.0 and 1.0".to_string());
+        }
+
+        if self.output.output_dir.trim().is_empty() {
+            return Err("output_dir must not be empty".to_string());
+        }
+
+        if self.output.score_precision > MAX_SCORE_PRECISION {
+            return Err(format!("score_precision must be at most {}", MAX_SCORE_PRECISION));
+        }
+
+        if self.output.alert_line_max_chars < crate::alert::MIN_ALERT_LINE_CHARS {
+            return Err(format!(
+                "alert_line_max_chars must be at least {}",
+                crate::alert::MIN_ALERT_LINE_CHARS
+            ));
+        }
+
+        self.check_report_formats()?;
+
+        if self.hardware.display_change_scans == 0 {
+            return Err("display_change_scans must be at least 1".to_string());
+        }
+
+        let weights = &self.hardware.connection_weights;
+        let connection_weights = [
+            weights.hdmi, weights.display_port, weights.usb,
+            weights.virtual_display, weights.wireless, weights.unknown,
+        ];
+        if connection_weights.iter().any(|w| !(0.0..=1.0).contains(w)) {
+            return Err("hardware.connection_weights must be between 0.0 and 1.0".to_string());
+        }
+
+        if let Some(ref url) = self.notifications.webhook_url {
+            if !(url.starts_with("https://") || url.starts_with("http://")) {
+                return Err("notifications.webhook_url must be an http(s) URL".to_string());
+            }
+        }
+
+        if let Some(hash) = self.denylist.hashes.iter().find(|h| h.len() != 64 || !h.chars().all(|c| c.is_ascii_hexdigit())) {
+            return Err(format!("denylist hash {} is not a hex SHA-256 digest", hash));
+        }
+
+        if self.denylist.names.iter().any(|name| name.trim().is_empty()) {
+            return Err("denylist names must not be empty".to_string());
+        }
+
+        if self.denylist.digest_cache_size == 0 {
+            return Err("digest_cache_size must be at least 1".to_string());
+        }
+
+        if self.notifications.command.first().is_some_and(|program| program.trim().is_empty()) {
+            return Err("notifications.command must start with a program".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.notifications.min_risk_to_alert) {
+            return Err("notifications.min_risk_to_alert must be between 0.0 and 1.0".to_string());
+        }
+
+        self.check_network_sinks(cfg!(feature = "network"))?;
+
+        if self.telemetry.enabled {
+            match self.telemetry.endpoint.as_deref() {
+                Some(endpoint) if endpoint.starts_with("https://") || endpoint.starts_with("http://") => {}
+                _ => return Err("telemetry.endpoint must be an http(s) URL when telemetry is enabled".to_string()),
+            }
+        }
+
+        if self.overlays.allowed_titles.iter().any(|t| t.trim().is_empty()) {
+            return Err("overlays.allowed_titles entries must not be empty".to_string());
+        }
+
+        if self.overlays.focus_sample_millis == Some(0) {
+            return Err("overlays.focus_sample_millis must be greater than 0".to_string());
+        }
+
+        if self.overlays.focus_changes_per_minute <= 0.0 {
+            return Err("overlays.focus_changes_per_minute must be greater than 0".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.overlays.focus_steal_weight) {
+            return Err("overlays.focus_steal_weight must be between 0.0 and 1.0".to_string());
+        }
+
+        if let Some(ref url) = self.definitions.url {
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                return Err("definitions.url must be an http(s) URL".to_string());
+            }
+        }
+        if self.definitions.cache_file.trim().is_empty() {
+            return Err("definitions.cache_file must not be empty".to_string());
+        }
+        let cadence = &self.cadence;
+        let every = [
+            cadence.vm_detection,
+            cadence.audio,
+            cadence.audio_loops,
+            cadence.audio_devices,
+            cadence.hardware,
+            cadence.input_devices,
+        ];
+        if every.contains(&0) {
+            return Err("cadence values must be at least 1 (run every scan)".to_string());
+        }
+
+        if self.scan.force_fresh_file.as_deref().is_some_and(|path| path.trim().is_empty()) {
+            return Err("scan.force_fresh_file must not be empty".to_string());
+        }
+        if self.definitions.refresh_minutes == Some(0) {
+            return Err("definitions.refresh_minutes must be greater than 0".to_string());
+        }
+        if self.heuristics.suspicious_names.iter().any(|name| name.trim().is_empty()) {
+            return Err("heuristics.suspicious_names entries must not be empty".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.correlation.weight) {
+            return Err("correlation.weight must be between 0.0 and 1.0".to_string());
+        }
+        if self.correlation.watch_dirs.iter().any(|dir| dir.trim().is_empty()) {
+            return Err("correlation.watch_dirs entries must not be empty".to_string());
+        }
+
+        if let Some(ref profile) = self.whitelist.active_profile {
+            if !self.whitelist.profiles.contains_key(profile) {
+                return Err(format!("active_profile '{}' is not defined in whitelist.profiles", profile));
+            }
+        }
+
+        for rule in &self.rules {
+            rule.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// At least one format, and no two of them writing the same file.
+    fn check_report_formats(&self) -> Result<(), String> {
+        let formats = &self.output.report_formats;
+        if formats.is_empty() {
+            return Err("report_formats must name at least one format".to_string());
+        }
+        let mut destinations = HashSet::new();
+        for spec in formats {
+            match (spec.format, spec.destination.as_deref()) {
+                (ReportFormat::Json, Some(_)) => {
+                    return Err("report_formats: json writes one file per report and takes no destination".to_string());
+                }
+                (_, Some(destination)) if destination.trim().is_empty() => {
+                    return Err("report_formats: destination must not be empty".to_string());
+                }
+                _ => {}
+            }
+            let key = spec.destination_file().map(str::to_string).unwrap_or_default();
+            if !destinations.insert(key) {
+                return Err(format!("report_formats: {:?} is configured twice for the same destination", spec.format));
+            }
+        }
+        Ok(())
+    }
+
+    /// Webhooks and telemetry are compiled out without the `network`
+    /// feature, so a config that uses them is rejected instead of silently
+    /// sending nothing.
+    fn check_network_sinks(&self, network: bool) -> Result<(), String> {
+        if network {
+            return Ok(());
+        }
+        if self.notifications.webhook_url.is_some() {
+            return Err("notifications.webhook_url needs Fairview built with the network feature".to_string());
+        }
+        if self.telemetry.enabled {
+            return Err("telemetry needs Fairview built with the network feature".to_string());
+        }
+        if self.definitions.url.is_some() {
+            return Err("definitions.url needs Fairview built with the network feature".to_string());
+        }
         Ok(())
     }
 
@@ -171,6 +1734,19 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_report_formats_are_validated() {
+        let mut config = Config::default();
+        config.output.report_formats.clear();
+        assert!(config.validate().is_err());
+
+        let csv = |destination: Option<&str>| OutputSpec { format: ReportFormat::Csv, destination: destination.map(str::to_string) };
+        config.output.report_formats = vec![csv(None), csv(Some("reports.csv"))];
+        assert!(config.validate().is_err());
+        config.output.report_formats = vec![csv(None), csv(Some("analysts.csv"))];
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_invalid_threshold() {
         let mut config = Config::default();
@@ -178,10 +1754,93 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_unknown_active_profile() {
+        let mut config = Config::default();
+        config.whitelist.active_profile = Some("whiteboard".to_string());
+        assert!(config.validate().is_err());
+
+        config.whitelist.profiles.insert("whiteboard".to_string(), WhitelistProfile::default());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_inline_and_reader_match_file() {
+        let toml_string = toml::to_string_pretty(&Config::default()).unwrap();
+        let path = std::env::temp_dir().join(format!("fairview_config_test_{}.toml", std::process::id()));
+        fs::write(&path, &toml_string).unwrap();
+
+        let from_file = Config::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let from_inline = Config::from_toml_str(&toml_string).unwrap();
+        let from_reader = Config::from_reader(&mut toml_string.as_bytes()).unwrap();
+
+        let expected = toml::to_string(&from_file).unwrap();
+        assert_eq!(toml::to_string(&from_inline).unwrap(), expected);
+        assert_eq!(toml::to_string(&from_reader).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_invalid_inline_config_is_rejected() {
+        let err = Config::from_toml_str("[scan\ninterval_seconds = ").unwrap_err();
+        assert!(err.starts_with("Failed to parse config"));
+
+        let mut config = Config::default();
+        config.weights.process_risk = 0.9;
+        let invalid = toml::to_string(&config).unwrap();
+        assert!(Config::from_reader(&mut invalid.as_bytes()).unwrap_err().starts_with("Weights must sum"));
+    }
+
+    #[test]
+    fn test_rules_are_validated_at_load() {
+        let base = toml::to_string(&Config::default()).unwrap();
+        let with_rule = |rule: &str| Config::from_toml_str(&format!("{}\n[[rules]]\n{}", base, rule));
+
+        let config = with_rule(
+            "name = \"audio helper\"\nprocess_name = \"helper\"\ncapabilities = [\"audio_capture\"]\n\
+             reason_codes = [\"started_during_interview\"]\nrisk_delta = 0.5\nforce_severity = \"high\"",
+        ).unwrap();
+        assert_eq!(config.rules[0].capabilities, vec![RuleCapability::AudioCapture]);
+        assert_eq!(config.rules[0].force_severity, Some(Severity::High));
+
+        assert!(with_rule("name = \"anything\"\nrisk_delta = 0.5").unwrap_err().contains("at least one condition"));
+        assert!(with_rule("name = \"noop\"\nprocess_name = \"x\"").unwrap_err().contains("no effect"));
+        assert!(with_rule("name = \"both\"\nprocess_name = \"x\"\nsuppress = true\nrisk_delta = 0.1").is_err());
+        assert!(with_rule("name = \"bad\"\nprocess_name = \"x\"\ncapabilities = [\"telepathy\"]\nsuppress = true").is_err());
+    }
+
+    #[test]
+    fn test_webhook_is_rejected_without_network_feature() {
+        let mut config = Config::default();
+        assert!(config.check_network_sinks(false).is_ok());
+
+        config.notifications.webhook_url = Some("https://proctor.example.com/fairview".to_string());
+        assert!(config.check_network_sinks(true).is_ok());
+        let error = config.check_network_sinks(false).unwrap_err();
+        assert!(error.contains("network feature"), "{}", error);
+    }
+
+    #[test]
+    fn test_long_interval_loads_without_raising_the_cap() {
+        let mut config = Config::default();
+        config.scan.interval_seconds = 600;
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_invalid_interview_type() {
         let mut config = Config::default();
         config.scan.interview_type = "invalid".to_string();
         assert!(config.validate().is_err());
     }
+
+    #[test]
+    fn test_interview_types_parse_case_insensitively() {
+        assert_eq!("coding".parse(), Ok(InterviewType::Coding));
+        assert_eq!("System_Design".parse(), Ok(InterviewType::SystemDesign));
+        assert_eq!("BEHAVIORAL".parse(), Ok(InterviewType::Behavioral));
+        let error = "pairing".parse::<InterviewType>().unwrap_err();
+        assert!(error.contains("'pairing'"), "{}", error);
+        assert!(Config::default().validate().is_ok());
+    }
 }
\ No newline at end of file
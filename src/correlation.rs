@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// Something a scan observed that a cross-scan rule can match against
+/// another scan's events.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanEvent {
+    /// An executable appeared in one of `correlation.watch_dirs`.
+    FileDropped { path: String },
+    /// A non-whitelisted process that wasn't running at the previous scan.
+    ProcessStarted { pid: u32, name: String, path: String },
+    FocusStolen { pid: u32 },
+    OverlayShown { owner_pid: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorrelationRule {
+    /// A process started from an executable dropped into a watched
+    /// directory shortly before.
+    DropThenRun,
+    /// A process flagged for focus stealing in more than one scan.
+    FocusBurst,
+    /// A process that showed a hidden overlay and stole focus in different
+    /// scans, which neither scan sees on its own.
+    OverlayThenFocus,
+}
+
+impl CorrelationRule {
+    pub fn description(&self) -> &'static str {
+        match self {
+            CorrelationRule::DropThenRun => "Started from a freshly dropped executable",
+            CorrelationRule::FocusBurst => "Stole input focus across several scans",
+            CorrelationRule::OverlayThenFocus => "Showed a hidden overlay and stole input focus",
+        }
+    }
+}
+
+/// Events from different scans within the correlation window that together
+/// implicate one process.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelatedFinding {
+    pub rule: CorrelationRule,
+    pub pid: u32,
+    /// Scans whose events matched, oldest first.
+    pub scans: Vec<usize>,
+}
+
+/// The events of the last `window_scans` scans.
+#[derive(Debug)]
+pub struct EventBuffer {
+    window_scans: usize,
+    scans: VecDeque<(usize, Vec<ScanEvent>)>,
+}
+
+impl EventBuffer {
+    pub fn new(window_scans: usize) -> Self {
+        Self { window_scans, scans: VecDeque::new() }
+    }
+
+    /// Adds a scan's events, dropping scans that fall out of the window.
+    pub fn push(&mut self, scan_number: usize, events: Vec<ScanEvent>) {
+        self.scans.push_back((scan_number, events));
+        while self
+            .scans
+            .front()
+            .is_some_and(|&(scan, _)| scan + self.window_scans <= scan_number)
+        {
+            self.scans.pop_front();
+        }
+    }
+
+    /// Findings that involve the latest scan, so each is reported when its
+    /// last piece arrives rather than again on every later scan.
+    pub fn correlate(&self) -> Vec<CorrelatedFinding> {
+        let Some((latest, events)) = self.scans.back() else {
+            return Vec::new();
+        };
+        let mut findings = Vec::new();
+
+        for event in events {
+            match event {
+                ScanEvent::ProcessStarted { pid, path, .. } => {
+                    let dropped = self.scans_with(|e| {
+                        matches!(e, ScanEvent::FileDropped { path: dropped } if same_path(dropped, path))
+                    });
+                    if let Some(&first) = dropped.first() {
+                        findings.push(finding(CorrelationRule::DropThenRun, *pid, first, *latest));
+                    }
+                }
+                ScanEvent::FocusStolen { pid } => {
+                    let earlier = self.earlier_scans_with(|e| *e == ScanEvent::FocusStolen { pid: *pid });
+                    if let Some(&first) = earlier.first() {
+                        findings.push(finding(CorrelationRule::FocusBurst, *pid, first, *latest));
+                    }
+                    let overlays = self.earlier_scans_with(|e| *e == ScanEvent::OverlayShown { owner_pid: *pid });
+                    if let Some(&first) = overlays.first() {
+                        findings.push(finding(CorrelationRule::OverlayThenFocus, *pid, first, *latest));
+                    }
+                }
+                ScanEvent::OverlayShown { owner_pid } => {
+                    let focus = self.earlier_scans_with(|e| *e == ScanEvent::FocusStolen { pid: *owner_pid });
+                    if let Some(&first) = focus.first() {
+                        findings.push(finding(CorrelationRule::OverlayThenFocus, *owner_pid, first, *latest));
+                    }
+                }
+                ScanEvent::FileDropped { .. } => {}
+            }
+        }
+
+        findings.sort_by_key(|f| (f.pid, f.rule as u8));
+        findings.dedup_by_key(|f| (f.pid, f.rule));
+        findings
+    }
+
+    fn scans_with(&self, matches: impl Fn(&ScanEvent) -> bool) -> Vec<usize> {
+        self.scans
+            .iter()
+            .filter(|(_, events)| events.iter().any(&matches))
+            .map(|&(scan, _)| scan)
+            .collect()
+    }
+
+    fn earlier_scans_with(&self, matches: impl Fn(&ScanEvent) -> bool) -> Vec<usize> {
+        let latest = self.scans.back().map(|&(scan, _)| scan);
+        self.scans_with(matches).into_iter().filter(|&scan| Some(scan) != latest).collect()
+    }
+}
+
+fn finding(rule: CorrelationRule, pid: u32, first: usize, latest: usize) -> CorrelatedFinding {
+    let scans = if first == latest { vec![latest] } else { vec![first, latest] };
+    CorrelatedFinding { rule, pid, scans }
+}
+
+fn same_path(a: &str, b: &str) -> bool {
+    if cfg!(any(target_os = "windows", target_os = "macos")) {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+/// Reports executables that appear in a set of directories between polls.
+/// The first poll only records what is already there.
+#[derive(Debug)]
+pub struct DropWatcher {
+    dirs: Vec<PathBuf>,
+    known: Option<HashSet<PathBuf>>,
+}
+
+impl DropWatcher {
+    pub fn new(dirs: &[String]) -> Self {
+        Self { dirs: dirs.iter().map(PathBuf::from).collect(), known: None }
+    }
+
+    /// Executables added since the previous poll. Unreadable directories
+    /// are skipped.
+    pub fn poll(&mut self) -> Vec<String> {
+        let current: HashSet<PathBuf> = self
+            .dirs
+            .iter()
+            .filter_map(|dir| std::fs::read_dir(dir).ok())
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| is_executable(path))
+            .collect();
+
+        let mut dropped: Vec<String> = match self.known {
+            Some(ref known) => current
+                .difference(known)
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect(),
+            None => Vec::new(),
+        };
+        dropped.sort();
+        self.known = Some(current);
+        dropped
+    }
+}
+
+const EXECUTABLE_EXTENSIONS: &[&str] = &["exe", "com", "scr", "bat", "cmd", "ps1", "msi", "app", "appimage"];
+
+fn is_executable(path: &Path) -> bool {
+    let by_extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| EXECUTABLE_EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)));
+    if by_extension {
+        return true;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    }
+
+    #[cfg(not(unix))]
+    {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_then_run_across_scans_is_correlated() {
+        let mut buffer = EventBuffer::new(3);
+        buffer.push(4, vec![ScanEvent::FileDropped { path: "/tmp/helper".to_string() }]);
+        assert!(buffer.correlate().is_empty());
+
+        buffer.push(5, vec![ScanEvent::ProcessStarted {
+            pid: 77,
+            name: "helper".to_string(),
+            path: "/tmp/helper".to_string(),
+        }]);
+        let findings = buffer.correlate();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule, CorrelationRule::DropThenRun);
+        assert_eq!(findings[0].pid, 77);
+        assert_eq!(findings[0].scans, vec![4, 5]);
+    }
+
+    #[test]
+    fn test_events_outside_window_are_forgotten() {
+        let mut buffer = EventBuffer::new(2);
+        buffer.push(1, vec![ScanEvent::FocusStolen { pid: 9 }]);
+        buffer.push(2, Vec::new());
+        buffer.push(3, vec![ScanEvent::FocusStolen { pid: 9 }]);
+        assert!(buffer.correlate().is_empty());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,251 @@
+use crate::config::{Config, DefinitionsConfig};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Response header carrying the hex HMAC-SHA256 of the body, keyed with
+/// `definitions.signing_key`.
+pub const SIGNATURE_HEADER: &str = "x-fairview-signature";
+
+/// Whitelist, denylist and suspicious-name entries published centrally, as
+/// a TOML document. They add to the local config's lists, never replace
+/// them.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Definitions {
+    #[serde(default)]
+    pub whitelist_processes: Vec<String>,
+    #[serde(default)]
+    pub whitelist_directories: Vec<String>,
+    #[serde(default)]
+    pub denylist_names: Vec<String>,
+    #[serde(default)]
+    pub denylist_hashes: Vec<String>,
+    #[serde(default)]
+    pub suspicious_names: Vec<String>,
+}
+
+impl Definitions {
+    pub fn parse(body: &str) -> Result<Self, String> {
+        toml::from_str(body).map_err(|e| format!("Invalid definitions: {}", e))
+    }
+
+    /// The copy in `cache_file`. With `signing_key` set, the signature
+    /// cached beside it must still verify, so an edited cache is refused.
+    pub fn load_cached(config: &DefinitionsConfig) -> Result<Self, String> {
+        let path = &config.cache_file;
+        let body = std::fs::read_to_string(path)
+            .map_err(|e| format!("No cached definitions at {}: {}", path, e))?;
+        if let Some(ref key) = config.signing_key {
+            let signature = std::fs::read_to_string(signature_file(path)).unwrap_or_default();
+            if !verify_signature(body.as_bytes(), key, &signature) {
+                return Err(format!("Cached definitions at {} do not verify against signing_key", path));
+            }
+        }
+        Self::parse(&body)
+    }
+
+    /// Adds the entries `config` doesn't have yet and returns those, so they
+    /// can be taken out again with `remove_from` when newer definitions
+    /// arrive.
+    pub fn apply_to(&self, config: &mut Config) -> Definitions {
+        Definitions {
+            whitelist_processes: merge(&mut config.whitelist.processes, &self.whitelist_processes),
+            whitelist_directories: merge(&mut config.whitelist.directories, &self.whitelist_directories),
+            denylist_names: merge(&mut config.denylist.names, &self.denylist_names),
+            denylist_hashes: merge(&mut config.denylist.hashes, &self.denylist_hashes),
+            suspicious_names: merge(&mut config.heuristics.suspicious_names, &self.suspicious_names),
+        }
+    }
+
+    pub fn remove_from(&self, config: &mut Config) {
+        unmerge(&mut config.whitelist.processes, &self.whitelist_processes);
+        unmerge(&mut config.whitelist.directories, &self.whitelist_directories);
+        unmerge(&mut config.denylist.names, &self.denylist_names);
+        unmerge(&mut config.denylist.hashes, &self.denylist_hashes);
+        unmerge(&mut config.heuristics.suspicious_names, &self.suspicious_names);
+    }
+}
+
+fn merge(local: &mut Vec<String>, remote: &[String]) -> Vec<String> {
+    let mut added = Vec::new();
+    for entry in remote {
+        if !local.iter().any(|existing| existing.eq_ignore_ascii_case(entry)) {
+            local.push(entry.clone());
+            added.push(entry.clone());
+        }
+    }
+    added
+}
+
+fn unmerge(local: &mut Vec<String>, added: &[String]) {
+    local.retain(|entry| !added.contains(entry));
+}
+
+/// Where the signature of the cached definitions is kept.
+fn signature_file(cache_file: &str) -> String {
+    format!("{}.sig", cache_file)
+}
+
+/// Writes the definitions and, when they came signed, their signature.
+fn write_cache(config: &DefinitionsConfig, body: &str, signature: Option<&str>) -> Result<(), String> {
+    std::fs::write(&config.cache_file, body).map_err(|e| format!("{}: {}", config.cache_file, e))?;
+    let signature_path = signature_file(&config.cache_file);
+    match signature {
+        Some(signature) => std::fs::write(&signature_path, signature).map_err(|e| format!("{}: {}", signature_path, e)),
+        None => {
+            std::fs::remove_file(&signature_path).ok();
+            Ok(())
+        }
+    }
+}
+
+/// Fetches the definitions from `definitions.url` and caches them. When the
+/// fetch, the signature check or parsing fails, the last cached copy is used
+/// instead; `None` means neither is available and only the local lists
+/// apply.
+pub async fn resolve(config: &DefinitionsConfig) -> Option<Definitions> {
+    let url = config.url.as_deref()?;
+
+    let fetched = fetch(config, url).await.and_then(|(body, signature)| {
+        Definitions::parse(&body).map(|definitions| (body, signature, definitions))
+    });
+    match fetched {
+        Ok((body, signature, definitions)) => {
+            if let Err(e) = write_cache(config, &body, signature.as_deref()) {
+                println!("[!] Failed to cache definitions: {}", e);
+            }
+            println!("[+] Loaded definitions from {}", url);
+            Some(definitions)
+        }
+        Err(e) => {
+            println!("[!] {}", e);
+            match Definitions::load_cached(config) {
+                Ok(definitions) => {
+                    println!("[*] Falling back to cached definitions from {}", config.cache_file);
+                    Some(definitions)
+                }
+                Err(e) => {
+                    println!("[!] {}; using the configured lists only", e);
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// The body and, when the response carried one, its signature.
+#[cfg(feature = "network")]
+async fn fetch(config: &DefinitionsConfig, url: &str) -> Result<(String, Option<String>), String> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(config.timeout_seconds))
+        .build()
+        .map_err(|e| format!("Failed to build definitions client: {}", e))?;
+
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch definitions: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Definitions endpoint returned {}", response.status()));
+    }
+
+    let signature = response
+        .headers()
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read definitions: {}", e))?;
+
+    if let Some(ref key) = config.signing_key {
+        let signature = signature.as_deref().ok_or_else(|| format!("Definitions carry no {} header", SIGNATURE_HEADER))?;
+        if !verify_signature(body.as_bytes(), key, signature) {
+            return Err("Definitions signature does not verify".to_string());
+        }
+    }
+    Ok((body, signature))
+}
+
+#[cfg(not(feature = "network"))]
+async fn fetch(_config: &DefinitionsConfig, _url: &str) -> Result<(String, Option<String>), String> {
+    Err("Fetching definitions needs Fairview built with the network feature".to_string())
+}
+
+/// Whether `signature` is the hex HMAC-SHA256 of `body` under `key`.
+pub fn verify_signature(body: &[u8], key: &str, signature: &str) -> bool {
+    let expected: String = hmac_sha256(key.as_bytes(), body).iter().map(|byte| format!("{:02x}", byte)).collect();
+    let signature = signature.trim().to_lowercase();
+    // Compare every byte so the time taken doesn't reveal the matching prefix.
+    expected.len() == signature.len()
+        && expected.bytes().zip(signature.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let inner_pad: Vec<u8> = block.iter().map(|byte| byte ^ 0x36).collect();
+    let outer_pad: Vec<u8> = block.iter().map(|byte| byte ^ 0x5c).collect();
+    let inner = Sha256::new().chain_update(&inner_pad).chain_update(message).finalize();
+    Sha256::new().chain_update(&outer_pad).chain_update(inner).finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_matches_rfc4231_vector() {
+        let signature = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+        assert!(verify_signature(b"what do ya want for nothing?", "Jefe", signature));
+        assert!(!verify_signature(b"what do ya want for nothing!", "Jefe", signature));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_url_falls_back_to_cache() {
+        let cache = std::env::temp_dir().join(format!("fairview_definitions_test_{}.toml", std::process::id()));
+        std::fs::write(&cache, "denylist_names = [\"cluely\"]\nwhitelist_processes = [\"zoom.exe\"]\n").unwrap();
+        let config = DefinitionsConfig {
+            url: Some("http://127.0.0.1:9/definitions.toml".to_string()),
+            cache_file: cache.to_string_lossy().into_owned(),
+            timeout_seconds: 1,
+            ..Default::default()
+        };
+
+        let definitions = resolve(&config).await;
+        std::fs::remove_file(&cache).ok();
+
+        let definitions = definitions.expect("cached definitions are used");
+        assert_eq!(definitions.denylist_names, vec!["cluely".to_string()]);
+        assert_eq!(definitions.whitelist_processes, vec!["zoom.exe".to_string()]);
+    }
+
+    #[test]
+    fn test_tampered_cache_is_refused_when_signed() {
+        let cache = std::env::temp_dir().join(format!("fairview_definitions_signed_{}.toml", std::process::id()));
+        let config = DefinitionsConfig {
+            cache_file: cache.to_string_lossy().into_owned(),
+            signing_key: Some("Jefe".to_string()),
+            ..Default::default()
+        };
+        let body = "denylist_names = [\"cluely\"]\n";
+        let signature: String = hmac_sha256(b"Jefe", body.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect();
+        write_cache(&config, body, Some(&signature)).unwrap();
+        let genuine = Definitions::load_cached(&config);
+
+        std::fs::write(&cache, format!("{}whitelist_processes = [\"helper.exe\"]\n", body)).unwrap();
+        let tampered = Definitions::load_cached(&config);
+        std::fs::remove_file(&cache).ok();
+        std::fs::remove_file(signature_file(&config.cache_file)).ok();
+
+        assert_eq!(genuine.unwrap().denylist_names, vec!["cluely".to_string()]);
+        assert!(tampered.unwrap_err().contains("do not verify"));
+    }
+}
\ No newline at end of file
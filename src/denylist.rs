@@ -0,0 +1,108 @@
+use crate::config::DenylistConfig;
+use crate::lru::LruCache;
+use crate::process_monitor::NormalizedProcess;
+use crate::Process;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::File;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Executables whose presence alone disqualifies, by exact name or by the
+/// SHA-256 of the file. Unlike the suspicious-name heuristic this is a hard
+/// rule: capabilities, weights and the whitelist don't matter.
+pub struct Denylist {
+    names: Vec<String>,
+    hashes: HashSet<String>,
+    /// Digests by path, valid while the file's size and modification time
+    /// are unchanged, so each executable is hashed once. At most
+    /// `digest_cache_size` are kept.
+    digests: Mutex<LruCache<CachedDigest>>,
+}
+
+struct CachedDigest {
+    len: u64,
+    modified: Option<SystemTime>,
+    digest: Option<String>,
+}
+
+impl Denylist {
+    pub fn new(config: &DenylistConfig) -> Self {
+        Self {
+            names: config.names.clone(),
+            hashes: config.hashes.iter().map(|hash| hash.to_lowercase()).collect(),
+            digests: Mutex::new(LruCache::new(config.digest_cache_size)),
+        }
+    }
+
+    /// What puts the process on the denylist, e.g. `name cluely.exe`.
+    pub fn match_process(&self, process: &Process, normalized: &NormalizedProcess) -> Option<String> {
+        if let Some(name) = self.names.iter().find(|name| normalized.stem_is(name)) {
+            return Some(format!("name {}", name));
+        }
+        if self.hashes.is_empty() || normalized.has_unknown_path() {
+            return None;
+        }
+
+        let digest = self.digest_of(&process.path)?;
+        self.hashes.contains(&digest).then(|| format!("SHA-256 {}", digest))
+    }
+
+    /// Forgets every cached digest, so each executable is hashed again.
+    pub fn clear_cache(&self) {
+        self.digests.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    fn digest_of(&self, path: &str) -> Option<String> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok();
+        let mut digests = self.digests.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cached) = digests.get(path).filter(|c| c.len == metadata.len() && c.modified == modified) {
+            return cached.digest.clone();
+        }
+
+        let digest = sha256_file(path).ok();
+        digests.insert(path, CachedDigest { len: metadata.len(), modified, digest: digest.clone() });
+        digest
+    }
+}
+
+/// Lowercase hex SHA-256 of the file at `path`.
+pub fn sha256_file(path: &str) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_cache_evicts_least_recently_used() {
+        let dir = std::env::temp_dir().join(format!("fairview_digest_cache_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths: Vec<String> = (0..3)
+            .map(|i| {
+                let path = dir.join(format!("tool{}", i));
+                std::fs::write(&path, format!("tool {}", i)).unwrap();
+                path.to_string_lossy().into_owned()
+            })
+            .collect();
+        let denylist = Denylist::new(&DenylistConfig { digest_cache_size: 2, ..Default::default() });
+
+        denylist.digest_of(&paths[0]);
+        denylist.digest_of(&paths[1]);
+        denylist.digest_of(&paths[0]);
+        denylist.digest_of(&paths[2]);
+        let cached: Vec<bool> = {
+            let digests = denylist.digests.lock().unwrap();
+            paths.iter().map(|path| digests.contains(path)).collect()
+        };
+        std::fs::remove_dir_all(&dir).ok();
+
+        // tool0 was used again after tool1, so tool1 made room for tool2.
+        assert_eq!(cached, vec![true, false, true]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+
+/// Which virtual desktop (Windows) or workspace (EWMH) a window is on,
+/// relative to the one the candidate is looking at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DesktopAssignment {
+    Current,
+    Other,
+    /// Sticky windows shown on every desktop.
+    AllDesktops,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopWindow {
+    pub handle: usize,
+    pub owner_pid: u32,
+    pub title: String,
+    pub assignment: DesktopAssignment,
+}
+
+/// A window of a flagged process parked on a desktop other than the current
+/// one, where a screen check or screen share does not see it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParkedWindow {
+    pub handle: usize,
+    pub owner_pid: u32,
+    pub owner_name: String,
+    pub title: String,
+}
+
+/// EWMH desktop index for windows shown on all desktops (`0xFFFFFFFF`).
+const EWMH_ALL_DESKTOPS: i64 = -1;
+
+/// Places a window by its EWMH `_NET_WM_DESKTOP` index given the current
+/// desktop from `_NET_CURRENT_DESKTOP`.
+pub fn classify_desktop(window_desktop: i64, current_desktop: i64) -> DesktopAssignment {
+    if window_desktop == EWMH_ALL_DESKTOPS || window_desktop == 0xFFFF_FFFF {
+        DesktopAssignment::AllDesktops
+    } else if window_desktop == current_desktop {
+        DesktopAssignment::Current
+    } else {
+        DesktopAssignment::Other
+    }
+}
+
+#[derive(Default)]
+pub struct DesktopDetector;
+
+impl DesktopDetector {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Top-level windows with their desktop assignment. macOS Spaces have no
+    /// public API, so nothing is reported there.
+    pub fn desktop_windows(&self) -> Result<Vec<DesktopWindow>, String> {
+        #[cfg(target_os = "windows")]
+        {
+            self.windows_desktop_windows()
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let current = parse_wmctrl_current_desktop(&run("wmctrl", &["-d"])?)
+                .ok_or_else(|| "wmctrl reported no current desktop".to_string())?;
+            Ok(parse_wmctrl_windows(&run("wmctrl", &["-l", "-p"])?, current))
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            Ok(Vec::new())
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    use crate::command::LimitedOutput;
+
+    let output = std::process::Command::new(program)
+        .args(args)
+        .limited_output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", program, output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Reads `_NET_CURRENT_DESKTOP` from `wmctrl -d`, where the current desktop
+/// is marked with `*` in the second column.
+pub fn parse_wmctrl_current_desktop(output: &str) -> Option<i64> {
+    output.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let index = fields.next()?.parse().ok()?;
+        (fields.next()? == "*").then_some(index)
+    })
+}
+
+/// Parses `wmctrl -l -p` rows: window id, `_NET_WM_DESKTOP`, PID, client
+/// machine and title.
+pub fn parse_wmctrl_windows(output: &str, current_desktop: i64) -> Vec<DesktopWindow> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let handle = usize::from_str_radix(fields.next()?.trim_start_matches("0x"), 16).ok()?;
+            let desktop: i64 = fields.next()?.parse().ok()?;
+            let owner_pid: u32 = fields.next()?.parse().ok()?;
+            let _host = fields.next()?;
+            Some(DesktopWindow {
+                handle,
+                owner_pid,
+                title: fields.collect::<Vec<_>>().join(" "),
+                assignment: classify_desktop(desktop, current_desktop),
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+impl DesktopDetector {
+    fn windows_desktop_windows(&self) -> Result<Vec<DesktopWindow>, String> {
+        use windows::Win32::Foundation::*;
+        use windows::Win32::System::Com::*;
+        use windows::Win32::UI::Shell::{IVirtualDesktopManager, VirtualDesktopManager};
+        use windows::Win32::UI::WindowsAndMessaging::*;
+
+        unsafe {
+            // Fails with RPC_E_CHANGED_MODE if the thread is already in an
+            // STA; COM is usable either way, but only a successful call is
+            // balanced with CoUninitialize.
+            let initialized = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+
+            let result = (|| {
+                let manager: IVirtualDesktopManager =
+                    CoCreateInstance(&VirtualDesktopManager, None, CLSCTX_ALL)
+                        .map_err(|e| format!("Failed to create virtual desktop manager: {}", e))?;
+
+                let mut handles: Vec<HWND> = Vec::new();
+                EnumWindows(Some(Self::collect_window), LPARAM(&mut handles as *mut Vec<HWND> as isize))
+                    .map_err(|e| format!("Failed to enumerate windows: {}", e))?;
+
+                let mut windows = Vec::new();
+                for hwnd in handles {
+                    if !IsWindowVisible(hwnd).as_bool() {
+                        continue;
+                    }
+                    let Ok(on_current) = manager.IsWindowOnCurrentVirtualDesktop(hwnd) else {
+                        continue;
+                    };
+
+                    let mut title_buf = [0u16; 256];
+                    let title_len = GetWindowTextW(hwnd, &mut title_buf).max(0) as usize;
+
+                    let mut pid: u32 = 0;
+                    GetWindowThreadProcessId(hwnd, Some(&mut pid));
+
+                    windows.push(DesktopWindow {
+                        handle: hwnd.0 as usize,
+                        owner_pid: pid,
+                        title: String::from_utf16_lossy(&title_buf[..title_len]),
+                        assignment: if on_current.as_bool() {
+                            DesktopAssignment::Current
+                        } else {
+                            DesktopAssignment::Other
+                        },
+                    });
+                }
+                Ok(windows)
+            })();
+
+            if initialized {
+                CoUninitialize();
+            }
+            result
+        }
+    }
+
+    unsafe extern "system" fn collect_window(
+        hwnd: windows::Win32::Foundation::HWND,
+        lparam: windows::Win32::Foundation::LPARAM,
+    ) -> windows::Win32::Foundation::BOOL {
+        let handles = &mut *(lparam.0 as *mut Vec<windows::Win32::Foundation::HWND>);
+        handles.push(hwnd);
+        windows::Win32::Foundation::BOOL(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_desktop() {
+        assert_eq!(classify_desktop(1, 1), DesktopAssignment::Current);
+        assert_eq!(classify_desktop(3, 1), DesktopAssignment::Other);
+        assert_eq!(classify_desktop(-1, 1), DesktopAssignment::AllDesktops);
+        assert_eq!(classify_desktop(0xFFFF_FFFF, 0), DesktopAssignment::AllDesktops);
+    }
+
+    #[test]
+    fn test_parse_wmctrl() {
+        let desktops = "\
+0  - DG: 3840x1080  VP: N/A  WA: 0,27 3840x1053  Workspace 1
+1  * DG: 3840x1080  VP: 0,0  WA: 0,27 3840x1053  Workspace 2
+";
+        assert_eq!(parse_wmctrl_current_desktop(desktops), Some(1));
+        assert_eq!(parse_wmctrl_current_desktop(""), None);
+
+        let windows = "\
+0x02a00003  1 2210   kiosk Visual Studio Code
+0x03c00007  0 4521   kiosk Interview Copilot - answers
+0x01e00001 -1 1180   kiosk Top Panel
+";
+        let parsed = parse_wmctrl_windows(windows, 1);
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[0].assignment, DesktopAssignment::Current);
+        assert_eq!(parsed[1].handle, 0x03c00007);
+        assert_eq!(parsed[1].owner_pid, 4521);
+        assert_eq!(parsed[1].title, "Interview Copilot - answers");
+        assert_eq!(parsed[1].assignment, DesktopAssignment::Other);
+        assert_eq!(parsed[2].assignment, DesktopAssignment::AllDesktops);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,311 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Kind of drive an executable lives on. Tools started from a USB stick or a
+/// network share leave no trace on the local disk, so those two count as
+/// external.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriveType {
+    #[default]
+    Unknown,
+    Fixed,
+    /// USB sticks, SD cards, optical media and external disks.
+    Removable,
+    Network,
+}
+
+impl DriveType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DriveType::Unknown => "unknown drive",
+            DriveType::Fixed => "fixed drive",
+            DriveType::Removable => "removable drive",
+            DriveType::Network => "network drive",
+        }
+    }
+
+    pub fn is_external(&self) -> bool {
+        matches!(self, DriveType::Removable | DriveType::Network)
+    }
+}
+
+/// One line of `/proc/mounts` (or of macOS `mount` output).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mount {
+    pub device: String,
+    pub mount_point: String,
+    pub fs_type: String,
+}
+
+/// Filesystems served over the network. FUSE mounts report `fuse.<helper>`.
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "afpfs", "webdav", "ncpfs", "afs", "9p", "ceph", "glusterfs",
+    "fuse.sshfs", "fuse.rclone", "fuse.s3fs", "fuse.davfs2",
+];
+
+/// Where desktop automounters put removable media (udisks on Linux, the
+/// Finder on macOS).
+const REMOVABLE_MOUNT_ROOTS: &[&str] = &["/media", "/run/media", "/Volumes"];
+
+/// Parses `/proc/mounts`. Spaces in mount points are escaped as `\040`.
+pub fn parse_proc_mounts(contents: &str) -> Vec<Mount> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some(Mount {
+                device: unescape_mount_field(fields.next()?),
+                mount_point: unescape_mount_field(fields.next()?),
+                fs_type: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parses macOS `mount` output, e.g.
+/// `//alice@nas/tools on /Volumes/tools (smbfs, nodev, nosuid, mounted by alice)`.
+pub fn parse_mount_output(output: &str) -> Vec<Mount> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (device, rest) = line.split_once(" on ")?;
+            let (mount_point, options) = rest.rsplit_once(" (")?;
+            let fs_type = options.split([',', ')']).next()?.trim();
+            Some(Mount {
+                device: device.to_string(),
+                mount_point: mount_point.to_string(),
+                fs_type: fs_type.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn unescape_mount_field(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut rest = field;
+    while let Some(index) = rest.find('\\') {
+        out.push_str(&rest[..index]);
+        let escape = rest.get(index + 1..index + 4);
+        match escape.and_then(|digits| u8::from_str_radix(digits, 8).ok()) {
+            Some(byte) => {
+                out.push(byte as char);
+                rest = &rest[index + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[index + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Classifies a Unix path by the mount it resolves to: the mount with the
+/// longest mount point containing it, the last one mounted winning ties.
+/// `is_removable_device` is asked about the mount's device, e.g. `/dev/sdb1`.
+pub fn mount_drive_type(path: &str, mounts: &[Mount], mut is_removable_device: impl FnMut(&str) -> bool) -> DriveType {
+    if !path.starts_with('/') {
+        return DriveType::Unknown;
+    }
+    let Some(mount) = mounts
+        .iter()
+        .filter(|mount| is_within(path, &mount.mount_point))
+        .max_by_key(|mount| mount.mount_point.len())
+    else {
+        return DriveType::Unknown;
+    };
+
+    if NETWORK_FILESYSTEMS.contains(&mount.fs_type.as_str()) {
+        DriveType::Network
+    } else if is_removable_device(&mount.device)
+        || REMOVABLE_MOUNT_ROOTS.iter().any(|root| is_within(&mount.mount_point, root) && mount.mount_point != *root)
+    {
+        DriveType::Removable
+    } else {
+        DriveType::Fixed
+    }
+}
+
+fn is_within(path: &str, directory: &str) -> bool {
+    let directory = directory.trim_end_matches('/');
+    path.starts_with(directory) && matches!(path.as_bytes().get(directory.len()), None | Some(b'/'))
+}
+
+// Values `GetDriveTypeW` returns.
+const DRIVE_REMOVABLE: u32 = 2;
+const DRIVE_FIXED: u32 = 3;
+const DRIVE_REMOTE: u32 = 4;
+const DRIVE_CDROM: u32 = 5;
+const DRIVE_RAMDISK: u32 = 6;
+
+/// Classifies a Windows path. UNC paths are network shares; a drive letter
+/// is looked up with `drive_kind`, which receives the root (`E:\`) and
+/// answers like `GetDriveTypeW`.
+pub fn windows_drive_type(path: &str, mut drive_kind: impl FnMut(&str) -> u32) -> DriveType {
+    let path = path.strip_prefix(r"\\?\").unwrap_or(path);
+    if path.starts_with(r"\\") || path.get(..4).is_some_and(|prefix| prefix.eq_ignore_ascii_case(r"UNC\")) {
+        return DriveType::Network;
+    }
+
+    let bytes = path.as_bytes();
+    if bytes.len() < 2 || !bytes[0].is_ascii_alphabetic() || bytes[1] != b':' {
+        return DriveType::Unknown;
+    }
+    match drive_kind(&format!("{}:\\", bytes[0].to_ascii_uppercase() as char)) {
+        DRIVE_REMOVABLE | DRIVE_CDROM => DriveType::Removable,
+        DRIVE_REMOTE => DriveType::Network,
+        DRIVE_FIXED | DRIVE_RAMDISK => DriveType::Fixed,
+        _ => DriveType::Unknown,
+    }
+}
+
+/// Per-listing lookups: the `GetDriveTypeW` answer per root on Windows,
+/// whether a device is removable elsewhere.
+#[cfg(target_os = "windows")]
+type LookupCache = HashMap<String, u32>;
+#[cfg(not(target_os = "windows"))]
+type LookupCache = HashMap<String, bool>;
+
+/// Resolves drive types for one process listing, reading the mount table
+/// once and caching per-drive lookups.
+#[derive(Default)]
+pub struct DriveResolver {
+    #[cfg_attr(target_os = "windows", allow(dead_code))]
+    mounts: Vec<Mount>,
+    cache: LookupCache,
+}
+
+impl DriveResolver {
+    pub fn load() -> Self {
+        #[cfg(target_os = "linux")]
+        let mounts = std::fs::read_to_string("/proc/mounts")
+            .map(|contents| parse_proc_mounts(&contents))
+            .unwrap_or_default();
+
+        #[cfg(target_os = "macos")]
+        let mounts = {
+            use crate::command::LimitedOutput;
+            std::process::Command::new("mount")
+                .limited_output()
+                .map(|output| parse_mount_output(&String::from_utf8_lossy(&output.stdout)))
+                .unwrap_or_default()
+        };
+
+        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+        let mounts = Vec::new();
+
+        Self { mounts, cache: LookupCache::new() }
+    }
+
+    pub fn drive_type(&mut self, path: &str) -> DriveType {
+        let cache = &mut self.cache;
+
+        #[cfg(target_os = "windows")]
+        {
+            use windows::core::HSTRING;
+            use windows::Win32::Storage::FileSystem::GetDriveTypeW;
+
+            windows_drive_type(path, |root| {
+                *cache
+                    .entry(root.to_string())
+                    .or_insert_with(|| unsafe { GetDriveTypeW(&HSTRING::from(root)) })
+            })
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            mount_drive_type(path, &self.mounts, |device| {
+                *cache.entry(device.to_string()).or_insert_with(|| is_removable_block_device(device))
+            })
+        }
+    }
+}
+
+/// Whether sysfs marks the block device behind `/dev/<name>` removable or
+/// places it on a USB bus. USB disks often report `removable` as 0, hence
+/// the bus check.
+#[cfg(target_os = "linux")]
+fn is_removable_block_device(device: &str) -> bool {
+    let Some(name) = device.strip_prefix("/dev/") else {
+        return false;
+    };
+    let Ok(sysfs_path) = std::fs::canonicalize(format!("/sys/class/block/{}", name)) else {
+        return false;
+    };
+    if sysfs_path.components().any(|c| c.as_os_str().to_string_lossy().starts_with("usb")) {
+        return true;
+    }
+    // Partitions inherit the flag from their parent disk.
+    [sysfs_path.join("removable"), sysfs_path.join("../removable")]
+        .iter()
+        .find_map(|flag| std::fs::read_to_string(flag).ok())
+        .is_some_and(|flag| flag.trim() == "1")
+}
+
+/// The mount table doesn't say elsewhere; `/Volumes` mounts are caught by
+/// their mount point instead.
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn is_removable_block_device(_device: &str) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROC_MOUNTS: &str = "\
+/dev/nvme0n1p2 / ext4 rw,relatime 0 0
+/dev/nvme0n1p1 /boot/efi vfat rw,relatime 0 0
+//nas/tools /mnt/tools cifs rw,vers=3.0 0 0
+nas:/export/home /home/alice/remote nfs4 rw 0 0
+/dev/sdb1 /run/media/alice/USB\\040STICK vfat rw,nosuid,nodev 0 0
+/dev/sdc1 /mnt/backup ext4 rw 0 0
+";
+
+    #[test]
+    fn test_mount_drive_type_classifies_by_mount() {
+        let mounts = parse_proc_mounts(PROC_MOUNTS);
+        assert_eq!(mounts[4].mount_point, "/run/media/alice/USB STICK");
+
+        let usb_disk = |device: &str| device == "/dev/sdc1";
+        let classify = |path: &str| mount_drive_type(path, &mounts, usb_disk);
+        assert_eq!(classify("/usr/bin/obs"), DriveType::Fixed);
+        assert_eq!(classify("/boot/efi/EFI/boot.efi"), DriveType::Fixed);
+        assert_eq!(classify("/mnt/tools/helper"), DriveType::Network);
+        assert_eq!(classify("/mnt/toolsmith/helper"), DriveType::Fixed);
+        assert_eq!(classify("/home/alice/remote/bin/copilot"), DriveType::Network);
+        assert_eq!(classify("/run/media/alice/USB STICK/agent"), DriveType::Removable);
+        assert_eq!(classify("/mnt/backup/agent"), DriveType::Removable);
+        assert_eq!(classify("Unknown"), DriveType::Unknown);
+
+        let mac = parse_mount_output(
+            "/dev/disk3s1s1 on / (apfs, sealed, local, read-only, journaled)\n\
+             //alice@nas/tools on /Volumes/tools (smbfs, nodev, nosuid, mounted by alice)\n\
+             /dev/disk4s1 on /Volumes/KINGSTON (msdos, local, nodev, nosuid, noowners)\n",
+        );
+        let classify = |path: &str| mount_drive_type(path, &mac, |_| false);
+        assert_eq!(classify("/Applications/Zoom.app/Contents/MacOS/zoom.us"), DriveType::Fixed);
+        assert_eq!(classify("/Volumes/tools/helper"), DriveType::Network);
+        assert_eq!(classify("/Volumes/KINGSTON/helper"), DriveType::Removable);
+    }
+
+    #[test]
+    fn test_windows_drive_type_classifies_by_root() {
+        let drive_kind = |root: &str| match root {
+            "C:\\" => DRIVE_FIXED,
+            "E:\\" => DRIVE_REMOVABLE,
+            "Z:\\" => DRIVE_REMOTE,
+            _ => 1,
+        };
+        assert_eq!(windows_drive_type("C:\\Program Files\\obs.exe", drive_kind), DriveType::Fixed);
+        assert_eq!(windows_drive_type("e:\\tools\\helper.exe", drive_kind), DriveType::Removable);
+        assert_eq!(windows_drive_type("Z:\\helper.exe", drive_kind), DriveType::Network);
+        assert_eq!(windows_drive_type("\\\\nas\\tools\\helper.exe", drive_kind), DriveType::Network);
+        assert_eq!(windows_drive_type("\\\\?\\UNC\\nas\\tools\\helper.exe", drive_kind), DriveType::Network);
+        assert_eq!(windows_drive_type("\\\\?\\E:\\helper.exe", drive_kind), DriveType::Removable);
+        assert_eq!(windows_drive_type("Q:\\missing.exe", drive_kind), DriveType::Unknown);
+        assert_eq!(windows_drive_type("Unknown", drive_kind), DriveType::Unknown);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,199 @@
+use crate::report_stream::Heartbeat;
+use crate::{DetectionReport, Severity};
+
+/// Maximum number of offending processes named in one event.
+const MAX_OFFENDERS: usize = 5;
+
+const EVENT_SOURCE: &str = "Fairview";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventLevel {
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl From<Severity> for EventLevel {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Low => EventLevel::Info,
+            Severity::Medium => EventLevel::Warning,
+            Severity::High => EventLevel::Error,
+            Severity::Critical => EventLevel::Critical,
+        }
+    }
+}
+
+/// One scan summarized for syslog or the Windows Event Log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanEvent {
+    pub level: EventLevel,
+    pub message: String,
+}
+
+/// Builds the single-line `key=value` event for a scan. Offenders are the
+/// highest-risk flagged processes, as `name(pid)`.
+pub fn format_event(report: &DetectionReport) -> ScanEvent {
+    let mut offenders: Vec<_> = report.suspicious_processes.iter().collect();
+    offenders.sort_by(|a, b| b.risk_score.partial_cmp(&a.risk_score).unwrap_or(std::cmp::Ordering::Equal));
+    let offenders: Vec<String> = offenders
+        .iter()
+        .take(MAX_OFFENDERS)
+        .map(|p| format!("{}({})", p.name, p.pid))
+        .collect();
+
+    let mut message = format!(
+        "fairview scan={} risk={:.2} severity={} breach={} processes={} overlays={} vm={}",
+        report.scan_number,
+        report.overall_risk_score,
+        severity_name(report.severity),
+        report.exceeds_threshold,
+        report.suspicious_processes.len() + report.omitted_processes,
+        report.hidden_overlays.len() + report.omitted_overlays,
+        report.vm_detection.as_ref().is_some_and(|vm| vm.is_vm),
+    );
+    if let Some(ref session_id) = report.session_id {
+        message.push_str(&format!(" session={:?}", session_id));
+    }
+    if !offenders.is_empty() {
+        message.push_str(&format!(" offenders={:?}", offenders.join(",")));
+    }
+    if report.observational {
+        message.push_str(" mode=observe");
+    }
+
+    // Observe mode must not page anyone watching the log for warnings.
+    let level = if report.observational { EventLevel::Info } else { report.severity.into() };
+    ScanEvent { level, message }
+}
+
+pub fn format_heartbeat(heartbeat: &Heartbeat) -> ScanEvent {
+    ScanEvent {
+        level: EventLevel::Info,
+        message: format!("fairview heartbeat scan={}", heartbeat.scan_number),
+    }
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Low => "low",
+        Severity::Medium => "medium",
+        Severity::High => "high",
+        Severity::Critical => "critical",
+    }
+}
+
+/// Writes the event to syslog (Unix) or the Application event log (Windows).
+pub fn write_event(event: &ScanEvent) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        write_syslog(event)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        write_windows_event(event)
+    }
+}
+
+#[cfg(unix)]
+fn write_syslog(event: &ScanEvent) -> Result<(), String> {
+    use syslog::{Facility, Formatter3164};
+
+    let formatter = Formatter3164 {
+        facility: Facility::LOG_DAEMON,
+        hostname: None,
+        process: EVENT_SOURCE.to_lowercase(),
+        pid: std::process::id(),
+    };
+    let mut logger = syslog::unix(formatter).map_err(|e| format!("Failed to connect to syslog: {}", e))?;
+
+    let message = event.message.as_str();
+    match event.level {
+        EventLevel::Info => logger.info(message),
+        EventLevel::Warning => logger.warning(message),
+        EventLevel::Error => logger.err(message),
+        EventLevel::Critical => logger.crit(message),
+    }
+    .map_err(|e| format!("Failed to write to syslog: {}", e))
+}
+
+#[cfg(target_os = "windows")]
+fn write_windows_event(event: &ScanEvent) -> Result<(), String> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::PSID;
+    use windows::Win32::System::EventLog::*;
+
+    // The Event Log has no critical type for ReportEventW; critical scans are
+    // errors with a distinct event id.
+    let (event_type, event_id) = match event.level {
+        EventLevel::Info => (EVENTLOG_INFORMATION_TYPE, 1000),
+        EventLevel::Warning => (EVENTLOG_WARNING_TYPE, 1001),
+        EventLevel::Error => (EVENTLOG_ERROR_TYPE, 1002),
+        EventLevel::Critical => (EVENTLOG_ERROR_TYPE, 1003),
+    };
+
+    let message = HSTRING::from(event.message.as_str());
+    unsafe {
+        let source = RegisterEventSourceW(PCWSTR::null(), &HSTRING::from(EVENT_SOURCE))
+            .map_err(|e| format!("Failed to open the event log: {}", e))?;
+        let result = ReportEventW(
+            source,
+            event_type,
+            0,
+            event_id,
+            PSID::default(),
+            0,
+            Some(&[PCWSTR(message.as_ptr())]),
+            None,
+        );
+        let _ = DeregisterEventSource(source);
+        result.map_err(|e| format!("Failed to write to the event log: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::report;
+    use crate::{ReasonCode, SuspiciousProcess};
+
+    fn flagged(pid: u32, name: &str, risk_score: f64) -> SuspiciousProcess {
+        SuspiciousProcess {
+            pid,
+            name: name.to_string(),
+            path: String::new(),
+            risk_score,
+            reasons: Vec::new(),
+            reason_codes: vec![ReasonCode::SuspiciousName],
+            started_during_interview: false,
+            is_whitelisted: false,
+            drive_type: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_event_payload_lists_top_offenders() {
+        let mut scan = report(3, 0.85, true);
+        scan.severity = Severity::Critical;
+        scan.session_id = Some("int-2041".to_string());
+        scan.suspicious_processes = vec![flagged(77, "helper.exe", 0.6), flagged(4521, "cluely.exe", 1.0)];
+
+        let event = format_event(&scan);
+
+        assert_eq!(event.level, EventLevel::Critical);
+        assert_eq!(
+            event.message,
+            "fairview scan=3 risk=0.85 severity=critical breach=true processes=2 overlays=0 vm=false \
+             session=\"int-2041\" offenders=\"cluely.exe(4521),helper.exe(77)\""
+        );
+    }
+
+    #[test]
+    fn test_quiet_scan_is_informational() {
+        let event = format_event(&report(1, 0.0, false));
+        assert_eq!(event.level, EventLevel::Info);
+        assert_eq!(event.message, "fairview scan=1 risk=0.00 severity=low breach=false processes=0 overlays=0 vm=false");
+    }
+}
\ No newline at end of file
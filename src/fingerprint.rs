@@ -0,0 +1,140 @@
+use raw_cpuid::CpuId;
+use sysinfo::{Networks, System};
+
+/// What a machine fingerprint is derived from. Each part can be missing;
+/// the fingerprint only needs to stay the same on one machine for a session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FingerprintInputs {
+    /// Burned-in MAC addresses, uppercase, sorted.
+    pub mac_addresses: Vec<String>,
+    pub hostname: Option<String>,
+    pub cpu_vendor: Option<String>,
+    /// System serial number from the firmware (DMI/SMBIOS).
+    pub dmi_serial: Option<String>,
+}
+
+impl FingerprintInputs {
+    pub fn collect() -> Self {
+        let networks = Networks::new_with_refreshed_list();
+        Self {
+            mac_addresses: hardware_macs(networks.values().map(|data| data.mac_address().to_string())),
+            hostname: System::host_name(),
+            cpu_vendor: CpuId::new().get_vendor_info().map(|vendor| vendor.as_str().to_string()),
+            dmi_serial: dmi_serial(),
+        }
+    }
+
+    /// 16 hex digits of FNV-1a over the inputs. Not a cryptographic hash: it
+    /// tells machines apart, it doesn't hide what they are.
+    pub fn fingerprint(&self) -> String {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut feed = |part: &str| {
+            // The separator keeps ("ab", "c") and ("a", "bc") apart.
+            for byte in part.bytes().chain(std::iter::once(0)) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+            }
+        };
+        feed(&self.mac_addresses.join(","));
+        feed(self.hostname.as_deref().unwrap_or_default());
+        feed(self.cpu_vendor.as_deref().unwrap_or_default());
+        feed(self.dmi_serial.as_deref().unwrap_or_default());
+        format!("{:016x}", hash)
+    }
+}
+
+/// Keeps globally administered MAC addresses. Locally administered ones
+/// (second-lowest bit of the first octet set) belong to VPNs, containers and
+/// randomized Wi-Fi, which come and go during a session.
+pub fn hardware_macs(addresses: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut macs: Vec<String> = addresses
+        .into_iter()
+        .map(|mac| mac.to_uppercase())
+        .filter(|mac| {
+            let first_octet = mac.get(..2).and_then(|octet| u8::from_str_radix(octet, 16).ok());
+            mac != "00:00:00:00:00:00" && first_octet.is_some_and(|octet| octet & 0x02 == 0)
+        })
+        .collect();
+    macs.sort();
+    macs.dedup();
+    macs
+}
+
+#[cfg(target_os = "linux")]
+fn dmi_serial() -> Option<String> {
+    // Both are usually readable by root only.
+    ["product_serial", "board_serial"]
+        .iter()
+        .filter_map(|file| std::fs::read_to_string(format!("/sys/class/dmi/id/{}", file)).ok())
+        .map(|serial| serial.trim().to_string())
+        .find(|serial| is_real_serial(serial))
+}
+
+#[cfg(target_os = "macos")]
+fn dmi_serial() -> Option<String> {
+    use crate::command::LimitedOutput;
+
+    let output = std::process::Command::new("ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .limited_output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.contains("\"IOPlatformSerialNumber\""))
+        .and_then(|line| line.split('"').nth(3))
+        .map(|serial| serial.to_string())
+        .filter(|serial| is_real_serial(serial))
+}
+
+#[cfg(target_os = "windows")]
+fn dmi_serial() -> Option<String> {
+    use crate::command::LimitedOutput;
+
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", "(Get-CimInstance Win32_BIOS).SerialNumber"])
+        .limited_output()
+        .ok()?;
+    let serial = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    is_real_serial(&serial).then_some(serial)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn dmi_serial() -> Option<String> {
+    None
+}
+
+/// OEMs often leave the serial field at a placeholder.
+fn is_real_serial(serial: &str) -> bool {
+    let lower = serial.to_lowercase();
+    !lower.is_empty()
+        && !["to be filled by o.e.m.", "default string", "system serial number", "none", "0"].contains(&lower.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_changes_with_inputs() {
+        let inputs = FingerprintInputs {
+            mac_addresses: hardware_macs(vec![
+                "a4:83:e7:12:34:56".to_string(),
+                "00:00:00:00:00:00".to_string(),
+                // Docker bridge, locally administered
+                "02:42:ac:11:00:02".to_string(),
+            ]),
+            hostname: Some("INTERVIEW-07".to_string()),
+            cpu_vendor: Some("GenuineIntel".to_string()),
+            dmi_serial: Some("C02XK1JHJG5J".to_string()),
+        };
+        assert_eq!(inputs.mac_addresses, vec!["A4:83:E7:12:34:56"]);
+        assert_eq!(inputs.fingerprint(), inputs.clone().fingerprint());
+        assert_eq!(inputs.fingerprint().len(), 16);
+
+        let renamed = FingerprintInputs { hostname: Some("INTERVIEW-08".to_string()), ..inputs.clone() };
+        let other_board = FingerprintInputs { dmi_serial: None, ..inputs.clone() };
+        assert_ne!(inputs.fingerprint(), renamed.fingerprint());
+        assert_ne!(inputs.fingerprint(), other_board.fingerprint());
+        assert!(!is_real_serial("To Be Filled By O.E.M."));
+    }
+}
\ No newline at end of file
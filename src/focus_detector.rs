@@ -0,0 +1,313 @@
+use crate::platform::{PlatformProvider, SystemPlatform};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The window with input focus, as the platform reports it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ForegroundWindow {
+    pub pid: u32,
+    /// Not read on macOS, where window titles need the Accessibility
+    /// permission.
+    pub title: Option<String>,
+}
+
+/// The application in the foreground when the scan ran, and how it relates
+/// to the scan's findings: a flagged app the candidate is looking at is
+/// stronger evidence than one running in the background.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForegroundApp {
+    pub pid: u32,
+    /// `None` when the process wasn't among those enumerated this scan.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The process is among this scan's suspicious processes.
+    pub flagged: bool,
+    /// The process owns one of this scan's hidden overlays.
+    pub overlay_owner: bool,
+}
+
+impl ForegroundApp {
+    pub fn is_suspicious(&self) -> bool {
+        self.flagged || self.overlay_owner
+    }
+}
+
+/// Focus gains below this are never frequent, however short the interval:
+/// switching to a window and back is ordinary use.
+const MIN_FOCUS_CHANGES: usize = 3;
+
+/// A process that kept taking the foreground between two scans.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusSteal {
+    pub owner_pid: u32,
+    pub owner_name: String,
+    /// Times focus moved to the process since the previous scan.
+    pub focus_changes: usize,
+    pub changes_per_minute: f64,
+    pub owner_kind: FocusOwnerKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusOwnerKind {
+    /// Owns one of the scan's hidden overlays, e.g. a cheat overlay grabbing
+    /// focus to receive typed answers.
+    OverlayOwner,
+    /// Not whitelisted and shows no visible window, yet takes the focus.
+    Windowless,
+    NotWhitelisted,
+}
+
+/// Counts how often each process gained the foreground.
+#[derive(Debug, Default)]
+pub struct FocusTracker {
+    current: Option<u32>,
+    gains: HashMap<u32, usize>,
+}
+
+impl FocusTracker {
+    /// Records one sample of the foreground window's owner. `None` (no
+    /// window has focus, or it couldn't be read) leaves the current owner
+    /// in place, and the first owner seen is not counted as a change.
+    pub fn record(&mut self, owner: Option<u32>) {
+        let Some(owner) = owner else {
+            return;
+        };
+        if self.current == Some(owner) {
+            return;
+        }
+        if self.current.is_some() {
+            *self.gains.entry(owner).or_default() += 1;
+        }
+        self.current = Some(owner);
+    }
+
+    /// Focus gains per process since the last call.
+    pub fn take_gains(&mut self) -> HashMap<u32, usize> {
+        std::mem::take(&mut self.gains)
+    }
+}
+
+/// Processes that gained focus at least `max_per_minute` times a minute over
+/// `elapsed`, with their gain count and rate, by PID.
+pub fn frequent_focus_gains(
+    gains: &HashMap<u32, usize>,
+    elapsed: Duration,
+    max_per_minute: f64,
+) -> Vec<(u32, usize, f64)> {
+    let minutes = elapsed.as_secs_f64() / 60.0;
+    if minutes <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut frequent: Vec<(u32, usize, f64)> = gains
+        .iter()
+        .map(|(&pid, &changes)| (pid, changes, changes as f64 / minutes))
+        .filter(|&(_, changes, rate)| changes >= MIN_FOCUS_CHANGES && rate >= max_per_minute)
+        .collect();
+    frequent.sort_by_key(|&(pid, _, _)| pid);
+    frequent
+}
+
+/// Samples the foreground window's owner on a background thread, since
+/// focus flipping back and forth is invisible to a scan every few seconds.
+pub struct FocusDetector {
+    platform: Arc<dyn PlatformProvider>,
+    tracker: Option<Arc<Mutex<FocusTracker>>>,
+    last_take: Instant,
+}
+
+impl Default for FocusDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FocusDetector {
+    pub fn new() -> Self {
+        Self::with_platform(Arc::new(SystemPlatform))
+    }
+
+    pub fn with_platform(platform: Arc<dyn PlatformProvider>) -> Self {
+        Self { platform, tracker: None, last_take: Instant::now() }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.tracker.is_some()
+    }
+
+    /// The window with input focus right now.
+    pub fn foreground_window(&self) -> Option<ForegroundWindow> {
+        self.platform.foreground_window()
+    }
+
+    /// Starts sampling every `interval` until the detector is dropped.
+    pub fn start(&mut self, interval: Duration) {
+        let tracker = Arc::new(Mutex::new(FocusTracker::default()));
+        let shared = Arc::downgrade(&tracker);
+        let platform = Arc::clone(&self.platform);
+        std::thread::spawn(move || loop {
+            let owner = platform.foreground_process();
+            let Some(tracker) = shared.upgrade() else {
+                break;
+            };
+            tracker.lock().unwrap_or_else(|e| e.into_inner()).record(owner);
+            drop(tracker);
+            std::thread::sleep(interval);
+        });
+        self.tracker = Some(tracker);
+        self.last_take = Instant::now();
+    }
+
+    /// Focus gains since the previous call and the time they cover. Empty
+    /// when sampling isn't running.
+    pub fn take(&mut self) -> (HashMap<u32, usize>, Duration) {
+        let elapsed = self.last_take.elapsed();
+        self.last_take = Instant::now();
+        let gains = match self.tracker {
+            Some(ref tracker) => tracker.lock().unwrap_or_else(|e| e.into_inner()).take_gains(),
+            None => HashMap::new(),
+        };
+        (gains, elapsed)
+    }
+}
+
+/// Window id in `xprop -root _NET_ACTIVE_WINDOW` output; `None` when no
+/// window has focus, which EWMH window managers report as id 0.
+pub fn parse_active_window_id(output: &str) -> Option<String> {
+    let id = output.split_once('#')?.1.split(|c: char| c == ',' || c.is_whitespace()).find(|s| !s.is_empty())?;
+    let value = u64::from_str_radix(id.trim_start_matches("0x"), 16).ok()?;
+    (value != 0).then(|| id.to_string())
+}
+
+/// PID and title from `xprop -id <window> _NET_WM_PID _NET_WM_NAME` output.
+/// A window without `_NET_WM_PID` can't be attributed and yields `None`.
+pub fn parse_window_properties(output: &str) -> Option<ForegroundWindow> {
+    let mut pid = None;
+    let mut title = None;
+    for line in output.lines() {
+        let Some((property, value)) = line.split_once(" = ") else {
+            continue;
+        };
+        if property.starts_with("_NET_WM_PID") {
+            pid = value.trim().parse::<u32>().ok();
+        } else if property.starts_with("_NET_WM_NAME") {
+            let value = value.trim();
+            let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+            title = Some(value.replace("\\\"", "\"").replace("\\\\", "\\"));
+        }
+    }
+    Some(ForegroundWindow { pid: pid.filter(|&pid| pid != 0)?, title: title.filter(|t| !t.is_empty()) })
+}
+
+/// PID in `lsappinfo info -only pid <ASN>` output, e.g. `"pid"=612`.
+pub fn parse_lsappinfo_pid(output: &str) -> Option<u32> {
+    let value = output.split_once("\"pid\"=")?.1;
+    value.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok()
+}
+
+impl SystemPlatform {
+    pub(crate) fn current_foreground_window(&self) -> Option<ForegroundWindow> {
+        #[cfg(target_os = "windows")]
+        {
+            use windows::Win32::UI::WindowsAndMessaging::{
+                GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId,
+            };
+
+            unsafe {
+                let hwnd = GetForegroundWindow();
+                if hwnd.0 == 0 {
+                    return None;
+                }
+                let mut pid = 0u32;
+                GetWindowThreadProcessId(hwnd, Some(&mut pid));
+                if pid == 0 {
+                    return None;
+                }
+                let mut title_buf = [0u16; 512];
+                let title_len = GetWindowTextW(hwnd, &mut title_buf).max(0) as usize;
+                let title = String::from_utf16_lossy(&title_buf[..title_len]);
+                Some(ForegroundWindow { pid, title: (!title.is_empty()).then_some(title) })
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            use crate::command::LimitedOutput;
+            use std::process::Command;
+
+            let front = Command::new("lsappinfo").arg("front").limited_output().ok()?;
+            let asn = String::from_utf8_lossy(&front.stdout).trim().to_string();
+            if asn.is_empty() {
+                return None;
+            }
+            let info = Command::new("lsappinfo").args(["info", "-only", "pid", &asn]).limited_output().ok()?;
+            let pid = parse_lsappinfo_pid(&String::from_utf8_lossy(&info.stdout))?;
+            Some(ForegroundWindow { pid, title: None })
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            use crate::command::LimitedOutput;
+            use std::process::Command;
+
+            let root = Command::new("xprop").args(["-root", "_NET_ACTIVE_WINDOW"]).limited_output().ok()?;
+            let window = parse_active_window_id(&String::from_utf8_lossy(&root.stdout))?;
+            let properties =
+                Command::new("xprop").args(["-id", &window, "_NET_WM_PID", "_NET_WM_NAME"]).limited_output().ok()?;
+            parse_window_properties(&String::from_utf8_lossy(&properties.stdout))
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+        {
+            None
+        }
+    }
+
+    pub(crate) fn current_foreground_process(&self) -> Option<u32> {
+        #[cfg(target_os = "windows")]
+        {
+            use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+            unsafe {
+                let hwnd = GetForegroundWindow();
+                if hwnd.0 == 0 {
+                    return None;
+                }
+                let mut pid = 0u32;
+                GetWindowThreadProcessId(hwnd, Some(&mut pid));
+                (pid != 0).then_some(pid)
+            }
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_foreground_window_is_read_from_xprop_output() {
+        let root = "_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007\n";
+        assert_eq!(parse_active_window_id(root).as_deref(), Some("0x3a00007"));
+        assert_eq!(parse_active_window_id("_NET_ACTIVE_WINDOW(WINDOW): window id # 0x0\n"), None);
+
+        let properties = "_NET_WM_PID(CARDINAL) = 4821\n_NET_WM_NAME(UTF8_STRING) = \"Notes \\\"draft\\\"\"\n";
+        assert_eq!(
+            parse_window_properties(properties),
+            Some(ForegroundWindow { pid: 4821, title: Some("Notes \"draft\"".to_string()) })
+        );
+        assert_eq!(parse_window_properties("_NET_WM_PID:  not found.\n_NET_WM_NAME(UTF8_STRING) = \"x\"\n"), None);
+
+        assert_eq!(parse_lsappinfo_pid("\"pid\"=612\n"), Some(612));
+    }
+}
\ No newline at end of file
@@ -1,10 +1,77 @@
+use crate::config::{ConnectionWeights, ExpectedDisplay};
+use crate::platform::{PlatformProvider, SystemPlatform};
+use crate::Process;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
 pub struct HardwareDetector {
+    platform: Arc<dyn PlatformProvider>,
     baseline_displays: Option<DisplayConfiguration>,
+    expected_displays: Vec<ExpectedDisplay>,
+    capture_card_weight: Option<f64>,
+    connection_weights: ConnectionWeights,
+    /// Display layout last accepted as stable, and a differing layout (by
+    /// display ids) with the number of consecutive scans it has been seen.
+    settled_displays: Option<DisplayConfiguration>,
+    pending_layout: Option<(Vec<String>, u32)>,
+    display_change_scans: u32,
+    /// Display checks at session start whose changes against the baseline
+    /// are flagged but not scored, and checks run so far.
+    display_grace_scans: u32,
+    checks_run: u32,
+    /// Display states recorded between scans by `start_display_sampler`,
+    /// and the risk added when one was missed.
+    display_sampler: Option<Arc<Mutex<DisplaySampler>>>,
+    transient_display_weight: f64,
 }
 
-#[derive(Debug, Clone)]
+/// A display state seen between two full scans that the later scan didn't
+/// see, e.g. a virtual display enabled for a few seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransientDisplayState {
+    pub at: SystemTime,
+    pub display_count: usize,
+    pub has_virtual_display: bool,
+}
+
+/// Records each change of the display count or virtual display flag seen by
+/// the cheap between-scan sampling.
+#[derive(Debug, Default)]
+pub struct DisplaySampler {
+    last: Option<(usize, bool)>,
+    changes: Vec<TransientDisplayState>,
+}
+
+impl DisplaySampler {
+    /// Starts from the state last seen by a full scan or the baseline.
+    pub fn new(display_count: usize, has_virtual_display: bool) -> Self {
+        Self { last: Some((display_count, has_virtual_display)), changes: Vec::new() }
+    }
+
+    pub fn record(&mut self, display_count: usize, has_virtual_display: bool, at: SystemTime) {
+        let state = (display_count, has_virtual_display);
+        if self.last.is_some_and(|last| last != state) {
+            self.changes.push(TransientDisplayState { at, display_count, has_virtual_display });
+        }
+        self.last = Some(state);
+    }
+
+    /// States sampled since the previous full scan that differ from what the
+    /// current scan sees, oldest first. A change still in place is left to
+    /// the full scan's own checks. The current state becomes the reference
+    /// for the next interval.
+    pub fn take_transients(&mut self, display_count: usize, has_virtual_display: bool) -> Vec<TransientDisplayState> {
+        self.last = Some((display_count, has_virtual_display));
+        std::mem::take(&mut self.changes)
+            .into_iter()
+            .filter(|state| (state.display_count, state.has_virtual_display) != (display_count, has_virtual_display))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DisplayConfiguration {
     pub display_count: usize,
     pub displays: Vec<DisplayInfo>,
@@ -12,7 +79,7 @@ pub struct DisplayConfiguration {
     pub has_hdmi_splitter_signature: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct DisplayInfo {
     pub id: String,
@@ -23,7 +90,8 @@ pub struct DisplayInfo {
     pub connection_type: ConnectionType,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum ConnectionType {
     HDMI,
     DisplayPort,
@@ -33,22 +101,308 @@ pub enum ConnectionType {
     Unknown,
 }
 
-#[derive(Debug)]
+/// Friendly-name markers of HDMI capture cards, lowercase.
+const CAPTURE_CARD_PATTERNS: &[&str] = &[
+    "hd60", "4k60", "4k x", "game capture", "cam link", "avermedia", "live gamer",
+    "capture card", "hdmi capture", "video capture", "usb3.0 capture", "hdmi to usb",
+];
+
+/// Markers of ordinary webcams that take precedence over the patterns above,
+/// e.g. Elgato's Facecam is a camera, not a capture card.
+const WEBCAM_PATTERNS: &[&str] = &["facecam", "webcam", "facetime", "integrated camera"];
+
+/// True when a video capture device's friendly name looks like a capture card
+/// rather than a camera.
+pub fn is_capture_card(name: &str) -> bool {
+    let name = name.to_lowercase();
+    if WEBCAM_PATTERNS.iter().any(|p| name.contains(p)) {
+        return false;
+    }
+    CAPTURE_CARD_PATTERNS.iter().any(|p| name.contains(p))
+}
+
+#[derive(Debug, Clone)]
 pub struct HardwareSuspicion {
     pub risk_score: f64,
     pub flags: Vec<String>,
     pub details: HashMap<String, String>,
+    pub remote_desktop: RemoteDesktopStatus,
+    pub cast_sessions: Vec<CastSession>,
+}
+
+/// How a process is streaming the screen to another device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CastKind {
+    /// Miracast / Wi-Fi Display: Windows `CastSrv`, GNOME Network Displays,
+    /// MiracleCast.
+    Miracast,
+    /// Command-line Chromecast senders (mkchromecast, catt, castnow).
+    Chromecast,
+    /// Game streaming hosts that mirror the desktop: Steam Remote Play,
+    /// Parsec, Sunshine.
+    RemotePlay,
+}
+
+impl CastKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CastKind::Miracast => "Miracast",
+            CastKind::Chromecast => "Chromecast",
+            CastKind::RemotePlay => "Remote Play",
+        }
+    }
+}
+
+/// A running process that streams the screen to another device.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CastSession {
+    pub pid: u32,
+    pub process: String,
+    pub kind: CastKind,
+}
+
+/// Executable names (without `.exe`, lowercase) of screen streaming
+/// processes. Matched exactly: several are short enough to occur inside
+/// unrelated names.
+const CAST_PROCESSES: &[(&str, CastKind)] = &[
+    ("castsrv", CastKind::Miracast),
+    ("gnome-network-displays", CastKind::Miracast),
+    ("miracle-wifid", CastKind::Miracast),
+    ("miracle-sinkctl", CastKind::Miracast),
+    ("mkchromecast", CastKind::Chromecast),
+    ("catt", CastKind::Chromecast),
+    ("castnow", CastKind::Chromecast),
+    ("streaming_client", CastKind::RemotePlay),
+    ("parsecd", CastKind::RemotePlay),
+    ("sunshine", CastKind::RemotePlay),
+];
+
+pub fn classify_cast_process(name: &str) -> Option<CastKind> {
+    let name = name.to_lowercase();
+    let stem = name.strip_suffix(".exe").unwrap_or(&name);
+    CAST_PROCESSES.iter().find(|(pattern, _)| *pattern == stem).map(|(_, kind)| *kind)
+}
+
+pub fn find_cast_sessions(processes: &[Process]) -> Vec<CastSession> {
+    processes
+        .iter()
+        .filter_map(|p| {
+            classify_cast_process(&p.name).map(|kind| CastSession { pid: p.pid, process: p.name.clone(), kind })
+        })
+        .collect()
+}
+
+/// Flags streaming sessions and adds `weight` to the hardware score once per
+/// kind, since one cast often runs several helper processes. A Miracast
+/// session is the sending side of a wireless display, so when a wireless
+/// display was already scored the session only names the process behind it.
+pub fn score_cast_sessions(sessions: &[CastSession], weight: f64, suspicion: &mut HardwareSuspicion) {
+    let wireless_display = suspicion.details.get("wireless_displays").is_some_and(|n| n != "0");
+    let mut scored_kinds = Vec::new();
+
+    for session in sessions {
+        if session.kind == CastKind::Miracast && wireless_display {
+            suspicion.flags.push(format!("Wireless display is cast by {} (PID {})", session.process, session.pid));
+            continue;
+        }
+
+        suspicion.flags.push(format!(
+            "Screen streaming session: {} (PID {}, {})",
+            session.process,
+            session.pid,
+            session.kind.label()
+        ));
+        if !scored_kinds.contains(&session.kind) {
+            scored_kinds.push(session.kind);
+            suspicion.risk_score += weight;
+        }
+    }
+
+    suspicion.cast_sessions = sessions.to_vec();
+    suspicion.risk_score = suspicion.risk_score.clamp(0.0, 1.0);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteDesktopKind {
+    /// Remote Desktop Protocol (Windows RDP, xrdp).
+    Rdp,
+    /// A VNC server.
+    Vnc,
+    /// macOS Screen Sharing / Apple Remote Desktop.
+    ScreenSharing,
+}
+
+impl RemoteDesktopKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RemoteDesktopKind::Rdp => "RDP",
+            RemoteDesktopKind::Vnc => "VNC",
+            RemoteDesktopKind::ScreenSharing => "Screen Sharing",
+        }
+    }
+}
+
+/// Whether someone may be viewing or controlling the machine remotely, and
+/// what gave it away. `kind` is that of the first piece of evidence.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RemoteDesktopStatus {
+    pub active: bool,
+    pub kind: Option<RemoteDesktopKind>,
+    pub evidence: Vec<String>,
+}
+
+impl RemoteDesktopStatus {
+    fn from_evidence(evidence: Vec<(RemoteDesktopKind, String)>) -> Self {
+        Self {
+            active: !evidence.is_empty(),
+            kind: evidence.first().map(|(kind, _)| *kind),
+            evidence: evidence.into_iter().map(|(_, e)| e).collect(),
+        }
+    }
+}
+
+/// Parses `qwinsta` for an active `rdp-` session; the `SESSIONNAME` of an
+/// RDP session starts with `RDP-`.
+pub fn parse_qwinsta(output: &str, session_name: Option<&str>) -> RemoteDesktopStatus {
+    let mut evidence: Vec<_> = output
+        .lines()
+        .filter(|line| line.contains("rdp-") && line.contains("Active"))
+        .map(|line| (RemoteDesktopKind::Rdp, format!("qwinsta: {}", line.split_whitespace().collect::<Vec<_>>().join(" "))))
+        .collect();
+    if let Some(name) = session_name.filter(|n| n.starts_with("RDP-")) {
+        evidence.push((RemoteDesktopKind::Rdp, format!("SESSIONNAME={}", name)));
+    }
+    RemoteDesktopStatus::from_evidence(evidence)
+}
+
+/// Parses `lsof -i :5900`. Apple's own daemons are Screen Sharing; anything
+/// else on the VNC port is a third-party VNC server.
+pub fn parse_lsof_vnc(output: &str) -> RemoteDesktopStatus {
+    let evidence = output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let command = fields.first()?;
+            let kind = match command.to_lowercase().as_str() {
+                "screensharingd" | "ardagent" | "screensha" => RemoteDesktopKind::ScreenSharing,
+                _ => RemoteDesktopKind::Vnc,
+            };
+            let name = fields[fields.len().saturating_sub(2)..].join(" ");
+            Some((kind, format!("lsof: {} (pid {}) {}", command, fields.get(1).unwrap_or(&"?"), name)))
+        })
+        .collect();
+    RemoteDesktopStatus::from_evidence(evidence)
+}
+
+/// Parses `netstat -tuln` for listeners on the VNC (590x) or RDP (3389) ports.
+pub fn parse_netstat_listeners(output: &str) -> RemoteDesktopStatus {
+    let evidence = output
+        .lines()
+        .filter(|line| line.contains("LISTEN"))
+        .filter_map(|line| {
+            let local = line.split_whitespace().nth(3)?;
+            let port = local.rsplit(':').next()?;
+            let kind = if port.starts_with("590") && port.len() == 4 {
+                RemoteDesktopKind::Vnc
+            } else if port == "3389" {
+                RemoteDesktopKind::Rdp
+            } else {
+                return None;
+            };
+            Some((kind, format!("netstat: listening on {}", local)))
+        })
+        .collect();
+    RemoteDesktopStatus::from_evidence(evidence)
+}
+
+impl Default for HardwareDetector {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl HardwareDetector {
     pub fn new() -> Self {
+        Self::with_platform(Arc::new(SystemPlatform))
+    }
+
+    pub fn with_platform(platform: Arc<dyn PlatformProvider>) -> Self {
         Self {
+            platform,
             baseline_displays: None,
+            expected_displays: Vec::new(),
+            capture_card_weight: None,
+            connection_weights: ConnectionWeights::default(),
+            settled_displays: None,
+            pending_layout: None,
+            display_change_scans: 1,
+            display_grace_scans: 0,
+            checks_run: 0,
+            display_sampler: None,
+            transient_display_weight: 0.0,
         }
     }
 
+    /// Reads the display configuration every `interval` on a background
+    /// thread until the detector is dropped, so a display enabled and
+    /// disabled again between two full scans is still reported, adding
+    /// `weight` to the next scan's risk.
+    pub fn start_display_sampler(&mut self, interval: Duration, weight: f64) {
+        let sampler = match self.settled_displays.as_ref().or(self.baseline_displays.as_ref()) {
+            Some(config) => DisplaySampler::new(config.display_count, config.has_virtual_display),
+            None => DisplaySampler::default(),
+        };
+        let sampler = Arc::new(Mutex::new(sampler));
+        let shared = Arc::downgrade(&sampler);
+        let platform = Arc::clone(&self.platform);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            let Some(sampler) = shared.upgrade() else {
+                break;
+            };
+            if let Ok(config) = platform.displays() {
+                sampler
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .record(config.display_count, config.has_virtual_display, SystemTime::now());
+            }
+        });
+        self.display_sampler = Some(sampler);
+        self.transient_display_weight = weight;
+    }
+
+    /// Consecutive scans a changed display layout must persist before it is
+    /// scored; 1 scores every change immediately.
+    pub fn set_display_change_scans(&mut self, scans: u32) {
+        self.display_change_scans = scans.max(1);
+    }
+
+    /// Leaves display changes unscored for the first `scans` checks, while
+    /// the topology may still be settling after login or wake.
+    pub fn set_display_grace_scans(&mut self, scans: u32) {
+        self.display_grace_scans = scans;
+    }
+
+    pub fn set_expected_displays(&mut self, expected: Vec<ExpectedDisplay>) {
+        self.expected_displays = expected;
+    }
+
+    /// Risk added per capture card found; `None` turns the check off.
+    pub fn set_capture_card_weight(&mut self, weight: Option<f64>) {
+        self.capture_card_weight = weight;
+    }
+
+    pub fn set_connection_weights(&mut self, weights: ConnectionWeights) {
+        self.connection_weights = weights;
+    }
+
     pub fn set_baseline(&mut self) -> Result<(), String> {
-        let config = self.get_current_display_configuration()?;
+        let config = self.platform.displays()?;
+        self.settled_displays = Some(config.clone());
+        self.pending_layout = None;
         self.baseline_displays = Some(config);
         Ok(())
     }
@@ -57,15 +411,31 @@ impl HardwareDetector {
         self.baseline_displays.as_ref()
     }
 
-    pub fn detect_hardware_cheating(&self) -> HardwareSuspicion {
+    /// Display count to compare against: the collected baseline if there is
+    /// one, otherwise the configured expected displays.
+    pub fn reference_display_count(&self) -> Option<usize> {
+        match self.baseline_displays {
+            Some(ref baseline) => Some(baseline.display_count),
+            None if !self.expected_displays.is_empty() => Some(self.expected_displays.len()),
+            None => None,
+        }
+    }
+
+    pub fn detect_hardware_cheating(&mut self) -> HardwareSuspicion {
         let mut suspicion = HardwareSuspicion {
             risk_score: 0.0,
             flags: Vec::new(),
             details: HashMap::new(),
+            remote_desktop: RemoteDesktopStatus::default(),
+            cast_sessions: Vec::new(),
         };
+        self.checks_run = self.checks_run.saturating_add(1);
 
-        let current_config = match self.get_current_display_configuration() {
-            Ok(config) => config,
+        let current_config = match self.platform.displays() {
+            Ok(config) => {
+                self.flag_transient_displays(&config, &mut suspicion);
+                self.debounce_layout(config)
+            }
             Err(e) => {
                 suspicion.flags.push(format!("Unable to detect display configuration: {}", e));
                 suspicion.details.insert("error".to_string(), "display_detection_failed".to_string());
@@ -90,6 +460,85 @@ impl HardwareDetector {
             suspicion.risk_score += if current_config.display_count == 2 { 0.05 } else { 0.15 };
         }
 
+        if self.checks_run <= self.display_grace_scans {
+            let (risk, flags) = (suspicion.risk_score, suspicion.flags.len());
+            self.compare_with_reference(&current_config, &mut suspicion);
+            for flag in &mut suspicion.flags[flags..] {
+                flag.push_str(" (startup grace, not scored)");
+            }
+            suspicion.risk_score = risk;
+        } else {
+            self.compare_with_reference(&current_config, &mut suspicion);
+        }
+
+        score_connections(&current_config.displays, &self.connection_weights, &mut suspicion);
+
+        if let Some(weight) = self.capture_card_weight {
+            match self.platform.video_capture_devices() {
+                Ok(devices) => flag_capture_cards(&devices, weight, &mut suspicion),
+                Err(e) => suspicion.flags.push(format!("Unable to enumerate video capture devices: {}", e)),
+            }
+        }
+
+        suspicion.remote_desktop = self.platform.remote_desktop();
+        if let Some(kind) = suspicion.remote_desktop.kind {
+            suspicion.flags.push(format!("Remote desktop connection detected ({})", kind.label()));
+            suspicion.risk_score += 0.8;
+        }
+
+        suspicion.risk_score = suspicion.risk_score.clamp(0.0, 1.0);
+        suspicion
+    }
+
+    fn flag_transient_displays(&self, current: &DisplayConfiguration, suspicion: &mut HardwareSuspicion) {
+        let Some(ref sampler) = self.display_sampler else {
+            return;
+        };
+        let transients = sampler
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take_transients(current.display_count, current.has_virtual_display);
+        for state in &transients {
+            let at: chrono::DateTime<chrono::Utc> = state.at.into();
+            suspicion.flags.push(format!(
+                "Display configuration changed between scans: {} displays{} at {}",
+                state.display_count,
+                if state.has_virtual_display { " including a virtual display" } else { "" },
+                at.format("%H:%M:%S UTC")
+            ));
+        }
+        if !transients.is_empty() {
+            suspicion.risk_score += self.transient_display_weight;
+        }
+    }
+
+    /// Returns the settled layout until a differing one (displays added,
+    /// removed or re-enumerated under new ids) has been seen for
+    /// `display_change_scans` scans in a row, so a monitor waking from sleep
+    /// with a transient id doesn't register as a change.
+    fn debounce_layout(&mut self, current: DisplayConfiguration) -> DisplayConfiguration {
+        let layout = layout_ids(&current);
+        let settled_layout = match self.settled_displays {
+            Some(ref settled) => layout_ids(settled),
+            None => layout.clone(),
+        };
+
+        let seen = match self.pending_layout {
+            _ if layout == settled_layout => 0,
+            Some((ref pending, scans)) if *pending == layout => scans + 1,
+            _ => 1,
+        };
+        if seen == 0 || seen >= self.display_change_scans.max(1) {
+            self.pending_layout = None;
+            self.settled_displays = Some(current.clone());
+            return current;
+        }
+
+        self.pending_layout = Some((layout, seen));
+        self.settled_displays.clone().unwrap_or(current)
+    }
+
+    fn compare_with_reference(&self, current_config: &DisplayConfiguration, suspicion: &mut HardwareSuspicion) {
         if let Some(ref baseline) = self.baseline_displays {
             if baseline.display_count != current_config.display_count {
                 suspicion.flags.push(format!(
@@ -107,30 +556,38 @@ impl HardwareDetector {
                     suspicion.risk_score += 0.3;
                 }
             }
-        }
-
-        for display in &current_config.displays {
-            if display.connection_type == ConnectionType::USB {
-                suspicion.flags.push(format!("USB display detected: {}", display.name));
-                suspicion.risk_score += 0.2;
+        } else if !self.expected_displays.is_empty() {
+            if self.expected_displays.len() != current_config.display_count {
+                suspicion.flags.push(format!(
+                    "Display configuration differs from expected (expected: {}, current: {})",
+                    self.expected_displays.len(),
+                    current_config.display_count
+                ));
+                suspicion.risk_score += 0.4;
             }
 
-            if display.connection_type == ConnectionType::Wireless {
-                suspicion.flags.push(format!("Wireless display detected: {}", display.name));
-                suspicion.risk_score += 0.25;
+            // Match each current display against an unused expected entry of the same resolution.
+            let mut unmatched = self.expected_displays.clone();
+            for display in &current_config.displays {
+                match unmatched.iter().position(|e| e.width == display.width && e.height == display.height) {
+                    Some(index) => {
+                        unmatched.swap_remove(index);
+                    }
+                    None => {
+                        suspicion.flags.push(format!(
+                            "Unexpected display connected: {} ({}x{})",
+                            display.name, display.width, display.height
+                        ));
+                        suspicion.risk_score += 0.3;
+                    }
+                }
             }
         }
-
-        if self.detect_remote_desktop_active() {
-            suspicion.flags.push("Remote desktop connection detected".to_string());
-            suspicion.risk_score += 0.8;
-        }
-
-        suspicion.risk_score = suspicion.risk_score.clamp(0.0, 1.0);
-        suspicion
     }
+}
 
-    fn get_current_display_configuration(&self) -> Result<DisplayConfiguration, String> {
+impl SystemPlatform {
+    pub(crate) fn current_display_configuration(&self) -> Result<DisplayConfiguration, String> {
         #[cfg(target_os = "windows")]
         {
             self.get_windows_displays()
@@ -147,7 +604,25 @@ impl HardwareDetector {
         }
     }
 
-    fn detect_remote_desktop_active(&self) -> bool {
+    /// Friendly names of attached video capture devices, deduplicated.
+    pub(crate) fn current_video_capture_devices(&self) -> Result<Vec<String>, String> {
+        #[cfg(target_os = "windows")]
+        {
+            self.get_windows_video_devices()
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            self.get_macos_video_devices()
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            self.get_linux_video_devices()
+        }
+    }
+
+    pub(crate) fn current_remote_desktop(&self) -> RemoteDesktopStatus {
         #[cfg(target_os = "windows")]
         {
             self.check_windows_rdp()
@@ -165,8 +640,98 @@ impl HardwareDetector {
     }
 }
 
+fn layout_ids(config: &DisplayConfiguration) -> Vec<String> {
+    let mut ids: Vec<String> = config.displays.iter().map(|d| d.id.clone()).collect();
+    ids.sort();
+    ids
+}
+
+fn connection_weight(weights: &ConnectionWeights, connection_type: &ConnectionType) -> f64 {
+    match connection_type {
+        ConnectionType::HDMI => weights.hdmi,
+        ConnectionType::DisplayPort => weights.display_port,
+        ConnectionType::USB => weights.usb,
+        ConnectionType::Virtual => weights.virtual_display,
+        ConnectionType::Wireless => weights.wireless,
+        ConnectionType::Unknown => weights.unknown,
+    }
+}
+
+fn score_connections(displays: &[DisplayInfo], weights: &ConnectionWeights, suspicion: &mut HardwareSuspicion) {
+    let wireless = displays.iter().filter(|d| d.connection_type == ConnectionType::Wireless).count();
+    suspicion.details.insert("wireless_displays".to_string(), wireless.to_string());
+
+    for display in displays {
+        let weight = connection_weight(weights, &display.connection_type);
+        let always_flagged = matches!(display.connection_type, ConnectionType::USB | ConnectionType::Wireless);
+        if always_flagged || weight > 0.0 {
+            suspicion.flags.push(format!("{:?} display detected: {}", display.connection_type, display.name));
+            suspicion.risk_score += weight;
+        }
+    }
+}
+
+fn flag_capture_cards(devices: &[String], weight: f64, suspicion: &mut HardwareSuspicion) {
+    for name in devices.iter().filter(|name| is_capture_card(name)) {
+        suspicion.flags.push(format!("Video capture card detected: {}", name));
+        suspicion.risk_score += weight;
+    }
+}
+
 #[cfg(target_os = "windows")]
-impl HardwareDetector {
+impl SystemPlatform {
+    /// Walks the Camera, Image and Media device classes, which is where UVC
+    /// webcams and vendor capture card drivers register.
+    fn get_windows_video_devices(&self) -> Result<Vec<String>, String> {
+        use windows::core::PCWSTR;
+        use windows::Win32::Devices::DeviceAndDriverInstallation::*;
+        use windows::Win32::Foundation::HWND;
+
+        let mut names = Vec::new();
+
+        for class in [GUID_DEVCLASS_CAMERA, GUID_DEVCLASS_IMAGE, GUID_DEVCLASS_MEDIA] {
+            unsafe {
+                let device_set = SetupDiGetClassDevsW(Some(&class), PCWSTR::null(), HWND(0), DIGCF_PRESENT)
+                    .map_err(|e| format!("SetupDiGetClassDevsW failed: {}", e))?;
+
+                let mut index = 0;
+                loop {
+                    let mut info = SP_DEVINFO_DATA {
+                        cbSize: std::mem::size_of::<SP_DEVINFO_DATA>() as u32,
+                        ..Default::default()
+                    };
+                    if SetupDiEnumDeviceInfo(device_set, index, &mut info).is_err() {
+                        break;
+                    }
+                    index += 1;
+
+                    let mut buffer = [0u8; 512];
+                    let found = [SPDRP_FRIENDLYNAME, SPDRP_DEVICEDESC].into_iter().any(|property| {
+                        SetupDiGetDeviceRegistryPropertyW(device_set, &info, property, None, Some(&mut buffer), None)
+                            .is_ok()
+                    });
+                    if !found {
+                        continue;
+                    }
+
+                    let wide: Vec<u16> = buffer
+                        .chunks_exact(2)
+                        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                        .take_while(|&c| c != 0)
+                        .collect();
+                    let name = String::from_utf16_lossy(&wide);
+                    if !name.is_empty() && !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+
+                let _ = SetupDiDestroyDeviceInfoList(device_set);
+            }
+        }
+
+        Ok(names)
+    }
+
     fn get_windows_displays(&self) -> Result<DisplayConfiguration, String> {
         use std::mem;
         use windows::Win32::Graphics::Gdi::*;
@@ -254,37 +819,28 @@ impl HardwareDetector {
         }
     }
 
-    fn check_windows_rdp(&self) -> bool {
+    fn check_windows_rdp(&self) -> RemoteDesktopStatus {
+        use crate::command::LimitedOutput;
         use std::process::Command;
 
-        if let Ok(output) = Command::new("qwinsta").output() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines() {
-                if line.contains("rdp-") && line.contains("Active") {
-                    return true;
-                }
-            }
-        }
-
-        if let Ok(session_name) = std::env::var("SESSIONNAME") {
-            if session_name.starts_with("RDP-") {
-                return true;
-            }
-        }
-
-        false
+        let qwinsta = Command::new("qwinsta")
+            .limited_output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+            .unwrap_or_default();
+        parse_qwinsta(&qwinsta, std::env::var("SESSIONNAME").ok().as_deref())
     }
 }
 
 #[cfg(target_os = "macos")]
-impl HardwareDetector {
+impl SystemPlatform {
     fn get_macos_displays(&self) -> Result<DisplayConfiguration, String> {
+        use crate::command::LimitedOutput;
         use std::process::Command;
 
         let mut displays = Vec::new();
         let mut has_virtual = false;
 
-        if let Ok(output) = Command::new("system_profiler").arg("SPDisplaysDataType").output() {
+        if let Ok(output) = Command::new("system_profiler").arg("SPDisplaysDataType").limited_output() {
             let stdout = String::from_utf8_lossy(&output.stdout);
             let mut current_display: Option<DisplayInfo> = None;
 
@@ -356,29 +912,51 @@ impl HardwareDetector {
         }
     }
 
-    fn check_macos_screen_sharing(&self) -> bool {
+    fn get_macos_video_devices(&self) -> Result<Vec<String>, String> {
+        use crate::command::LimitedOutput;
         use std::process::Command;
 
-        if let Ok(output) = Command::new("lsof").args(&["-i", ":5900"]).output() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if stdout.lines().count() > 1 {
-                return true;
+        let output = Command::new("system_profiler")
+            .arg("SPCameraDataType")
+            .limited_output()
+            .map_err(|e| format!("Failed to run system_profiler: {}", e))?;
+
+        // Device names are the four-space-indented headings, e.g. "    Cam Link 4K:".
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut names: Vec<String> = Vec::new();
+        for line in stdout.lines() {
+            if line.starts_with("    ") && !line.starts_with("     ") && line.trim_end().ends_with(':') {
+                let name = line.trim().trim_end_matches(':').to_string();
+                if !names.contains(&name) {
+                    names.push(name);
+                }
             }
         }
 
-        false
+        Ok(names)
+    }
+
+    fn check_macos_screen_sharing(&self) -> RemoteDesktopStatus {
+        use crate::command::LimitedOutput;
+        use std::process::Command;
+
+        match Command::new("lsof").args(["-i", ":5900"]).limited_output() {
+            Ok(output) => parse_lsof_vnc(&String::from_utf8_lossy(&output.stdout)),
+            Err(_) => RemoteDesktopStatus::default(),
+        }
     }
 }
 
 #[cfg(target_os = "linux")]
-impl HardwareDetector {
+impl SystemPlatform {
     fn get_linux_displays(&self) -> Result<DisplayConfiguration, String> {
+        use crate::command::LimitedOutput;
         use std::process::Command;
 
         let mut displays = Vec::new();
         let mut has_virtual = false;
 
-        if let Ok(output) = Command::new("xrandr").arg("--query").output() {
+        if let Ok(output) = Command::new("xrandr").arg("--query").limited_output() {
             let stdout = String::from_utf8_lossy(&output.stdout);
 
             for line in stdout.lines() {
@@ -440,18 +1018,317 @@ impl HardwareDetector {
         }
     }
 
-    fn check_linux_remote_desktop(&self) -> bool {
-        use std::process::Command;
+    /// Reads the V4L2 device names from sysfs. Webcams usually expose a second
+    /// metadata node under the same name, hence the dedup.
+    fn get_linux_video_devices(&self) -> Result<Vec<String>, String> {
+        let entries = match std::fs::read_dir("/sys/class/video4linux") {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read /sys/class/video4linux: {}", e)),
+        };
 
-        if let Ok(output) = Command::new("netstat").args(&["-tuln"]).output() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines() {
-                if line.contains(":590") && line.contains("LISTEN") {
-                    return true;
+        let mut names: Vec<String> = Vec::new();
+        for entry in entries.flatten() {
+            if let Ok(name) = std::fs::read_to_string(entry.path().join("name")) {
+                let name = name.trim().to_string();
+                if !name.is_empty() && !names.contains(&name) {
+                    names.push(name);
                 }
             }
         }
 
-        false
+        Ok(names)
+    }
+
+    fn check_linux_remote_desktop(&self) -> RemoteDesktopStatus {
+        use crate::command::LimitedOutput;
+        use std::process::Command;
+
+        match Command::new("netstat").args(["-tuln"]).limited_output() {
+            Ok(output) => parse_netstat_listeners(&String::from_utf8_lossy(&output.stdout)),
+            Err(_) => RemoteDesktopStatus::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display(id: &str, width: u32, height: u32) -> DisplayInfo {
+        DisplayInfo {
+            id: id.to_string(),
+            name: format!("Display {}", id),
+            width,
+            height,
+            is_primary: id == "0",
+            connection_type: ConnectionType::Unknown,
+        }
+    }
+
+    fn configuration(displays: Vec<DisplayInfo>) -> DisplayConfiguration {
+        DisplayConfiguration {
+            display_count: displays.len(),
+            displays,
+            has_virtual_display: false,
+            has_hdmi_splitter_signature: false,
+        }
+    }
+
+    fn empty_suspicion() -> HardwareSuspicion {
+        HardwareSuspicion {
+            risk_score: 0.0,
+            flags: Vec::new(),
+            details: HashMap::new(),
+            remote_desktop: RemoteDesktopStatus::default(),
+            cast_sessions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_remote_desktop_parsers_name_the_kind() {
+        let qwinsta = " SESSIONNAME       USERNAME      ID  STATE   TYPE        DEVICE\n\
+                       >rdp-tcp#0         candidate      2  Active  rdpwd\n\
+                       \x20console                          1  Conn    wdcon\n";
+        let rdp = parse_qwinsta(qwinsta, Some("RDP-Tcp#0"));
+        assert!(rdp.active);
+        assert_eq!(rdp.kind, Some(RemoteDesktopKind::Rdp));
+        assert_eq!(rdp.evidence, vec![
+            "qwinsta: >rdp-tcp#0 candidate 2 Active rdpwd".to_string(),
+            "SESSIONNAME=RDP-Tcp#0".to_string(),
+        ]);
+        assert_eq!(parse_qwinsta(" console  candidate  1  Active  wdcon\n", Some("Console")), RemoteDesktopStatus::default());
+
+        let lsof = "COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME\n\
+                    screensharingd 412 root 4u IPv6 0x1 0t0 TCP *:5900 (LISTEN)\n";
+        let sharing = parse_lsof_vnc(lsof);
+        assert_eq!(sharing.kind, Some(RemoteDesktopKind::ScreenSharing));
+        assert_eq!(sharing.evidence, vec!["lsof: screensharingd (pid 412) *:5900 (LISTEN)".to_string()]);
+        let vnc = parse_lsof_vnc("COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME\nvncserver 88 me 5u IPv4 0x2 0t0 TCP *:5900 (LISTEN)\n");
+        assert_eq!(vnc.kind, Some(RemoteDesktopKind::Vnc));
+        assert!(!parse_lsof_vnc("COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME\n").active);
+
+        let netstat = "Proto Recv-Q Send-Q Local Address           Foreign Address         State\n\
+                       tcp        0      0 0.0.0.0:22              0.0.0.0:*               LISTEN\n\
+                       tcp        0      0 0.0.0.0:3389            0.0.0.0:*               LISTEN\n\
+                       tcp6       0      0 :::5901                 :::*                    LISTEN\n\
+                       tcp        0      0 127.0.0.1:59000         0.0.0.0:*               LISTEN\n";
+        let linux = parse_netstat_listeners(netstat);
+        assert_eq!(linux.kind, Some(RemoteDesktopKind::Rdp));
+        assert_eq!(linux.evidence, vec![
+            "netstat: listening on 0.0.0.0:3389".to_string(),
+            "netstat: listening on :::5901".to_string(),
+        ]);
+    }
+
+    fn laptop_detector() -> HardwareDetector {
+        let mut detector = HardwareDetector::new();
+        detector.set_expected_displays(vec![ExpectedDisplay { width: 2560, height: 1600 }]);
+        detector
+    }
+
+    #[test]
+    fn test_matching_expected_displays_raise_nothing() {
+        let detector = laptop_detector();
+        let mut suspicion = empty_suspicion();
+        detector.compare_with_reference(&configuration(vec![display("0", 2560, 1600)]), &mut suspicion);
+
+        assert!(suspicion.flags.is_empty());
+        assert_eq!(detector.reference_display_count(), Some(1));
+    }
+
+    #[test]
+    fn test_mismatch_against_expected_displays_is_flagged() {
+        let detector = laptop_detector();
+        let mut suspicion = empty_suspicion();
+        detector.compare_with_reference(
+            &configuration(vec![display("0", 2560, 1600), display("1", 1920, 1080)]),
+            &mut suspicion,
+        );
+
+        assert_eq!(suspicion.flags, vec![
+            "Display configuration differs from expected (expected: 1, current: 2)".to_string(),
+            "Unexpected display connected: Display 1 (1920x1080)".to_string(),
+        ]);
+        assert!((suspicion.risk_score - 0.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_capture_card_classification() {
+        for name in ["Elgato HD60 S+", "Cam Link 4K", "AVerMedia Live Gamer Portable 2", "USB3.0 Capture", "USB Video Capture Device"] {
+            assert!(is_capture_card(name), "{}", name);
+        }
+        for name in ["Elgato Facecam", "Integrated Camera", "FaceTime HD Camera", "Logitech BRIO", "Realtek High Definition Audio"] {
+            assert!(!is_capture_card(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_capture_cards_are_flagged_with_weight() {
+        let devices = vec!["Integrated Camera".to_string(), "Elgato HD60 X".to_string()];
+        let mut suspicion = empty_suspicion();
+        flag_capture_cards(&devices, 0.6, &mut suspicion);
+
+        assert_eq!(suspicion.flags, vec!["Video capture card detected: Elgato HD60 X".to_string()]);
+        assert!((suspicion.risk_score - 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cast_sessions_are_classified_and_scored_once_per_kind() {
+        use crate::test_support::process;
+
+        assert_eq!(classify_cast_process("CastSrv.exe"), Some(CastKind::Miracast));
+        assert_eq!(classify_cast_process("catt"), Some(CastKind::Chromecast));
+        assert_eq!(classify_cast_process("scatter.exe"), None);
+
+        let processes = vec![
+            process(10, "chrome.exe", "C:\\Program Files\\chrome.exe"),
+            process(20, "CastSrv.exe", "C:\\Windows\\System32\\CastSrv.exe"),
+            process(30, "parsecd.exe", "C:\\Program Files\\Parsec\\parsecd.exe"),
+            process(31, "sunshine.exe", "C:\\Program Files\\Sunshine\\sunshine.exe"),
+        ];
+        let sessions = find_cast_sessions(&processes);
+        assert_eq!(sessions.iter().map(|s| s.pid).collect::<Vec<_>>(), vec![20, 30, 31]);
+
+        let mut suspicion = empty_suspicion();
+        score_cast_sessions(&sessions, 0.3, &mut suspicion);
+        assert!((suspicion.risk_score - 0.6).abs() < 1e-9);
+        assert_eq!(suspicion.cast_sessions.len(), 3);
+
+        // Miracast behind an already-scored wireless display only names the process.
+        let mut suspicion = empty_suspicion();
+        suspicion.details.insert("wireless_displays".to_string(), "1".to_string());
+        score_cast_sessions(&sessions[..1], 0.3, &mut suspicion);
+        assert_eq!(suspicion.risk_score, 0.0);
+        assert_eq!(suspicion.flags, vec!["Wireless display is cast by CastSrv.exe (PID 20)".to_string()]);
+    }
+
+    #[test]
+    fn test_detection_reads_the_platform() {
+        use crate::test_support::MockPlatform;
+
+        let platform = MockPlatform {
+            displays: vec![display("0", 2560, 1600), display("1", 1920, 1080)],
+            capture_devices: vec!["Elgato HD60 X".to_string()],
+            ..Default::default()
+        };
+        let mut detector = HardwareDetector::with_platform(Arc::new(platform));
+        detector.set_capture_card_weight(Some(0.6));
+
+        let suspicion = detector.detect_hardware_cheating();
+
+        assert_eq!(suspicion.details.get("display_count").map(String::as_str), Some("2"));
+        assert!(suspicion.flags.contains(&"Video capture card detected: Elgato HD60 X".to_string()));
+        assert!(!suspicion.remote_desktop.active);
+        assert!((suspicion.risk_score - 0.65).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_connection_weights_are_configurable() {
+        let mut usb = display("1", 1920, 1080);
+        usb.connection_type = ConnectionType::USB;
+        let mut hdmi = display("2", 1920, 1080);
+        hdmi.connection_type = ConnectionType::HDMI;
+        let displays = vec![usb, hdmi];
+
+        let mut suspicion = empty_suspicion();
+        score_connections(&displays, &ConnectionWeights::default(), &mut suspicion);
+        assert_eq!(suspicion.flags, vec!["USB display detected: Display 1".to_string()]);
+        assert!((suspicion.risk_score - 0.2).abs() < 1e-9);
+
+        let weights = ConnectionWeights {
+            usb: 0.0,
+            ..ConnectionWeights::default()
+        };
+        let mut suspicion = empty_suspicion();
+        score_connections(&displays, &weights, &mut suspicion);
+        assert_eq!(suspicion.flags, vec!["USB display detected: Display 1".to_string()]);
+        assert_eq!(suspicion.risk_score, 0.0);
+    }
+
+    #[test]
+    fn test_display_changes_must_persist() {
+        let mut detector = HardwareDetector::new();
+        detector.set_display_change_scans(2);
+        let laptop = configuration(vec![display("0", 2560, 1600)]);
+        let docked = configuration(vec![display("0", 2560, 1600), display("1", 1920, 1080)]);
+        detector.baseline_displays = Some(laptop.clone());
+        detector.settled_displays = Some(laptop.clone());
+
+        let mut scan = |config: &DisplayConfiguration| {
+            let settled = detector.debounce_layout(config.clone());
+            let mut suspicion = empty_suspicion();
+            detector.compare_with_reference(&settled, &mut suspicion);
+            suspicion.flags.len()
+        };
+
+        // A one-scan blip is ignored.
+        assert_eq!(scan(&docked), 0);
+        assert_eq!(scan(&laptop), 0);
+        // A sustained change is flagged once it has lasted two scans.
+        assert_eq!(scan(&docked), 0);
+        assert_eq!(scan(&docked), 2);
+        assert_eq!(scan(&docked), 2);
+        // Going back to the baseline layout is debounced the same way.
+        assert_eq!(scan(&laptop), 2);
+        assert_eq!(scan(&laptop), 0);
+    }
+
+    #[test]
+    fn test_display_change_in_startup_grace_is_not_scored() {
+        use crate::test_support::MockPlatform;
+
+        let platform = MockPlatform {
+            displays: vec![display("0", 2560, 1600), display("1", 1920, 1080)],
+            ..Default::default()
+        };
+        let mut detector = HardwareDetector::with_platform(Arc::new(platform));
+        detector.set_capture_card_weight(None);
+        detector.set_display_grace_scans(1);
+        detector.baseline_displays = Some(configuration(vec![display("0", 2560, 1600)]));
+
+        let grace = detector.detect_hardware_cheating();
+        assert!(grace.flags.contains(
+            &"Display configuration changed during interview (baseline: 1, current: 2) (startup grace, not scored)".to_string()
+        ));
+        assert!((grace.risk_score - 0.05).abs() < 1e-9);
+
+        let scored = detector.detect_hardware_cheating();
+        assert!(scored.flags.contains(&"Display configuration changed during interview (baseline: 1, current: 2)".to_string()));
+        assert!((scored.risk_score - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_display_sampler_keeps_only_missed_states() {
+        let at = |seconds: u64| SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + seconds);
+        let mut sampler = DisplaySampler::new(1, false);
+        sampler.record(1, false, at(1));
+        sampler.record(2, true, at(2));
+        sampler.record(2, true, at(3));
+        sampler.record(1, false, at(4));
+
+        // The virtual display came and went before the scan.
+        let missed = sampler.take_transients(1, false);
+        assert_eq!(missed, vec![TransientDisplayState { at: at(2), display_count: 2, has_virtual_display: true }]);
+        assert!(sampler.take_transients(1, false).is_empty());
+
+        // A display still connected at the scan is the scan's to report.
+        sampler.record(2, false, at(5));
+        assert!(sampler.take_transients(2, false).is_empty());
+        sampler.record(1, false, at(6));
+        assert_eq!(sampler.take_transients(2, false).len(), 1);
+    }
+
+    #[test]
+    fn test_collected_baseline_wins_over_expected_displays() {
+        let mut detector = laptop_detector();
+        let current = configuration(vec![display("0", 2560, 1600), display("1", 1920, 1080)]);
+        detector.baseline_displays = Some(current.clone());
+
+        let mut suspicion = empty_suspicion();
+        detector.compare_with_reference(&current, &mut suspicion);
+
+        assert!(suspicion.flags.is_empty());
+        assert_eq!(detector.reference_display_count(), Some(2));
     }
 }
\ No newline at end of file
@@ -0,0 +1,286 @@
+use serde::{Deserialize, Serialize};
+
+/// A connected keyboard, mouse or other HID device. `id` is stable across
+/// snapshots for the same physical device; `name` is for display.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputDevice {
+    pub id: String,
+    pub name: String,
+}
+
+/// Watches for input devices that appear after the baseline, e.g. a KVM
+/// switch presenting a second keyboard shared with another machine.
+#[derive(Default)]
+pub struct InputDeviceDetector {
+    baseline: Option<Vec<InputDevice>>,
+}
+
+impl InputDeviceDetector {
+    pub fn new() -> Self {
+        Self { baseline: None }
+    }
+
+    pub fn set_baseline(&mut self) -> Result<usize, String> {
+        let devices = self.get_input_devices()?;
+        let count = devices.len();
+        self.baseline = Some(devices);
+        Ok(count)
+    }
+
+    /// Returns devices connected since the baseline. Without a baseline the
+    /// first call records one and reports nothing.
+    pub fn detect_new_devices(&mut self) -> Result<Vec<InputDevice>, String> {
+        let current = self.get_input_devices()?;
+
+        match self.baseline {
+            Some(ref baseline) => Ok(new_devices(baseline, &current)),
+            None => {
+                self.baseline = Some(current);
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    /// Devices in the baseline, 0 before one is taken.
+    pub fn baseline_count(&self) -> usize {
+        self.baseline.as_ref().map_or(0, |baseline| baseline.len())
+    }
+
+    fn get_input_devices(&self) -> Result<Vec<InputDevice>, String> {
+        #[cfg(target_os = "windows")]
+        {
+            self.get_windows_hid_devices()
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            self.get_macos_hid_devices()
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let contents = std::fs::read_to_string("/proc/bus/input/devices")
+                .map_err(|e| format!("Failed to read /proc/bus/input/devices: {}", e))?;
+            Ok(parse_proc_input_devices(&contents))
+        }
+    }
+}
+
+/// Devices present in `current` but not in `baseline`, matched by id.
+pub fn new_devices(baseline: &[InputDevice], current: &[InputDevice]) -> Vec<InputDevice> {
+    current
+        .iter()
+        .filter(|device| !baseline.iter().any(|b| b.id == device.id))
+        .cloned()
+        .collect()
+}
+
+/// Parses `/proc/bus/input/devices`, keeping only devices with a keyboard or
+/// mouse handler. Power buttons, lid switches and the like are ignored: ACPI
+/// buttons carry a `kbd` handler, so host-bus (`Bus=0019`) and `/button/`
+/// devices are skipped too.
+pub fn parse_proc_input_devices(contents: &str) -> Vec<InputDevice> {
+    let mut devices = Vec::new();
+
+    for block in contents.split("\n\n") {
+        let mut ids = "";
+        let mut name = "";
+        let mut phys = "";
+        let mut handlers = "";
+
+        for line in block.lines() {
+            if let Some(rest) = line.strip_prefix("I: ") {
+                ids = rest.trim();
+            } else if let Some(rest) = line.strip_prefix("N: Name=") {
+                name = rest.trim().trim_matches('"');
+            } else if let Some(rest) = line.strip_prefix("P: Phys=") {
+                phys = rest.trim();
+            } else if let Some(rest) = line.strip_prefix("H: Handlers=") {
+                handlers = rest.trim();
+            }
+        }
+
+        let is_input = handlers
+            .split_whitespace()
+            .any(|h| h == "kbd" || h == "mouse" || h.starts_with("mouse"));
+
+        let is_button = phys.contains("/button/") || ids.split_whitespace().any(|id| id == "Bus=0019");
+
+        if !name.is_empty() && is_input && !is_button {
+            devices.push(InputDevice {
+                id: format!("{} {}", ids, phys),
+                name: name.to_string(),
+            });
+        }
+    }
+
+    devices
+}
+
+#[cfg(target_os = "windows")]
+impl InputDeviceDetector {
+    fn get_windows_hid_devices(&self) -> Result<Vec<InputDevice>, String> {
+        use windows::core::PCWSTR;
+        use windows::Win32::Devices::DeviceAndDriverInstallation::*;
+        use windows::Win32::Devices::HumanInterfaceDevice::HidD_GetHidGuid;
+        use windows::Win32::Foundation::HWND;
+
+        let mut devices = Vec::new();
+
+        unsafe {
+            let hid_guid = HidD_GetHidGuid();
+            let device_set = SetupDiGetClassDevsW(
+                Some(&hid_guid),
+                PCWSTR::null(),
+                HWND(0),
+                DIGCF_PRESENT | DIGCF_DEVICEINTERFACE,
+            )
+            .map_err(|e| format!("SetupDiGetClassDevsW failed: {}", e))?;
+
+            let mut index = 0;
+            loop {
+                let mut info = SP_DEVINFO_DATA {
+                    cbSize: std::mem::size_of::<SP_DEVINFO_DATA>() as u32,
+                    ..Default::default()
+                };
+                if SetupDiEnumDeviceInfo(device_set, index, &mut info).is_err() {
+                    break;
+                }
+                index += 1;
+
+                let mut id_buffer = [0u16; 512];
+                if SetupDiGetDeviceInstanceIdW(device_set, &info, Some(&mut id_buffer), None).is_err() {
+                    continue;
+                }
+                let id = utf16_until_nul(&id_buffer);
+
+                let mut name_buffer = [0u8; 512];
+                let name = if SetupDiGetDeviceRegistryPropertyW(
+                    device_set,
+                    &info,
+                    SPDRP_DEVICEDESC,
+                    None,
+                    Some(&mut name_buffer),
+                    None,
+                )
+                .is_ok()
+                {
+                    let wide: Vec<u16> = name_buffer
+                        .chunks_exact(2)
+                        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                        .collect();
+                    utf16_until_nul(&wide)
+                } else {
+                    id.clone()
+                };
+
+                devices.push(InputDevice { id, name });
+            }
+
+            let _ = SetupDiDestroyDeviceInfoList(device_set);
+        }
+
+        Ok(devices)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn utf16_until_nul(buffer: &[u16]) -> String {
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    String::from_utf16_lossy(&buffer[..len])
+}
+
+#[cfg(target_os = "macos")]
+impl InputDeviceDetector {
+    fn get_macos_hid_devices(&self) -> Result<Vec<InputDevice>, String> {
+        use crate::command::LimitedOutput;
+        use std::process::Command;
+
+        let output = Command::new("ioreg")
+            .args(["-r", "-c", "IOHIDDevice", "-l"])
+            .limited_output()
+            .map_err(|e| format!("Failed to run ioreg: {}", e))?;
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        let mut devices = Vec::new();
+
+        for block in text.split("+-o ").skip(1) {
+            let property = |key: &str| {
+                block
+                    .lines()
+                    .find_map(|line| line.trim().strip_prefix(&format!("\"{}\" = ", key)))
+                    .map(|value| value.trim_matches('"').to_string())
+            };
+
+            if let Some(name) = property("Product") {
+                let id = format!(
+                    "{}:{}:{}",
+                    property("VendorID").unwrap_or_default(),
+                    property("ProductID").unwrap_or_default(),
+                    property("LocationID").unwrap_or_default()
+                );
+                devices.push(InputDevice { id, name });
+            }
+        }
+
+        Ok(devices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: &str, name: &str) -> InputDevice {
+        InputDevice {
+            id: id.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_new_devices_diff() {
+        let baseline = vec![device("kbd-1", "Internal Keyboard"), device("mouse-1", "Touchpad")];
+        let current = vec![
+            device("kbd-1", "Internal Keyboard"),
+            device("mouse-1", "Touchpad"),
+            device("kvm-7", "KVM Switch Keyboard"),
+        ];
+
+        assert_eq!(new_devices(&baseline, &current), vec![device("kvm-7", "KVM Switch Keyboard")]);
+        assert!(new_devices(&current, &baseline).is_empty());
+    }
+
+    #[test]
+    fn test_parse_proc_input_devices() {
+        let contents = "\
+I: Bus=0019 Vendor=0000 Product=0001 Version=0000
+N: Name=\"Power Button\"
+P: Phys=PNP0C0C/button/input0
+H: Handlers=kbd event0
+
+I: Bus=0011 Vendor=0001 Product=0001 Version=ab41
+N: Name=\"AT Translated Set 2 keyboard\"
+P: Phys=isa0060/serio0/input0
+H: Handlers=sysrq kbd event1 leds
+
+I: Bus=0003 Vendor=046d Product=c52b Version=0111
+N: Name=\"Logitech USB Receiver\"
+P: Phys=usb-0000:00:14.0-2/input0
+H: Handlers=mouse0 event2
+
+I: Bus=0019 Vendor=0000 Product=0005 Version=0000
+N: Name=\"Lid Switch\"
+P: Phys=PNP0C0D/button/input0
+H: Handlers=event3
+";
+
+        let devices = parse_proc_input_devices(contents);
+        let names: Vec<_> = devices.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["AT Translated Set 2 keyboard", "Logitech USB Receiver"]);
+        assert_eq!(
+            devices[1].id,
+            "Bus=0003 Vendor=046d Product=c52b Version=0111 usb-0000:00:14.0-2/input0"
+        );
+    }
+}
\ No newline at end of file
@@ -0,0 +1,72 @@
+use crate::DetectionReport;
+use std::fs::File;
+use std::io::Write;
+
+/// Reports as newline-delimited JSON on the process's real stdout, for
+/// `--stdout-jsonl`. Everything else Fairview prints is moved to stderr so
+/// the stream stays parseable.
+pub struct JsonlStdout {
+    out: File,
+}
+
+impl JsonlStdout {
+    /// Keeps a handle to stdout for the reports and points the standard
+    /// output handle at stderr, so every later `println!`, in Fairview or
+    /// its dependencies, lands on stderr. Call it before printing anything.
+    pub fn claim() -> Result<Self, String> {
+        std::io::stdout().flush().map_err(|e| format!("Failed to flush stdout: {}", e))?;
+        Ok(Self { out: redirect_stdout_to_stderr()? })
+    }
+
+    pub fn write_report(&mut self, report: &DetectionReport) -> Result<(), String> {
+        write_line(&mut self.out, report)
+    }
+}
+
+/// Writes `report` as one compact JSON line and flushes it, so a consumer
+/// reading line by line sees each scan as soon as it finishes.
+pub fn write_line<W: Write>(out: &mut W, report: &DetectionReport) -> Result<(), String> {
+    let mut line = serde_json::to_vec(report).map_err(|e| format!("Failed to serialize report: {}", e))?;
+    line.push(b'\n');
+    out.write_all(&line)
+        .and_then(|()| out.flush())
+        .map_err(|e| format!("Failed to write report line: {}", e))
+}
+
+#[cfg(unix)]
+fn redirect_stdout_to_stderr() -> Result<File, String> {
+    use std::os::unix::io::FromRawFd;
+
+    unsafe {
+        let original = libc::dup(libc::STDOUT_FILENO);
+        if original < 0 {
+            return Err(format!("Failed to duplicate stdout: {}", std::io::Error::last_os_error()));
+        }
+        if libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+            let error = std::io::Error::last_os_error();
+            libc::close(original);
+            return Err(format!("Failed to redirect stdout to stderr: {}", error));
+        }
+        Ok(File::from_raw_fd(original))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn redirect_stdout_to_stderr() -> Result<File, String> {
+    use std::os::windows::io::FromRawHandle;
+    use windows::Win32::System::Console::{GetStdHandle, SetStdHandle, STD_ERROR_HANDLE, STD_OUTPUT_HANDLE};
+
+    // The standard library looks the handle up on every write, so swapping
+    // it redirects `println!` too.
+    unsafe {
+        let stdout = GetStdHandle(STD_OUTPUT_HANDLE).map_err(|e| format!("Failed to get stdout: {}", e))?;
+        let stderr = GetStdHandle(STD_ERROR_HANDLE).map_err(|e| format!("Failed to get stderr: {}", e))?;
+        SetStdHandle(STD_OUTPUT_HANDLE, stderr).map_err(|e| format!("Failed to redirect stdout to stderr: {}", e))?;
+        Ok(File::from_raw_handle(stdout.0 as _))
+    }
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn redirect_stdout_to_stderr() -> Result<File, String> {
+    Err("--stdout-jsonl is not supported on this platform".to_string())
+}
\ No newline at end of file
@@ -0,0 +1,4670 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+pub mod alert;
+pub mod audio_detector;
+pub mod baseline;
+pub mod bundle;
+pub mod cadence;
+pub mod cli;
+pub mod clipboard_detector;
+pub mod clock;
+pub mod command;
+pub mod config;
+pub mod correlation;
+pub mod definitions;
+pub mod denylist;
+pub mod desktop_detector;
+pub mod drive;
+pub mod event_log;
+pub mod fingerprint;
+pub mod focus_detector;
+pub mod hardware_detector;
+pub mod input_detector;
+pub mod jsonl;
+pub mod lru;
+pub mod module_activity;
+pub mod network_detector;
+pub mod notify;
+pub mod observations;
+pub mod overlay_detector;
+pub mod pacing;
+pub mod platform;
+pub mod process_monitor;
+pub mod provenance;
+pub mod report_formats;
+pub mod report_stream;
+pub mod review;
+pub mod rules;
+pub mod run_dir;
+pub mod selftest;
+pub mod session;
+pub mod snapshot;
+pub mod system_load;
+pub mod tcc;
+pub mod telemetry;
+pub mod trigger;
+pub mod unit;
+pub mod vm_detector;
+pub mod watch;
+
+use audio_detector::AudioCaptureDetector;
+use config::Config;
+use desktop_detector::DesktopDetector;
+use hardware_detector::HardwareDetector;
+use input_detector::{InputDevice, InputDeviceDetector};
+use clipboard_detector::ClipboardDetector;
+use network_detector::NetworkDetector;
+use overlay_detector::OverlayDetector;
+use platform::{PlatformProvider, SystemPlatform};
+use process_monitor::{normalize_process, NormalizedProcess, ProcessMonitor, ProcessSource};
+use vm_detector::VmDetector;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Process {
+    pub pid: u32,
+    pub name: String,
+    pub path: String,
+    pub stats: ProcessStats,
+    /// Login session the process runs in. `None` outside Windows or when the
+    /// session can't be read.
+    pub session_id: Option<u32>,
+    /// Drive the executable lives on.
+    pub drive_type: drive::DriveType,
+    /// Process that launched this one, when the platform reports it.
+    pub parent_pid: Option<u32>,
+}
+
+/// Resource footprint sampled alongside the process listing.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProcessStats {
+    pub memory_bytes: u64,
+    pub cpu_usage: f32,
+    pub run_time_seconds: u64,
+    /// `None` when the platform can't tell whether the process owns a window.
+    pub has_visible_window: Option<bool>,
+    /// GDI plus USER objects held; Windows only.
+    pub gui_objects: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuspiciousProcess {
+    pub pid: u32,
+    pub name: String,
+    pub path: String,
+    pub risk_score: f64,
+    pub reasons: Vec<String>,
+    pub reason_codes: Vec<ReasonCode>,
+    pub started_during_interview: bool,
+    pub is_whitelisted: bool,
+    pub drive_type: drive::DriveType,
+}
+
+/// One inspected process in the optional capability matrix, flagged or not,
+/// so a reviewer can audit every flagging decision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityEntry {
+    pub pid: u32,
+    pub name: String,
+    pub path: String,
+    pub screen_capture: bool,
+    pub audio_capture: bool,
+    pub accessibility: bool,
+    pub inspection_denied: bool,
+    /// Risk the process scan computed, before overlay and window
+    /// correlation.
+    pub risk_score: f64,
+    pub reason_codes: Vec<ReasonCode>,
+    pub flagged: bool,
+}
+
+/// Every non-whitelisted process a scan inspected, with `output.full_capability_matrix`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CapabilityMatrix {
+    pub entries: Vec<CapabilityEntry>,
+    /// Entries dropped by `max_reported_processes`, lowest risk first.
+    pub omitted: usize,
+}
+
+/// A baseline refresh from `rebaseline_interval_scans` or
+/// `rebaseline_interval_seconds`, logged in the scan that started with it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rebaseline {
+    pub baseline_processes: usize,
+    /// `None` when hardware monitoring is off or the displays couldn't be
+    /// read. The previous baseline's count when hardware flags kept it.
+    pub baseline_displays: Option<usize>,
+    /// Processes flagged in the previous scan that were kept out of the new
+    /// baseline.
+    pub carried_forward_pids: Vec<u32>,
+}
+
+/// A whitelisted process that also tripped a suspicious signal, so the
+/// proctor can see who an over-broad whitelist may be hiding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WhitelistConflict {
+    pub pid: u32,
+    pub name: String,
+    pub path: String,
+    /// Reason codes the process had when the whitelist was applied.
+    pub signals: Vec<ReasonCode>,
+    pub resolution: ConflictResolution,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// The whitelist cleared the process.
+    Cleared,
+    /// Scored at reduced risk, with `conflict_resolution = "downgrade"`.
+    Downgraded,
+    /// The whitelist didn't apply: the name is suspicious and
+    /// `whitelist_overrides_suspicious_name` is off, or another signal that
+    /// ignores the whitelist fired.
+    Overridden,
+}
+
+/// Result of one pass over the process list.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessScan {
+    pub suspicious: Vec<SuspiciousProcess>,
+    /// Non-whitelisted processes whose capabilities couldn't be inspected.
+    pub inaccessible: usize,
+    /// Highest `force_severity` of the custom rules matching a flagged process.
+    pub forced_severity: Option<Severity>,
+    /// The `force_severity` behind `forced_severity`, by flagged PID.
+    pub forced_severities: Vec<(u32, Severity)>,
+    /// Processes streaming the screen to another device, scored with the
+    /// hardware category.
+    pub cast_sessions: Vec<hardware_detector::CastSession>,
+    /// Filled with `output.full_capability_matrix`.
+    pub capability_matrix: Option<CapabilityMatrix>,
+    /// Processes enumerated, whitelisted or not.
+    pub inspected: usize,
+    /// Processes skipped because of `scan.max_processes_inspected`.
+    pub uninspected: usize,
+    pub whitelist_conflicts: Vec<WhitelistConflict>,
+    /// PID and path of every process enumerated, for first-seen tracking.
+    pub running: Vec<(u32, String)>,
+    /// Processes that exited between enumeration and inspection, whether or
+    /// not a retry recovered them.
+    pub exited_during_inspection: usize,
+    /// The listing was empty or left out Fairview itself, so finding nothing
+    /// says nothing about the machine.
+    pub enumeration_blocked: bool,
+}
+
+/// Machine-readable form of a process flag reason. `reasons` holds the
+/// matching human-readable text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasonCode {
+    ScreenCapture,
+    ScreenCaptureHint,
+    AudioCapture,
+    Accessibility,
+    SuspiciousName,
+    StartedDuringInterview,
+    QuietHelper,
+    CaptureExcludedWindow,
+    OwnsHiddenOverlay,
+    LocalBrowserProxy,
+    ParkedOnInactiveDesktop,
+    FocusStealing,
+    CorrelatedAcrossScans,
+    CustomRule,
+    UnknownPath,
+    GuiObjectOutlier,
+    ExternalDrive,
+    UnexpectedParent,
+    IdeDuringBehavioralInterview,
+    PermissionGrantedDuringInterview,
+    SystemNameImpersonation,
+    FreshExecutable,
+    Denylisted,
+}
+
+impl ReasonCode {
+    pub fn description(&self) -> &'static str {
+        match self {
+            ReasonCode::ScreenCapture => "Has screen capture permission",
+            ReasonCode::ScreenCaptureHint => "Links DXGI/Direct3D (weak screen capture hint)",
+            ReasonCode::AudioCapture => "Has audio capture permission",
+            ReasonCode::Accessibility => "Has accessibility API access",
+            ReasonCode::SuspiciousName => "Suspicious process name",
+            ReasonCode::StartedDuringInterview => "Started during interview",
+            ReasonCode::QuietHelper => "Quiet background helper profile (low footprint, no window, capture capability)",
+            ReasonCode::CaptureExcludedWindow => "Owns a window excluded from screen capture",
+            ReasonCode::OwnsHiddenOverlay => "Suspicious process owns hidden overlay",
+            ReasonCode::LocalBrowserProxy => "Serves a localhost port the browser is connected to",
+            ReasonCode::ParkedOnInactiveDesktop => "Suspicious window on inactive virtual desktop",
+            ReasonCode::FocusStealing => "Repeatedly takes input focus",
+            ReasonCode::CorrelatedAcrossScans => "Implicated by events across several scans",
+            ReasonCode::CustomRule => "Matched custom rule",
+            ReasonCode::UnknownPath => "Executable path could not be resolved",
+            ReasonCode::GuiObjectOutlier => "Holds unusually many GDI/USER objects for its size (UI scraping)",
+            ReasonCode::ExternalDrive => "Runs from a removable or network drive",
+            ReasonCode::UnexpectedParent => "Browser launched by an unexpected parent process (automation)",
+            ReasonCode::IdeDuringBehavioralInterview => "IDE opened during a behavioral interview",
+            ReasonCode::PermissionGrantedDuringInterview => "Privacy permission granted during interview",
+            ReasonCode::SystemNameImpersonation => "System process name outside the system directories",
+            ReasonCode::FreshExecutable => "Executable written shortly before the scan",
+            ReasonCode::Denylisted => "On the configured denylist",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    #[default]
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Breaches are at least `High`, and `Critical` once the overall score
+    /// reaches 0.8. Below the threshold, scores from 0.25 up are `Medium`.
+    pub fn from_score(overall_risk_score: f64, exceeds_threshold: bool) -> Self {
+        if exceeds_threshold && overall_risk_score >= 0.8 {
+            Severity::Critical
+        } else if exceeds_threshold {
+            Severity::High
+        } else if overall_risk_score >= 0.25 {
+            Severity::Medium
+        } else {
+            Severity::Low
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DetectionReport {
+    #[serde(with = "timestamp_format")]
+    pub timestamp: SystemTime,
+    pub scan_number: usize,
+    pub session_id: Option<String>,
+    pub candidate_ref: Option<String>,
+    /// Identifies the machine the report came from; see `fingerprint`.
+    pub machine_fingerprint: Option<String>,
+    /// Host and OS account the scan ran under.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<provenance::Provenance>,
+    pub suspicious_processes: Vec<SuspiciousProcess>,
+    pub hidden_overlays: Vec<OverlayWindow>,
+    /// Score of the most suspicious overlay, including any left out of the list.
+    pub overlay_confidence: f64,
+    /// Windows of flagged processes on a virtual desktop other than the current one.
+    pub parked_windows: Vec<desktop_detector::ParkedWindow>,
+    /// All windows of non-whitelisted processes on other virtual desktops.
+    pub inactive_desktop_windows: usize,
+    /// Processes that took the foreground unusually often since the last scan.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub focus_steals: Vec<focus_detector::FocusSteal>,
+    /// The application with input focus when the scan ran; absent when no
+    /// window had focus or the platform couldn't tell.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub foreground_app: Option<focus_detector::ForegroundApp>,
+    /// Cross-scan rules matched by this scan's events and earlier ones
+    /// within `correlation.window_scans`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub correlated_findings: Vec<correlation::CorrelatedFinding>,
+    /// Non-whitelisted processes Fairview was denied permission to inspect;
+    /// their capabilities are unknown, so coverage was incomplete.
+    pub inaccessible_processes: usize,
+    /// Processes left uninspected by `scan.max_processes_inspected`; absent
+    /// when every process was inspected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uninspected_processes: Option<usize>,
+    /// Processes that exited while being inspected, handled per
+    /// `scan.exited_process_policy`; absent when there were none. Steady
+    /// counts point at flaky inspection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exited_during_inspection: Option<usize>,
+    pub audio_monitoring_detected: bool,
+    /// Capture streams counted, where the platform can (Linux).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_capture_streams: Option<audio_detector::CaptureStreams>,
+    pub audio_loops: Vec<audio_detector::AudioLoop>,
+    /// Audio inputs connected since the baseline; virtual ones count as
+    /// audio monitoring.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub new_audio_inputs: Vec<audio_detector::AudioInputDevice>,
+    pub hardware_suspicion: Option<HardwareSuspicionReport>,
+    pub new_input_devices: Vec<InputDevice>,
+    /// Windows Cloud Clipboard uploads copies to the user's other devices,
+    /// so a second device can put answers on this machine's clipboard.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cloud_clipboard_sync: bool,
+    pub vm_detection: Option<vm_detector::VmCheckResult>,
+    /// How busy the whole machine was, with `monitoring.enable_system_load`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_load: Option<system_load::SystemLoad>,
+    pub overall_risk_score: f64,
+    /// Every finding added up without the 1.0 cap; see `calculate_raw_risk`.
+    /// Verdicts only use `overall_risk_score`.
+    pub raw_risk_score: f64,
+    pub exceeds_threshold: bool,
+    pub severity: Severity,
+    /// How far `overall_risk_score` can be trusted given module coverage.
+    pub uncertainty: Uncertainty,
+    pub category_breaches: Vec<CategoryBreach>,
+    /// Set when a configured policy forced the breach and severity, as
+    /// opposed to the scores reaching their thresholds.
+    pub policy_breach: Option<PolicyBreach>,
+    /// Enforcing without a baseline: "started during the interview" and
+    /// every other comparison with the start of the session is off. See
+    /// `FairviewDetector::baseline_missing`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub baseline_missing: bool,
+    /// Set in observe mode: the report carries scores and would-be breaches,
+    /// but `exceeds_threshold` is never set and severity ignores thresholds.
+    pub observational: bool,
+    /// Set when `suspicious_processes` or `hidden_overlays` were cut down to the
+    /// configured maximum; the `omitted_*` counts say how many were dropped.
+    pub truncated: bool,
+    pub omitted_processes: usize,
+    pub omitted_overlays: usize,
+    /// Processes and overlays dropped for scoring below `min_report_confidence`.
+    pub suppressed_findings: usize,
+    pub module_failures: Vec<String>,
+    pub operator_annotation: Option<review::OperatorAnnotation>,
+    pub whitelist_profile: Option<String>,
+    pub scan_duration_ms: u64,
+    pub interval_adjustment: Option<pacing::IntervalAdjustment>,
+    pub clock_tampering_suspected: bool,
+    pub clock_tampering_reason: Option<String>,
+    /// Every cache was dropped before this scan, on operator request.
+    pub forced_fresh: bool,
+    /// Modules whose `[cadence]` skipped this scan; their fields repeat the
+    /// module's last result.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub carried_forward_modules: Vec<String>,
+    /// Only with `output.full_capability_matrix`; left out of the JSON
+    /// otherwise to keep reports small.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_capability_matrix: Option<CapabilityMatrix>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rebaseline: Option<Rebaseline>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub whitelist_conflicts: Vec<WhitelistConflict>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskCategory {
+    Hardware,
+    Audio,
+    Overlay,
+}
+
+/// Policies that force a report to a critical breach.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyBreach {
+    /// A VM was detected with `vm.hard_fail` set.
+    VmHardFail,
+    /// A virtual display was detected with `hardware.virtual_display_hard_fail` set.
+    VirtualDisplayHardFail,
+    /// A wireless display was detected with `hardware.wireless_display_hard_fail` set.
+    WirelessDisplayHardFail,
+    /// A `[[rules]]` entry with `force_severity` of `high` or `critical`
+    /// matched a flagged process.
+    CustomRule,
+    /// The machine fingerprint differs from the one the session started on.
+    MachineChanged,
+    /// A process on the `[denylist]` was running.
+    Denylisted,
+}
+
+/// A single category whose own score crossed its threshold in `[thresholds]`,
+/// which marks the report as a breach regardless of the weighted overall score.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryBreach {
+    pub category: RiskCategory,
+    pub score: f64,
+    pub threshold: f64,
+}
+
+/// How much of the scan's coverage was lost to failed or degraded modules,
+/// so a score gathered under poor conditions isn't read with false precision.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Uncertainty {
+    /// 0 when every enabled module ran fully, 1 when none did; a degraded
+    /// module counts half.
+    pub score: f64,
+    pub modules_run: usize,
+    /// Modules that ran but with partial coverage: processes that couldn't
+    /// be inspected, or a module that has never produced output.
+    pub degraded_modules: usize,
+    pub failed_modules: usize,
+}
+
+impl Uncertainty {
+    pub fn from_modules(modules_run: usize, degraded_modules: usize, failed_modules: usize) -> Self {
+        let score = if modules_run == 0 {
+            0.0
+        } else {
+            ((failed_modules as f64 + degraded_modules as f64 * 0.5) / modules_run as f64).min(1.0)
+        };
+        Self { score, modules_run, degraded_modules, failed_modules }
+    }
+}
+
+pub fn round_score(score: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    (score * factor).round() / factor
+}
+
+/// RFC3339 (de)serialization of `SystemTime`, for every time field that
+/// ends up in output: `#[serde(with = "crate::timestamp_format")]`, or
+/// `timestamp_format::option` for an `Option<SystemTime>`. Serde's own form
+/// is a `{secs_since_epoch, nanos_since_epoch}` object.
+pub mod timestamp_format {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::SystemTime;
+    use chrono::{DateTime, Utc};
+
+    pub fn format(time: &SystemTime) -> String {
+        let datetime: DateTime<Utc> = (*time).into();
+        datetime.to_rfc3339()
+    }
+
+    pub fn serialize<S>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        format(time).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<SystemTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let text = String::deserialize(deserializer)?;
+        DateTime::parse_from_rfc3339(&text)
+            .map(|datetime| datetime.with_timezone(&Utc).into())
+            .map_err(serde::de::Error::custom)
+    }
+
+    pub mod option {
+        use serde::{Deserialize, Deserializer, Serializer};
+        use std::time::SystemTime;
+
+        pub fn serialize<S>(time: &Option<SystemTime>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match time {
+                Some(time) => super::serialize(time, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<SystemTime>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            #[derive(Deserialize)]
+            struct Timestamp(#[serde(with = "super")] SystemTime);
+
+            Ok(Option::<Timestamp>::deserialize(deserializer)?.map(|Timestamp(time)| time))
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HardwareSuspicionReport {
+    pub risk_score: f64,
+    pub display_count: usize,
+    pub has_virtual_display: bool,
+    /// A wireless display is connected or a Miracast session is casting.
+    #[serde(default)]
+    pub has_wireless_display: bool,
+    pub has_hdmi_splitter: bool,
+    pub remote_desktop: hardware_detector::RemoteDesktopStatus,
+    pub cast_sessions: Vec<hardware_detector::CastSession>,
+    pub flags: Vec<String>,
+    pub baseline_display_count: Option<usize>,
+    pub display_changed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayWindow {
+    /// The same across scans for the same window, unlike `handle`, which the
+    /// OS may reuse. See `overlay_detector::overlay_id`.
+    #[serde(default)]
+    pub id: String,
+    pub handle: usize,
+    pub position: (i32, i32),
+    pub size: (u32, u32),
+    pub owner_pid: u32,
+    pub is_transparent: bool,
+    pub is_topmost: bool,
+    pub is_cloaked: bool,
+    pub excluded_from_capture: bool,
+    pub title: String,
+    pub class_name: String,
+    /// Set when the owning process was independently flagged as suspicious.
+    pub owner_flagged: bool,
+    /// Constant alpha of a layered window (0 = invisible, 255 = opaque), when
+    /// it sets one.
+    pub alpha: Option<u8>,
+    /// `suspicion_score` at the time of the scan.
+    pub risk_score: f64,
+    /// Scan that first reported this overlay.
+    #[serde(default)]
+    pub first_seen_scan: usize,
+}
+
+/// Layered alpha at or below which a window is barely visible on screen.
+const NEAR_INVISIBLE_ALPHA: u8 = 64;
+
+/// Overlays at least this large cover a whole display, which is what game,
+/// chat and recording overlays do; answer panels are smaller.
+const FULL_SCREEN_OVERLAY_AREA: u64 = 1920 * 1080;
+
+impl OverlayWindow {
+    /// Windows hidden from screen capture score 1.0, since legitimate apps have
+    /// little reason to hide from the proctor's recording. Other layered
+    /// windows start at 0.2, with 0.4 more each for being click-through and for
+    /// being always-on-top, 0.3 more when a flagged process owns them and 0.2
+    /// more when nearly transparent. Full-screen overlays count 0.2 less.
+    pub fn suspicion_score(&self) -> f64 {
+        if self.excluded_from_capture {
+            return 1.0;
+        }
+
+        let area = self.size.0 as u64 * self.size.1 as u64;
+        let score = 0.2
+            + if self.is_transparent { 0.4 } else { 0.0 }
+            + if self.is_topmost { 0.4 } else { 0.0 }
+            + if self.owner_flagged { OWNER_FLAGGED_OVERLAY_BOOST } else { 0.0 }
+            + if self.alpha.is_some_and(|a| a <= NEAR_INVISIBLE_ALPHA) { 0.2 } else { 0.0 }
+            - if area >= FULL_SCREEN_OVERLAY_AREA { 0.2 } else { 0.0 };
+        score.clamp(0.0, 1.0)
+    }
+}
+
+/// Confidence that the scan found a cheating overlay: the score of the most
+/// suspicious window, or 0.0 without overlays.
+pub fn overlay_confidence(overlays: &[OverlayWindow]) -> f64 {
+    overlays
+        .iter()
+        .map(OverlayWindow::suspicion_score)
+        .fold(0.0, f64::max)
+}
+
+/// Audio category score. A loop, or a virtual input plugged in mid-interview,
+/// is conclusive. Otherwise the interview app's own microphone stream is
+/// expected, so only the streams beyond it count, and a stock microphone in
+/// use (or an uncounted yes/no detection) scores 0.
+pub fn audio_category_score(streams: usize, conclusive: bool) -> f64 {
+    if conclusive {
+        return 1.0;
+    }
+    match streams {
+        0 => 0.0,
+        n => (n - 1) as f64 / n as f64,
+    }
+}
+
+impl DetectionReport {
+    /// Rounds every score in the report to `decimals`, from
+    /// `output.score_precision`. Run last, once every threshold and verdict
+    /// has been decided on the full-precision scores.
+    pub fn round_scores(&mut self, decimals: u32) {
+        let round = |score: &mut f64| *score = round_score(*score, decimals);
+        round(&mut self.overall_risk_score);
+        round(&mut self.raw_risk_score);
+        round(&mut self.overlay_confidence);
+        round(&mut self.uncertainty.score);
+        self.suspicious_processes.iter_mut().for_each(|p| round(&mut p.risk_score));
+        self.hidden_overlays.iter_mut().for_each(|o| round(&mut o.risk_score));
+        for breach in &mut self.category_breaches {
+            round(&mut breach.score);
+            round(&mut breach.threshold);
+        }
+        if let Some(ref mut hardware) = self.hardware_suspicion {
+            round(&mut hardware.risk_score);
+        }
+        if let Some(ref mut vm) = self.vm_detection {
+            round(&mut vm.confidence_score);
+            round(&mut vm.raw_confidence_score);
+        }
+        if let Some(ref mut matrix) = self.full_capability_matrix {
+            matrix.entries.iter_mut().for_each(|entry| round(&mut entry.risk_score));
+        }
+    }
+
+    /// Drops processes and overlays scoring below `min_confidence`, counting
+    /// them in `suppressed_findings`. Call after scoring so they still count
+    /// toward `overall_risk_score`.
+    pub fn suppress_below(&mut self, min_confidence: f64) {
+        let before = self.suspicious_processes.len() + self.hidden_overlays.len();
+        self.suspicious_processes.retain(|p| p.risk_score >= min_confidence);
+        self.hidden_overlays.retain(|o| o.suspicion_score() >= min_confidence);
+        self.suppressed_findings += before - self.suspicious_processes.len() - self.hidden_overlays.len();
+    }
+
+    /// Keeps only the highest-risk `max_processes` processes and `max_overlays`
+    /// overlays, counting the rest in `omitted_*`. Scores are computed before
+    /// truncation, so this only bounds the size of the report. Kept overlays
+    /// go back to id order, as `OverlayDetector::identify` left them.
+    pub fn truncate(&mut self, max_processes: usize, max_overlays: usize) {
+        if self.suspicious_processes.len() > max_processes {
+            self.suspicious_processes
+                .sort_by(|a, b| b.risk_score.partial_cmp(&a.risk_score).unwrap_or(std::cmp::Ordering::Equal));
+            self.omitted_processes += self.suspicious_processes.len() - max_processes;
+            self.suspicious_processes.truncate(max_processes);
+            self.truncated = true;
+        }
+
+        if self.hidden_overlays.len() > max_overlays {
+            self.hidden_overlays.sort_by(|a, b| {
+                b.suspicion_score()
+                    .partial_cmp(&a.suspicion_score())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            self.omitted_overlays += self.hidden_overlays.len() - max_overlays;
+            self.hidden_overlays.truncate(max_overlays);
+            self.hidden_overlays.sort_by(|a, b| a.id.cmp(&b.id));
+            self.truncated = true;
+        }
+
+        if let Some(ref mut matrix) = self.full_capability_matrix {
+            if matrix.entries.len() > max_processes {
+                matrix.entries
+                    .sort_by(|a, b| b.risk_score.partial_cmp(&a.risk_score).unwrap_or(std::cmp::Ordering::Equal));
+                matrix.omitted += matrix.entries.len() - max_processes;
+                matrix.entries.truncate(max_processes);
+                self.truncated = true;
+            }
+        }
+    }
+}
+
+/// Process risk added for owning a window hidden from screen capture.
+const CAPTURE_EXCLUDED_WEIGHT: f64 = 0.8;
+
+/// Process risk added when an already flagged process owns a hidden overlay.
+const OVERLAY_OWNER_WEIGHT: f64 = 0.2;
+
+/// Overlay suspicion added when its owner is an already flagged process.
+const OWNER_FLAGGED_OVERLAY_BOOST: f64 = 0.3;
+
+/// Process risk added for serving a localhost port a browser is connected to.
+const LOCAL_BROWSER_PROXY_WEIGHT: f64 = 0.6;
+
+/// Browser executables (see `NormalizedProcess::name_matches`) whose
+/// loopback connections point at a possible extension-backed helper.
+const BROWSER_NAMES: &[&str] = &["chrome", "chromium", "msedge", "firefox", "brave", "opera", "safari", "vivaldi"];
+
+fn is_browser(process: &NormalizedProcess) -> bool {
+    BROWSER_NAMES.iter().any(|browser| process.name_matches(browser))
+}
+
+/// How much to scale the risk of a process started during the interview
+/// `run_time_seconds` before the scan: `max_multiplier` when it just started,
+/// falling linearly to 1.0 at `window_seconds`.
+pub fn recency_multiplier(run_time_seconds: u64, window_seconds: u64, max_multiplier: f64) -> f64 {
+    if run_time_seconds >= window_seconds {
+        return 1.0;
+    }
+    let freshness = 1.0 - run_time_seconds as f64 / window_seconds as f64;
+    1.0 + (max_multiplier - 1.0) * freshness
+}
+
+/// Windows system processes whose names a tool may borrow to pass as one.
+/// Each lives in System32 or SysWOW64, except `explorer.exe` in the Windows
+/// directory itself.
+const SYSTEM_PROCESS_NAMES: &[&str] = &[
+    "svchost", "explorer", "lsass", "csrss", "winlogon", "wininit", "services", "smss", "spoolsv",
+    "taskhostw", "sihost", "runtimebroker", "dllhost", "conhost", "ctfmon", "dwm", "searchhost",
+    "searchindexer", "rundll32", "fontdrvhost", "audiodg",
+];
+
+/// Process risk added for linking the graphics stack without a capture
+/// session; a held capture session adds 0.3.
+const SCREEN_CAPTURE_HINT_WEIGHT: f64 = 0.05;
+
+/// Process risk added for keeping a window on an inactive virtual desktop.
+const PARKED_WINDOW_WEIGHT: f64 = 0.4;
+
+/// Process risk added for holding an outlying number of GUI objects.
+const GUI_OBJECT_OUTLIER_WEIGHT: f64 = 0.1;
+
+/// Risk multiplier for a whitelisted process scored anyway under
+/// `conflict_resolution = "downgrade"`.
+const WHITELIST_CONFLICT_DISCOUNT: f64 = 0.5;
+
+/// Process risk added, with `flag_unknown_path_processes`, for a capable
+/// process whose executable path can't be resolved.
+const UNKNOWN_PATH_WEIGHT: f64 = 0.1;
+
+/// IDEs, expected in coding and system design interviews. Matched as whole
+/// executable names, so a tool merely named after one gets no pass.
+const IDE_NAMES: &[&str] = &[
+    "code", "devenv", "xcode", "idea", "idea64", "pycharm", "pycharm64", "webstorm", "webstorm64",
+    "clion", "clion64", "goland", "goland64", "rider", "rider64", "sublime_text",
+];
+
+/// Terminals and compilers, expected only in coding interviews; matched
+/// like `IDE_NAMES`.
+const DEVELOPMENT_TOOL_NAMES: &[&str] = &[
+    "cmd", "powershell", "pwsh", "windowsterminal", "terminal", "iterm2", "gnome-terminal",
+    "konsole", "alacritty", "wezterm-gui", "gcc", "g++", "clang", "cl", "rustc", "cargo", "javac",
+];
+
+fn is_ide(process: &NormalizedProcess) -> bool {
+    IDE_NAMES.iter().any(|name| process.stem_is(name))
+}
+
+pub struct FairviewDetector {
+    process_monitor: ProcessMonitor,
+    audio_detector: AudioCaptureDetector,
+    overlay_detector: OverlayDetector,
+    hardware_detector: HardwareDetector,
+    input_detector: InputDeviceDetector,
+    network_detector: NetworkDetector,
+    desktop_detector: DesktopDetector,
+    focus_detector: focus_detector::FocusDetector,
+    clipboard_detector: ClipboardDetector,
+    vm_detector: VmDetector,
+    /// `scan.interview_type`, parsed once; unknown values fall back to coding.
+    interview_type: config::InterviewType,
+    system_load_monitor: system_load::SystemLoadMonitor,
+    platform: Arc<dyn PlatformProvider>,
+    events: correlation::EventBuffer,
+    drop_watcher: correlation::DropWatcher,
+    /// PIDs running at the previous scan, to tell processes started since.
+    previous_pids: Option<HashSet<u32>>,
+    /// Monotonic time each running process, by PID and path, was first
+    /// seen, for `min_process_lifetime_seconds`.
+    first_seen: HashMap<(u32, String), Duration>,
+    denylist: denylist::Denylist,
+    /// Entries `[definitions]` added to the config, taken out again when
+    /// newer definitions replace them.
+    remote_definitions: definitions::Definitions,
+    config: Config,
+    scan_count: usize,
+    /// Set by `force_fresh_next_scan` until the next scan.
+    force_fresh: bool,
+    vm_cadence: cadence::Cadenced<vm_detector::VmCheckResult>,
+    audio_cadence: cadence::Cadenced<(bool, Option<audio_detector::CaptureStreams>)>,
+    audio_loop_cadence: cadence::Cadenced<Vec<audio_detector::AudioLoop>>,
+    audio_device_cadence: cadence::Cadenced<Vec<audio_detector::AudioInputDevice>>,
+    hardware_cadence: cadence::Cadenced<hardware_detector::HardwareSuspicion>,
+    input_cadence: cadence::Cadenced<Vec<InputDevice>>,
+    baseline_collected: bool,
+    /// Wall time the baseline was first taken, to tell permissions granted
+    /// since the interview started. Rebaselining leaves it as is.
+    baseline_time: Option<SystemTime>,
+    /// When the baseline was last taken or refreshed, and scans run since.
+    last_baseline: Option<clock::ClockSample>,
+    scans_since_baseline: usize,
+    /// Processes flagged by the previous scan, for `carry_forward_flagged`.
+    flagged_pids: HashSet<u32>,
+    machine_fingerprint: Option<String>,
+    provenance: Option<provenance::Provenance>,
+    /// Fingerprint the session started on, when resumed on a later run.
+    session_fingerprint: Option<String>,
+    module_activity: module_activity::ModuleActivity,
+    last_clock: Option<clock::ClockSample>,
+    clock: Box<dyn clock::Clock>,
+}
+
+impl FairviewDetector {
+    pub fn new(config: Config) -> Self {
+        Self::with_platform(config, Arc::new(SystemPlatform))
+    }
+
+    /// Reads processes, displays, remote desktop and audio state through
+    /// `platform` instead of the OS.
+    pub fn with_platform(config: Config, platform: Arc<dyn PlatformProvider>) -> Self {
+        Self::with_sources(config, Box::new(platform.clone()), platform)
+    }
+
+    /// Takes processes from `source`; everything else comes from the OS.
+    pub fn with_process_source(config: Config, source: Box<dyn ProcessSource>) -> Self {
+        Self::with_sources(config, source, Arc::new(SystemPlatform))
+    }
+
+    fn with_sources(config: Config, source: Box<dyn ProcessSource>, platform: Arc<dyn PlatformProvider>) -> Self {
+        command::limiter().set_max(config.scan.max_concurrent_commands);
+        Self {
+            process_monitor: ProcessMonitor::with_source(config.clone(), source),
+            audio_detector: AudioCaptureDetector::with_platform(platform.clone()),
+            overlay_detector: OverlayDetector::with_platform(platform.clone()),
+            focus_detector: focus_detector::FocusDetector::with_platform(platform.clone()),
+            clipboard_detector: ClipboardDetector::with_platform(platform.clone()),
+            system_load_monitor: system_load::SystemLoadMonitor::with_platform(platform.clone()),
+            platform: platform.clone(),
+            interview_type: config.scan.interview_type.parse().unwrap_or_default(),
+            hardware_detector: {
+                let mut detector = HardwareDetector::with_platform(platform.clone());
+                detector.set_expected_displays(config.hardware.expected_displays.clone());
+                detector.set_connection_weights(config.hardware.connection_weights.clone());
+                detector.set_display_change_scans(config.hardware.display_change_scans);
+                detector.set_display_grace_scans(config.hardware.display_grace_scans);
+                if config.hardware.enable_capture_card_detection {
+                    detector.set_capture_card_weight(Some(config.hardware.capture_card_weight));
+                }
+                detector
+            },
+            input_detector: InputDeviceDetector::new(),
+            network_detector: NetworkDetector::new(),
+            desktop_detector: DesktopDetector::new(),
+            vm_detector: {
+                let mut detector = VmDetector::with_platform(platform.clone());
+                detector.set_smoothing_factor(config.vm.smoothing_factor);
+                detector
+            },
+            denylist: denylist::Denylist::new(&config.denylist),
+            remote_definitions: definitions::Definitions::default(),
+            events: correlation::EventBuffer::new(config.correlation.window_scans),
+            drop_watcher: correlation::DropWatcher::new(&config.correlation.watch_dirs),
+            previous_pids: None,
+            first_seen: HashMap::new(),
+            vm_cadence: cadence::Cadenced::new(config.cadence.vm_detection),
+            audio_cadence: cadence::Cadenced::new(config.cadence.audio),
+            audio_loop_cadence: cadence::Cadenced::new(config.cadence.audio_loops),
+            audio_device_cadence: cadence::Cadenced::new(config.cadence.audio_devices),
+            hardware_cadence: cadence::Cadenced::new(config.cadence.hardware),
+            input_cadence: cadence::Cadenced::new(config.cadence.input_devices),
+            config,
+            scan_count: 0,
+            force_fresh: false,
+            baseline_collected: false,
+            baseline_time: None,
+            last_baseline: None,
+            scans_since_baseline: 0,
+            flagged_pids: HashSet::new(),
+            machine_fingerprint: None,
+            provenance: None,
+            session_fingerprint: None,
+            module_activity: module_activity::ModuleActivity::new(),
+            last_clock: None,
+            clock: Box::new(clock::SystemClock::new()),
+        }
+    }
+
+    pub fn collect_baseline(&mut self) {
+        if !self.config.monitoring.collect_baseline {
+            println!("[*] Baseline collection disabled in config");
+            return;
+        }
+
+        println!("\n{}", "=".repeat(60));
+        println!("COLLECTING BASELINE");
+        println!("{}", "=".repeat(60));
+        println!("[*] Please ensure all necessary applications are running");
+        println!("[*] Baseline collection will take {} seconds...\n", 
+                 self.config.monitoring.baseline_duration_seconds);
+
+        self.platform.refresh_snapshot();
+        self.process_monitor.collect_baseline();
+        self.drop_watcher.poll();
+
+        if let Err(e) = self.hardware_detector.set_baseline() {
+            println!("[!] Warning: Failed to collect hardware baseline: {}", e);
+        } else {
+            if let Some(baseline) = self.hardware_detector.get_baseline() {
+                println!("[+] Hardware baseline: {} displays detected", baseline.display_count);
+            }
+        }
+
+        if self.config.monitoring.enable_input_device_monitoring {
+            match self.input_detector.set_baseline() {
+                Ok(count) => println!("[+] Input device baseline: {} devices detected", count),
+                Err(e) => println!("[!] Warning: Failed to collect input device baseline: {}", e),
+            }
+        }
+
+        if self.config.monitoring.enable_audio_device_monitoring {
+            match self.audio_detector.set_baseline() {
+                Ok(count) => println!("[+] Audio input baseline: {} devices detected", count),
+                Err(e) => println!("[!] Warning: Failed to collect audio input baseline: {}", e),
+            }
+        }
+
+        println!("[+] Baseline collection complete\n");
+        self.baseline_collected = true;
+        let now = self.clock.now();
+        self.baseline_time = Some(now.wall);
+        self.last_baseline = Some(now);
+        self.scans_since_baseline = 0;
+
+        if let Some(ref path) = self.config.monitoring.baseline_file {
+            match self.baseline_snapshot().save(path) {
+                Ok(()) => println!("[+] Baseline saved to {}", path),
+                Err(e) => println!("[!] {}", e),
+            }
+        }
+    }
+
+    /// Whether this session enforces verdicts with nothing to compare the
+    /// machine against: baseline collection is off, there are no
+    /// `expected_displays`, and `warn_without_baseline` is on. New
+    /// processes, display and device changes all go unnoticed then.
+    pub fn baseline_missing(&self) -> bool {
+        let monitoring = &self.config.monitoring;
+        monitoring.warn_without_baseline
+            && !monitoring.collect_baseline
+            && self.config.mode == config::Mode::Enforce
+            && self.config.hardware.expected_displays.is_empty()
+    }
+
+    /// The collected process and display baseline in its persisted form.
+    pub fn baseline_snapshot(&self) -> baseline::BaselineSnapshot {
+        baseline::BaselineSnapshot {
+            captured_at: timestamp_format::format(&self.clock.now().wall),
+            processes: self
+                .process_monitor
+                .baseline_processes()
+                .map(|(name, path)| baseline::BaselineProcess {
+                    name: name.to_string(),
+                    path: path.to_string(),
+                })
+                .collect(),
+            displays: self
+                .hardware_detector
+                .get_baseline()
+                .map(|config| {
+                    config
+                        .displays
+                        .iter()
+                        .map(|d| baseline::BaselineDisplay {
+                            id: d.id.clone(),
+                            name: d.name.clone(),
+                            width: d.width,
+                            height: d.height,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Starts the between-scan display sampling of `hardware.display_sample_seconds`,
+    /// if set. Call it after the baseline so sampling starts from it.
+    pub fn start_display_sampler(&mut self) {
+        let Some(seconds) = self.config.hardware.display_sample_seconds else {
+            return;
+        };
+        if !self.config.monitoring.enable_hardware_monitoring {
+            return;
+        }
+        self.hardware_detector
+            .start_display_sampler(std::time::Duration::from_secs(seconds), self.config.hardware.transient_display_weight);
+        println!("[*] Sampling the display configuration every {} seconds between scans", seconds);
+    }
+
+    /// Starts sampling the foreground window with
+    /// `overlays.focus_sample_millis`, unless process monitoring is off.
+    pub fn start_focus_tracking(&mut self) {
+        let Some(millis) = self.config.overlays.focus_sample_millis else {
+            return;
+        };
+        if !self.config.monitoring.enable_process_monitoring {
+            return;
+        }
+        self.focus_detector.start(Duration::from_millis(millis));
+        println!("[*] Sampling the foreground window every {} ms for focus stealing", millis);
+    }
+
+    pub fn set_whitelist_profile(&mut self, name: Option<&str>) -> Result<(), String> {
+        self.process_monitor.set_active_profile(name)
+    }
+
+    /// Merges centrally published whitelist, denylist and suspicious-name
+    /// entries into the config, replacing those of earlier definitions.
+    pub fn set_definitions(&mut self, definitions: &definitions::Definitions) {
+        let mut config = self.config.clone();
+        self.remote_definitions.remove_from(&mut config);
+        self.remote_definitions = definitions.apply_to(&mut config);
+        self.process_monitor.set_config(config.clone());
+        self.denylist = denylist::Denylist::new(&config.denylist);
+        self.config = config;
+    }
+
+    /// Replaces the time source used for report timestamps, scan durations and
+    /// clock tampering checks.
+    pub fn set_clock(&mut self, clock: Box<dyn clock::Clock>) {
+        self.clock = clock;
+    }
+
+    /// The time source set by `set_clock`, for stamping files written
+    /// alongside reports.
+    pub fn clock(&self) -> &dyn clock::Clock {
+        self.clock.as_ref()
+    }
+
+    /// Picks up a stage change requested through `whitelist.profile_switch_file`.
+    /// An empty or missing file selects the configured `active_profile`, so a
+    /// deleted switch file can't quietly drop the session to the base whitelist.
+    fn refresh_whitelist_profile(&mut self) {
+        let Some(ref path) = self.config.whitelist.profile_switch_file else {
+            return;
+        };
+
+        let requested = std::fs::read_to_string(path)
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default();
+        let requested = if requested.is_empty() {
+            self.config.whitelist.active_profile.clone()
+        } else {
+            Some(requested)
+        };
+
+        if requested.as_deref() == self.process_monitor.active_profile_name() {
+            return;
+        }
+
+        match self.set_whitelist_profile(requested.as_deref()) {
+            Ok(()) => println!("[*] Switched whitelist profile to {}",
+                               requested.as_deref().unwrap_or("<base>")),
+            Err(e) => println!("[!] Ignoring profile switch: {}", e),
+        }
+    }
+
+    /// Makes the next scan drop every cache first, so nothing in its report
+    /// comes from an earlier scan's lookups.
+    pub fn force_fresh_next_scan(&mut self) {
+        self.force_fresh = true;
+    }
+
+    /// Whether this scan is forced fresh, by `force_fresh_next_scan` or by
+    /// `scan.force_fresh_file` appearing, and if so drops the caches. The
+    /// file is removed so it applies to one scan.
+    fn take_force_fresh(&mut self) -> bool {
+        if let Some(ref path) = self.config.scan.force_fresh_file {
+            if std::fs::remove_file(path).is_ok() {
+                self.force_fresh = true;
+            }
+        }
+        if !std::mem::take(&mut self.force_fresh) {
+            return false;
+        }
+
+        self.denylist.clear_cache();
+        tcc::clear_bundle_ids();
+        println!("[*] Forced fresh scan: caches cleared");
+        true
+    }
+
+    /// Stamps every report with `fingerprint`. `session_fingerprint` is the
+    /// one the session's earlier runs recorded, if any; a different machine
+    /// is a critical breach.
+    pub fn set_machine_fingerprint(&mut self, fingerprint: String, session_fingerprint: Option<String>) {
+        self.machine_fingerprint = Some(fingerprint);
+        self.session_fingerprint = session_fingerprint;
+    }
+
+    /// Stamps every report with where it came from.
+    pub fn set_provenance(&mut self, provenance: provenance::Provenance) {
+        self.provenance = Some(provenance);
+    }
+
+    /// Continues numbering after `last_scan_number`, for a monitor resumed
+    /// from a session checkpoint.
+    pub fn resume_scan_count(&mut self, last_scan_number: usize) {
+        self.scan_count = last_scan_number;
+    }
+
+    pub fn scan(&mut self) -> DetectionReport {
+        self.scan_count += 1;
+        let clock = self.clock.now();
+        println!("\n[*] Starting scan #{} at {:?}", self.scan_count, clock.wall);
+
+        let tolerance = std::time::Duration::from_secs(self.config.scan.clock_tolerance_seconds);
+        let clock_tampering_reason = self.last_clock
+            .and_then(|previous| clock.compare_with(&previous, tolerance));
+        if let Some(ref reason) = clock_tampering_reason {
+            println!("[!] Clock tampering suspected: {}", reason);
+        }
+        self.last_clock = Some(clock);
+
+        // One snapshot and one process listing serve every module. A panic
+        // here is left for the process module to hit again under its own
+        // isolation and report.
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.platform.refresh_snapshot();
+            self.process_monitor.hold_listing();
+        }));
+
+        self.refresh_whitelist_profile();
+        let forced_fresh = self.take_force_fresh();
+        let rebaseline = self.rebaseline_if_due(&clock);
+
+        let mut module_failures = Vec::new();
+
+        // A forced-fresh scan runs every module whatever its cadence.
+        let scan_number = self.scan_count;
+        let mut carried_forward_modules = Vec::new();
+        let mut carry_forward = |enabled: bool, due: bool, module: &str| {
+            let skip = enabled && !due && !forced_fresh;
+            if skip {
+                carried_forward_modules.push(module.to_string());
+            }
+            skip
+        };
+        let skip_vm = carry_forward(self.config.monitoring.enable_vm_detection, self.vm_cadence.is_due(scan_number), "VM detection");
+        let skip_audio_loops = carry_forward(self.config.monitoring.enable_audio_loop_detection, self.audio_loop_cadence.is_due(scan_number), "Audio loop detection");
+        let skip_audio_devices = carry_forward(self.config.monitoring.enable_audio_device_monitoring, self.audio_device_cadence.is_due(scan_number), "Audio device monitoring");
+        let skip_audio = carry_forward(self.config.monitoring.enable_audio_monitoring, self.audio_cadence.is_due(scan_number), "Audio detection");
+        let skip_hardware = carry_forward(self.config.monitoring.enable_hardware_monitoring, self.hardware_cadence.is_due(scan_number), "Hardware monitoring");
+        let skip_input = carry_forward(self.config.monitoring.enable_input_device_monitoring, self.input_cadence.is_due(scan_number), "Input device monitoring");
+        if !carried_forward_modules.is_empty() {
+            println!("[*] Reusing the last results of {} per [cadence]", carried_forward_modules.join(", "));
+        }
+
+        let vm_result = if skip_vm {
+            self.vm_cadence.last()
+        } else if self.config.monitoring.enable_vm_detection {
+             match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                self.vm_detector.detect()
+            })) {
+                Ok(result) => {
+                    self.module_activity.record("VM detection", true);
+                    if result.is_vm {
+                         println!("[!] VM DETECTED! Confidence: {:.2}", result.confidence_score);
+                    }
+                    Some(self.vm_cadence.store(result))
+                },
+                Err(_) => {
+                    let error = "VM detection module failed";
+                    module_failures.push(error.to_string());
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // The passes that build on the process list enumerate processes
+        // again, so they are skipped once the process module has failed.
+        let mut processes_scanned = false;
+        let process_scan = if self.config.monitoring.enable_process_monitoring {
+            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                self.scan_processes()
+            })) {
+                Ok(mut scan) => {
+                    processes_scanned = true;
+                    self.hold_back_short_lived(&mut scan);
+                    self.module_activity.record("Process monitoring", scan.inspected > 0);
+                    println!("[+] Found {} suspicious processes", scan.suspicious.len());
+                    if !scan.whitelist_conflicts.is_empty() {
+                        println!("[!] {} whitelisted processes also tripped suspicious signals",
+                                 scan.whitelist_conflicts.len());
+                    }
+                    if scan.uninspected > 0 {
+                        println!("[!] Inspected {} of {} processes (max_processes_inspected)",
+                                 scan.inspected - scan.uninspected, scan.inspected);
+                    }
+                    if scan.inaccessible > 0 {
+                        println!("[!] {} processes could not be inspected (access denied); run elevated for full coverage",
+                                 scan.inaccessible);
+                    }
+                    // Fail closed: an empty scan from a blocked listing
+                    // must not read as a clean machine.
+                    if scan.enumeration_blocked {
+                        let error = "Process enumeration unavailable: the process list was empty or omitted Fairview itself";
+                        println!("[!] {}", error);
+                        module_failures.push(error.to_string());
+                    }
+                    scan
+                },
+                Err(_) => {
+                    let error = "Process monitoring module failed";
+                    module_failures.push(error.to_string());
+                    println!("[!] {}", error);
+                    ProcessScan::default()
+                }
+            }
+        } else {
+            ProcessScan::default()
+        };
+        let mut suspicious_processes = process_scan.suspicious;
+
+        let mut hidden_overlays = if self.config.monitoring.enable_overlay_monitoring {
+            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                self.overlay_detector.find_hidden_overlays()
+            })) {
+                Ok(overlays) => {
+                    self.module_activity.record(
+                        "Overlay monitoring",
+                        OverlayDetector::is_supported() || !overlays.is_empty(),
+                    );
+                    let mut overlays = self.drop_allowlisted_overlays(overlays);
+                    self.overlay_detector.identify(&mut overlays, self.scan_count);
+                    println!("[+] Found {} suspicious overlays", overlays.len());
+                    overlays
+                },
+                Err(_) => {
+                    let error = "Overlay detection module failed";
+                    module_failures.push(error.to_string());
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        if processes_scanned {
+            // Correlate first so owners flagged only for a capture-excluded
+            // window aren't boosted a second time for the same window.
+            self.correlate_overlay_owners(&mut suspicious_processes, &mut hidden_overlays);
+            self.flag_capture_excluded_owners(&mut suspicious_processes, &hidden_overlays);
+        }
+
+        for overlay in &mut hidden_overlays {
+            overlay.risk_score = overlay.suspicion_score();
+        }
+
+        if processes_scanned && self.config.monitoring.enable_network_monitoring {
+            match self.network_detector.tcp_connections() {
+                Ok(connections) => {
+                    self.module_activity.record("Network monitoring", !connections.is_empty());
+                    self.flag_local_browser_proxies(&mut suspicious_processes, &connections);
+                }
+                Err(e) => module_failures.push(format!("Network inspection failed: {}", e)),
+            }
+        }
+
+        let mut parked_windows = Vec::new();
+        let mut inactive_desktop_windows = 0;
+        if processes_scanned && self.config.monitoring.enable_virtual_desktop_detection {
+            match self.desktop_detector.desktop_windows() {
+                Ok(windows) => {
+                    self.module_activity.record("Virtual desktop detection", !windows.is_empty());
+                    (parked_windows, inactive_desktop_windows) =
+                        self.flag_parked_windows(&mut suspicious_processes, &windows);
+                    println!("[+] Found {} windows on inactive virtual desktops ({} suspicious)",
+                             inactive_desktop_windows, parked_windows.len());
+                }
+                Err(e) => module_failures.push(format!("Virtual desktop inspection failed: {}", e)),
+            }
+        }
+
+        let mut focus_steals = Vec::new();
+        if processes_scanned && self.focus_detector.is_running() {
+            focus_steals = self.flag_focus_steals(&mut suspicious_processes, &hidden_overlays);
+            if !focus_steals.is_empty() {
+                println!("[!] {} processes repeatedly took input focus", focus_steals.len());
+            }
+        }
+
+        let audio_loops = if skip_audio_loops {
+            self.audio_loop_cadence.last().unwrap_or_default()
+        } else if self.config.monitoring.enable_audio_loop_detection {
+            match self.audio_detector.audio_endpoints() {
+                Ok(endpoints) => {
+                    self.module_activity.record("Audio loop detection", !endpoints.is_empty());
+                    let loops = audio_detector::find_audio_loops(&endpoints);
+                    for audio_loop in &loops {
+                        println!("[!] Audio loop: {} is recorded through {}", audio_loop.render_name, audio_loop.capture_name);
+                    }
+                    self.audio_loop_cadence.store(loops)
+                }
+                Err(e) => {
+                    module_failures.push(format!("Audio loop detection failed: {}", e));
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        let new_audio_inputs = if skip_audio_devices {
+            self.audio_device_cadence.last().unwrap_or_default()
+        } else if self.config.monitoring.enable_audio_device_monitoring {
+            match self.audio_detector.detect_new_input_devices() {
+                Ok(inputs) => {
+                    self.module_activity.record(
+                        "Audio device monitoring",
+                        !inputs.is_empty() || self.audio_detector.baseline_input_count() > 0,
+                    );
+                    for input in inputs.iter().filter(|input| input.virtual_device) {
+                        println!("[!] Virtual audio input connected: {}", input.name);
+                    }
+                    println!("[+] Found {} new audio inputs", inputs.len());
+                    self.audio_device_cadence.store(inputs)
+                }
+                Err(e) => {
+                    module_failures.push(format!("Audio device detection failed: {}", e));
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        let system_load = if self.config.monitoring.enable_system_load {
+            self.system_load_monitor.read(self.config.heuristics.saturated_cpu_percent)
+        } else {
+            None
+        };
+        if let Some(load) = system_load.filter(|load| load.saturated) {
+            println!("[*] System is saturated (CPU {:.0}%); CPU-based signals are down-weighted", load.cpu_percent);
+        }
+
+        let (audio_monitoring, audio_capture_streams) = if skip_audio {
+            self.audio_cadence.last().unwrap_or_default()
+        } else if self.config.monitoring.enable_audio_monitoring {
+            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                self.audio_detector.detect_capture_streams()
+            })) {
+                Ok((detected, streams)) => {
+                    println!("[+] Audio monitoring detected: {}", detected);
+                    if let Some(ref streams) = streams {
+                        if streams.count > 1 {
+                            println!("[!] {} audio capture streams at once ({})", streams.count, streams.applications.join(", "));
+                        }
+                    }
+                    self.audio_cadence.store((detected, streams))
+                },
+                Err(_) => {
+                    let error = "Audio detection module failed";
+                    module_failures.push(error.to_string());
+                    (false, None)
+                }
+            }
+        } else {
+            (false, None)
+        };
+        // A loop, or a virtual input plugged in mid-interview, is a stronger
+        // audio signal than a capture-capable app, so it counts toward the
+        // audio weight and threshold on its own.
+        let audio_monitoring = audio_monitoring
+            || !audio_loops.is_empty()
+            || new_audio_inputs.iter().any(|input| input.virtual_device);
+        let audio_streams = match audio_capture_streams {
+            _ if !audio_monitoring => 0,
+            Some(ref streams) => streams.count.max(1),
+            None => 1,
+        };
+        // Without counted streams, loops or virtual inputs the detection
+        // rests on the realtime fallback alone.
+        let audio_weight = if AudioCaptureDetector::REALTIME_AUDIO_USES_CPU
+            && audio_capture_streams.is_none()
+            && audio_loops.is_empty()
+            && !new_audio_inputs.iter().any(|input| input.virtual_device)
+        {
+            self.cpu_signal_weight(system_load.as_ref())
+        } else {
+            1.0
+        };
+        let audio_score = if audio_monitoring { audio_weight } else { 0.0 };
+        let audio_category = audio_category_score(
+            audio_streams,
+            !audio_loops.is_empty() || new_audio_inputs.iter().any(|input| input.virtual_device),
+        );
+
+        let hardware_suspicion = if skip_hardware {
+            self.hardware_cadence.last()
+        } else if self.config.monitoring.enable_hardware_monitoring {
+            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                self.hardware_detector.detect_hardware_cheating()
+            })) {
+                Ok(mut suspicion) => {
+                    let saw_displays = suspicion.details.get("display_count").is_some_and(|count| count != "0");
+                    self.module_activity.record("Hardware monitoring", saw_displays || !suspicion.flags.is_empty());
+                    if self.config.hardware.enable_cast_detection {
+                        hardware_detector::score_cast_sessions(
+                            &process_scan.cast_sessions,
+                            self.config.hardware.cast_session_weight,
+                            &mut suspicion,
+                        );
+                    }
+                    println!("[+] Hardware risk score: {:.2}", suspicion.risk_score);
+                    Some(self.hardware_cadence.store(suspicion))
+                },
+                Err(_) => {
+                    let error = "Hardware detection module failed";
+                    module_failures.push(error.to_string());
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let new_input_devices = if skip_input {
+            self.input_cadence.last().unwrap_or_default()
+        } else if self.config.monitoring.enable_input_device_monitoring {
+            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                self.input_detector.detect_new_devices()
+            })) {
+                Ok(Ok(devices)) => {
+                    self.module_activity.record(
+                        "Input device monitoring",
+                        !devices.is_empty() || self.input_detector.baseline_count() > 0,
+                    );
+                    println!("[+] Found {} new input devices", devices.len());
+                    self.input_cadence.store(devices)
+                },
+                Ok(Err(e)) => {
+                    module_failures.push(format!("Input device detection failed: {}", e));
+                    Vec::new()
+                },
+                Err(_) => {
+                    let error = "Input device detection module failed";
+                    module_failures.push(error.to_string());
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        let cloud_clipboard_sync = if self.config.monitoring.enable_clipboard_monitoring {
+            let sync = self.clipboard_detector.cloud_clipboard_sync();
+            self.module_activity.record("Clipboard monitoring", sync.is_some());
+            if sync == Some(true) {
+                println!("[!] Cloud Clipboard sync is on: another device can relay clipboard content");
+            }
+            sync == Some(true)
+        } else {
+            false
+        };
+
+        let correlated_findings = if self.config.correlation.window_scans > 0 && processes_scanned {
+            let findings = self.correlate_events(&mut suspicious_processes, &hidden_overlays, &focus_steals);
+            if !findings.is_empty() {
+                println!("[!] {} findings correlated with earlier scans", findings.len());
+            }
+            findings
+        } else {
+            Vec::new()
+        };
+
+        let foreground_app = if self.config.overlays.capture_foreground_app {
+            let app = self.foreground_app(&suspicious_processes, &hidden_overlays, &process_scan.running);
+            match app {
+                Some(ref app) if app.is_suspicious() => {
+                    println!("[!] Foreground app {} (PID {}) is {}",
+                             app.name.as_deref().unwrap_or("unknown"), app.pid,
+                             if app.flagged { "flagged" } else { "the owner of a hidden overlay" });
+                }
+                _ => {}
+            }
+            app
+        } else {
+            None
+        };
+
+        // Silent-module warnings below are not failures of this scan.
+        let failed_modules = module_failures.len();
+        let mut degraded_modules = usize::from(process_scan.inaccessible > 0 || process_scan.uninspected > 0);
+
+        // Audio monitoring only answers yes or no, so a silent run can't be
+        // told from a clean one and it isn't tracked.
+        let silent_module_scans = self.config.monitoring.silent_module_scans;
+        if silent_module_scans > 0 {
+            degraded_modules += self.module_activity.silent_count(silent_module_scans);
+            for module in self.module_activity.newly_silent(silent_module_scans) {
+                let warning = format!(
+                    "{} has produced no output in {} scans and may not work in this environment",
+                    module, silent_module_scans
+                );
+                println!("[!] {}", warning);
+                module_failures.push(warning);
+            }
+        }
+
+        let input_score = self.input_score(&new_input_devices, cloud_clipboard_sync);
+        let overall_risk = self.calculate_overall_risk(
+            &suspicious_processes,
+            &hidden_overlays,
+            audio_score,
+            hardware_suspicion.as_ref(),
+            vm_result.as_ref(),
+            input_score,
+        );
+        let raw_risk = self.calculate_raw_risk(
+            &suspicious_processes,
+            &hidden_overlays,
+            audio_streams as f64 * audio_weight,
+            hardware_suspicion.as_ref(),
+            vm_result.as_ref(),
+            input_score,
+        );
+
+        let category_breaches = self.category_breaches(
+            hardware_suspicion.as_ref(),
+            audio_category,
+            &hidden_overlays,
+        );
+        for breach in &category_breaches {
+            println!("[!] {:?} score {:.2} exceeds its threshold of {:.2}",
+                     breach.category, breach.score, breach.threshold);
+        }
+
+        let exceeds_threshold = overall_risk >= self.config.scan.risk_threshold
+            || !category_breaches.is_empty();
+
+        println!("[!] Overall risk score: {:.2}/1.0 {}", 
+                 overall_risk,
+                 match (exceeds_threshold, self.config.mode) {
+                     (false, _) => "",
+                     (true, config::Mode::Enforce) => "(EXCEEDS THRESHOLD)",
+                     (true, config::Mode::Observe) => "(would exceed threshold; observe mode)",
+                 });
+
+        let hardware_report = hardware_suspicion.map(|hs| {
+            let (display_count, has_virtual_display, has_wireless_display, has_hdmi_splitter) =
+                Self::summarize_hardware(&hs);
+            
+            let baseline_count = self.hardware_detector.reference_display_count();
+            
+            let display_changed = if let Some(baseline) = baseline_count {
+                baseline != display_count
+            } else {
+                false
+            };
+
+            HardwareSuspicionReport {
+                risk_score: hs.risk_score,
+                display_count,
+                has_virtual_display,
+                has_wireless_display,
+                has_hdmi_splitter,
+                remote_desktop: hs.remote_desktop.clone(),
+                cast_sessions: hs.cast_sessions.clone(),
+                flags: hs.flags.clone(),
+                baseline_display_count: baseline_count,
+                display_changed,
+            }
+        });
+
+        self.flagged_pids = suspicious_processes.iter().map(|p| p.pid).collect();
+
+        let uncertainty = Uncertainty::from_modules(self.enabled_module_count(), degraded_modules, failed_modules);
+        if uncertainty.score > 0.0 {
+            println!("[!] Score uncertainty {:.2}: {} of {} modules failed, {} degraded",
+                     uncertainty.score, failed_modules, uncertainty.modules_run, degraded_modules);
+        }
+
+        let finished = self.clock.now();
+        let mut report = DetectionReport {
+            timestamp: finished.wall,
+            scan_number: self.scan_count,
+            session_id: self.config.session.session_id.clone(),
+            candidate_ref: self.config.session.candidate_ref.clone(),
+            machine_fingerprint: self.machine_fingerprint.clone(),
+            provenance: self.provenance.clone(),
+            suspicious_processes,
+            overlay_confidence: overlay_confidence(&hidden_overlays),
+            hidden_overlays,
+            parked_windows,
+            inactive_desktop_windows,
+            focus_steals,
+            foreground_app,
+            correlated_findings,
+            inaccessible_processes: process_scan.inaccessible,
+            uninspected_processes: (process_scan.uninspected > 0).then_some(process_scan.uninspected),
+            exited_during_inspection: (process_scan.exited_during_inspection > 0)
+                .then_some(process_scan.exited_during_inspection),
+            audio_monitoring_detected: audio_monitoring,
+            audio_capture_streams,
+            audio_loops,
+            new_audio_inputs,
+            hardware_suspicion: hardware_report,
+            new_input_devices,
+            cloud_clipboard_sync,
+            vm_detection: vm_result,
+            system_load,
+            overall_risk_score: overall_risk,
+            raw_risk_score: raw_risk,
+            exceeds_threshold,
+            severity: Severity::from_score(overall_risk, exceeds_threshold),
+            uncertainty,
+            category_breaches,
+            policy_breach: None,
+            baseline_missing: self.baseline_missing(),
+            observational: false,
+            truncated: false,
+            omitted_processes: 0,
+            omitted_overlays: 0,
+            suppressed_findings: 0,
+            module_failures,
+            operator_annotation: None,
+            whitelist_profile: self.process_monitor.active_profile_name().map(|s| s.to_string()),
+            scan_duration_ms: finished.since(&clock).as_millis() as u64,
+            interval_adjustment: None,
+            clock_tampering_suspected: clock_tampering_reason.is_some(),
+            clock_tampering_reason,
+            forced_fresh,
+            carried_forward_modules,
+            full_capability_matrix: process_scan.capability_matrix,
+            rebaseline,
+            whitelist_conflicts: process_scan.whitelist_conflicts,
+        };
+
+        self.process_monitor.release_listing();
+        self.apply_vm_policy(&mut report);
+        self.apply_display_policy(&mut report);
+        self.apply_machine_policy(&mut report);
+        Self::apply_denylist_policy(&mut report);
+        Self::apply_rule_severity(&mut report, process_scan.forced_severity);
+        self.apply_mode(&mut report);
+        // Everything above saw every finding. Trimming only shortens the
+        // lists, so consumers of the returned report (the session verdict's
+        // reason counts, telemetry, notifications) see the reported findings
+        // alone, while its scores and verdict still cover all of them.
+        report.suppress_below(self.config.scan.min_report_confidence);
+        report.truncate(self.config.scan.max_reported_processes, self.config.scan.max_reported_overlays);
+        report.round_scores(self.config.output.score_precision);
+        report
+    }
+
+    /// Modules a scan runs under the current config.
+    fn enabled_module_count(&self) -> usize {
+        let monitoring = &self.config.monitoring;
+        let process = monitoring.enable_process_monitoring;
+        [
+            monitoring.enable_vm_detection,
+            process,
+            monitoring.enable_overlay_monitoring,
+            process && monitoring.enable_network_monitoring,
+            process && monitoring.enable_virtual_desktop_detection,
+            monitoring.enable_audio_loop_detection,
+            monitoring.enable_audio_device_monitoring,
+            monitoring.enable_audio_monitoring,
+            monitoring.enable_hardware_monitoring,
+            monitoring.enable_input_device_monitoring,
+            monitoring.enable_clipboard_monitoring,
+        ]
+        .iter()
+        .filter(|&&enabled| enabled)
+        .count()
+    }
+
+    /// Refreshes the process and display baseline once a configured interval
+    /// has passed since it was last taken.
+    fn rebaseline_if_due(&mut self, now: &clock::ClockSample) -> Option<Rebaseline> {
+        let last = self.last_baseline?;
+        let monitoring = &self.config.monitoring;
+        let due = monitoring.rebaseline_interval_scans.is_some_and(|scans| self.scans_since_baseline >= scans)
+            || monitoring
+                .rebaseline_interval_seconds
+                .is_some_and(|seconds| now.since(&last) >= Duration::from_secs(seconds));
+        if !due {
+            self.scans_since_baseline += 1;
+            return None;
+        }
+
+        let carried_forward = if monitoring.carry_forward_flagged {
+            std::mem::take(&mut self.flagged_pids)
+        } else {
+            HashSet::new()
+        };
+        let baseline_processes = self.process_monitor.rebaseline(&carried_forward);
+        let hardware_flagged = self.hardware_cadence.last().is_some_and(|hardware| !hardware.flags.is_empty());
+        let baseline_displays = if !self.config.monitoring.enable_hardware_monitoring {
+            None
+        } else if monitoring.carry_forward_flagged && hardware_flagged {
+            // Refreshing now would make a flagged display part of the baseline.
+            println!("[*] Keeping the display baseline while hardware flags are raised");
+            self.hardware_detector.get_baseline().map(|b| b.display_count)
+        } else {
+            match self.hardware_detector.set_baseline() {
+                Ok(()) => self.hardware_detector.get_baseline().map(|b| b.display_count),
+                Err(e) => {
+                    println!("[!] Warning: Failed to refresh hardware baseline: {}", e);
+                    None
+                }
+            }
+        };
+
+        let mut carried_forward_pids: Vec<u32> = carried_forward.into_iter().collect();
+        carried_forward_pids.sort_unstable();
+        println!(
+            "[*] Baseline refreshed: {} processes, {} flagged process(es) carried forward",
+            baseline_processes,
+            carried_forward_pids.len()
+        );
+        self.last_baseline = Some(*now);
+        // This scan already runs against the new baseline.
+        self.scans_since_baseline = 1;
+        Some(Rebaseline { baseline_processes, baseline_displays, carried_forward_pids })
+    }
+
+    /// With `vm.hard_fail`, a detected VM is a critical breach on its own;
+    /// the overall score is left as calculated.
+    pub fn apply_vm_policy(&self, report: &mut DetectionReport) {
+        let is_vm = report.vm_detection.as_ref().is_some_and(|vm| vm.is_vm);
+        if !self.config.vm.hard_fail || !is_vm {
+            return;
+        }
+
+        println!("[!] Virtual machine detected with vm.hard_fail set; forcing a critical breach");
+        report.exceeds_threshold = true;
+        report.severity = Severity::Critical;
+        report.policy_breach = Some(PolicyBreach::VmHardFail);
+    }
+
+    /// With `hardware.virtual_display_hard_fail` or `wireless_display_hard_fail`,
+    /// a display of that kind is a critical breach on its own. The connection
+    /// weights have already scored it; the overall score is left as calculated.
+    pub fn apply_display_policy(&self, report: &mut DetectionReport) {
+        let Some(ref hardware) = report.hardware_suspicion else {
+            return;
+        };
+        let config = &self.config.hardware;
+        let breach = if config.virtual_display_hard_fail && hardware.has_virtual_display {
+            PolicyBreach::VirtualDisplayHardFail
+        } else if config.wireless_display_hard_fail && hardware.has_wireless_display {
+            PolicyBreach::WirelessDisplayHardFail
+        } else {
+            return;
+        };
+
+        println!("[!] {} with its hard_fail set; forcing a critical breach",
+                 if breach == PolicyBreach::VirtualDisplayHardFail { "Virtual display detected" } else { "Wireless display detected" });
+        report.exceeds_threshold = true;
+        report.severity = Severity::Critical;
+        report.policy_breach = Some(breach);
+    }
+
+    /// A session that moved to another machine is being relayed or was handed
+    /// to someone else, so it's a critical breach whatever the scores say.
+    pub fn apply_machine_policy(&self, report: &mut DetectionReport) {
+        let (Some(current), Some(session)) = (&self.machine_fingerprint, &self.session_fingerprint) else {
+            return;
+        };
+        if current == session {
+            return;
+        }
+
+        println!("[!] Machine fingerprint {} differs from the session's {}; forcing a critical breach", current, session);
+        report.exceeds_threshold = true;
+        report.severity = Severity::Critical;
+        report.policy_breach = Some(PolicyBreach::MachineChanged);
+    }
+
+    /// A denylisted process is a critical breach on its own.
+    pub fn apply_denylist_policy(report: &mut DetectionReport) {
+        let denylisted: Vec<&str> = report
+            .suspicious_processes
+            .iter()
+            .filter(|p| p.reason_codes.contains(&ReasonCode::Denylisted))
+            .map(|p| p.name.as_str())
+            .collect();
+        if denylisted.is_empty() {
+            return;
+        }
+
+        println!("[!] Denylisted process running: {}; forcing a critical breach", denylisted.join(", "));
+        report.exceeds_threshold = true;
+        report.severity = Severity::Critical;
+        report.policy_breach = Some(PolicyBreach::Denylisted);
+    }
+
+    /// Raises the report to the severity forced by a matching custom rule.
+    /// `high` and above are breaches, as with score-based severities.
+    pub fn apply_rule_severity(report: &mut DetectionReport, forced: Option<Severity>) {
+        let Some(forced) = forced else {
+            return;
+        };
+
+        report.severity = report.severity.max(forced);
+        if forced >= Severity::High && !report.exceeds_threshold {
+            println!("[!] Custom rule forced {:?} severity", forced);
+            report.exceeds_threshold = true;
+            report.policy_breach.get_or_insert(PolicyBreach::CustomRule);
+        }
+    }
+
+    /// In observe mode, withdraws the verdict so nothing downstream (review
+    /// prompts, event log levels, the session verdict) treats the scan as a
+    /// breach. Category and policy breaches stay in the report as would-be
+    /// verdicts for calibration.
+    pub fn apply_mode(&self, report: &mut DetectionReport) {
+        if self.config.mode != config::Mode::Observe {
+            return;
+        }
+
+        report.observational = true;
+        report.exceeds_threshold = false;
+        report.severity = Severity::from_score(report.overall_risk_score, false);
+    }
+
+    fn summarize_hardware(
+        hardware_suspicion: &hardware_detector::HardwareSuspicion,
+    ) -> (usize, bool, bool, bool) {
+        let display_count = hardware_suspicion
+            .details
+            .get("display_count")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(1);
+
+        let has_virtual_display = hardware_suspicion
+            .flags
+            .iter()
+            .any(|f| f.to_lowercase().contains("virtual display"));
+
+        let has_wireless_display = hardware_suspicion.details.get("wireless_displays").is_some_and(|n| n != "0")
+            || hardware_suspicion
+                .cast_sessions
+                .iter()
+                .any(|session| session.kind == hardware_detector::CastKind::Miracast);
+
+        let has_hdmi_splitter = hardware_suspicion
+            .flags
+            .iter()
+            .any(|f| f.to_lowercase().contains("hdmi splitter"));
+
+        (display_count, has_virtual_display, has_wireless_display, has_hdmi_splitter)
+    }
+
+    pub fn scan_for_suspicious_processes(&self) -> Vec<SuspiciousProcess> {
+        self.scan_processes().suspicious
+    }
+
+    /// Flags suspicious processes and counts the non-whitelisted ones whose
+    /// capabilities couldn't be inspected.
+    pub fn scan_processes(&self) -> ProcessScan {
+        let mut suspicious = Vec::new();
+        let mut inaccessible = 0;
+        let mut forced_severity = None;
+        let mut forced_severities = Vec::new();
+        let mut whitelist_conflicts = Vec::new();
+        let mut exited_during_inspection = 0;
+        let processes = self.process_monitor.get_all_processes();
+        let enumeration_blocked = self.process_monitor.enumeration_blocked(&processes);
+        let inspected = processes.len();
+        let running = processes.iter().map(|p| (p.pid, p.path.clone())).collect();
+        let cast_sessions = if self.config.hardware.enable_cast_detection {
+            hardware_detector::find_cast_sessions(&processes)
+        } else {
+            Vec::new()
+        };
+        let heuristics = &self.config.heuristics;
+        let gui_outliers = if heuristics.enable_gui_object_outliers {
+            process_monitor::gui_object_outliers(&processes, heuristics.gui_outlier_factor, heuristics.gui_outlier_min_objects)
+        } else {
+            Default::default()
+        };
+        let mut matrix = self.config.output.full_capability_matrix.then(CapabilityMatrix::default);
+        let (processes, uninspected) = self.cap_inspected(processes);
+        let names_by_pid: HashMap<u32, NormalizedProcess> = if heuristics.enable_browser_parent_check {
+            processes.iter().map(|p| (p.pid, normalize_process(p))).collect()
+        } else {
+            HashMap::new()
+        };
+
+        // Read once, and only when an executable's age is checked, so
+        // `FixedClock` reports don't depend on the processes scanned.
+        let scan_time = std::cell::OnceCell::new();
+        for process in processes {
+            let mut reason_codes = Vec::new();
+            let mut risk_score: f64 = 0.0;
+
+            let normalized = normalize_process(&process);
+            let denylisted = self.denylist.match_process(&process, &normalized);
+            if denylisted.is_some() {
+                reason_codes.push(ReasonCode::Denylisted);
+            }
+            let is_whitelisted = self.process_monitor.is_whitelisted(&process);
+            let was_in_baseline = self.process_monitor.was_in_baseline(process.pid);
+            let started_during = self.baseline_collected && !was_in_baseline;
+
+            let trusted_from_baseline = self.baseline_collected
+                && was_in_baseline
+                && !self.config.heuristics.flag_baseline_processes;
+            let mut capabilities = if trusted_from_baseline {
+                process_monitor::ProcessCapabilities::default()
+            } else {
+                self.process_monitor.capabilities(&process)
+            };
+            if capabilities.process_exited {
+                exited_during_inspection += 1;
+                if self.config.scan.exited_process_policy == config::ExitedProcessPolicy::RetryOnce {
+                    capabilities = self.process_monitor.capabilities(&process);
+                }
+                if capabilities.process_exited {
+                    println!("[*] {} (PID {}) exited during inspection; skipped", process.name, process.pid);
+                    continue;
+                }
+            }
+            let has_screen = capabilities.screen_capture;
+            let has_audio = capabilities.audio_capture;
+            let has_access = capabilities.accessibility;
+            let has_suspicious_name = self.is_suspicious_name(&normalized);
+            let is_common_legit = self.is_common_legit_app(&normalized);
+
+            if has_screen {
+                reason_codes.push(ReasonCode::ScreenCapture);
+                risk_score += 0.3;
+            } else if capabilities.screen_capture_hint {
+                reason_codes.push(ReasonCode::ScreenCaptureHint);
+                risk_score += SCREEN_CAPTURE_HINT_WEIGHT;
+            }
+
+            if has_audio {
+                reason_codes.push(ReasonCode::AudioCapture);
+                risk_score += 0.3;
+            }
+
+            if has_access {
+                reason_codes.push(ReasonCode::Accessibility);
+                risk_score += 0.2;
+            }
+
+            if has_suspicious_name {
+                reason_codes.push(ReasonCode::SuspiciousName);
+                risk_score += 0.4;
+            }
+
+            if gui_outliers.contains(&process.pid) {
+                reason_codes.push(ReasonCode::GuiObjectOutlier);
+                risk_score += GUI_OBJECT_OUTLIER_WEIGHT;
+            }
+
+            if started_during && !is_whitelisted {
+                reason_codes.push(ReasonCode::StartedDuringInterview);
+                risk_score += 0.3;
+            }
+
+            let capability_count = capabilities.count();
+
+            if capabilities.inspection_denied && !is_whitelisted {
+                inaccessible += 1;
+            }
+
+            let mut record = |risk_score: f64, reason_codes: &[ReasonCode], flagged: bool| {
+                if let Some(ref mut matrix) = matrix {
+                    if !is_whitelisted && !trusted_from_baseline {
+                        matrix.entries.push(CapabilityEntry {
+                            pid: process.pid,
+                            name: process.name.clone(),
+                            path: process.path.clone(),
+                            screen_capture: capabilities.screen_capture,
+                            audio_capture: capabilities.audio_capture,
+                            accessibility: capabilities.accessibility,
+                            inspection_denied: capabilities.inspection_denied,
+                            risk_score: risk_score.clamp(0.0, 1.0),
+                            reason_codes: reason_codes.to_vec(),
+                            flagged,
+                        });
+                    }
+                }
+            };
+
+            // The launch context is what's suspicious here, so this holds
+            // even for a whitelisted browser.
+            let unexpected_parent = if heuristics.enable_browser_parent_check && is_browser(&normalized) {
+                self.unexpected_browser_parent(&process, &names_by_pid)
+            } else {
+                None
+            };
+            if unexpected_parent.is_some() {
+                reason_codes.push(ReasonCode::UnexpectedParent);
+                risk_score += heuristics.unexpected_browser_parent_weight;
+            }
+
+            // Nothing in a behavioral interview calls for an IDE, so one
+            // opened mid-session counts even when whitelisted.
+            let behavioral_ide = started_during
+                && self.interview_type == config::InterviewType::Behavioral
+                && is_ide(&normalized);
+            if behavioral_ide {
+                reason_codes.push(ReasonCode::IdeDuringBehavioralInterview);
+                risk_score += heuristics.behavioral_ide_weight;
+            }
+
+            // Apps in /Applications are whitelisted by default, and that is
+            // where such tools get installed, so this too ignores the whitelist.
+            let fresh_grants = match self.baseline_time {
+                Some(since) if heuristics.flag_fresh_permission_grants => self.permissions_granted_since(&process, since),
+                _ => Vec::new(),
+            };
+            if !fresh_grants.is_empty() {
+                reason_codes.push(ReasonCode::PermissionGrantedDuringInterview);
+                risk_score += heuristics.fresh_permission_grant_weight;
+            }
+
+            // The borrowed name is what the legit-app list and a name-based
+            // whitelist would clear, so this ignores both.
+            let impersonates_system = heuristics.flag_system_name_impersonation
+                && self.impersonates_system_process(&normalized);
+            if impersonates_system {
+                reason_codes.push(ReasonCode::SystemNameImpersonation);
+                risk_score += heuristics.system_name_impersonation_weight;
+            }
+
+            let trusted_by_whitelist = is_whitelisted && self.config.whitelist.whitelist_overrides_suspicious_name;
+            let cleared = unexpected_parent.is_none()
+                && !behavioral_ide
+                && fresh_grants.is_empty()
+                && !impersonates_system
+                && denylisted.is_none()
+                && (trusted_by_whitelist || ((is_whitelisted || is_common_legit) && !has_suspicious_name));
+            let whitelist_conflict = is_whitelisted && (has_suspicious_name || capability_count >= 3);
+            let downgraded = cleared
+                && whitelist_conflict
+                && self.config.whitelist.conflict_resolution == config::WhitelistConflictMode::Downgrade;
+            if whitelist_conflict {
+                whitelist_conflicts.push(WhitelistConflict {
+                    pid: process.pid,
+                    name: process.name.clone(),
+                    path: process.path.clone(),
+                    signals: reason_codes.clone(),
+                    resolution: match (cleared, downgraded) {
+                        (true, false) => ConflictResolution::Cleared,
+                        (true, true) => ConflictResolution::Downgraded,
+                        (false, _) => ConflictResolution::Overridden,
+                    },
+                });
+            }
+            if cleared && !downgraded {
+                record(risk_score, &reason_codes, false);
+                continue;
+            }
+
+            let is_quiet_helper = ProcessMonitor::matches_quiet_helper_profile(
+                &process,
+                &capabilities,
+                &self.config.heuristics,
+            );
+            if is_quiet_helper {
+                reason_codes.push(ReasonCode::QuietHelper);
+                risk_score += self.config.heuristics.quiet_helper_weight;
+            }
+
+            let flag_unknown_path = self.config.heuristics.flag_unknown_path_processes
+                && normalized.has_unknown_path()
+                && !is_whitelisted
+                && capability_count >= 1;
+            if flag_unknown_path {
+                reason_codes.push(ReasonCode::UnknownPath);
+                risk_score += UNKNOWN_PATH_WEIGHT;
+            }
+
+            // A capture-capable tool whose binary was written minutes ago was
+            // most likely downloaded during the interview.
+            let fresh_executable_age = if heuristics.flag_fresh_executables
+                && !is_whitelisted
+                && capability_count >= 1
+                && !normalized.has_unknown_path()
+            {
+                process_monitor::executable_age(&process.path, || *scan_time.get_or_init(|| self.clock.now().wall))
+                    .filter(|age| age.as_secs() < heuristics.fresh_executable_window_seconds)
+            } else {
+                None
+            };
+            if fresh_executable_age.is_some() {
+                reason_codes.push(ReasonCode::FreshExecutable);
+                risk_score += heuristics.fresh_executable_weight;
+            }
+
+            let flag_external_drive = self.config.heuristics.flag_external_drive_processes
+                && process.drive_type.is_external()
+                && !is_whitelisted;
+            if flag_external_drive {
+                reason_codes.push(ReasonCode::ExternalDrive);
+                risk_score += self.config.heuristics.external_drive_weight;
+            }
+
+            let rule_outcome = rules::evaluate(&self.config.rules, &normalized, &capabilities, &reason_codes);
+            if rule_outcome.suppress && denylisted.is_none() {
+                record(risk_score, &reason_codes, false);
+                continue;
+            }
+            let matched_rule = !rule_outcome.matched.is_empty();
+            if matched_rule {
+                reason_codes.push(ReasonCode::CustomRule);
+                risk_score += rule_outcome.risk_delta;
+            }
+
+            // Appearing seconds before the scan is more damning than an hour
+            // before; the age comes from the OS start time.
+            if started_during && !is_whitelisted && risk_score > 0.0 {
+                risk_score *= recency_multiplier(
+                    process.stats.run_time_seconds,
+                    heuristics.recent_start_window_seconds,
+                    heuristics.recent_start_multiplier,
+                );
+            }
+
+            let should_flag = (has_suspicious_name && capability_count >= 1 && !is_common_legit)
+                || (!has_suspicious_name
+                    && capability_count >= 3
+                    && !is_common_legit
+                    && !self.is_exempt_system_binary(&process, &normalized))
+                || (started_during && capability_count >= 2)
+                || (is_quiet_helper && !is_common_legit)
+                || (flag_unknown_path && !is_common_legit)
+                || (flag_external_drive && capability_count >= 1 && !is_common_legit)
+                || (fresh_executable_age.is_some() && !is_common_legit)
+                || unexpected_parent.is_some()
+                || behavioral_ide
+                || !fresh_grants.is_empty()
+                || impersonates_system
+                || denylisted.is_some()
+                || matched_rule;
+            if denylisted.is_some() {
+                risk_score = 1.0;
+            }
+            if downgraded {
+                risk_score *= WHITELIST_CONFLICT_DISCOUNT;
+            }
+
+            let flagged = should_flag && !reason_codes.is_empty() && risk_score >= self.config.thresholds.process_threshold;
+            record(risk_score, &reason_codes, flagged);
+            if flagged {
+                suspicious.push(SuspiciousProcess {
+                    pid: process.pid,
+                    name: process.name.clone(),
+                    path: process.path.clone(),
+                    risk_score: risk_score.clamp(0.0, 1.0),
+                    reasons: reason_codes
+                        .iter()
+                        .map(|code| match code {
+                            ReasonCode::CustomRule => {
+                                format!("{}: {}", code.description(), rule_outcome.matched.join(", "))
+                            }
+                            ReasonCode::ExternalDrive => {
+                                format!("{} ({})", code.description(), process.drive_type.label())
+                            }
+                            ReasonCode::FreshExecutable => {
+                                let minutes = fresh_executable_age.unwrap_or_default().as_secs() / 60;
+                                format!("{} (written {} min ago)", code.description(), minutes)
+                            }
+                            ReasonCode::UnexpectedParent => {
+                                format!("{}: {}", code.description(), unexpected_parent.as_deref().unwrap_or_default())
+                            }
+                            ReasonCode::Denylisted => {
+                                format!("{} ({})", code.description(), denylisted.as_deref().unwrap_or_default())
+                            }
+                            ReasonCode::PermissionGrantedDuringInterview => {
+                                let services: Vec<&str> = fresh_grants.iter().map(|s| s.label()).collect();
+                                format!("{} ({})", code.description(), services.join(", "))
+                            }
+                            _ => capabilities.reason(*code),
+                        })
+                        .collect(),
+                    reason_codes,
+                    started_during_interview: started_during,
+                    is_whitelisted,
+                    drive_type: process.drive_type,
+                });
+                forced_severity = forced_severity.max(rule_outcome.forced_severity);
+                forced_severities.extend(rule_outcome.forced_severity.map(|severity| (process.pid, severity)));
+            }
+        }
+
+        ProcessScan {
+            suspicious,
+            inaccessible,
+            forced_severity,
+            forced_severities,
+            cast_sessions,
+            capability_matrix: matrix,
+            inspected,
+            uninspected,
+            whitelist_conflicts,
+            running,
+            exited_during_inspection,
+            enumeration_blocked,
+        }
+    }
+
+    /// Keeps the first `scan.max_processes_inspected` processes, taking
+    /// non-whitelisted ones before whitelisted, those started since the
+    /// baseline before those in it, and the most recently started first
+    /// within each. Returns them with the number dropped.
+    fn cap_inspected(&self, mut processes: Vec<Process>) -> (Vec<Process>, usize) {
+        let Some(max) = self.config.scan.max_processes_inspected.filter(|&max| processes.len() > max) else {
+            return (processes, 0);
+        };
+        processes.sort_by_key(|p| {
+            (
+                self.process_monitor.is_whitelisted(p),
+                self.process_monitor.was_in_baseline(p.pid),
+                p.stats.run_time_seconds,
+            )
+        });
+        let uninspected = processes.len() - max;
+        processes.truncate(max);
+        (processes, uninspected)
+    }
+
+    /// Records when each running process was first seen and, with
+    /// `min_process_lifetime_seconds` set, drops the flagged processes
+    /// seen for less than that. A process seen by one scan only has been
+    /// seen for no time at all. Denylisted processes are never held back,
+    /// and a custom rule's severity only counts for processes kept.
+    fn hold_back_short_lived(&mut self, scan: &mut ProcessScan) {
+        let min_lifetime = self.config.heuristics.min_process_lifetime_seconds;
+        if min_lifetime == 0 {
+            return;
+        }
+
+        let now = self.clock.now().monotonic;
+        let running: HashSet<&(u32, String)> = scan.running.iter().collect();
+        self.first_seen.retain(|key, _| running.contains(key));
+        for key in &scan.running {
+            self.first_seen.entry(key.clone()).or_insert(now);
+        }
+
+        let before = scan.suspicious.len();
+        scan.suspicious.retain(|p| {
+            p.reason_codes.contains(&ReasonCode::Denylisted)
+                || self
+                    .first_seen
+                    .get(&(p.pid, p.path.clone()))
+                    .is_some_and(|&first| now.saturating_sub(first).as_secs() >= min_lifetime)
+        });
+        let kept: HashSet<u32> = scan.suspicious.iter().map(|p| p.pid).collect();
+        scan.forced_severities.retain(|(pid, _)| kept.contains(pid));
+        scan.forced_severity = scan.forced_severities.iter().map(|&(_, severity)| severity).max();
+        let held_back = before - scan.suspicious.len();
+        if held_back > 0 {
+            println!("[*] Holding back {} processes seen for less than {}s", held_back, min_lifetime);
+        }
+    }
+
+    /// Removes overlays whose owning process or title is on the configured
+    /// allowlist. Owner names are only looked up when an allowlisted process
+    /// name could match.
+    pub fn drop_allowlisted_overlays(&self, overlays: Vec<OverlayWindow>) -> Vec<OverlayWindow> {
+        let allowlist = &self.config.overlays;
+        let processes = if overlays.is_empty() || allowlist.allowed_processes.is_empty() {
+            Vec::new()
+        } else {
+            self.process_monitor.get_all_processes()
+        };
+
+        overlays
+            .into_iter()
+            .filter(|overlay| {
+                let owner_name = processes
+                    .iter()
+                    .find(|p| p.pid == overlay.owner_pid)
+                    .map(|p| p.name.as_str());
+                !overlay_detector::is_allowlisted(overlay, owner_name, allowlist)
+            })
+            .collect()
+    }
+
+    /// Cross-references overlay owners with flagged processes. A flagged
+    /// process that owns a hidden overlay gets an extra reason and weight, and
+    /// the overlay is marked so its own suspicion score rises too.
+    pub fn correlate_overlay_owners(
+        &self,
+        suspicious: &mut [SuspiciousProcess],
+        hidden_overlays: &mut [OverlayWindow],
+    ) {
+        let code = ReasonCode::OwnsHiddenOverlay;
+
+        for process in suspicious.iter_mut().filter(|p| !p.is_whitelisted) {
+            let mut owns_overlay = false;
+            for overlay in hidden_overlays.iter_mut().filter(|o| o.owner_pid == process.pid) {
+                overlay.owner_flagged = true;
+                owns_overlay = true;
+            }
+
+            if owns_overlay && !process.reason_codes.contains(&code) {
+                process.reason_codes.push(code);
+                process.reasons.push(code.description().to_string());
+                process.risk_score = (process.risk_score + OVERLAY_OWNER_WEIGHT).min(1.0);
+            }
+        }
+    }
+
+    /// Adds a high-weight reason to every non-whitelisted process that owns a
+    /// window hidden from screen capture, flagging the process if the regular
+    /// process scan didn't.
+    pub fn flag_capture_excluded_owners(
+        &self,
+        suspicious: &mut Vec<SuspiciousProcess>,
+        hidden_overlays: &[OverlayWindow],
+    ) {
+        let mut owners: Vec<u32> = hidden_overlays
+            .iter()
+            .filter(|o| o.excluded_from_capture)
+            .map(|o| o.owner_pid)
+            .collect();
+        owners.sort_unstable();
+        owners.dedup();
+
+        if owners.is_empty() {
+            return;
+        }
+
+        let processes = self.process_monitor.get_all_processes();
+
+        for pid in owners {
+            let Some(process) = processes.iter().find(|p| p.pid == pid) else {
+                continue;
+            };
+            if !self.process_monitor.is_whitelisted(process) {
+                self.add_reason(suspicious, process, ReasonCode::CaptureExcludedWindow, CAPTURE_EXCLUDED_WEIGHT);
+            }
+        }
+    }
+
+    /// Flags non-whitelisted, non-browser processes listening on a localhost
+    /// port that a browser process holds an established connection to.
+    pub fn flag_local_browser_proxies(
+        &self,
+        suspicious: &mut Vec<SuspiciousProcess>,
+        connections: &[network_detector::TcpConnection],
+    ) {
+        let processes = self.process_monitor.get_all_processes();
+        let is_browser = |pid: u32| {
+            processes
+                .iter()
+                .find(|p| p.pid == pid)
+                .map(|p| is_browser(&normalize_process(p)))
+                .unwrap_or(false)
+        };
+
+        for pair in network_detector::find_local_proxy_pairs(connections, is_browser) {
+            let Some(process) = processes.iter().find(|p| p.pid == pair.server_pid) else {
+                continue;
+            };
+            if !self.process_monitor.is_whitelisted(process) {
+                self.add_reason(suspicious, process, ReasonCode::LocalBrowserProxy, LOCAL_BROWSER_PROXY_WEIGHT);
+            }
+        }
+    }
+
+    /// Looks at windows on other virtual desktops. Those owned by a process
+    /// that is already flagged are parked cheat windows and add to its score;
+    /// the rest only count toward the total of non-whitelisted windows there.
+    pub fn flag_parked_windows(
+        &self,
+        suspicious: &mut [SuspiciousProcess],
+        windows: &[desktop_detector::DesktopWindow],
+    ) -> (Vec<desktop_detector::ParkedWindow>, usize) {
+        let processes = self.process_monitor.get_all_processes();
+        let mut parked = Vec::new();
+        let mut inactive = 0;
+
+        for window in windows {
+            if window.assignment != desktop_detector::DesktopAssignment::Other {
+                continue;
+            }
+            let Some(process) = processes.iter().find(|p| p.pid == window.owner_pid) else {
+                continue;
+            };
+            if self.process_monitor.is_whitelisted(process) {
+                continue;
+            }
+            inactive += 1;
+
+            let Some(owner) = suspicious.iter_mut().find(|s| s.pid == process.pid) else {
+                continue;
+            };
+            if !owner.reason_codes.contains(&ReasonCode::ParkedOnInactiveDesktop) {
+                owner.reason_codes.push(ReasonCode::ParkedOnInactiveDesktop);
+                owner.reasons.push(ReasonCode::ParkedOnInactiveDesktop.description().to_string());
+                owner.risk_score = (owner.risk_score + PARKED_WINDOW_WEIGHT).min(1.0);
+            }
+            parked.push(desktop_detector::ParkedWindow {
+                handle: window.handle,
+                owner_pid: process.pid,
+                owner_name: process.name.clone(),
+                title: window.title.clone(),
+            });
+        }
+
+        (parked, inactive)
+    }
+
+    /// Flags processes that took the foreground at least
+    /// `overlays.focus_changes_per_minute` times a minute since the last scan
+    /// and own a hidden overlay, show no window, or aren't whitelisted. Focus
+    /// moving between whitelisted apps is ordinary use.
+    pub fn flag_focus_steals(
+        &mut self,
+        suspicious: &mut Vec<SuspiciousProcess>,
+        overlays: &[OverlayWindow],
+    ) -> Vec<focus_detector::FocusSteal> {
+        let (gains, elapsed) = self.focus_detector.take();
+        self.score_focus_gains(suspicious, overlays, &gains, elapsed)
+    }
+
+    fn score_focus_gains(
+        &self,
+        suspicious: &mut Vec<SuspiciousProcess>,
+        overlays: &[OverlayWindow],
+        gains: &HashMap<u32, usize>,
+        elapsed: Duration,
+    ) -> Vec<focus_detector::FocusSteal> {
+        use focus_detector::FocusOwnerKind;
+
+        let frequent = focus_detector::frequent_focus_gains(gains, elapsed, self.config.overlays.focus_changes_per_minute);
+        if frequent.is_empty() {
+            return Vec::new();
+        }
+        let processes = self.process_monitor.get_all_processes();
+        let mut steals = Vec::new();
+        for (pid, focus_changes, changes_per_minute) in frequent {
+            let Some(process) = processes.iter().find(|p| p.pid == pid) else {
+                continue;
+            };
+            let owner_kind = if overlays.iter().any(|o| o.owner_pid == pid) {
+                FocusOwnerKind::OverlayOwner
+            } else if self.process_monitor.is_whitelisted(process) {
+                continue;
+            } else if process.stats.has_visible_window == Some(false) {
+                FocusOwnerKind::Windowless
+            } else {
+                FocusOwnerKind::NotWhitelisted
+            };
+
+            self.add_reason(suspicious, process, ReasonCode::FocusStealing, self.config.overlays.focus_steal_weight);
+            steals.push(focus_detector::FocusSteal {
+                owner_pid: pid,
+                owner_name: process.name.clone(),
+                focus_changes,
+                changes_per_minute,
+                owner_kind,
+            });
+        }
+        steals
+    }
+
+    /// The application with input focus, named from this scan's process
+    /// list and marked when it is flagged or owns a hidden overlay.
+    fn foreground_app(
+        &self,
+        suspicious: &[SuspiciousProcess],
+        overlays: &[OverlayWindow],
+        running: &[(u32, String)],
+    ) -> Option<focus_detector::ForegroundApp> {
+        let window = self.focus_detector.foreground_window()?;
+        let flagged = suspicious.iter().find(|p| p.pid == window.pid);
+        let name = flagged.map(|p| p.name.clone()).or_else(|| {
+            running
+                .iter()
+                .find(|(pid, _)| *pid == window.pid)
+                .and_then(|(_, path)| path.rsplit(['/', '\\']).next())
+                .filter(|name| !name.is_empty())
+                .map(|name| name.to_string())
+        });
+        Some(focus_detector::ForegroundApp {
+            pid: window.pid,
+            name,
+            title: window.title,
+            flagged: flagged.is_some(),
+            overlay_owner: overlays.iter().any(|o| o.owner_pid == window.pid),
+        })
+    }
+
+    /// Records this scan's events in the correlation window and applies the
+    /// cross-scan rules, raising the risk of every process they implicate.
+    fn correlate_events(
+        &mut self,
+        suspicious: &mut Vec<SuspiciousProcess>,
+        overlays: &[OverlayWindow],
+        focus_steals: &[focus_detector::FocusSteal],
+    ) -> Vec<correlation::CorrelatedFinding> {
+        use correlation::ScanEvent;
+
+        let processes = self.process_monitor.get_all_processes();
+        let mut events: Vec<ScanEvent> = self
+            .drop_watcher
+            .poll()
+            .into_iter()
+            .map(|path| ScanEvent::FileDropped { path })
+            .collect();
+        if let Some(ref previous) = self.previous_pids {
+            events.extend(
+                processes
+                    .iter()
+                    .filter(|p| !previous.contains(&p.pid) && !self.process_monitor.is_whitelisted(p))
+                    .map(|p| ScanEvent::ProcessStarted { pid: p.pid, name: p.name.clone(), path: p.path.clone() }),
+            );
+        }
+        self.previous_pids = Some(processes.iter().map(|p| p.pid).collect());
+        events.extend(focus_steals.iter().map(|steal| ScanEvent::FocusStolen { pid: steal.owner_pid }));
+        events.extend(overlays.iter().map(|overlay| ScanEvent::OverlayShown { owner_pid: overlay.owner_pid }));
+        self.events.push(self.scan_count, events);
+
+        let findings = self.events.correlate();
+        for finding in &findings {
+            if let Some(process) = processes.iter().find(|p| p.pid == finding.pid) {
+                self.add_reason(suspicious, process, ReasonCode::CorrelatedAcrossScans, self.config.correlation.weight);
+            }
+        }
+        findings
+    }
+
+    /// Adds `code` with `weight` to the process's entry, flagging the process
+    /// if it isn't already. A reason is only counted once per process.
+    fn add_reason(&self, suspicious: &mut Vec<SuspiciousProcess>, process: &Process, code: ReasonCode, weight: f64) {
+        match suspicious.iter_mut().find(|s| s.pid == process.pid) {
+            Some(existing) => {
+                if !existing.reason_codes.contains(&code) {
+                    existing.reason_codes.push(code);
+                    existing.reasons.push(code.description().to_string());
+                    existing.risk_score = (existing.risk_score + weight).min(1.0);
+                }
+            }
+            None => suspicious.push(SuspiciousProcess {
+                pid: process.pid,
+                name: process.name.clone(),
+                path: process.path.clone(),
+                risk_score: weight,
+                reasons: vec![code.description().to_string()],
+                reason_codes: vec![code],
+                started_during_interview: self.baseline_collected
+                    && !self.process_monitor.was_in_baseline(process.pid),
+                is_whitelisted: false,
+                drive_type: process.drive_type,
+            }),
+        }
+    }
+
+    /// Whether the process is exempt from the three-capability flag path for
+    /// living in one of `system_paths`. With `require_signed_system_binaries`
+    /// its signature must also verify, so malware masquerading in System32
+    /// is still evaluated.
+    fn is_exempt_system_binary(&self, process: &Process, normalized: &NormalizedProcess) -> bool {
+        let heuristics = &self.config.heuristics;
+        heuristics.exempt_system_paths
+            && heuristics.system_paths.iter().any(|path| normalized.is_under(path))
+            && (!heuristics.require_signed_system_binaries
+                || self.process_monitor.signature_verified(process) == Some(true))
+    }
+
+    /// Privacy permissions the process was granted after `since`.
+    fn permissions_granted_since(&self, process: &Process, since: SystemTime) -> Vec<tcc::TccService> {
+        tcc::granted_since(&self.process_monitor.permission_grants(process), since)
+    }
+
+    /// Names the parent of a browser process unless it is a browser itself
+    /// or one of `expected_browser_parents`. A parent that has already exited
+    /// is unknown and so unexpected; a platform that doesn't report parents
+    /// flags nothing.
+    fn unexpected_browser_parent(&self, process: &Process, names_by_pid: &HashMap<u32, NormalizedProcess>) -> Option<String> {
+        let parent_pid = process.parent_pid?;
+        let Some(parent) = names_by_pid.get(&parent_pid) else {
+            return Some(format!("unknown parent (PID {})", parent_pid));
+        };
+        let expected = is_browser(parent)
+            || self.config.heuristics.expected_browser_parents.iter().any(|name| parent.stem_is(name));
+        (!expected).then(|| parent.name.clone())
+    }
+
+    fn is_suspicious_name(&self, process: &NormalizedProcess) -> bool {
+        let suspicious_patterns = [
+            "cluely", "interview", "gpt", "chatgpt", "llm", "copilot",
+            "aiassistant", "ai-assistant", "interview-bot", "interview-ai",
+        ];
+
+        suspicious_patterns.iter().any(|pattern| process.name_matches(pattern))
+            || self.config.heuristics.suspicious_names.iter().any(|pattern| process.name_matches(pattern))
+    }
+
+    /// Whether a Windows executable carries a system process name but runs
+    /// from outside `system_paths` (and, for `explorer.exe`, the Windows
+    /// directory). Only Windows paths are checked, since names like `dwm`
+    /// are ordinary programs elsewhere; an unknown path proves nothing.
+    fn impersonates_system_process(&self, process: &NormalizedProcess) -> bool {
+        let windows_path = process.path.as_bytes().get(1) == Some(&b':') || process.path.starts_with("//");
+        if !windows_path || !SYSTEM_PROCESS_NAMES.iter().any(|name| process.stem_is(name)) {
+            return false;
+        }
+        let in_windows_dir = process.stem_is("explorer")
+            && process.path.rsplit_once('/').is_some_and(|(dir, _)| dir == "c:/windows");
+        !in_windows_dir && !self.config.heuristics.system_paths.iter().any(|path| process.is_under(path))
+    }
+
+    /// Everyday apps, plus the developer tools the interview type expects.
+    fn is_common_legit_app(&self, process: &NormalizedProcess) -> bool {
+        if self.process_monitor.is_revoked(process) {
+            return false;
+        }
+
+        let expected_tool = match self.interview_type {
+            config::InterviewType::Coding => is_ide(process)
+                || DEVELOPMENT_TOOL_NAMES.iter().any(|name| process.stem_is(name)),
+            config::InterviewType::SystemDesign => is_ide(process),
+            config::InterviewType::Behavioral => false,
+        };
+        if expected_tool {
+            return true;
+        }
+
+        let whitelist = [
+            "explorer.exe", "chrome.exe", "firefox.exe", "msedge.exe", 
+            "msedgewebview2.exe", "brave.exe", "opera.exe",
+            "discord.exe", "slack.exe", "teams.exe", "zoom.exe",
+            "sharex.exe", "obs", "obs64.exe", "streamlabs",
+            "steam.exe", "steamwebhelper.exe",
+            "svchost.exe", "searchhost.exe", "applicationframehost.exe",
+            "shellexperiencehost.exe", "systemsettings.exe",
+            "camera hub.exe", "streamdeck.exe",
+        ];
+        whitelist.iter().any(|w| process.name_matches(w))
+    }
+
+    /// Checks the hardware, audio and overlay scores against their own
+    /// thresholds. Audio scores by what was captured (see
+    /// `audio_category_score`). Overlays score by their worst window (see
+    /// `OverlayWindow::suspicion_score`).
+    pub fn category_breaches(
+        &self,
+        hardware_suspicion: Option<&hardware_detector::HardwareSuspicion>,
+        audio_score: f64,
+        hidden_overlays: &[OverlayWindow],
+    ) -> Vec<CategoryBreach> {
+        let thresholds = &self.config.thresholds;
+        let mut breaches = Vec::new();
+
+        let mut check = |category, score: f64, threshold: f64| {
+            if score > 0.0 && score >= threshold {
+                breaches.push(CategoryBreach { category, score, threshold });
+            }
+        };
+
+        if let Some(hardware) = hardware_suspicion {
+            check(RiskCategory::Hardware, hardware.risk_score, thresholds.hardware_threshold);
+        }
+
+        check(RiskCategory::Audio, audio_score, thresholds.audio_threshold);
+
+        check(RiskCategory::Overlay, overlay_confidence(hidden_overlays), thresholds.overlay_threshold);
+
+        breaches
+    }
+
+    pub fn calculate_overall_risk(
+        &self,
+        suspicious_processes: &[SuspiciousProcess],
+        hidden_overlays: &[OverlayWindow],
+        audio_score: f64,
+        hardware_suspicion: Option<&hardware_detector::HardwareSuspicion>,
+        vm_result: Option<&vm_detector::VmCheckResult>,
+        input_score: f64,
+    ) -> f64 {
+        let max_process_risk = suspicious_processes.iter().map(|p| p.risk_score).fold(0.0, f64::max);
+        let risk = self.weighted_risk(
+            max_process_risk,
+            overlay_confidence(hidden_overlays),
+            audio_score.min(1.0),
+            hardware_suspicion,
+            vm_result,
+            input_score.min(1.0),
+        );
+        risk.min(1.0)
+    }
+
+    /// Like `calculate_overall_risk`, but every flagged process, overlay,
+    /// audio capture stream and input device adds to its category instead of
+    /// only the strongest, and the total isn't capped, so sessions past the
+    /// 1.0 ceiling can still be told apart. `audio_score` is the stream
+    /// count, 1 when audio monitoring was detected without the streams being
+    /// counted, times any `cpu_signal_weight`.
+    pub fn calculate_raw_risk(
+        &self,
+        suspicious_processes: &[SuspiciousProcess],
+        hidden_overlays: &[OverlayWindow],
+        audio_score: f64,
+        hardware_suspicion: Option<&hardware_detector::HardwareSuspicion>,
+        vm_result: Option<&vm_detector::VmCheckResult>,
+        input_score: f64,
+    ) -> f64 {
+        self.weighted_risk(
+            suspicious_processes.iter().map(|p| p.risk_score).sum(),
+            hidden_overlays.iter().map(OverlayWindow::suspicion_score).sum(),
+            audio_score,
+            hardware_suspicion,
+            vm_result,
+            input_score,
+        )
+    }
+
+    /// What signals read from per-process CPU use count for:
+    /// `heuristics.saturated_cpu_signal_weight` while the machine is
+    /// saturated, since any process then looks busy, and 1.0 otherwise.
+    pub fn cpu_signal_weight(&self, system_load: Option<&system_load::SystemLoad>) -> f64 {
+        match system_load {
+            Some(load) if load.saturated => self.config.heuristics.saturated_cpu_signal_weight,
+            _ => 1.0,
+        }
+    }
+
+    /// The input category score both risk totals take: half per input
+    /// device connected since the baseline, plus `cloud_clipboard_weight`
+    /// while Cloud Clipboard sync is on. Uncapped.
+    pub fn input_score(&self, new_input_devices: &[InputDevice], cloud_clipboard_sync: bool) -> f64 {
+        let clipboard = if cloud_clipboard_sync { self.config.heuristics.cloud_clipboard_weight } else { 0.0 };
+        new_input_devices.len() as f64 * 0.5 + clipboard
+    }
+
+    fn weighted_risk(
+        &self,
+        process_score: f64,
+        overlay_score: f64,
+        audio_score: f64,
+        hardware_suspicion: Option<&hardware_detector::HardwareSuspicion>,
+        vm_result: Option<&vm_detector::VmCheckResult>,
+        input_score: f64,
+    ) -> f64 {
+        let mut risk = process_score * self.config.weights.process_risk;
+        risk += overlay_score * self.config.weights.overlay_risk;
+        risk += audio_score * self.config.weights.audio_risk;
+
+        if let Some(hardware) = hardware_suspicion {
+            risk += hardware.risk_score * self.config.weights.hardware_risk;
+        }
+
+        if let Some(vm) = vm_result {
+            if vm.is_vm {
+                risk += vm.confidence_score * self.config.weights.vm_risk;
+            }
+        }
+
+        risk + input_score * self.config.weights.input_risk
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::*;
+    use crate::audio_detector::{CaptureStreams, EndpointState};
+    use crate::hardware_detector::{ConnectionType, DisplayConfiguration, DisplayInfo, RemoteDesktopStatus};
+    use crate::process_monitor::ProcessCapabilities;
+    use crate::vm_detector::VmCheckResult;
+
+    /// Scripted OS state. Defaults to a single built-in display and nothing
+    /// else running.
+    pub struct MockPlatform {
+        pub processes: Vec<(Process, ProcessCapabilities)>,
+        pub displays: Vec<DisplayInfo>,
+        pub capture_devices: Vec<String>,
+        pub remote_desktop: RemoteDesktopStatus,
+        pub audio_monitoring: bool,
+        pub capture_streams: Option<CaptureStreams>,
+        pub audio_endpoints: Vec<EndpointState>,
+        /// Signature check result for every process.
+        pub signed: Option<bool>,
+        /// TCC entries, matched to processes by path.
+        pub tcc_grants: Vec<crate::tcc::TccGrant>,
+        pub overlays: Vec<OverlayWindow>,
+        pub cloud_clipboard: Option<bool>,
+        pub foreground: Option<crate::focus_detector::ForegroundWindow>,
+        pub system_load: Option<crate::system_load::SystemLoad>,
+        /// Displays connected after the platform was handed to a detector.
+        pub connected_displays: std::sync::Mutex<Vec<DisplayInfo>>,
+        /// How many times the process list was read.
+        pub listings: std::sync::atomic::AtomicUsize,
+        /// Makes audio endpoint queries fail and overlay enumeration panic,
+        /// as a broken module would.
+        pub broken: bool,
+    }
+
+    impl Default for MockPlatform {
+        fn default() -> Self {
+            Self {
+                processes: Vec::new(),
+                displays: vec![display("0", ConnectionType::Unknown)],
+                capture_devices: Vec::new(),
+                remote_desktop: RemoteDesktopStatus::default(),
+                audio_monitoring: false,
+                capture_streams: None,
+                audio_endpoints: Vec::new(),
+                signed: None,
+                tcc_grants: Vec::new(),
+                overlays: Vec::new(),
+                cloud_clipboard: None,
+                foreground: None,
+                system_load: None,
+                connected_displays: Default::default(),
+                listings: Default::default(),
+                broken: false,
+            }
+        }
+    }
+
+    impl ProcessSource for MockPlatform {
+        fn processes(&self) -> Vec<Process> {
+            self.listings.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.processes.iter().map(|(p, _)| p.clone()).collect()
+        }
+
+        fn capabilities(&self, process: &Process) -> ProcessCapabilities {
+            self.processes
+                .iter()
+                .find(|(p, _)| p.pid == process.pid)
+                .map(|(_, c)| c.clone())
+                .unwrap_or_default()
+        }
+
+        fn signature_verified(&self, _process: &Process) -> Option<bool> {
+            self.signed
+        }
+
+        fn permission_grants(&self, process: &Process) -> Vec<crate::tcc::TccGrant> {
+            let grants = crate::tcc::TccGrants::new(self.tcc_grants.clone());
+            grants.of_client(&process.path, None).cloned().collect()
+        }
+    }
+
+    impl PlatformProvider for MockPlatform {
+        fn displays(&self) -> Result<DisplayConfiguration, String> {
+            let mut displays = self.displays.clone();
+            displays.extend(self.connected_displays.lock().unwrap().iter().cloned());
+            Ok(DisplayConfiguration {
+                display_count: displays.len(),
+                has_virtual_display: displays.iter().any(|d| d.connection_type == ConnectionType::Virtual),
+                displays,
+                has_hdmi_splitter_signature: false,
+            })
+        }
+
+        fn video_capture_devices(&self) -> Result<Vec<String>, String> {
+            Ok(self.capture_devices.clone())
+        }
+
+        fn remote_desktop(&self) -> RemoteDesktopStatus {
+            self.remote_desktop.clone()
+        }
+
+        fn audio_monitoring(&self) -> bool {
+            self.audio_monitoring
+        }
+
+        fn audio_capture_streams(&self) -> Option<CaptureStreams> {
+            self.capture_streams.clone()
+        }
+
+        fn audio_endpoints(&self) -> Result<Vec<EndpointState>, String> {
+            if self.broken {
+                return Err("endpoint query failed".to_string());
+            }
+            Ok(self.audio_endpoints.clone())
+        }
+
+        fn overlays(&self) -> Vec<OverlayWindow> {
+            assert!(!self.broken, "overlay enumeration crashed");
+            self.overlays.clone()
+        }
+
+        fn foreground_process(&self) -> Option<u32> {
+            self.foreground.as_ref().map(|window| window.pid)
+        }
+
+        fn foreground_window(&self) -> Option<crate::focus_detector::ForegroundWindow> {
+            self.foreground.clone()
+        }
+
+        fn cloud_clipboard_sync(&self) -> Option<bool> {
+            self.cloud_clipboard
+        }
+
+        fn vm_signals(&self) -> VmCheckResult {
+            VmCheckResult::default()
+        }
+
+        fn system_load(&self) -> Option<crate::system_load::SystemLoad> {
+            self.system_load
+        }
+
+        fn refresh_snapshot(&self) {}
+    }
+
+    pub fn display(id: &str, connection_type: ConnectionType) -> DisplayInfo {
+        DisplayInfo {
+            id: id.to_string(),
+            name: format!("Display {}", id),
+            width: 1920,
+            height: 1080,
+            is_primary: id == "0",
+            connection_type,
+        }
+    }
+
+    pub fn process(pid: u32, name: &str, path: &str) -> Process {
+        Process {
+            pid,
+            name: name.to_string(),
+            path: path.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn capabilities(screen_capture: bool, audio_capture: bool, accessibility: bool) -> ProcessCapabilities {
+        ProcessCapabilities {
+            screen_capture,
+            audio_capture,
+            accessibility,
+            ..Default::default()
+        }
+    }
+
+    /// Default config with overlay, audio, hardware and VM monitoring off, so
+    /// a scan only scores the scripted processes.
+    pub fn process_only_config() -> Config {
+        let mut config = Config::default();
+        config.monitoring.enable_overlay_monitoring = false;
+        config.monitoring.enable_audio_monitoring = false;
+        config.monitoring.enable_hardware_monitoring = false;
+        config.monitoring.enable_vm_detection = false;
+        config
+    }
+
+    pub fn detector_with(config: Config, processes: Vec<(Process, ProcessCapabilities)>) -> FairviewDetector {
+        FairviewDetector::with_platform(config, Arc::new(MockPlatform { processes, ..Default::default() }))
+    }
+
+    pub fn report(scan_number: usize, overall_risk_score: f64, exceeds_threshold: bool) -> DetectionReport {
+        DetectionReport {
+            timestamp: SystemTime::now(),
+            scan_number,
+            session_id: None,
+            candidate_ref: None,
+            machine_fingerprint: None,
+            provenance: None,
+            suspicious_processes: Vec::new(),
+            hidden_overlays: Vec::new(),
+            overlay_confidence: 0.0,
+            parked_windows: Vec::new(),
+            inactive_desktop_windows: 0,
+            focus_steals: Vec::new(),
+            foreground_app: None,
+            correlated_findings: Vec::new(),
+            inaccessible_processes: 0,
+            uninspected_processes: None,
+            exited_during_inspection: None,
+            audio_monitoring_detected: false,
+            audio_capture_streams: None,
+            audio_loops: Vec::new(),
+            new_audio_inputs: Vec::new(),
+            hardware_suspicion: None,
+            new_input_devices: Vec::new(),
+            cloud_clipboard_sync: false,
+            vm_detection: None,
+            system_load: None,
+            overall_risk_score,
+            raw_risk_score: overall_risk_score,
+            exceeds_threshold,
+            severity: Severity::from_score(overall_risk_score, exceeds_threshold),
+            uncertainty: Uncertainty::default(),
+            category_breaches: Vec::new(),
+            policy_breach: None,
+            baseline_missing: false,
+            observational: false,
+            truncated: false,
+            omitted_processes: 0,
+            omitted_overlays: 0,
+            suppressed_findings: 0,
+            module_failures: Vec::new(),
+            operator_annotation: None,
+            whitelist_profile: None,
+            scan_duration_ms: 0,
+            interval_adjustment: None,
+            clock_tampering_suspected: false,
+            clock_tampering_reason: None,
+            forced_fresh: false,
+            carried_forward_modules: Vec::new(),
+            full_capability_matrix: None,
+            rebaseline: None,
+            whitelist_conflicts: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{capabilities, detector_with, process, process_only_config, report, MockPlatform};
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn test_scan_flags_suspicious_process_from_source() {
+        let detector = detector_with(Config::default(), vec![
+            (process(10, "interview-ai.exe", "C:\\Users\\candidate\\interview-ai.exe"), capabilities(true, true, false)),
+            (process(11, "chrome.exe", "C:\\Program Files\\Google\\chrome.exe"), capabilities(true, true, true)),
+            (process(12, "notepad.exe", "C:\\Windows\\notepad.exe"), capabilities(false, false, false)),
+        ]);
+
+        let flagged = detector.scan_for_suspicious_processes();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].pid, 10);
+        assert!(flagged[0].reasons.contains(&"Suspicious process name".to_string()));
+    }
+
+    fn helper_process(pid: u32, memory_mb: u64, cpu_usage: f32, has_visible_window: Option<bool>) -> Process {
+        let mut helper = process(pid, "svc-helper.exe", "C:\\Users\\candidate\\AppData\\svc-helper.exe");
+        helper.stats = ProcessStats {
+            memory_bytes: memory_mb * 1024 * 1024,
+            cpu_usage,
+            run_time_seconds: 120,
+            has_visible_window,
+            gui_objects: None,
+        };
+        helper
+    }
+
+    #[test]
+    fn test_quiet_helper_profile_is_flagged() {
+        let mut config = Config::default();
+        config.heuristics.enable_quiet_helper = true;
+        let detector = detector_with(config, vec![
+            (helper_process(20, 12, 0.1, Some(false)), capabilities(false, true, false)),
+            (helper_process(21, 12, 0.1, Some(true)), capabilities(false, true, false)),
+            (helper_process(22, 400, 0.1, None), capabilities(false, true, false)),
+            (helper_process(23, 12, 15.0, None), capabilities(false, true, false)),
+            (helper_process(24, 12, 0.1, None), capabilities(false, false, true)),
+        ]);
+
+        let flagged = detector.scan_for_suspicious_processes();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].pid, 20);
+        assert!(flagged[0].reasons.iter().any(|r| r.starts_with("Quiet background helper")));
+    }
+
+    #[test]
+    fn test_quiet_helper_is_off_by_default() {
+        let detector = detector_with(Config::default(), vec![
+            (helper_process(20, 12, 0.1, Some(false)), capabilities(false, true, false)),
+        ]);
+
+        assert!(detector.scan_for_suspicious_processes().is_empty());
+    }
+
+    fn overlay(is_transparent: bool, is_topmost: bool) -> OverlayWindow {
+        OverlayWindow {
+            id: String::new(),
+            handle: 1,
+            position: (0, 0),
+            size: (400, 300),
+            owner_pid: 42,
+            is_transparent,
+            is_topmost,
+            is_cloaked: false,
+            excluded_from_capture: false,
+            title: String::new(),
+            class_name: String::new(),
+            owner_flagged: false,
+            alpha: None,
+            risk_score: 0.0,
+            first_seen_scan: 0,
+        }
+    }
+
+    #[test]
+    fn test_overlay_score_follows_evidence() {
+        let visible_topmost = overlay(false, true);
+        let hidden_from_capture = OverlayWindow {
+            excluded_from_capture: true,
+            alpha: Some(0),
+            ..overlay(false, false)
+        };
+        assert!(hidden_from_capture.suspicion_score() > visible_topmost.suspicion_score());
+
+        let translucent = OverlayWindow { alpha: Some(40), ..overlay(false, true) };
+        assert!((translucent.suspicion_score() - 0.8).abs() < 1e-9);
+        let full_screen = OverlayWindow { size: (2560, 1440), ..overlay(false, true) };
+        assert!((full_screen.suspicion_score() - 0.4).abs() < 1e-9);
+
+        assert_eq!(overlay_confidence(&[]), 0.0);
+        assert!((overlay_confidence(&[full_screen, translucent]) - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_overlay_risk_scales_with_confidence() {
+        let detector = detector_with(Config::default(), Vec::new());
+        let risk = |overlays: &[OverlayWindow]| detector.calculate_overall_risk(&[], overlays, 0.0, None, None, 0.0);
+        let weight = Config::default().weights.overlay_risk;
+
+        assert!((risk(&[overlay(false, false)]) - 0.2 * weight).abs() < 1e-9);
+        assert!((risk(&[overlay(true, true)]) - weight).abs() < 1e-9);
+    }
+
+    fn hardware(risk_score: f64) -> hardware_detector::HardwareSuspicion {
+        hardware_detector::HardwareSuspicion {
+            risk_score,
+            flags: Vec::new(),
+            details: std::collections::HashMap::new(),
+            remote_desktop: Default::default(),
+            cast_sessions: Vec::new(),
+        }
+    }
+
+    fn breached_categories(breaches: &[CategoryBreach]) -> Vec<RiskCategory> {
+        breaches.iter().map(|b| b.category).collect()
+    }
+
+    #[test]
+    fn test_hardware_threshold_triggers_breach_alone() {
+        let detector = detector_with(Config::default(), Vec::new());
+        assert!(detector.category_breaches(Some(&hardware(0.4)), 0.0, &[]).is_empty());
+
+        let breaches = detector.category_breaches(Some(&hardware(0.5)), 0.0, &[]);
+        assert_eq!(breached_categories(&breaches), vec![RiskCategory::Hardware]);
+        assert!(detector.calculate_overall_risk(&[], &[], 0.0, Some(&hardware(0.5)), None, 0.0)
+            < detector.config.scan.risk_threshold);
+    }
+
+    #[test]
+    fn test_audio_threshold_triggers_breach_alone() {
+        let mut config = Config::default();
+        let detector = detector_with(config.clone(), Vec::new());
+        assert!(detector.category_breaches(None, 0.0, &[]).is_empty());
+        assert_eq!(
+            breached_categories(&detector.category_breaches(None, 1.0, &[])),
+            vec![RiskCategory::Audio]
+        );
+
+        config.thresholds.audio_threshold = 1.5;
+        let detector = detector_with(config, Vec::new());
+        assert!(detector.category_breaches(None, 1.0, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_stock_microphone_does_not_breach_audio() {
+        let scan = |audio_monitoring, capture_streams| {
+            let mut config = Config::default();
+            config.monitoring.enable_hardware_monitoring = false;
+            config.monitoring.enable_vm_detection = false;
+            let platform = MockPlatform { audio_monitoring, capture_streams, ..Default::default() };
+            FairviewDetector::with_platform(config, Arc::new(platform)).scan()
+        };
+        let streams = |count| Some(audio_detector::CaptureStreams { count, applications: vec!["zoom".to_string()] });
+
+        for report in [scan(true, None), scan(true, streams(1))] {
+            assert!(report.audio_monitoring_detected);
+            assert!(report.category_breaches.iter().all(|b| b.category != RiskCategory::Audio));
+        }
+        let two = scan(true, streams(2));
+        assert!(two.category_breaches.iter().any(|b| b.category == RiskCategory::Audio));
+    }
+
+    #[test]
+    fn test_saturated_machine_down_weights_cpu_signals() {
+        let mut config = Config::default();
+        config.heuristics.saturated_cpu_signal_weight = 0.5;
+        let detector = detector_with(config, Vec::new());
+        let idle = system_load::SystemLoad { cpu_percent: 20.0, memory_percent: 40.0, saturated: false };
+        let busy = system_load::SystemLoad { cpu_percent: 98.0, memory_percent: 40.0, saturated: true };
+
+        assert_eq!(detector.cpu_signal_weight(None), 1.0);
+        assert_eq!(detector.cpu_signal_weight(Some(&idle)), 1.0);
+        let risk = |load| detector.calculate_overall_risk(&[], &[], detector.cpu_signal_weight(Some(&load)), None, None, 0.0);
+        assert!((risk(busy) - risk(idle) / 2.0).abs() < 1e-9);
+        assert!(risk(busy) > 0.0);
+
+        let platform = test_support::MockPlatform {
+            system_load: Some(system_load::SystemLoad { cpu_percent: 95.0, ..idle }),
+            ..Default::default()
+        };
+        let monitor = system_load::SystemLoadMonitor::with_platform(Arc::new(platform));
+        assert!(monitor.read(detector.config.heuristics.saturated_cpu_percent).unwrap().saturated);
+    }
+
+    #[test]
+    fn test_overlay_threshold_triggers_breach_alone() {
+        let detector = detector_with(Config::default(), Vec::new());
+        assert!(detector.category_breaches(None, 0.0, &[overlay(false, false)]).is_empty());
+
+        let breaches = detector.category_breaches(None, 0.0, &[overlay(false, false), overlay(true, false)]);
+        assert_eq!(breached_categories(&breaches), vec![RiskCategory::Overlay]);
+        assert!((breaches[0].score - 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_vm_hard_fail_forces_critical_breach() {
+        let vm_report = || {
+            let mut report = crate::test_support::report(1, 0.1, false);
+            report.vm_detection = Some(vm_detector::VmCheckResult {
+                is_vm: true,
+                reasons: vec!["CPUID hypervisor bit set".to_string()],
+                confidence_score: 0.9,
+                raw_confidence_score: 0.9,
+            });
+            report
+        };
+
+        let mut report = vm_report();
+        detector_with(Config::default(), Vec::new()).apply_vm_policy(&mut report);
+        assert!(!report.exceeds_threshold);
+        assert_eq!(report.policy_breach, None);
+
+        let mut config = Config::default();
+        config.vm.hard_fail = true;
+        let mut report = vm_report();
+        detector_with(config, Vec::new()).apply_vm_policy(&mut report);
+        assert!(report.exceeds_threshold);
+        assert_eq!(report.severity, Severity::Critical);
+        assert_eq!(report.policy_breach, Some(PolicyBreach::VmHardFail));
+        assert!((report.overall_risk_score - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_virtual_display_hard_fail_forces_critical_breach() {
+        use crate::hardware_detector::ConnectionType;
+        use crate::test_support::display;
+
+        let scan = |hard_fail: bool| {
+            let platform = MockPlatform {
+                displays: vec![display("0", ConnectionType::Virtual)],
+                ..Default::default()
+            };
+            let mut config = Config::default();
+            config.monitoring.enable_vm_detection = false;
+            config.monitoring.enable_input_device_monitoring = false;
+            config.monitoring.enable_network_monitoring = false;
+            config.monitoring.enable_virtual_desktop_detection = false;
+            config.hardware.virtual_display_hard_fail = hard_fail;
+            FairviewDetector::with_platform(config, Arc::new(platform)).scan()
+        };
+
+        let weighted = scan(false);
+        assert!(weighted.hardware_suspicion.as_ref().unwrap().has_virtual_display);
+        assert!(weighted.severity < Severity::Critical);
+        assert_eq!(weighted.policy_breach, None);
+
+        let forced = scan(true);
+        assert!(forced.exceeds_threshold);
+        assert_eq!(forced.severity, Severity::Critical);
+        assert_eq!(forced.policy_breach, Some(PolicyBreach::VirtualDisplayHardFail));
+        assert!((forced.overall_risk_score - weighted.overall_risk_score).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_custom_rule_adjusts_score() {
+        let notes = || (process(30, "notes-sync.exe", "C:\\Users\\candidate\\AppData\\notes-sync.exe"), capabilities(false, true, false));
+        let rule = config::RuleConfig {
+            name: "appdata audio".to_string(),
+            path_contains: Some("appdata".to_string()),
+            capabilities: vec![config::RuleCapability::AudioCapture],
+            risk_delta: 0.5,
+            force_severity: Some(Severity::Critical),
+            ..Default::default()
+        };
+        let mut config = process_only_config();
+
+        assert!(detector_with(config.clone(), vec![notes()]).scan_for_suspicious_processes().is_empty());
+
+        config.rules = vec![rule.clone()];
+        let mut detector = detector_with(config.clone(), vec![notes()]);
+        let flagged = detector.scan_for_suspicious_processes();
+        assert_eq!(flagged.len(), 1);
+        assert!((flagged[0].risk_score - 0.8).abs() < 1e-9);
+        assert_eq!(flagged[0].reason_codes, vec![ReasonCode::AudioCapture, ReasonCode::CustomRule]);
+        assert_eq!(flagged[0].reasons[1], "Matched custom rule: appdata audio");
+
+        let report = detector.scan();
+        assert!(report.exceeds_threshold);
+        assert_eq!(report.severity, Severity::Critical);
+        assert_eq!(report.policy_breach, Some(PolicyBreach::CustomRule));
+
+        config.rules = vec![config::RuleConfig { suppress: true, risk_delta: 0.0, force_severity: None, ..rule }];
+        let loud = (process(31, "interview-ai.exe", "C:\\Users\\candidate\\AppData\\interview-ai.exe"), capabilities(true, true, false));
+        assert!(detector_with(config, vec![notes(), loud]).scan_for_suspicious_processes().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_path_is_flagged_when_configured() {
+        let unresolved = || (process(40, "capture-agent", process_monitor::UNKNOWN_PATH), capabilities(true, true, false));
+        let mut config = Config::default();
+        config.whitelist.directories.push("Unknown".to_string());
+
+        assert!(detector_with(config.clone(), vec![unresolved()]).scan_for_suspicious_processes().is_empty());
+
+        config.heuristics.flag_unknown_path_processes = true;
+        let flagged = detector_with(config, vec![unresolved()]).scan_for_suspicious_processes();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].reason_codes.last(), Some(&ReasonCode::UnknownPath));
+        assert!((flagged[0].risk_score - (0.6 + UNKNOWN_PATH_WEIGHT)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_whitelisted_browser_under_unexpected_parent_is_flagged() {
+        let launcher = |pid, name: &str| (process(pid, name, &format!("C:\\Windows\\{}", name)), capabilities(false, false, false));
+        let browser = |pid, parent_pid| {
+            let chrome = Process { parent_pid: Some(parent_pid), ..process(pid, "chrome.exe", "C:\\Program Files\\Google\\chrome.exe") };
+            (chrome, capabilities(false, false, false))
+        };
+        let mut config = Config::default();
+
+        let shell = detector_with(config.clone(), vec![launcher(1, "explorer.exe"), browser(2, 1), browser(3, 2)]);
+        assert!(shell.process_monitor.is_whitelisted(&process(2, "chrome.exe", "C:\\Program Files\\Google\\chrome.exe")));
+        assert!(shell.scan_for_suspicious_processes().is_empty());
+
+        let automated = detector_with(config.clone(), vec![launcher(1, "python.exe"), browser(2, 1), browser(3, 2)]);
+        let flagged = automated.scan_for_suspicious_processes();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].pid, 2);
+        assert!(flagged[0].is_whitelisted);
+        assert_eq!(flagged[0].reason_codes, vec![ReasonCode::UnexpectedParent]);
+        assert!(flagged[0].reasons[0].ends_with(": python.exe"));
+
+        let orphaned = detector_with(config.clone(), vec![browser(2, 77)]).scan_for_suspicious_processes();
+        assert!(orphaned[0].reasons[0].ends_with(": unknown parent (PID 77)"));
+
+        config.heuristics.expected_browser_parents.push("python".to_string());
+        let allowed = detector_with(config, vec![launcher(1, "python.exe"), browser(2, 1)]);
+        assert!(allowed.scan_for_suspicious_processes().is_empty());
+    }
+
+    #[test]
+    fn test_capability_matrix_lists_unflagged_processes() {
+        let processes = || vec![
+            (process(50, "recorder.exe", "C:\\Users\\candidate\\recorder.exe"), capabilities(true, true, false)),
+            (process(51, "interview-ai.exe", "C:\\Users\\candidate\\interview-ai.exe"), capabilities(true, false, false)),
+            (process(52, "chrome.exe", "C:\\Program Files\\Google\\chrome.exe"), capabilities(true, true, true)),
+        ];
+        let mut config = Config::default();
+        assert!(detector_with(config.clone(), processes()).scan_processes().capability_matrix.is_none());
+
+        config.output.full_capability_matrix = true;
+        let scan = detector_with(config, processes()).scan_processes();
+        let matrix = scan.capability_matrix.unwrap();
+        assert_eq!(scan.suspicious.len(), 1);
+        assert_eq!(matrix.entries.len(), 2);
+
+        let recorder = matrix.entries.iter().find(|e| e.pid == 50).unwrap();
+        assert!(recorder.screen_capture && recorder.audio_capture && !recorder.accessibility);
+        assert!(!recorder.flagged);
+        assert!((recorder.risk_score - 0.6).abs() < 1e-9);
+        assert!(matrix.entries.iter().find(|e| e.pid == 51).unwrap().flagged);
+
+        let mut report = crate::test_support::report(1, 0.5, false);
+        report.full_capability_matrix = Some(matrix);
+        report.truncate(1, 1);
+        let matrix = report.full_capability_matrix.unwrap();
+        assert_eq!((matrix.entries.len(), matrix.omitted), (1, 1));
+        assert_eq!(matrix.entries[0].pid, 51);
+    }
+
+    #[test]
+    fn test_permission_granted_after_baseline_is_flagged() {
+        use crate::tcc::{TccGrant, TccService};
+
+        let grant = |client: &str, service, last_modified: u64| TccGrant {
+            service,
+            client: client.to_string(),
+            client_is_path: true,
+            allowed: true,
+            last_modified: UNIX_EPOCH + Duration::from_secs(last_modified),
+        };
+        let helper = "/Applications/Helper.app/Contents/MacOS/Helper";
+        let notes = "/Applications/Notes.app/Contents/MacOS/Notes";
+        let platform = MockPlatform {
+            processes: vec![
+                (process(60, "Helper", helper), capabilities(true, false, true)),
+                (process(61, "Notes", notes), capabilities(true, false, true)),
+            ],
+            tcc_grants: vec![
+                grant(helper, TccService::ScreenCapture, 1_000_000_000),
+                grant(helper, TccService::Accessibility, 4_000_000_000),
+                grant(notes, TccService::Accessibility, 1_000_000_000),
+            ],
+            ..Default::default()
+        };
+        let mut detector = FairviewDetector::with_platform(Config::default(), Arc::new(platform));
+        assert!(detector.scan_for_suspicious_processes().is_empty());
+
+        detector.collect_baseline();
+        let flagged = detector.scan_for_suspicious_processes();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].pid, 60);
+        assert!(flagged[0].reason_codes.contains(&ReasonCode::PermissionGrantedDuringInterview));
+        assert!(flagged[0].reasons.contains(&"Privacy permission granted during interview (accessibility)".to_string()));
+    }
+
+    #[test]
+    fn test_denylisted_process_forces_critical() {
+        let tool = std::env::temp_dir().join(format!("fairview_denylist_test_{}.bin", std::process::id()));
+        std::fs::write(&tool, b"not really an answer overlay").unwrap();
+        let tool_path = tool.to_string_lossy().into_owned();
+
+        let mut config = process_only_config();
+        config.weights.process_risk = 0.01;
+        config.thresholds.process_threshold = 0.99;
+        config.denylist.names = vec!["cluely".to_string()];
+        config.denylist.hashes = vec![denylist::sha256_file(&tool_path).unwrap().to_uppercase()];
+        let processes = vec![
+            (process(70, "Cluely.exe", "C:\\Program Files\\Cluely\\Cluely.exe"), capabilities(false, false, false)),
+            (process(71, "cluely-updater.exe", "C:\\Program Files\\Cluely\\cluely-updater.exe"), capabilities(false, false, false)),
+            (process(72, "notes.exe", &tool_path), capabilities(false, false, false)),
+        ];
+        let report = detector_with(config, processes).scan();
+        std::fs::remove_file(&tool).unwrap();
+
+        assert_eq!(report.severity, Severity::Critical);
+        assert!(report.exceeds_threshold);
+        assert_eq!(report.policy_breach, Some(PolicyBreach::Denylisted));
+        let flagged: Vec<u32> = report.suspicious_processes.iter().map(|p| p.pid).collect();
+        assert_eq!(flagged, vec![70, 72]);
+        assert!(report.suspicious_processes.iter().all(|p| p.risk_score == 1.0));
+        assert_eq!(report.suspicious_processes[0].reasons[0], "On the configured denylist (name cluely)");
+    }
+
+    #[test]
+    fn test_forced_fresh_scan_bypasses_digest_cache() {
+        let tool = std::env::temp_dir().join(format!("fairview_force_fresh_test_{}.bin", std::process::id()));
+        std::fs::write(&tool, b"harmless-build-1").unwrap();
+        let tool_path = tool.to_string_lossy().into_owned();
+        let replacement = std::env::temp_dir().join(format!("fairview_force_fresh_tool_{}.bin", std::process::id()));
+        std::fs::write(&replacement, b"answer-overlay-1").unwrap();
+
+        let mut config = process_only_config();
+        config.denylist.hashes = vec![denylist::sha256_file(&replacement.to_string_lossy()).unwrap()];
+        std::fs::remove_file(&replacement).unwrap();
+        let mut detector = detector_with(config, vec![(process(73, "notes.exe", &tool_path), capabilities(false, false, false))]);
+        assert!(detector.scan().suspicious_processes.is_empty());
+
+        // Same size and modification time, so the cached digest still looks valid.
+        let modified = std::fs::metadata(&tool).unwrap().modified().unwrap();
+        std::fs::write(&tool, b"answer-overlay-1").unwrap();
+        std::fs::File::options().write(true).open(&tool).unwrap().set_modified(modified).unwrap();
+        let stale = detector.scan();
+        assert!(stale.suspicious_processes.is_empty());
+        assert!(!stale.forced_fresh);
+
+        detector.force_fresh_next_scan();
+        let fresh = detector.scan();
+        let next = detector.scan();
+        std::fs::remove_file(&tool).unwrap();
+
+        assert!(fresh.forced_fresh);
+        assert_eq!(fresh.policy_breach, Some(PolicyBreach::Denylisted));
+        assert!(!next.forced_fresh);
+    }
+
+    #[test]
+    fn test_missing_profile_switch_file_keeps_configured_profile() {
+        let switch = std::env::temp_dir().join(format!("fairview_profile_switch_{}.txt", std::process::id()));
+        let mut config = process_only_config();
+        for name in ["coding", "whiteboard"] {
+            config.whitelist.profiles.insert(name.to_string(), Default::default());
+        }
+        config.whitelist.active_profile = Some("coding".to_string());
+        config.whitelist.profile_switch_file = Some(switch.to_string_lossy().into_owned());
+        let mut detector = detector_with(config, Vec::new());
+
+        let missing = detector.scan().whitelist_profile;
+        std::fs::write(&switch, "whiteboard\n").unwrap();
+        let switched = detector.scan().whitelist_profile;
+        std::fs::write(&switch, "").unwrap();
+        let emptied = detector.scan().whitelist_profile;
+        std::fs::remove_file(&switch).unwrap();
+
+        assert_eq!(missing.as_deref(), Some("coding"));
+        assert_eq!(switched.as_deref(), Some("whiteboard"));
+        assert_eq!(emptied.as_deref(), Some("coding"));
+    }
+
+    #[test]
+    fn test_machine_fingerprint_is_stable_and_checked_against_session() {
+        let config = process_only_config();
+        let mut detector = detector_with(config.clone(), Vec::new());
+        detector.set_machine_fingerprint("3f9a0c2be71d4a55".to_string(), None);
+        let first = detector.scan();
+        let second = detector.scan();
+        assert_eq!(first.machine_fingerprint.as_deref(), Some("3f9a0c2be71d4a55"));
+        assert_eq!(first.machine_fingerprint, second.machine_fingerprint);
+        assert_eq!(second.policy_breach, None);
+
+        let mut verdict = session::SessionVerdict::new();
+        verdict.record(&first);
+        let mut resumed = detector_with(config, Vec::new());
+        resumed.set_machine_fingerprint("81c07e5d2a6b9f10".to_string(), verdict.machine_fingerprint.clone());
+        let relayed = resumed.scan();
+        assert_eq!(relayed.policy_breach, Some(PolicyBreach::MachineChanged));
+        assert_eq!(relayed.severity, Severity::Critical);
+        assert!(relayed.exceeds_threshold);
+    }
+
+    /// Processes that tests start and stop between scans.
+    struct ChangingSource(Arc<std::sync::Mutex<Vec<Process>>>);
+
+    impl ProcessSource for ChangingSource {
+        fn processes(&self) -> Vec<Process> {
+            self.0.lock().unwrap().clone()
+        }
+
+        fn capabilities(&self, _process: &Process) -> process_monitor::ProcessCapabilities {
+            capabilities(true, true, false)
+        }
+    }
+
+    #[test]
+    fn test_interview_type_decides_which_developer_tools_are_expected() {
+        let flagged_under = |interview_type: &str| {
+            let running = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let mut config = Config::default();
+            config.scan.interview_type = interview_type.to_string();
+            let mut detector = FairviewDetector::with_process_source(config, Box::new(ChangingSource(running.clone())));
+            detector.collect_baseline();
+            running.lock().unwrap().extend([
+                process(2, "Code.exe", "C:\\Users\\candidate\\AppData\\Local\\Programs\\Microsoft VS Code\\Code.exe"),
+                process(3, "WindowsTerminal.exe", "C:\\Program Files\\WindowsApps\\WindowsTerminal.exe"),
+                process(4, "interviewcoder-terminal.exe", "C:\\Users\\candidate\\interviewcoder-terminal.exe"),
+                process(5, "rider", "/home/candidate/.local/share/JetBrains/Toolbox/apps/rider/bin/rider"),
+            ]);
+            detector.scan_for_suspicious_processes()
+        };
+
+        // Named after a terminal isn't a terminal.
+        let pids = |flagged: &[SuspiciousProcess]| flagged.iter().map(|p| p.pid).collect::<Vec<_>>();
+        assert_eq!(pids(&flagged_under("Coding")), vec![4]);
+        assert_eq!(pids(&flagged_under("system_design")), vec![3, 4]);
+
+        let behavioral = flagged_under("behavioral");
+        assert_eq!(pids(&behavioral), vec![2, 3, 4, 5]);
+        assert!(behavioral[0].reason_codes.contains(&ReasonCode::IdeDuringBehavioralInterview));
+        assert!(!behavioral[1].reason_codes.contains(&ReasonCode::IdeDuringBehavioralInterview));
+    }
+
+    #[test]
+    fn test_vm_detection_follows_its_cadence() {
+        let mut config = Config::default();
+        config.monitoring.enable_vm_detection = true;
+        config.cadence.vm_detection = 5;
+        let mut detector = detector_with(config, Vec::new());
+
+        let mut ran = Vec::new();
+        for _ in 0..11 {
+            let report = detector.scan();
+            assert!(report.vm_detection.is_some());
+            if !report.carried_forward_modules.contains(&"VM detection".to_string()) {
+                ran.push(report.scan_number);
+            }
+        }
+        assert_eq!(ran, vec![1, 6, 11]);
+
+        detector.force_fresh_next_scan();
+        assert!(detector.scan().carried_forward_modules.is_empty());
+    }
+
+    #[test]
+    fn test_recently_started_process_scores_higher() {
+        let running = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut config = Config::default();
+        config.heuristics.recent_start_multiplier = 1.1;
+        let mut detector = FairviewDetector::with_process_source(config, Box::new(ChangingSource(running.clone())));
+        detector.collect_baseline();
+
+        let started = |pid, run_time_seconds| {
+            let mut recorder = process(pid, "recorder", "/opt/recorder/recorder");
+            recorder.stats.run_time_seconds = run_time_seconds;
+            recorder
+        };
+        running.lock().unwrap().extend([started(2, 5), started(3, 3600)]);
+        let flagged = detector.scan_for_suspicious_processes();
+
+        assert_eq!(flagged.len(), 2);
+        assert!((flagged[0].risk_score - 0.9 * recency_multiplier(5, 120, 1.1)).abs() < 1e-9);
+        assert!((flagged[1].risk_score - 0.9).abs() < 1e-9);
+        assert!(flagged[0].risk_score > flagged[1].risk_score);
+    }
+
+    #[test]
+    fn test_recently_written_executable_is_flagged() {
+        let binary = std::env::temp_dir().join(format!("fairview_fresh_{}", std::process::id()));
+        std::fs::write(&binary, b"helper").unwrap();
+        let path = binary.to_string_lossy().into_owned();
+        let missing = format!("{}_missing", path);
+
+        let mut detector = detector_with(Config::default(), vec![
+            (process(90, "helper", &path), capabilities(true, false, false)),
+            (process(91, "helper2", &missing), capabilities(true, false, false)),
+        ]);
+        let fresh = detector.scan_for_suspicious_processes();
+
+        // Two days on, the same binary is no longer fresh.
+        let later = SystemTime::now() + Duration::from_secs(2 * 24 * 3600);
+        detector.set_clock(Box::new(clock::FixedClock::new(later, Duration::from_secs(1))));
+        let aged = detector.scan_for_suspicious_processes();
+        std::fs::remove_file(&binary).ok();
+
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh[0].pid, 90);
+        assert!(fresh[0].reason_codes.contains(&ReasonCode::FreshExecutable));
+        assert!(aged.is_empty());
+    }
+
+    #[test]
+    fn test_inspection_cap_keeps_most_recent_processes() {
+        let started = |pid: u32, run_time_seconds: u64| {
+            let mut cheat = process(pid, &format!("interview-ai-{}.exe", pid), "C:\\Tools\\interview-ai.exe");
+            cheat.stats.run_time_seconds = run_time_seconds;
+            (cheat, capabilities(true, false, false))
+        };
+        let mut config = Config::default();
+        config.scan.max_processes_inspected = Some(2);
+        let detector = detector_with(config, vec![started(1, 3600), started(2, 60), started(3, 600)]);
+
+        let scan = detector.scan_processes();
+        let mut flagged: Vec<u32> = scan.suspicious.iter().map(|p| p.pid).collect();
+        flagged.sort();
+        assert_eq!(flagged, vec![2, 3]);
+        assert_eq!((scan.inspected, scan.uninspected), (3, 1));
+    }
+
+    #[test]
+    fn test_process_seen_once_is_not_flagged_under_min_lifetime() {
+        let mut config = process_only_config();
+        config.monitoring.enable_overlay_monitoring = true;
+        config.heuristics.min_process_lifetime_seconds = 30;
+        let cheat = || (process(42, "interview-ai.exe", "C:\\Tools\\interview-ai.exe"), capabilities(true, false, false));
+
+        assert_eq!(detector_with(Config::default(), vec![cheat()]).scan().suspicious_processes.len(), 1);
+
+        let mut detector = detector_with(config, vec![cheat()]);
+        detector.set_clock(Box::new(clock::FixedClock::new(SystemTime::now(), Duration::from_secs(10))));
+        assert!(detector.scan().suspicious_processes.is_empty());
+        let persisted = (0..5).map(|_| detector.scan().suspicious_processes.len()).last();
+        assert_eq!(persisted, Some(1));
+    }
+
+    #[test]
+    fn test_min_lifetime_holds_back_rule_severity_but_not_denylist() {
+        let mut config = process_only_config();
+        config.monitoring.enable_overlay_monitoring = true;
+        config.heuristics.min_process_lifetime_seconds = 30;
+        config.rules = vec![config::RuleConfig {
+            name: "appdata screen".to_string(),
+            path_contains: Some("appdata".to_string()),
+            risk_delta: 0.5,
+            force_severity: Some(Severity::Critical),
+            ..Default::default()
+        }];
+        let helper = (process(43, "helper.exe", "C:\\Users\\candidate\\AppData\\helper.exe"), capabilities(true, false, false));
+        let report = detector_with(config.clone(), vec![helper]).scan();
+        assert!(report.suspicious_processes.is_empty());
+        assert_ne!(report.severity, Severity::Critical);
+
+        config.denylist.names = vec!["cluely".to_string()];
+        let cluely = (process(70, "Cluely.exe", "C:\\Program Files\\Cluely\\Cluely.exe"), capabilities(false, false, false));
+        let report = detector_with(config, vec![cluely]).scan();
+        assert_eq!(report.suspicious_processes.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![70]);
+        assert_eq!(report.policy_breach, Some(PolicyBreach::Denylisted));
+    }
+
+    #[test]
+    fn test_baseline_is_refreshed_after_interval() {
+        let running = Arc::new(std::sync::Mutex::new(vec![process(1, "editor", "/opt/editor/editor")]));
+        let mut config = process_only_config();
+        config.monitoring.rebaseline_interval_scans = Some(2);
+        let mut detector = FairviewDetector::with_process_source(config, Box::new(ChangingSource(running.clone())));
+        detector.collect_baseline();
+
+        running.lock().unwrap().push(process(2, "recorder", "/opt/recorder/recorder"));
+        running.lock().unwrap().push(process(3, "notes", "/opt/notes/notes"));
+        let first = detector.scan();
+        assert!(first.rebaseline.is_none());
+        assert_eq!(first.suspicious_processes.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![2, 3]);
+        running.lock().unwrap().retain(|p| p.pid != 3);
+        assert!(detector.scan().rebaseline.is_none());
+        assert!(!detector.process_monitor.was_in_baseline(2));
+
+        running.lock().unwrap().push(process(4, "notes", "/opt/notes/notes"));
+        let refreshed = detector.scan();
+        let rebaseline = refreshed.rebaseline.unwrap();
+        assert_eq!(rebaseline.baseline_processes, 2);
+        assert_eq!(rebaseline.carried_forward_pids, vec![2]);
+        assert!(detector.process_monitor.was_in_baseline(4));
+        assert!(!detector.process_monitor.was_in_baseline(2));
+        assert_eq!(refreshed.suspicious_processes.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![2]);
+        assert!(detector.scan().rebaseline.is_none());
+    }
+
+    #[test]
+    fn test_rebaseline_keeps_display_baseline_while_hardware_is_flagged() {
+        use crate::hardware_detector::ConnectionType;
+        use crate::test_support::display;
+
+        let mut config = process_only_config();
+        config.monitoring.enable_hardware_monitoring = true;
+        config.monitoring.rebaseline_interval_scans = Some(2);
+        config.hardware.display_change_scans = 1;
+        let platform = Arc::new(MockPlatform::default());
+        let mut detector = FairviewDetector::with_platform(config, platform.clone());
+        detector.collect_baseline();
+
+        platform.connected_displays.lock().unwrap().push(display("1", ConnectionType::HDMI));
+        let connected = detector.scan();
+        let refreshed = (0..3).map(|_| detector.scan()).find(|report| report.rebaseline.is_some()).unwrap();
+
+        assert!(!connected.hardware_suspicion.unwrap().flags.is_empty());
+        assert_eq!(refreshed.rebaseline.unwrap().baseline_displays, Some(1));
+        let flags = refreshed.hardware_suspicion.unwrap().flags;
+        assert!(flags.iter().any(|flag| flag.starts_with("Display configuration changed")), "{:?}", flags);
+    }
+
+    #[test]
+    fn test_silent_module_is_reported_once() {
+        let mut config = process_only_config();
+        config.monitoring.enable_hardware_monitoring = true;
+        config.monitoring.silent_module_scans = 3;
+        // No displays: hardware monitoring runs but never sees anything.
+        let platform = MockPlatform {
+            processes: vec![(process(1, "editor", "/opt/editor/editor"), capabilities(false, false, false))],
+            displays: Vec::new(),
+            ..Default::default()
+        };
+        let mut detector = FairviewDetector::with_platform(config, Arc::new(platform));
+
+        let failures: Vec<Vec<String>> = (0..4).map(|_| detector.scan().module_failures).collect();
+        assert!(failures[0].is_empty() && failures[1].is_empty() && failures[3].is_empty());
+        assert_eq!(
+            failures[2],
+            vec!["Hardware monitoring has produced no output in 3 scans and may not work in this environment"]
+        );
+    }
+
+    #[test]
+    fn test_raw_risk_score_keeps_counting_past_the_cap() {
+        let mut config = process_only_config();
+        config.weights.process_risk = 1.0;
+        config.weights.overlay_risk = 0.0;
+        config.weights.audio_risk = 0.0;
+        config.weights.hardware_risk = 0.0;
+        config.weights.vm_risk = 0.0;
+        config.weights.input_risk = 0.0;
+
+        let tools = (0..3)
+            .map(|i| {
+                let name = format!("interview-ai-{}.exe", i);
+                let path = format!("C:\\Users\\candidate\\{}", name);
+                (process(20 + i, &name, &path), capabilities(true, true, false))
+            })
+            .collect();
+        let report = detector_with(config, tools).scan();
+
+        assert_eq!(report.suspicious_processes.len(), 3);
+        assert_eq!(report.overall_risk_score, 1.0);
+        assert!(report.raw_risk_score > 1.0);
+        assert_eq!(report.severity, Severity::Critical);
+    }
+
+    #[test]
+    fn test_each_capture_stream_adds_to_raw_risk() {
+        let scan = |count| {
+            let mut config = process_only_config();
+            config.monitoring.enable_audio_monitoring = true;
+            let platform = MockPlatform {
+                capture_streams: Some(audio_detector::CaptureStreams {
+                    count,
+                    applications: vec!["whisper".to_string(), "zoom".to_string()],
+                }),
+                ..Default::default()
+            };
+            FairviewDetector::with_platform(config, Arc::new(platform)).scan()
+        };
+
+        let one = scan(1);
+        let three = scan(3);
+        assert!(one.audio_monitoring_detected && three.audio_monitoring_detected);
+        assert_eq!(one.overall_risk_score, three.overall_risk_score);
+        assert!((three.raw_risk_score - one.raw_risk_score - 2.0 * Config::default().weights.audio_risk).abs() < 1e-9);
+        assert_eq!(three.audio_capture_streams.map(|streams| streams.count), Some(3));
+        assert!(!scan(0).audio_monitoring_detected);
+    }
+
+    #[test]
+    fn test_cloud_clipboard_sync_is_scored_as_input_risk() {
+        let scan = |sync| {
+            let mut config = process_only_config();
+            config.monitoring.enable_audio_monitoring = true;
+            config.monitoring.enable_clipboard_monitoring = true;
+            config.output.score_precision = 6;
+            let platform = MockPlatform { cloud_clipboard: Some(sync), ..Default::default() };
+            FairviewDetector::with_platform(config, Arc::new(platform)).scan()
+        };
+
+        let off = scan(false);
+        let on = scan(true);
+        assert!(!off.cloud_clipboard_sync && on.cloud_clipboard_sync);
+        let expected = Config::default().heuristics.cloud_clipboard_weight * Config::default().weights.input_risk;
+        assert!((on.overall_risk_score - off.overall_risk_score - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_foreground_app_is_correlated_with_flagged_overlay_owner() {
+        use crate::focus_detector::ForegroundWindow;
+
+        let scan = |foreground: Option<ForegroundWindow>| {
+            let mut config = Config::default();
+            config.monitoring.enable_hardware_monitoring = false;
+            config.monitoring.enable_vm_detection = false;
+            let platform = MockPlatform {
+                processes: vec![
+                    (process(42, "answers.exe", "C:\\Users\\candidate\\answers.exe"), capabilities(true, false, false)),
+                    (process(7, "notepad.exe", "C:\\Windows\\notepad.exe"), capabilities(false, false, false)),
+                ],
+                overlays: vec![OverlayWindow { excluded_from_capture: true, ..overlay(true, true) }],
+                foreground,
+                ..Default::default()
+            };
+            FairviewDetector::with_platform(config, Arc::new(platform)).scan()
+        };
+
+        let focused = scan(Some(ForegroundWindow { pid: 42, title: Some("Answers".to_string()) }));
+        let app = focused.foreground_app.expect("foreground app captured");
+        assert_eq!((app.name.as_deref(), app.title.as_deref()), (Some("answers.exe"), Some("Answers")));
+        assert!(app.flagged && app.overlay_owner);
+
+        let editor = scan(Some(ForegroundWindow { pid: 7, title: None })).foreground_app.unwrap();
+        assert_eq!(editor.name.as_deref(), Some("notepad.exe"));
+        assert!(!editor.is_suspicious());
+
+        assert!(scan(None).foreground_app.is_none());
+    }
+
+    #[test]
+    fn test_system_process_name_outside_system_dirs_is_flagged() {
+        let detector = detector_with(Config::default(), vec![
+            (process(70, "svchost.exe", "C:\\Users\\candidate\\Downloads\\svchost.exe"), capabilities(false, false, false)),
+            (process(71, "svchost.exe", "C:\\Windows\\System32\\svchost.exe"), capabilities(false, false, false)),
+            (process(72, "explorer.exe", "C:\\Windows\\explorer.exe"), capabilities(false, false, false)),
+        ]);
+
+        let flagged = detector.scan_for_suspicious_processes();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].pid, 70);
+        assert_eq!(flagged[0].reason_codes, vec![ReasonCode::SystemNameImpersonation]);
+    }
+
+    #[test]
+    fn test_failed_modules_raise_uncertainty() {
+        let scan = |broken| {
+            let mut config = Config::default();
+            config.monitoring.enable_vm_detection = false;
+            config.monitoring.enable_audio_monitoring = false;
+            config.monitoring.enable_audio_loop_detection = true;
+            let platform = MockPlatform {
+                processes: vec![(process(1, "editor", "/opt/editor/editor"), capabilities(false, false, false))],
+                broken,
+                ..Default::default()
+            };
+            FairviewDetector::with_platform(config, Arc::new(platform)).scan().uncertainty
+        };
+
+        let clean = scan(false);
+        let broken = scan(true);
+        assert_eq!(clean.score, 0.0);
+        assert_eq!(broken.failed_modules, 2);
+        assert!(broken.score > clean.score);
+    }
+
+    #[test]
+    fn test_system_path_exemption_is_configurable() {
+        let scan = |config: Config, signed| {
+            let wlanext = process(60, "wlanext.exe", "C:\\Windows\\System32\\wlanext.exe");
+            let platform = MockPlatform { processes: vec![(wlanext, capabilities(true, true, true))], signed, ..Default::default() };
+            FairviewDetector::with_platform(config, Arc::new(platform)).scan_for_suspicious_processes()
+        };
+        let mut config = Config::default();
+        config.whitelist.directories.clear();
+        assert!(scan(config.clone(), None).is_empty());
+
+        config.heuristics.require_signed_system_binaries = true;
+        assert!(scan(config.clone(), Some(true)).is_empty());
+        assert_eq!(scan(config.clone(), Some(false)).len(), 1);
+        assert_eq!(scan(config.clone(), None).len(), 1);
+
+        config.heuristics.require_signed_system_binaries = false;
+        config.heuristics.exempt_system_paths = false;
+        let flagged = scan(config, None);
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].reason_codes, vec![ReasonCode::ScreenCapture, ReasonCode::AudioCapture, ReasonCode::Accessibility]);
+    }
+
+    #[test]
+    fn test_external_drive_process_is_flagged() {
+        let on_drive = |drive_type| {
+            let process = Process { drive_type, ..process(41, "notes-sync.exe", "E:\\tools\\notes-sync.exe") };
+            (process, capabilities(true, false, false))
+        };
+        let mut config = Config::default();
+
+        let fixed = detector_with(config.clone(), vec![on_drive(drive::DriveType::Fixed)]);
+        assert!(fixed.scan_for_suspicious_processes().is_empty());
+
+        let flagged = detector_with(config.clone(), vec![on_drive(drive::DriveType::Removable)]).scan_for_suspicious_processes();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].reason_codes, vec![ReasonCode::ScreenCapture, ReasonCode::ExternalDrive]);
+        assert_eq!(flagged[0].reasons[1], "Runs from a removable or network drive (removable drive)");
+        assert_eq!(flagged[0].drive_type, drive::DriveType::Removable);
+        assert!((flagged[0].risk_score - 0.6).abs() < 1e-9);
+
+        config.heuristics.flag_external_drive_processes = false;
+        let disabled = detector_with(config, vec![on_drive(drive::DriveType::Network)]);
+        assert!(disabled.scan_for_suspicious_processes().is_empty());
+    }
+
+    #[test]
+    fn test_access_denied_processes_are_counted() {
+        let denied = process_monitor::ProcessCapabilities { inspection_denied: true, ..Default::default() };
+        let mut detector = detector_with(Config::default(), vec![
+            (process(10, "svc-agent.exe", "C:\\ProgramData\\svc-agent.exe"), denied.clone()),
+            (process(11, "updater.exe", "C:\\ProgramData\\updater.exe"), denied.clone()),
+            (process(12, "svchost.exe", "C:\\Windows\\System32\\svchost.exe"), denied),
+            (process(13, "notes.exe", "C:\\Users\\candidate\\notes.exe"), capabilities(false, false, false)),
+        ]);
+
+        let scan = detector.scan_processes();
+        assert!(scan.suspicious.is_empty());
+        assert_eq!(scan.inaccessible, 2);
+        assert_eq!(detector.scan().inaccessible_processes, 2);
+    }
+
+    /// Lists `processes` and reports PID 20 as exited for its first
+    /// `exited_inspections` inspections.
+    struct ExitingSource {
+        processes: Vec<Process>,
+        exited_inspections: usize,
+        inspections: std::sync::Mutex<usize>,
+    }
+
+    impl ProcessSource for ExitingSource {
+        fn processes(&self) -> Vec<Process> {
+            self.processes.clone()
+        }
+
+        fn capabilities(&self, process: &Process) -> process_monitor::ProcessCapabilities {
+            if process.pid != 20 {
+                return capabilities(false, false, false);
+            }
+            let mut inspections = self.inspections.lock().unwrap();
+            *inspections += 1;
+            if *inspections <= self.exited_inspections {
+                return process_monitor::ProcessCapabilities { process_exited: true, ..Default::default() };
+            }
+            capabilities(true, true, false)
+        }
+    }
+
+    #[test]
+    fn test_process_exiting_during_inspection_is_counted() {
+        let scan = |policy, exited_inspections| {
+            let mut config = Config::default();
+            config.scan.exited_process_policy = policy;
+            let source = ExitingSource {
+                processes: vec![
+                    process(20, "interview-helper.exe", "C:\\Users\\candidate\\interview-helper.exe"),
+                    process(21, "notes.exe", "C:\\Users\\candidate\\notes.exe"),
+                ],
+                exited_inspections,
+                inspections: std::sync::Mutex::new(0),
+            };
+            FairviewDetector::with_process_source(config, Box::new(source)).scan_processes()
+        };
+
+        let skipped = scan(config::ExitedProcessPolicy::Skip, 1);
+        assert!(skipped.suspicious.is_empty());
+        assert_eq!((skipped.exited_during_inspection, skipped.inaccessible), (1, 0));
+
+        let recovered = scan(config::ExitedProcessPolicy::RetryOnce, 1);
+        assert_eq!(recovered.suspicious.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![20]);
+        assert_eq!(recovered.exited_during_inspection, 1);
+
+        let gone = scan(config::ExitedProcessPolicy::RetryOnce, 2);
+        assert!(gone.suspicious.is_empty());
+        assert_eq!(gone.exited_during_inspection, 1);
+    }
+
+    #[test]
+    fn test_empty_process_list_is_not_a_clean_scan() {
+        let mut config = process_only_config();
+        config.monitoring.enable_audio_monitoring = true;
+
+        let blocked = detector_with(config.clone(), Vec::new()).scan();
+        assert_eq!(blocked.module_failures.len(), 1);
+        assert!(blocked.module_failures[0].starts_with("Process enumeration unavailable"));
+        assert_eq!(blocked.uncertainty.failed_modules, 1);
+        assert!(blocked.uncertainty.score > 0.0);
+
+        let listed = detector_with(config, vec![
+            (process(1, "explorer.exe", "C:\\Windows\\explorer.exe"), capabilities(false, false, false)),
+        ]).scan();
+        assert!(listed.module_failures.is_empty());
+        assert_eq!(listed.uncertainty.failed_modules, 0);
+    }
+
+    #[test]
+    fn test_enforcing_without_baseline_is_noted_on_reports() {
+        let scan = |configure: fn(&mut Config)| {
+            let mut config = process_only_config();
+            config.monitoring.collect_baseline = false;
+            configure(&mut config);
+            detector_with(config, Vec::new()).scan().baseline_missing
+        };
+
+        assert!(scan(|_| {}));
+        assert!(!scan(|config| config.monitoring.collect_baseline = true));
+        assert!(!scan(|config| config.mode = config::Mode::Observe));
+        assert!(!scan(|config| config.monitoring.warn_without_baseline = false));
+        assert!(!scan(|config| {
+            config.hardware.expected_displays = vec![config::ExpectedDisplay { width: 1920, height: 1080 }];
+        }));
+    }
+
+    #[test]
+    fn test_observe_mode_never_breaches() {
+        let scan = |mode| {
+            let mut config = process_only_config();
+            config.scan.risk_threshold = 0.1;
+            config.mode = mode;
+            detector_with(config, vec![
+                (process(10, "interview-ai.exe", "C:\\Users\\candidate\\interview-ai.exe"), capabilities(true, false, false)),
+            ]).scan()
+        };
+
+        let enforced = scan(config::Mode::Enforce);
+        assert!(enforced.exceeds_threshold);
+        assert_ne!(event_log::format_event(&enforced).level, event_log::EventLevel::Info);
+
+        let observed = scan(config::Mode::Observe);
+        assert!(observed.observational);
+        assert!(!observed.exceeds_threshold);
+        assert_eq!(observed.overall_risk_score, enforced.overall_risk_score);
+        assert_eq!(observed.suspicious_processes.len(), 1);
+        let event = event_log::format_event(&observed);
+        assert_eq!(event.level, event_log::EventLevel::Info);
+        assert!(event.message.ends_with(" mode=observe"));
+    }
+
+    #[test]
+    fn test_restart_continues_scan_numbering() {
+        let path = std::env::temp_dir().join(format!("fairview_numbering_test_{}.json", std::process::id()));
+        let mut config = process_only_config();
+        config.session.session_id = Some("int-2041".to_string());
+
+        let mut verdict = session::SessionVerdict::new();
+        let mut detector = detector_with(config.clone(), Vec::new());
+        for _ in 0..3 {
+            verdict.record(&detector.scan());
+        }
+        verdict.save_checkpoint(&path).unwrap();
+
+        let resumed = session::SessionVerdict::load_checkpoint(&path, "int-2041").unwrap().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut restarted = detector_with(config, Vec::new());
+        restarted.resume_scan_count(resumed.last_scan_number);
+
+        assert_eq!(resumed.last_scan_number, 3);
+        assert_eq!(restarted.scan().scan_number, 4);
+    }
+
+    #[test]
+    fn test_scores_are_rounded_to_the_configured_precision() {
+        assert_eq!(round_score(0.12345, 3), 0.123);
+
+        let mut scan = report(1, 0.12345, true);
+        scan.category_breaches.push(CategoryBreach {
+            category: RiskCategory::Hardware,
+            score: 0.30000000000000004,
+            threshold: 0.3,
+        });
+        scan.round_scores(3);
+        let json = serde_json::to_value(&scan).unwrap();
+        assert_eq!(json["overall_risk_score"], 0.123);
+        assert_eq!(json["category_breaches"][0]["score"], 0.3);
+
+        // Each detector rounds to its own config, whichever was built last.
+        let detector = |precision| {
+            let mut config = process_only_config();
+            config.output.score_precision = precision;
+            detector_with(config, vec![
+                (process(10, "interview-ai.exe", "C:\\Users\\candidate\\interview-ai.exe"), capabilities(true, false, false)),
+            ])
+        };
+        let mut coarse = detector(1);
+        let mut fine = detector(3);
+        assert_eq!(fine.scan().overall_risk_score, 0.21);
+        assert_eq!(coarse.scan().overall_risk_score, 0.2);
+    }
+
+    #[test]
+    fn test_scan_lists_processes_once() {
+        use std::sync::atomic::Ordering;
+
+        let mut config = Config::default();
+        config.monitoring.enable_vm_detection = false;
+        config.monitoring.enable_input_device_monitoring = false;
+        config.monitoring.enable_network_monitoring = false;
+        config.monitoring.enable_virtual_desktop_detection = false;
+        let platform = Arc::new(MockPlatform {
+            processes: vec![(process(10, "helper.exe", "C:\\Users\\candidate\\helper.exe"), capabilities(true, true, false))],
+            overlays: vec![OverlayWindow { owner_pid: 10, ..overlay(true, true) }],
+            ..Default::default()
+        });
+        let mut detector = FairviewDetector::with_platform(config, platform.clone());
+
+        detector.collect_baseline();
+        let baseline = platform.listings.load(Ordering::SeqCst);
+        let report = detector.scan();
+        assert!(!report.hidden_overlays.is_empty());
+        assert_eq!(platform.listings.load(Ordering::SeqCst) - baseline, 1);
+
+        // Outside a scan the listing is live again.
+        detector.scan_for_suspicious_processes();
+        assert_eq!(platform.listings.load(Ordering::SeqCst) - baseline, 2);
+    }
+
+    #[test]
+    fn test_mock_platform_drives_full_scan() {
+        use crate::hardware_detector::{CastKind, ConnectionType, RemoteDesktopKind, RemoteDesktopStatus};
+        use crate::test_support::display;
+
+        let mut config = Config::default();
+        config.monitoring.enable_overlay_monitoring = false;
+        config.monitoring.enable_vm_detection = false;
+        config.monitoring.enable_input_device_monitoring = false;
+        let platform = MockPlatform {
+            processes: vec![
+                (process(10, "interview-ai.exe", "C:\\Users\\candidate\\interview-ai.exe"), capabilities(true, true, false)),
+                (process(20, "parsecd.exe", "C:\\Program Files\\Parsec\\parsecd.exe"), capabilities(false, false, false)),
+            ],
+            displays: vec![display("0", ConnectionType::Unknown), display("1", ConnectionType::Wireless)],
+            remote_desktop: RemoteDesktopStatus {
+                active: true,
+                kind: Some(RemoteDesktopKind::Rdp),
+                evidence: vec!["SESSIONNAME=RDP-Tcp#0".to_string()],
+            },
+            audio_monitoring: true,
+            ..Default::default()
+        };
+
+        let report = FairviewDetector::with_platform(config, Arc::new(platform)).scan();
+
+        assert_eq!(report.suspicious_processes.len(), 1);
+        assert_eq!(report.suspicious_processes[0].pid, 10);
+        assert!(report.audio_monitoring_detected);
+        let hardware = report.hardware_suspicion.expect("hardware monitoring is on");
+        assert_eq!(hardware.display_count, 2);
+        assert_eq!(hardware.remote_desktop.kind, Some(RemoteDesktopKind::Rdp));
+        assert_eq!(hardware.cast_sessions[0].kind, CastKind::RemotePlay);
+        assert_eq!(hardware.risk_score, 1.0);
+        assert!(report.exceeds_threshold);
+        assert!(report.module_failures.is_empty());
+    }
+
+    #[test]
+    fn test_report_truncates_to_top_processes() {
+        let max = 10;
+        let mut report = crate::test_support::report(1, 0.9, true);
+        report.suspicious_processes = (0..max + 5)
+            .map(|i| SuspiciousProcess {
+                pid: i as u32,
+                name: format!("tool-{}.exe", i),
+                path: "C:\\tools".to_string(),
+                risk_score: i as f64 / 100.0,
+                reasons: Vec::new(),
+                reason_codes: Vec::new(),
+                started_during_interview: false,
+                is_whitelisted: false,
+                drive_type: Default::default(),
+            })
+            .collect();
+        let faint = OverlayWindow { id: "a".to_string(), ..overlay(false, false) };
+        let topmost = OverlayWindow { id: "b".to_string(), ..overlay(false, true) };
+        let layered = OverlayWindow { id: "c".to_string(), ..overlay(true, true) };
+        report.hidden_overlays = vec![faint, topmost, layered];
+
+        report.truncate(max, 2);
+
+        assert!(report.truncated);
+        assert_eq!(report.suspicious_processes.len(), max);
+        assert_eq!(report.omitted_processes, 5);
+        assert_eq!(report.suspicious_processes[0].pid, 14);
+        assert!(report.suspicious_processes.iter().all(|p| p.pid >= 5));
+        assert_eq!(report.omitted_overlays, 1);
+        let ids: Vec<_> = report.hidden_overlays.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_report_under_limit_is_not_truncated() {
+        let mut report = crate::test_support::report(1, 0.1, false);
+        report.hidden_overlays = vec![overlay(true, true)];
+        report.truncate(10, 10);
+        assert!(!report.truncated);
+        assert_eq!(report.hidden_overlays.len(), 1);
+    }
+
+    #[test]
+    fn test_serialized_time_fields_are_rfc3339() {
+        use crate::tcc::{TccGrant, TccService};
+
+        /// Fails on serde's default `SystemTime` form, and on any of the
+        /// known time fields that doesn't hold an RFC3339 string.
+        fn check(value: &serde_json::Value, found: &mut usize) {
+            match value {
+                serde_json::Value::Object(fields) => {
+                    assert!(!fields.contains_key("secs_since_epoch"), "raw SystemTime in {}", value);
+                    for (key, field) in fields {
+                        if ["timestamp", "captured_at", "annotated_at", "last_modified"].contains(&key.as_str()) {
+                            let text = field.as_str().unwrap_or_default();
+                            assert!(chrono::DateTime::parse_from_rfc3339(text).is_ok(), "{} is {}", key, field);
+                            *found += 1;
+                        }
+                        check(field, found);
+                    }
+                }
+                serde_json::Value::Array(items) => items.iter().for_each(|item| check(item, found)),
+                _ => {}
+            }
+        }
+
+        let path = "/Applications/Helper.app/Contents/MacOS/Helper";
+        let platform = Arc::new(MockPlatform {
+            processes: vec![(process(60, "Helper", path), capabilities(true, false, false))],
+            tcc_grants: vec![TccGrant {
+                service: TccService::ScreenCapture,
+                client: path.to_string(),
+                client_is_path: true,
+                allowed: true,
+                last_modified: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            }],
+            ..Default::default()
+        });
+        let recorder = Arc::new(observations::RecordingPlatform::new(platform));
+        let mut detector = FairviewDetector::with_platform(Config::default(), recorder.clone());
+        detector.set_clock(Box::new(clock::FixedClock::deterministic()));
+        detector.collect_baseline();
+        let report = detector.scan();
+        let observation = recorder.take(observations::ObservationPhase::Scan, 1, report.timestamp);
+
+        let mut found = 0;
+        check(&serde_json::to_value(&report).unwrap(), &mut found);
+        check(&serde_json::to_value(&observation).unwrap(), &mut found);
+        check(&serde_json::to_value(detector.baseline_snapshot()).unwrap(), &mut found);
+        assert_eq!(found, 4);
+    }
+
+    #[test]
+    fn test_report_snapshot_with_fixed_clock() {
+        let mut detector = detector_with(process_only_config(), vec![
+            (process(10, "interview-ai.exe", "C:\\Users\\candidate\\interview-ai.exe"), capabilities(true, false, false)),
+        ]);
+        detector.set_clock(Box::new(clock::FixedClock::deterministic()));
+
+        let first = serde_json::to_string_pretty(&detector.scan()).unwrap();
+        assert_eq!(first, r#"{
+  "timestamp": "2023-11-14T22:13:21+00:00",
+  "scan_number": 1,
+  "session_id": null,
+  "candidate_ref": null,
+  "machine_fingerprint": null,
+  "suspicious_processes": [
+    {
+      "pid": 10,
+      "name": "interview-ai.exe",
+      "path": "C:\\Users\\candidate\\interview-ai.exe",
+      "risk_score": 0.7,
+      "reasons": [
+        "Has screen capture permission",
+        "Suspicious process name"
+      ],
+      "reason_codes": [
+        "screen_capture",
+        "suspicious_name"
+      ],
+      "started_during_interview": false,
+      "is_whitelisted": false,
+      "drive_type": "unknown"
+    }
+  ],
+  "hidden_overlays": [],
+  "overlay_confidence": 0.0,
+  "parked_windows": [],
+  "inactive_desktop_windows": 0,
+  "inaccessible_processes": 0,
+  "audio_monitoring_detected": false,
+  "audio_loops": [],
+  "hardware_suspicion": null,
+  "new_input_devices": [],
+  "vm_detection": null,
+  "overall_risk_score": 0.21,
+  "raw_risk_score": 0.21,
+  "exceeds_threshold": false,
+  "severity": "low",
+  "uncertainty": {
+    "score": 0.0,
+    "modules_run": 1,
+    "degraded_modules": 0,
+    "failed_modules": 0
+  },
+  "category_breaches": [],
+  "policy_breach": null,
+  "observational": false,
+  "truncated": false,
+  "omitted_processes": 0,
+  "omitted_overlays": 0,
+  "suppressed_findings": 0,
+  "module_failures": [],
+  "operator_annotation": null,
+  "whitelist_profile": null,
+  "scan_duration_ms": 1000,
+  "interval_adjustment": null,
+  "clock_tampering_suspected": false,
+  "clock_tampering_reason": null,
+  "forced_fresh": false
+}"#);
+
+        let second = detector.scan();
+        assert_eq!(second.scan_number, 2);
+        assert_eq!(second.timestamp, SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_003));
+        assert!(!second.clock_tampering_suspected);
+    }
+
+    #[test]
+    fn test_low_confidence_findings_are_omitted_but_scored() {
+        let mut config = process_only_config();
+        config.scan.min_report_confidence = 0.9;
+
+        let mut detector = detector_with(config, vec![
+            (process(10, "interview-ai.exe", "C:\\Users\\candidate\\interview-ai.exe"), capabilities(true, false, false)),
+        ]);
+        let report = detector.scan();
+
+        assert!(report.suspicious_processes.is_empty());
+        assert_eq!(report.suppressed_findings, 1);
+        assert!((report.overall_risk_score - 0.7 * 0.30).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_session_identifiers_appear_in_report() {
+        let mut config = process_only_config();
+        config.session.session_id = Some("int-2041".to_string());
+        config.session.candidate_ref = Some("ats:88213".to_string());
+
+        let mut detector = detector_with(config, Vec::new());
+        let json = serde_json::to_value(detector.scan()).unwrap();
+
+        assert_eq!(json["session_id"], "int-2041");
+        assert_eq!(json["candidate_ref"], "ats:88213");
+    }
+
+    #[test]
+    fn test_allowlisted_overlays_are_dropped() {
+        let detector = detector_with(Config::default(), vec![
+            (process(42, "helper.exe", "C:\\Users\\candidate\\helper.exe"), capabilities(false, false, false)),
+            (process(43, "Discord.exe", "C:\\Users\\candidate\\Discord.exe"), capabilities(false, false, false)),
+        ]);
+        let mut toast = overlay(true, true);
+        toast.title = "New notification".to_string();
+        let mut unknown = overlay(true, true);
+        unknown.title = "Interview Copilot".to_string();
+        let mut discord = overlay(true, true);
+        discord.owner_pid = 43;
+
+        let kept = detector.drop_allowlisted_overlays(vec![toast, unknown, discord]);
+
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].title, "Interview Copilot");
+    }
+
+    #[test]
+    fn test_allowlisted_capture_excluded_overlay_does_not_breach() {
+        let mut config = process_only_config();
+        config.monitoring.enable_overlay_monitoring = true;
+        let platform = MockPlatform {
+            processes: vec![
+                (process(43, "Discord.exe", "C:\\Users\\candidate\\Discord.exe"), capabilities(false, false, false)),
+            ],
+            overlays: vec![OverlayWindow { excluded_from_capture: true, owner_pid: 43, ..overlay(true, true) }],
+            ..Default::default()
+        };
+
+        let report = FairviewDetector::with_platform(config, Arc::new(platform)).scan();
+
+        assert!(report.hidden_overlays.is_empty());
+        assert!(report.category_breaches.iter().all(|b| b.category != RiskCategory::Overlay));
+    }
+
+    #[test]
+    fn test_overlay_owner_correlation_boosts_combined_score() {
+        let detector = detector_with(Config::default(), Vec::new());
+        let flagged = SuspiciousProcess {
+            pid: 42,
+            name: "copilot-helper.exe".to_string(),
+            path: "C:\\Users\\candidate\\copilot-helper.exe".to_string(),
+            risk_score: 0.7,
+            reasons: vec![ReasonCode::SuspiciousName.description().to_string()],
+            reason_codes: vec![ReasonCode::SuspiciousName],
+            started_during_interview: false,
+            is_whitelisted: false,
+            drive_type: Default::default(),
+        };
+        let mut orphan = overlay(false, false);
+        orphan.owner_pid = 99;
+
+        let mut uncorrelated_processes = vec![flagged.clone()];
+        let mut uncorrelated_overlays = vec![orphan];
+        detector.correlate_overlay_owners(&mut uncorrelated_processes, &mut uncorrelated_overlays);
+
+        let mut correlated_processes = vec![flagged];
+        let mut correlated_overlays = vec![overlay(false, false)];
+        detector.correlate_overlay_owners(&mut correlated_processes, &mut correlated_overlays);
+
+        assert!(!uncorrelated_overlays[0].owner_flagged);
+        assert!(correlated_overlays[0].owner_flagged);
+        assert!(correlated_overlays[0].suspicion_score() > uncorrelated_overlays[0].suspicion_score());
+        assert_eq!(
+            correlated_processes[0].reason_codes,
+            vec![ReasonCode::SuspiciousName, ReasonCode::OwnsHiddenOverlay]
+        );
+
+        let uncorrelated = detector.calculate_overall_risk(&uncorrelated_processes, &uncorrelated_overlays, 0.0, None, None, 0.0);
+        let correlated = detector.calculate_overall_risk(&correlated_processes, &correlated_overlays, 0.0, None, None, 0.0);
+        assert!(correlated > uncorrelated);
+    }
+
+    #[test]
+    fn test_local_browser_proxy_is_flagged() {
+        use network_detector::{TcpConnection, TcpState};
+
+        let detector = detector_with(Config::default(), vec![
+            (process(100, "answer-server.exe", "C:\\Users\\candidate\\answer-server.exe"), capabilities(false, false, false)),
+            (process(500, "chrome.exe", "C:\\Program Files\\Google\\chrome.exe"), capabilities(false, false, false)),
+        ]);
+        let connections = vec![
+            TcpConnection {
+                pid: 100,
+                local: "127.0.0.1:5000".parse().unwrap(),
+                remote: None,
+                state: TcpState::Listen,
+            },
+            TcpConnection {
+                pid: 500,
+                local: "127.0.0.1:54000".parse().unwrap(),
+                remote: Some("127.0.0.1:5000".parse().unwrap()),
+                state: TcpState::Established,
+            },
+        ];
+
+        let mut suspicious = Vec::new();
+        detector.flag_local_browser_proxies(&mut suspicious, &connections);
+
+        assert_eq!(suspicious.len(), 1);
+        assert_eq!(suspicious[0].pid, 100);
+        assert_eq!(suspicious[0].reason_codes, vec![ReasonCode::LocalBrowserProxy]);
+    }
+
+    #[test]
+    fn test_whitelist_can_override_suspicious_name() {
+        let scan = |overrides| {
+            let mut config = Config::default();
+            config.whitelist.processes.push("interview-helper.exe".to_string());
+            config.whitelist.whitelist_overrides_suspicious_name = overrides;
+            detector_with(config, vec![
+                (process(10, "interview-helper.exe", "C:\\Tools\\interview-helper.exe"), capabilities(true, false, false)),
+            ])
+            .scan_for_suspicious_processes()
+        };
+
+        assert_eq!(scan(false).len(), 1);
+        assert!(scan(true).is_empty());
+    }
+
+    #[test]
+    fn test_whitelisted_suspicious_name_is_noted_as_conflict() {
+        let scan = |whitelisted, resolution| {
+            let mut config = Config::default();
+            if whitelisted {
+                config.whitelist.processes.push("interview-helper.exe".to_string());
+            }
+            config.whitelist.whitelist_overrides_suspicious_name = true;
+            config.whitelist.conflict_resolution = resolution;
+            detector_with(config, vec![
+                (process(10, "interview-helper.exe", "C:\\Tools\\interview-helper.exe"), capabilities(true, true, true)),
+            ])
+            .scan_processes()
+        };
+
+        let annotated = scan(true, config::WhitelistConflictMode::Annotate);
+        assert!(annotated.suspicious.is_empty());
+        assert_eq!(annotated.whitelist_conflicts.len(), 1);
+        let conflict = &annotated.whitelist_conflicts[0];
+        assert_eq!((conflict.pid, conflict.resolution), (10, ConflictResolution::Cleared));
+        assert!(conflict.signals.contains(&ReasonCode::SuspiciousName));
+
+        let unlisted = scan(false, config::WhitelistConflictMode::Annotate);
+        assert!(unlisted.whitelist_conflicts.is_empty());
+        let downgraded = scan(true, config::WhitelistConflictMode::Downgrade);
+        assert_eq!(downgraded.whitelist_conflicts[0].resolution, ConflictResolution::Downgraded);
+        assert_eq!(downgraded.suspicious.len(), 1);
+        assert!(downgraded.suspicious[0].is_whitelisted);
+        assert!(downgraded.suspicious[0].risk_score < unlisted.suspicious[0].risk_score);
+    }
+
+    #[test]
+    fn test_capture_session_outweighs_linking_dxgi() {
+        let links_dxgi = process_monitor::ProcessCapabilities {
+            audio_capture: true,
+            screen_capture_hint: true,
+            ..Default::default()
+        };
+        let detector = detector_with(Config::default(), vec![
+            (process(10, "interview-ai.exe", "C:\\Users\\candidate\\interview-ai.exe"), capabilities(true, true, false)),
+            (process(11, "interview-notes.exe", "C:\\Users\\candidate\\interview-notes.exe"), links_dxgi),
+        ]);
+
+        let flagged = detector.scan_for_suspicious_processes();
+        let find = |pid| flagged.iter().find(|p| p.pid == pid).unwrap();
+        assert!((find(10).risk_score - 1.0).abs() < 1e-9);
+        assert!((find(11).risk_score - 0.75).abs() < 1e-9);
+        assert!(find(11).reason_codes.contains(&ReasonCode::ScreenCaptureHint));
+    }
+
+    #[test]
+    fn test_baseline_processes_can_be_exempt_from_capability_flags() {
+        let scan = |flag_baseline_processes| {
+            let mut config = Config::default();
+            config.heuristics.flag_baseline_processes = flag_baseline_processes;
+            let mut detector = detector_with(config, vec![
+                (process(10, "interview-ai.exe", "C:\\Users\\candidate\\interview-ai.exe"), capabilities(true, true, false)),
+            ]);
+            detector.collect_baseline();
+            detector.scan_for_suspicious_processes()
+        };
+
+        assert_eq!(scan(true).len(), 1);
+        assert!(scan(false).is_empty());
+    }
+
+    #[test]
+    fn test_windows_on_inactive_desktop_are_classified() {
+        use desktop_detector::{DesktopAssignment, DesktopWindow};
+
+        let detector = detector_with(Config::default(), vec![
+            (process(10, "cluely.exe", "C:\\Users\\candidate\\cluely.exe"), capabilities(true, false, false)),
+            (process(11, "code.exe", "C:\\Program Files\\VS Code\\code.exe"), capabilities(false, false, false)),
+            (process(12, "notepad.exe", "C:\\Users\\candidate\\notepad.exe"), capabilities(false, false, false)),
+        ]);
+        let window = |handle, owner_pid, assignment| DesktopWindow {
+            handle,
+            owner_pid,
+            title: format!("window {}", handle),
+            assignment,
+        };
+        let windows = vec![
+            window(1, 10, DesktopAssignment::Other),
+            window(2, 10, DesktopAssignment::Current),
+            window(3, 11, DesktopAssignment::Other),
+            window(4, 12, DesktopAssignment::Other),
+            window(5, 12, DesktopAssignment::AllDesktops),
+        ];
+
+        let mut suspicious = detector.scan_for_suspicious_processes();
+        let before = suspicious[0].risk_score;
+        let (parked, inactive) = detector.flag_parked_windows(&mut suspicious, &windows);
+
+        assert_eq!(inactive, 2);
+        assert_eq!(parked.len(), 1);
+        assert_eq!(parked[0].handle, 1);
+        assert_eq!(parked[0].owner_name, "cluely.exe");
+        assert!(suspicious[0].reason_codes.contains(&ReasonCode::ParkedOnInactiveDesktop));
+        assert!(suspicious[0].risk_score > before);
+    }
+
+    #[test]
+    fn test_frequent_focus_changes_are_scored() {
+        let mut helper = process(20, "helper.exe", "C:\\Users\\candidate\\helper.exe");
+        helper.stats.has_visible_window = Some(false);
+        let detector = detector_with(Config::default(), vec![
+            (process(11, "code.exe", "C:\\Program Files\\VS Code\\code.exe"), capabilities(false, false, false)),
+            (helper, capabilities(false, false, false)),
+            (process(30, "notes.exe", "C:\\Users\\candidate\\notes.exe"), capabilities(false, false, false)),
+        ]);
+
+        // The helper grabs focus four times and hands it back to the editor;
+        // the candidate switches to their notes twice.
+        let mut tracker = focus_detector::FocusTracker::default();
+        for owner in [11, 20, 11, 20, 11, 30, 11, 20, 0, 11, 20, 11, 30, 11] {
+            tracker.record((owner != 0).then_some(owner));
+        }
+        let gains = tracker.take_gains();
+        assert_eq!(gains.get(&20), Some(&4));
+        assert_eq!(gains.get(&11), Some(&6));
+
+        let mut suspicious = Vec::new();
+        let steals = detector.score_focus_gains(&mut suspicious, &[], &gains, Duration::from_secs(30));
+        assert_eq!(steals.len(), 1);
+        assert_eq!((steals[0].owner_pid, steals[0].focus_changes), (20, 4));
+        assert_eq!(steals[0].owner_kind, focus_detector::FocusOwnerKind::Windowless);
+        assert!((steals[0].changes_per_minute - 8.0).abs() < 1e-9);
+        assert_eq!(suspicious.len(), 1);
+        assert_eq!(suspicious[0].reason_codes, vec![ReasonCode::FocusStealing]);
+
+        // The same four gains over two minutes are under the default rate.
+        let mut suspicious = Vec::new();
+        assert!(detector.score_focus_gains(&mut suspicious, &[], &gains, Duration::from_secs(120)).is_empty());
+    }
+
+    #[test]
+    fn test_capture_excluded_owner_gets_dedicated_reason() {
+        let detector = detector_with(Config::default(), vec![
+            (process(30, "helper.exe", "C:\\Users\\candidate\\helper.exe"), capabilities(false, false, false)),
+            (process(31, "chrome.exe", "C:\\Program Files\\Google\\chrome.exe"), capabilities(false, false, false)),
+        ]);
+        let mut excluded = overlay(false, false);
+        excluded.excluded_from_capture = true;
+        let mut whitelisted = excluded.clone();
+        whitelisted.owner_pid = 31;
+        excluded.owner_pid = 30;
+
+        let mut suspicious = Vec::new();
+        detector.flag_capture_excluded_owners(&mut suspicious, &[excluded.clone(), whitelisted, overlay(true, true)]);
+
+        assert_eq!(suspicious.len(), 1);
+        assert_eq!(suspicious[0].pid, 30);
+        assert_eq!(suspicious[0].reason_codes, vec![ReasonCode::CaptureExcludedWindow]);
+        assert!(suspicious[0].risk_score >= 0.8);
+
+        detector.flag_capture_excluded_owners(&mut suspicious, &[excluded]);
+        assert_eq!(suspicious[0].reason_codes.len(), 1);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+/// At most `capacity` entries by key; inserting past that drops the least
+/// recently used one, so a cache kept across scans stays bounded however
+/// many distinct executables or bundles a long session sees.
+pub struct LruCache<V> {
+    capacity: usize,
+    entries: HashMap<String, (V, u64)>,
+    uses: u64,
+}
+
+impl<V> LruCache<V> {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), entries: HashMap::new(), uses: 0 }
+    }
+
+    /// The entry for `key`, marked as just used.
+    pub fn get(&mut self, key: &str) -> Option<&V> {
+        self.uses += 1;
+        let (value, last_used) = self.entries.get_mut(key)?;
+        *last_used = self.uses;
+        Some(value)
+    }
+
+    pub fn insert(&mut self, key: &str, value: V) {
+        if !self.entries.contains_key(key) && self.entries.len() >= self.capacity {
+            let oldest = self.entries.iter().min_by_key(|(_, (_, last_used))| *last_used).map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key.to_string(), (value, self.uses));
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_least_recently_used_entry_is_dropped() {
+        let mut cache = LruCache::new(2);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(cache.get("a"), Some(&1));
+        cache.insert("c", 3);
+
+        assert!(cache.contains("a") && cache.contains("c"));
+        assert!(!cache.contains("b"));
+        cache.insert("c", 4);
+        assert_eq!(cache.get("c"), Some(&4));
+        assert!(cache.contains("a"));
+    }
+}
\ No newline at end of file
@@ -1,461 +1,43 @@
-use serde::Serialize;
-use std::time::{Duration, SystemTime};
 use chrono::{DateTime, Utc};
-
-mod audio_detector;
-mod config;
-mod hardware_detector;
-mod overlay_detector;
-mod process_monitor;
-mod vm_detector;
-
-use audio_detector::AudioCaptureDetector;
-use config::Config;
-use hardware_detector::HardwareDetector;
-use overlay_detector::OverlayDetector;
-use process_monitor::ProcessMonitor;
-use vm_detector::VmDetector;
-
-#[derive(Debug, Clone)]
-pub struct Process {
-    pub pid: u32,
-    pub name: String,
-    pub path: String,
-}
-
-#[derive(Debug, Clone, Serialize)]
-pub struct SuspiciousProcess {
-    pub pid: u32,
-    pub name: String,
-    pub path: String,
-    pub risk_score: f64,
-    pub reasons: Vec<String>,
-    pub started_during_interview: bool,
-    pub is_whitelisted: bool,
-}
-
-#[derive(Debug, Serialize)]
-pub struct DetectionReport {
-    #[serde(with = "timestamp_format")]
-    pub timestamp: SystemTime,
-    pub scan_number: usize,
-    pub suspicious_processes: Vec<SuspiciousProcess>,
-    pub hidden_overlays: Vec<OverlayWindow>,
-    pub audio_monitoring_detected: bool,
-    pub hardware_suspicion: Option<HardwareSuspicionReport>,
-    pub vm_detection: Option<vm_detector::VmCheckResult>,
-    pub overall_risk_score: f64,
-    pub exceeds_threshold: bool,
-    pub module_failures: Vec<String>,
-}
-
-mod timestamp_format {
-    use serde::{Serialize, Serializer};
-    use std::time::SystemTime;
-    use chrono::{DateTime, Utc};
-
-    pub fn serialize<S>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let datetime: DateTime<Utc> = (*time).into();
-        datetime.to_rfc3339().serialize(serializer)
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::future::Future;
+use std::io::{BufRead, IsTerminal};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use fairview::cli::{CliArgs, Command};
+use fairview::clock::{Clock, FixedClock, SystemClock};
+use fairview::config::{Config, Mode, ScanTrigger};
+use fairview::fingerprint::FingerprintInputs;
+use fairview::baseline::{self, BaselineSnapshot};
+use fairview::notify::NotificationRouter;
+use fairview::observations::{self, ObservationLog, ObservationPhase, RecordingPlatform};
+use fairview::platform::SystemPlatform;
+use fairview::jsonl::JsonlStdout;
+use fairview::process_monitor::ProcessMonitor;
+use fairview::provenance::Provenance;
+use fairview::report_stream::{self, ConsoleView, Emission, ReportStream};
+use fairview::run_dir::RunDirectory;
+use fairview::trigger::{self, Trigger, TriggerDispatcher};
+use fairview::{alert, definitions, event_log, pacing, report_formats, review, session, telemetry, unit, watch, DetectionReport, FairviewDetector, PolicyBreach, Process, Severity};
+
+#[cfg(target_os = "windows")]
+mod service;
+
+const DETERMINISTIC_SEED: u64 = 0;
+
+/// One line per change since the previous scan, for `output.console = "delta"`.
+fn print_delta(previous: &DetectionReport, report: &DetectionReport) {
+    let lines = report_stream::render_delta(previous, report);
+    if lines.is_empty() {
+        println!("[*] Scan #{}: no changes (risk {:.2})", report.scan_number, report.overall_risk_score);
+        return;
     }
-}
-
-#[derive(Debug, Serialize)]
-pub struct HardwareSuspicionReport {
-    pub risk_score: f64,
-    pub display_count: usize,
-    pub has_virtual_display: bool,
-    pub has_hdmi_splitter: bool,
-    pub remote_desktop_active: bool,
-    pub flags: Vec<String>,
-    pub baseline_display_count: Option<usize>,
-    pub display_changed: bool,
-}
-
-#[derive(Debug, Clone, Serialize)]
-pub struct OverlayWindow {
-    pub handle: usize,
-    pub position: (i32, i32),
-    pub size: (u32, u32),
-    pub owner_pid: u32,
-    pub is_transparent: bool,
-    pub is_topmost: bool,
-}
-
-pub struct FairviewDetector {
-    process_monitor: ProcessMonitor,
-    audio_detector: AudioCaptureDetector,
-    overlay_detector: OverlayDetector,
-    hardware_detector: HardwareDetector,
-    vm_detector: VmDetector,
-    config: Config,
-    scan_count: usize,
-    baseline_collected: bool,
-}
-
-impl FairviewDetector {
-    pub fn new(config: Config) -> Self {
-        Self {
-            process_monitor: ProcessMonitor::new(config.clone()),
-            audio_detector: AudioCaptureDetector::new(),
-            overlay_detector: OverlayDetector::new(),
-            hardware_detector: HardwareDetector::new(),
-            vm_detector: VmDetector::new(),
-            config,
-            scan_count: 0,
-            baseline_collected: false,
-        }
-    }
-
-    pub fn collect_baseline(&mut self) {
-        if !self.config.monitoring.collect_baseline {
-            println!("[*] Baseline collection disabled in config");
-            return;
-        }
-
-        println!("\n{}", "=".repeat(60));
-        println!("COLLECTING BASELINE");
-        println!("{}", "=".repeat(60));
-        println!("[*] Please ensure all necessary applications are running");
-        println!("[*] Baseline collection will take {} seconds...\n", 
-                 self.config.monitoring.baseline_duration_seconds);
-
-        self.process_monitor.collect_baseline();
-
-        if let Err(e) = self.hardware_detector.set_baseline() {
-            println!("[!] Warning: Failed to collect hardware baseline: {}", e);
-        } else {
-            if let Some(baseline) = self.hardware_detector.get_baseline() {
-                println!("[+] Hardware baseline: {} displays detected", baseline.display_count);
-            }
-        }
-
-        println!("[+] Baseline collection complete\n");
-        self.baseline_collected = true;
-    }
-
-    pub fn scan(&mut self) -> DetectionReport {
-        self.scan_count += 1;
-        println!("\n[*] Starting scan #{} at {:?}", self.scan_count, SystemTime::now());
-
-        let mut module_failures = Vec::new();
-
-        let vm_result = if self.config.monitoring.enable_vm_detection {
-             match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                self.vm_detector.detect()
-            })) {
-                Ok(result) => {
-                    if result.is_vm {
-                         println!("[!] VM DETECTED! Confidence: {:.2}", result.confidence_score);
-                    }
-                    Some(result)
-                },
-                Err(_) => {
-                    let error = "VM detection module failed";
-                    module_failures.push(error.to_string());
-                    None
-                }
-            }
-        } else {
-            None
-        };
-
-        let suspicious_processes = if self.config.monitoring.enable_process_monitoring {
-            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                self.scan_for_suspicious_processes()
-            })) {
-                Ok(procs) => {
-                    println!("[+] Found {} suspicious processes", procs.len());
-                    procs
-                },
-                Err(_) => {
-                    let error = "Process monitoring module failed";
-                    module_failures.push(error.to_string());
-                    println!("[!] {}", error);
-                    Vec::new()
-                }
-            }
-        } else {
-            Vec::new()
-        };
-
-        let hidden_overlays = if self.config.monitoring.enable_overlay_monitoring {
-            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                self.overlay_detector.find_hidden_overlays()
-            })) {
-                Ok(overlays) => {
-                    println!("[+] Found {} suspicious overlays", overlays.len());
-                    overlays
-                },
-                Err(_) => {
-                    let error = "Overlay detection module failed";
-                    module_failures.push(error.to_string());
-                    Vec::new()
-                }
-            }
-        } else {
-            Vec::new()
-        };
-
-        let audio_monitoring = if self.config.monitoring.enable_audio_monitoring {
-            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                self.audio_detector.detect_realtime_audio_processing()
-            })) {
-                Ok(detected) => {
-                    println!("[+] Audio monitoring detected: {}", detected);
-                    detected
-                },
-                Err(_) => {
-                    let error = "Audio detection module failed";
-                    module_failures.push(error.to_string());
-                    false
-                }
-            }
-        } else {
-            false
-        };
-
-        let hardware_suspicion = if self.config.monitoring.enable_hardware_monitoring {
-            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                self.hardware_detector.detect_hardware_cheating()
-            })) {
-                Ok(suspicion) => {
-                    println!("[+] Hardware risk score: {:.2}", suspicion.risk_score);
-                    Some(suspicion)
-                },
-                Err(_) => {
-                    let error = "Hardware detection module failed";
-                    module_failures.push(error.to_string());
-                    None
-                }
-            }
-        } else {
-            None
-        };
-
-        let overall_risk = self.calculate_overall_risk(
-            &suspicious_processes,
-            &hidden_overlays,
-            audio_monitoring,
-            hardware_suspicion.as_ref(),
-            vm_result.as_ref(),
-        );
-
-        let exceeds_threshold = overall_risk >= self.config.scan.risk_threshold;
-
-        println!("[!] Overall risk score: {:.2}/1.0 {}", 
-                 overall_risk,
-                 if exceeds_threshold { "(EXCEEDS THRESHOLD)" } else { "" });
-
-        let hardware_report = hardware_suspicion.map(|hs| {
-            let (display_count, has_virtual_display, has_hdmi_splitter, remote_desktop_active) =
-                Self::summarize_hardware(&hs);
-            
-            let baseline_count = self.hardware_detector.get_baseline()
-                .map(|b| b.display_count);
-            
-            let display_changed = if let Some(baseline) = baseline_count {
-                baseline != display_count
-            } else {
-                false
-            };
-
-            HardwareSuspicionReport {
-                risk_score: hs.risk_score,
-                display_count,
-                has_virtual_display,
-                has_hdmi_splitter,
-                remote_desktop_active,
-                flags: hs.flags.clone(),
-                baseline_display_count: baseline_count,
-                display_changed,
-            }
-        });
-
-        DetectionReport {
-            timestamp: SystemTime::now(),
-            scan_number: self.scan_count,
-            suspicious_processes,
-            hidden_overlays,
-            audio_monitoring_detected: audio_monitoring,
-            hardware_suspicion: hardware_report,
-            vm_detection: vm_result,
-            overall_risk_score: overall_risk,
-            exceeds_threshold,
-            module_failures,
-        }
-    }
-
-    fn summarize_hardware(
-        hardware_suspicion: &hardware_detector::HardwareSuspicion,
-    ) -> (usize, bool, bool, bool) {
-        let display_count = hardware_suspicion
-            .details
-            .get("display_count")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(1);
-
-        let has_virtual_display = hardware_suspicion
-            .flags
-            .iter()
-            .any(|f| f.to_lowercase().contains("virtual display"));
-
-        let has_hdmi_splitter = hardware_suspicion
-            .flags
-            .iter()
-            .any(|f| f.to_lowercase().contains("hdmi splitter"));
-
-        let remote_desktop_active = hardware_suspicion
-            .flags
-            .iter()
-            .any(|f| f.to_lowercase().contains("remote desktop"));
-
-        (display_count, has_virtual_display, has_hdmi_splitter, remote_desktop_active)
-    }
-
-    fn scan_for_suspicious_processes(&self) -> Vec<SuspiciousProcess> {
-        let mut suspicious = Vec::new();
-        let processes = self.process_monitor.get_all_processes();
-
-        for process in processes {
-            let mut reasons = Vec::new();
-            let mut risk_score: f64 = 0.0;
-
-            let is_whitelisted = self.process_monitor.is_whitelisted(&process);
-            let was_in_baseline = self.process_monitor.was_in_baseline(process.pid);
-            let started_during = self.baseline_collected && !was_in_baseline;
-
-            let has_screen = self.process_monitor.has_screen_capture_permission(&process);
-            let has_audio = self.process_monitor.has_audio_capture_permission(&process);
-            let has_access = self.process_monitor.has_accessibility_permission(&process);
-            let has_suspicious_name = self.is_suspicious_name(&process.name);
-            let is_common_legit = self.is_common_legit_app(&process.name);
-
-            if has_screen {
-                reasons.push("Has screen capture permission".to_string());
-                risk_score += 0.3;
-            }
-
-            if has_audio {
-                reasons.push("Has audio capture permission".to_string());
-                risk_score += 0.3;
-            }
-
-            if has_access {
-                reasons.push("Has accessibility API access".to_string());
-                risk_score += 0.2;
-            }
-
-            if has_suspicious_name {
-                reasons.push("Suspicious process name".to_string());
-                risk_score += 0.4;
-            }
-
-            if started_during && !is_whitelisted {
-                reasons.push("Started during interview".to_string());
-                risk_score += 0.3;
-            }
-
-            let capability_count = [has_screen, has_audio, has_access]
-                .iter()
-                .filter(|&&b| b)
-                .count();
-
-            if (is_whitelisted || is_common_legit) && !has_suspicious_name {
-                continue;
-            }
-
-            let path_lower = process.path.to_lowercase();
-            let is_windows_core = path_lower.starts_with("c:\\windows\\system32")
-                || path_lower.starts_with("c:\\windows\\syswow64");
-
-            let should_flag = (has_suspicious_name && capability_count >= 1 && !is_common_legit)
-                || (!has_suspicious_name && capability_count >= 3 && !is_common_legit && !is_windows_core)
-                || (started_during && capability_count >= 2);
-
-            if should_flag && !reasons.is_empty() && risk_score >= self.config.thresholds.process_threshold {
-                suspicious.push(SuspiciousProcess {
-                    pid: process.pid,
-                    name: process.name.clone(),
-                    path: process.path.clone(),
-                    risk_score: risk_score.min(1.0),
-                    reasons,
-                    started_during_interview: started_during,
-                    is_whitelisted,
-                });
-            }
-        }
-
-        suspicious
-    }
-
-    fn is_suspicious_name(&self, name: &str) -> bool {
-        let suspicious_patterns = [
-            "cluely", "interview", "gpt", "chatgpt", "llm", "copilot",
-            "aiassistant", "ai-assistant", "interview-bot", "interview-ai",
-        ];
-
-        let name_lower = name.to_lowercase();
-        suspicious_patterns.iter().any(|&pattern| name_lower.contains(pattern))
-    }
-
-    fn is_common_legit_app(&self, name: &str) -> bool {
-        let name_lower = name.to_lowercase();
-        let whitelist = [
-            "explorer.exe", "chrome.exe", "firefox.exe", "msedge.exe", 
-            "msedgewebview2.exe", "brave.exe", "opera.exe",
-            "discord.exe", "slack.exe", "teams.exe", "zoom.exe",
-            "code.exe", "vscode.exe", "visual studio",
-            "sharex.exe", "obs", "obs64.exe", "streamlabs",
-            "steam.exe", "steamwebhelper.exe",
-            "svchost.exe", "searchhost.exe", "applicationframehost.exe",
-            "shellexperiencehost.exe", "systemsettings.exe",
-            "camera hub.exe", "elgato",
-        ];
-        whitelist.iter().any(|w| name_lower == *w || name_lower.contains(*w))
-    }
-
-    fn calculate_overall_risk(
-        &self,
-        suspicious_processes: &[SuspiciousProcess],
-        hidden_overlays: &[OverlayWindow],
-        audio_monitoring: bool,
-        hardware_suspicion: Option<&hardware_detector::HardwareSuspicion>,
-        vm_result: Option<&vm_detector::VmCheckResult>,
-    ) -> f64 {
-        let mut risk = 0.0;
-
-        if !suspicious_processes.is_empty() {
-            let max_process_risk = suspicious_processes
-                .iter()
-                .map(|p| p.risk_score)
-                .max_by(|a, b| a.partial_cmp(b).unwrap())
-                .unwrap_or(0.0);
-            risk += max_process_risk * self.config.weights.process_risk;
-        }
-
-        if !hidden_overlays.is_empty() {
-            risk += self.config.weights.overlay_risk;
-        }
-
-        if audio_monitoring {
-            risk += self.config.weights.audio_risk;
-        }
-
-        if let Some(hardware) = hardware_suspicion {
-            risk += hardware.risk_score * self.config.weights.hardware_risk;
-        }
-
-        if let Some(vm) = vm_result {
-            if vm.is_vm {
-                risk += vm.confidence_score * self.config.weights.vm_risk;
-            }
-        }
-
-        risk.min(1.0)
+    println!("[*] Scan #{}:", report.scan_number);
+    for line in lines {
+        println!("    {}", line);
     }
 }
 
@@ -466,11 +48,33 @@ fn print_report(report: &DetectionReport, config: &Config) {
     println!("FAIRVIEW DETECTION REPORT - Scan #{}", report.scan_number);
     println!("{}", "=".repeat(60));
     println!("Timestamp: {}", datetime.format("%Y-%m-%d %H:%M:%S UTC"));
+    if let Some(ref session_id) = report.session_id {
+        println!("Session: {}", session_id);
+    }
+    if let Some(ref candidate_ref) = report.candidate_ref {
+        println!("Candidate: {}", candidate_ref);
+    }
+    if let Some(ref profile) = report.whitelist_profile {
+        println!("Whitelist profile: {}", profile);
+    }
+    if let Some(ref reason) = report.clock_tampering_reason {
+        println!("⚠️  CLOCK TAMPERING SUSPECTED: {}", reason);
+    }
+    if report.forced_fresh {
+        println!("Forced fresh scan: all caches were cleared");
+    }
+    if !report.carried_forward_modules.is_empty() {
+        println!("Carried forward from an earlier scan: {}", report.carried_forward_modules.join(", "));
+    }
+    if let Some(load) = report.system_load {
+        println!("System load: CPU {:.0}%, memory {:.0}%{}", load.cpu_percent, load.memory_percent,
+                 if load.saturated { " (saturated)" } else { "" });
+    }
     
     if let Some(ref vm) = report.vm_detection {
         if vm.is_vm {
             println!("\n🔴 🔴 CRITICAL: VIRTUAL MACHINE DETECTED 🔴 🔴");
-            println!("Confidence Score: {:.2}", vm.confidence_score);
+            println!("Confidence Score: {:.2} (this scan: {:.2})", vm.confidence_score, vm.raw_confidence_score);
             for reason in &vm.reasons {
                 println!("  - {}", reason);
             }
@@ -479,9 +83,46 @@ fn print_report(report: &DetectionReport, config: &Config) {
     }
 
     println!("Overall Risk Score: {:.2}/1.0", report.overall_risk_score);
+    if report.raw_risk_score > 1.0 {
+        println!("  Capped from {:.2}: findings add up past the 1.0 ceiling", report.raw_risk_score);
+    }
+    let uncertainty = &report.uncertainty;
+    if uncertainty.score > 0.0 {
+        println!(
+            "  Uncertainty {:.2}: {} of {} modules failed and {} ran degraded; the true score may be higher",
+            uncertainty.score, uncertainty.failed_modules, uncertainty.modules_run, uncertainty.degraded_modules
+        );
+    }
     
-    if report.exceeds_threshold {
+    if report.baseline_missing {
+        println!("⚠️  NO BASELINE: new processes and display or device changes are not being detected");
+    }
+
+    if report.observational {
+        println!("👁  STATUS: Observation only (no verdict)");
+        for breach in &report.category_breaches {
+            println!("  - {:?} score {:.2} would exceed threshold {:.2}", breach.category, breach.score, breach.threshold);
+        }
+    } else if report.exceeds_threshold {
         println!("⚠️  STATUS: RISK THRESHOLD EXCEEDED");
+        if report.policy_breach == Some(PolicyBreach::VmHardFail) {
+            println!("  - Forced by policy: virtual machine detected (vm.hard_fail)");
+        }
+        if report.policy_breach == Some(PolicyBreach::VirtualDisplayHardFail) {
+            println!("  - Forced by policy: virtual display detected (hardware.virtual_display_hard_fail)");
+        }
+        if report.policy_breach == Some(PolicyBreach::WirelessDisplayHardFail) {
+            println!("  - Forced by policy: wireless display detected (hardware.wireless_display_hard_fail)");
+        }
+        if report.policy_breach == Some(PolicyBreach::Denylisted) {
+            println!("  - Forced by policy: denylisted process running");
+        }
+        if report.policy_breach == Some(PolicyBreach::MachineChanged) {
+            println!("  - Forced by policy: session resumed on a different machine");
+        }
+        for breach in &report.category_breaches {
+            println!("  - {:?} score {:.2} >= threshold {:.2}", breach.category, breach.score, breach.threshold);
+        }
     } else {
         println!("✓ STATUS: Within acceptable risk levels");
     }
@@ -495,6 +136,29 @@ fn print_report(report: &DetectionReport, config: &Config) {
         println!();
     }
 
+    if report.inaccessible_processes > 0 {
+        println!("⚠️  {} processes could not be inspected (access denied); coverage is incomplete\n",
+                 report.inaccessible_processes);
+    }
+
+    if let Some(uninspected) = report.uninspected_processes {
+        println!("⚠️  {} processes were not inspected (max_processes_inspected); coverage is incomplete\n",
+                 uninspected);
+    }
+
+    if let Some(exited) = report.exited_during_inspection {
+        println!("{} processes exited while being inspected (exited_process_policy)\n", exited);
+    }
+
+    if !report.whitelist_conflicts.is_empty() {
+        println!("WHITELIST CONFLICTS:");
+        for conflict in &report.whitelist_conflicts {
+            let signals: Vec<String> = conflict.signals.iter().map(|code| format!("{:?}", code)).collect();
+            println!("  - {} (PID: {}) {:?}: {}", conflict.name, conflict.pid, conflict.resolution, signals.join(", "));
+        }
+        println!();
+    }
+
     if !report.suspicious_processes.is_empty() {
         println!("SUSPICIOUS PROCESSES:");
         for proc in &report.suspicious_processes {
@@ -509,19 +173,121 @@ fn print_report(report: &DetectionReport, config: &Config) {
             }
             println!();
         }
+        if report.omitted_processes > 0 {
+            println!("  ... and {} lower-risk processes not listed\n", report.omitted_processes);
+        }
     }
 
     if !report.hidden_overlays.is_empty() {
         println!("HIDDEN OVERLAYS DETECTED:");
         for overlay in &report.hidden_overlays {
             println!("  - Window Handle: {}", overlay.handle);
+            if !overlay.title.is_empty() {
+                println!("    Title: {}", overlay.title);
+            }
+            if !overlay.class_name.is_empty() {
+                println!("    Class: {}", overlay.class_name);
+            }
             println!("    Size: {:?}", overlay.size);
+            if overlay.excluded_from_capture {
+                println!("    ⚠️  Excluded from screen capture");
+            }
+            if overlay.owner_flagged {
+                println!("    ⚠️  Owned by a flagged process (PID: {})", overlay.owner_pid);
+            }
+            if overlay.is_cloaked {
+                println!("    Cloaked by the compositor");
+            }
             println!();
         }
+        if report.omitted_overlays > 0 {
+            println!("  ... and {} more overlays not listed\n", report.omitted_overlays);
+        }
+    }
+
+    if !report.parked_windows.is_empty() {
+        println!("⚠️  SUSPICIOUS WINDOWS ON INACTIVE VIRTUAL DESKTOPS:");
+        for window in &report.parked_windows {
+            println!("  - {} (PID: {}): {}", window.owner_name, window.owner_pid, window.title);
+        }
+        println!();
+    }
+
+    if !report.focus_steals.is_empty() {
+        println!("⚠️  REPEATED FOCUS STEALING:");
+        for steal in &report.focus_steals {
+            println!("  - {} (PID: {}): took focus {} times ({:.1}/min, {:?})",
+                     steal.owner_name, steal.owner_pid, steal.focus_changes, steal.changes_per_minute, steal.owner_kind);
+        }
+        println!();
+    }
+
+    if let Some(ref app) = report.foreground_app {
+        let name = app.name.as_deref().unwrap_or("unknown");
+        let title = app.title.as_ref().map(|title| format!(" \"{}\"", title)).unwrap_or_default();
+        let marks: Vec<&str> = [(app.flagged, "flagged"), (app.overlay_owner, "owns a hidden overlay")]
+            .iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, mark)| *mark)
+            .collect();
+        if marks.is_empty() {
+            println!("Foreground app: {} (PID: {}){}\n", name, app.pid, title);
+        } else {
+            println!("⚠️  FOREGROUND APP: {} (PID: {}){} ({})\n", name, app.pid, title, marks.join(", "));
+        }
+    }
+
+    if !report.correlated_findings.is_empty() {
+        println!("⚠️  CORRELATED ACROSS SCANS:");
+        for finding in &report.correlated_findings {
+            let scans: Vec<String> = finding.scans.iter().map(|scan| format!("#{}", scan)).collect();
+            println!("  - PID {}: {} (scans {})", finding.pid, finding.rule.description(), scans.join(", "));
+        }
+        println!();
+    }
+
+    if report.suppressed_findings > 0 {
+        println!("({} low-confidence findings below min_report_confidence not listed)\n", report.suppressed_findings);
+    }
+
+    if !report.new_input_devices.is_empty() {
+        println!("⚠️  NEW INPUT DEVICES CONNECTED:");
+        for device in &report.new_input_devices {
+            println!("  - {} ({})", device.name, device.id);
+        }
+        println!();
+    }
+
+    if report.cloud_clipboard_sync {
+        println!("⚠️  CLOUD CLIPBOARD SYNC ON: another signed-in device can relay clipboard content\n");
     }
 
     if report.audio_monitoring_detected {
-        println!("⚠️  AUDIO MONITORING DETECTED\n");
+        match report.audio_capture_streams {
+            Some(ref streams) if streams.count > 1 => println!(
+                "⚠️  AUDIO MONITORING DETECTED: {} capture streams ({})\n",
+                streams.count,
+                streams.applications.join(", ")
+            ),
+            _ => println!("⚠️  AUDIO MONITORING DETECTED\n"),
+        }
+    }
+
+    if !report.new_audio_inputs.is_empty() {
+        println!("⚠️  NEW AUDIO INPUTS CONNECTED:");
+        for input in &report.new_audio_inputs {
+            let kind = if input.virtual_device { " (virtual)" } else { "" };
+            println!("  - {}{}", input.name, kind);
+        }
+        println!();
+    }
+
+    if !report.audio_loops.is_empty() {
+        println!("⚠️  AUDIO OUTPUT RECORDED BACK INTO AN INPUT:");
+        for audio_loop in &report.audio_loops {
+            println!("  - {} -> {}", audio_loop.render_name, audio_loop.capture_name);
+        }
+        println!();
     }
 
     if let Some(ref hardware) = report.hardware_suspicion {
@@ -539,6 +305,13 @@ fn print_report(report: &DetectionReport, config: &Config) {
             }
         }
 
+        if let Some(kind) = hardware.remote_desktop.kind {
+            println!("  ⚠️  Remote desktop active ({}):", kind.label());
+            for evidence in &hardware.remote_desktop.evidence {
+                println!("    - {}", evidence);
+            }
+        }
+
         if !hardware.flags.is_empty() {
             println!("  Flags:");
             for flag in &hardware.flags {
@@ -551,13 +324,281 @@ fn print_report(report: &DetectionReport, config: &Config) {
     println!("{}\n", "=".repeat(60));
 }
 
-#[tokio::main]
-async fn main() {
-    println!("Fairview v0.1.0 - Interview Monitoring System\n");
+fn main() {
+    let cli = match CliArgs::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            println!("[!] {}", e);
+            std::process::exit(2);
+        }
+    };
 
-    let config = match Config::from_file("fairview_config.toml") {
+    let result = match cli.command {
+        Command::GenerateUnit => generate_unit(&cli),
+        Command::CompareBaseline => compare_baselines(&cli),
+        Command::Rescore => rescore_observations(&cli),
+        Command::CheckConfig => check_config(&cli),
+        Command::Watch => watch_reports(&cli),
+        Command::Selftest => selftest(&cli),
+        Command::Bundle => bundle_report(&cli),
+        Command::Monitor => {
+            let runtime = tokio::runtime::Runtime::new().expect("failed to start tokio runtime");
+            runtime.block_on(run_monitor(&cli, true, async {
+                let _ = tokio::signal::ctrl_c().await;
+            }));
+            Ok(())
+        }
+        #[cfg(target_os = "windows")]
+        Command::InstallService => service::install(),
+        #[cfg(target_os = "windows")]
+        Command::UninstallService => service::uninstall(),
+        #[cfg(target_os = "windows")]
+        Command::Service => service::run(),
+        #[cfg(not(target_os = "windows"))]
+        Command::InstallService | Command::UninstallService | Command::Service => {
+            Err("Service installation is only supported on Windows".to_string())
+        }
+    };
+
+    if let Err(e) = result {
+        println!("[!] {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn generate_unit(cli: &CliArgs) -> Result<(), String> {
+    if cli.config_inline.is_some() || cli.reads_config_from_stdin() {
+        return Err("generate-unit needs a config file path, not stdin or inline config".to_string());
+    }
+
+    let options = unit::UnitOptions::for_config(&cli.config_path)?;
+    let contents = unit::for_current_platform(&options)?;
+
+    match cli.output {
+        Some(ref path) => {
+            std::fs::write(path, contents)
+                .map_err(|e| format!("Failed to write unit to {}: {}", path, e))?;
+            println!("[+] Wrote unit to {}", path);
+            println!("[*] Install with: {}", unit::install_hint(path));
+        }
+        None => print!("{}", contents),
+    }
+
+    Ok(())
+}
+
+/// Diffs the baselines of two sessions on the same machine. Changes to
+/// whitelisted processes are listed but only other changes fail the check.
+fn compare_baselines(cli: &CliArgs) -> Result<(), String> {
+    let before = BaselineSnapshot::load(&cli.baseline_files[0])?;
+    let after = BaselineSnapshot::load(&cli.baseline_files[1])?;
+    let monitor = ProcessMonitor::new(load_config(cli)?);
+
+    let diff = baseline::diff_baselines(&before, &after, |p| {
+        monitor.is_whitelisted(&Process {
+            pid: 0,
+            name: p.name.clone(),
+            path: p.path.clone(),
+            ..Default::default()
+        })
+    });
+
+    println!("[*] Comparing baseline {} ({}) with {} ({})",
+             cli.baseline_files[0], before.captured_at, cli.baseline_files[1], after.captured_at);
+    print!("{}", diff.render());
+
+    if diff.has_unexpected_changes() {
+        return Err("Environment differs from the previous baseline".to_string());
+    }
+    println!("[+] No unexpected changes between baselines");
+    Ok(())
+}
+
+/// Replays an observation log through the detector under the current
+/// config and prints, or writes as JSON lines, the resulting reports.
+fn rescore_observations(cli: &CliArgs) -> Result<(), String> {
+    let path = cli.observation_file.as_deref().unwrap_or_default();
+    let observations = observations::read_log(path)?;
+    let config = load_config(cli)?;
+    println!("[*] Rescoring {} observations from {}", observations.len(), path);
+    let reports = observations::rescore(config, &observations);
+
+    println!();
+    for report in &reports {
+        println!("[*] Scan #{}: risk {:.2}, severity {}{}",
+                 report.scan_number,
+                 report.overall_risk_score,
+                 alert::severity_label(report.severity),
+                 if report.exceeds_threshold { " (EXCEEDS THRESHOLD)" } else { "" });
+    }
+
+    if let Some(ref output) = cli.output {
+        let mut file = std::fs::File::create(output)
+            .map_err(|e| format!("Failed to create {}: {}", output, e))?;
+        for report in &reports {
+            fairview::jsonl::write_line(&mut file, report)?;
+        }
+        println!("[+] Wrote {} rescored reports to {}", reports.len(), output);
+    }
+    Ok(())
+}
+
+/// Packages one scan from a report file, with the observation log and
+/// config of the run directory it was written to, into an evidence bundle
+/// next to the report or at `--output`.
+fn bundle_report(cli: &CliArgs) -> Result<(), String> {
+    let path = std::path::Path::new(cli.report_file.as_deref().unwrap_or_default());
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let report = fairview::bundle::select_report(&contents, cli.scan)?;
+    let config = load_config(cli)?;
+
+    let run_dir = path.parent().unwrap_or(std::path::Path::new("."));
+    let log_path = run_dir.join("observations.jsonl");
+    let observations = if log_path.exists() {
+        Some(observations::read_log(&log_path.to_string_lossy())?)
+    } else {
+        None
+    };
+    let dir = match cli.output {
+        Some(ref output) => std::path::PathBuf::from(output),
+        None => run_dir.join(fairview::bundle::bundle_name(&report)),
+    };
+
+    let clock: Box<dyn Clock> = if cli.deterministic {
+        Box::new(FixedClock::deterministic())
+    } else {
+        Box::new(SystemClock::new())
+    };
+    let manifest = fairview::bundle::write_bundle(&dir, &report, observations.as_deref(), &config, clock.as_ref())?;
+    for missing in &manifest.missing {
+        println!("[!] Not bundled: {}", missing);
+    }
+    println!("[+] Bundled scan #{} ({} files) in {}", report.scan_number, manifest.files.len() + 1, dir.display());
+    Ok(())
+}
+
+/// Follows a JSON-lines report file and prints each report as it is
+/// appended: in full, or as the one-line alert summary with `--quiet`.
+/// Runs until interrupted.
+fn watch_reports(cli: &CliArgs) -> Result<(), String> {
+    let path = cli.watch_file.as_deref().unwrap_or_default();
+    let config = load_config(cli)?;
+    if !std::path::Path::new(path).exists() {
+        println!("[*] Waiting for {} to appear", path);
+    }
+    println!("[*] Watching {} (Ctrl-C to stop)", path);
+
+    let mut tail = watch::ReportTail::new(path);
+    loop {
+        for report in tail.poll() {
+            match report {
+                Ok(report) if cli.quiet => {
+                    println!("{}", alert::format_alert_line(&report, config.output.alert_line_max_chars))
+                }
+                Ok(report) => print_report(&report, &config),
+                Err(e) => println!("[!] {}", e),
+            }
+        }
+        std::thread::sleep(Duration::from_secs(1));
+    }
+}
+
+/// Loads the config as `monitor` would, except that a missing or broken
+/// file is an error rather than a fallback to defaults, and prints the
+/// effective values. Nothing is scanned.
+fn check_config(cli: &CliArgs) -> Result<(), String> {
+    let mut config = load_config_strictly(cli)?;
+
+    for applied in apply_cli_overrides(&mut config, cli) {
+        println!("[*] Override applied: {}", applied);
+    }
+
+    let weights = &config.weights;
+    let weight_sum = weights.process_risk + weights.overlay_risk + weights.audio_risk
+        + weights.hardware_risk + weights.vm_risk + weights.input_risk;
+    println!("[*] Category weights sum to {:.4}; normalized: process {:.3}, overlay {:.3}, audio {:.3}, hardware {:.3}, vm {:.3}, input {:.3}",
+             weight_sum,
+             weights.process_risk / weight_sum,
+             weights.overlay_risk / weight_sum,
+             weights.audio_risk / weight_sum,
+             weights.hardware_risk / weight_sum,
+             weights.vm_risk / weight_sum,
+             weights.input_risk / weight_sum);
+
+    let effective = toml::to_string_pretty(&config.redacted())
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    println!("\n# Effective configuration\n{}", effective);
+    println!("[+] Configuration is valid");
+    Ok(())
+}
+
+/// Applies `--session-id` and `--candidate-ref` over the config and
+/// describes each override.
+fn apply_cli_overrides(config: &mut Config, cli: &CliArgs) -> Vec<String> {
+    let mut applied = Vec::new();
+    if let Some(ref session_id) = cli.session_id {
+        config.session.session_id = Some(session_id.clone());
+        applied.push(format!("session.session_id = {} (--session-id)", session_id));
+    }
+    if let Some(ref candidate_ref) = cli.candidate_ref {
+        config.session.candidate_ref = Some(candidate_ref.clone());
+        applied.push(format!("session.candidate_ref = {} (--candidate-ref)", candidate_ref));
+    }
+    applied
+}
+
+/// Runs the readiness checks and fails unless every critical one passes.
+fn selftest(cli: &CliArgs) -> Result<(), String> {
+    let config = load_config_strictly(cli);
+    println!("[*] Running self-test (one scan of this machine)");
+    let result = fairview::selftest::run(config, Arc::new(SystemPlatform));
+
+    println!();
+    print!("{}", result.render());
+    if !result.passed() {
+        return Err("Fairview is not ready to monitor this machine".to_string());
+    }
+    Ok(())
+}
+
+/// Like `load_config`, but a missing or broken file is an error rather
+/// than a fallback to the defaults.
+fn load_config_strictly(cli: &CliArgs) -> Result<Config, String> {
+    if cli.config_inline.is_some() || cli.reads_config_from_stdin() {
+        return load_config(cli);
+    }
+    let config = Config::from_file(&cli.config_path)
+        .map_err(|e| format!("{} is invalid: {}", cli.config_path, e))?;
+    println!("[+] Loaded configuration from {}", cli.config_path);
+    Ok(config)
+}
+
+/// Loads config from `--config-inline`, stdin (`--config -`) or a file. Only a
+/// missing or broken file falls back to defaults; bad inline or stdin config is
+/// an error since there is nowhere to save a replacement.
+fn load_config(cli: &CliArgs) -> Result<Config, String> {
+    if let Some(ref inline) = cli.config_inline {
+        let config = Config::from_toml_str(inline)
+            .map_err(|e| format!("Invalid --config-inline: {}", e))?;
+        println!("[+] Loaded configuration from --config-inline");
+        return Ok(config);
+    }
+
+    if cli.reads_config_from_stdin() {
+        let config = Config::from_reader(&mut std::io::stdin().lock())
+            .map_err(|e| format!("Invalid config on stdin: {}", e))?;
+        println!("[+] Loaded configuration from stdin");
+        return Ok(config);
+    }
+
+    Ok(load_config_file(&cli.config_path))
+}
+
+fn load_config_file(path: &str) -> Config {
+    match Config::from_file(path) {
         Ok(cfg) => {
-            println!("[+] Loaded configuration from fairview_config.toml");
+            println!("[+] Loaded configuration from {}", path);
             cfg
         }
         Err(e) => {
@@ -565,47 +606,452 @@ async fn main() {
             println!("[*] Using default configuration");
             let default_cfg = Config::default();
             
-            if let Err(e) = default_cfg.save_to_file("fairview_config.toml") {
+            if let Err(e) = default_cfg.save_to_file(path) {
                 println!("[!] Failed to save default config: {}", e);
             } else {
-                println!("[+] Saved default configuration to fairview_config.toml");
+                println!("[+] Saved default configuration to {}", path);
             }
             
             default_cfg
         }
+    }
+}
+
+/// Waits for the operator to press Enter after the baseline. Skipped when
+/// `auto_start` is set or stdin is not a terminal (service, CI, piped input),
+/// and a closed or failing stdin never stops monitoring from starting.
+fn wait_for_start<R: BufRead>(input: &mut R, is_terminal: bool, auto_start: bool) {
+    if auto_start {
+        println!("[*] auto_start enabled; starting monitoring");
+        return;
+    }
+
+    if !is_terminal {
+        println!("[*] stdin is not a terminal; starting monitoring automatically");
+        return;
+    }
+
+    println!("Press Enter to start monitoring...");
+    let mut line = String::new();
+    match input.read_line(&mut line) {
+        Ok(0) => println!("[*] stdin closed; starting monitoring"),
+        Ok(_) => {}
+        Err(e) => println!("[!] Failed to read from stdin ({}); starting monitoring", e),
+    }
+}
+
+/// Waits `delay` before the first scan, counting down on the console every
+/// 10 seconds and then each of the last 5. Returns false when `shutdown`
+/// resolved first.
+async fn wait_startup_delay<F: Future<Output = ()>>(delay: Duration, mut shutdown: Pin<&mut F>) -> bool {
+    let deadline = tokio::time::Instant::now() + delay;
+    let seconds = delay.as_secs();
+    println!("[*] Waiting {} seconds before the first scan", seconds);
+
+    let mut marks: Vec<u64> = (10..seconds).step_by(10).chain(1..seconds.min(6)).collect();
+    marks.sort_unstable_by(|a, b| b.cmp(a));
+    marks.dedup();
+    for mark in marks {
+        tokio::select! {
+            _ = tokio::time::sleep_until(deadline - Duration::from_secs(mark)) => {}
+            _ = &mut shutdown => return false,
+        }
+        println!("[*] First scan in {} seconds...", mark);
+    }
+
+    tokio::select! {
+        _ = tokio::time::sleep_until(deadline) => true,
+        _ = &mut shutdown => false,
+    }
+}
+
+/// Writes the evidence bundle for a scan that just turned Critical into the
+/// run directory.
+fn bundle_critical_scan(report: &DetectionReport, config: &Config, run_dir: &RunDirectory, clock: &dyn Clock) {
+    let observations = if config.output.observation_log {
+        match observations::read_log(&run_dir.file("observations.jsonl").to_string_lossy()) {
+            Ok(observations) => Some(observations),
+            Err(e) => {
+                println!("[!] {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let dir = run_dir.file(fairview::bundle::bundle_name(report));
+    match fairview::bundle::write_bundle(&dir, report, observations.as_deref(), config, clock) {
+        Ok(_) => println!("[+] Evidence bundle for scan #{} written to {}", report.scan_number, dir.display()),
+        Err(e) => println!("[!] {}", e),
+    }
+}
+
+/// Appends what the baseline or last scan read to the observation log. The
+/// log is closed after a failed write rather than left with gaps.
+fn record_observation(
+    observation_log: &mut Option<(Arc<RecordingPlatform>, ObservationLog)>,
+    phase: ObservationPhase,
+    scan_number: usize,
+    timestamp: SystemTime,
+) {
+    let Some((ref recorder, ref mut log)) = observation_log else {
+        return;
+    };
+    if let Err(e) = log.write(&recorder.take(phase, scan_number, timestamp)) {
+        println!("[!] {}; no longer recording raw observations", e);
+        *observation_log = None;
+    }
+}
+
+/// Runs the scan loop until `shutdown` resolves. `attended` is false when no
+/// operator is at the console (e.g. under the service control manager), which
+/// skips every prompt.
+async fn run_monitor<F: Future<Output = ()>>(cli: &CliArgs, attended: bool, shutdown: F) {
+    // Before anything is printed, so stdout carries nothing but reports.
+    let mut jsonl = if cli.stdout_jsonl {
+        match JsonlStdout::claim() {
+            Ok(jsonl) => Some(jsonl),
+            Err(e) => {
+                eprintln!("[!] {}", e);
+                std::process::exit(2);
+            }
+        }
+    } else {
+        None
+    };
+
+    println!("Fairview v0.1.0 - Interview Monitoring System\n");
+
+    let mut config = match load_config(cli) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("[!] {}", e);
+            std::process::exit(2);
+        }
+    };
+    apply_cli_overrides(&mut config, cli);
+
+    let run_dir = match RunDirectory::create(&config.output, config.session.session_id.as_deref(), Utc::now()) {
+        Ok(run_dir) => run_dir,
+        Err(e) => {
+            println!("[!] {}", e);
+            std::process::exit(2);
+        }
+    };
+    println!("[*] Writing this run's output to {}", run_dir.path().display());
+
+    if config.mode == Mode::Observe {
+        println!("[*] Observe mode: reports carry scores only; no breaches are raised");
+    }
+
+    let mut observation_log = if config.output.observation_log {
+        let path = run_dir.file("observations.jsonl");
+        match ObservationLog::create(&path) {
+            Ok(log) => {
+                println!("[*] Recording raw observations to {}", path.display());
+                Some((Arc::new(RecordingPlatform::new(Arc::new(SystemPlatform))), log))
+            }
+            Err(e) => {
+                println!("[!] {}; raw observations will not be recorded", e);
+                None
+            }
+        }
+    } else {
+        None
     };
 
-    let mut detector = FairviewDetector::new(config.clone());
+    let mut detector = match observation_log {
+        Some((ref recorder, _)) => FairviewDetector::with_platform(config.clone(), recorder.clone()),
+        None => FairviewDetector::new(config.clone()),
+    };
+    if cli.deterministic {
+        println!("[*] Deterministic mode: fixed clock and RNG seed {}", DETERMINISTIC_SEED);
+        detector.set_clock(Box::new(FixedClock::deterministic()));
+    }
+    if let Some(definitions) = definitions::resolve(&config.definitions).await {
+        detector.set_definitions(&definitions);
+    }
+    if cli.force_fresh {
+        detector.force_fresh_next_scan();
+    }
+    let definitions_refresh = config.definitions.refresh_minutes.map(|minutes| Duration::from_secs(minutes * 60));
+    let mut definitions_fetched_at = Instant::now();
 
     if config.monitoring.collect_baseline {
         detector.collect_baseline();
+        record_observation(&mut observation_log, ObservationPhase::Baseline, 0, SystemTime::now());
         
-        println!("Press Enter to start monitoring...");
-        let mut input = String::new();
-        std::io::stdin().read_line(&mut input).unwrap();
+        let is_terminal = attended && std::io::stdin().is_terminal();
+        wait_for_start(&mut std::io::stdin().lock(), is_terminal, config.monitoring.auto_start);
+    } else if detector.baseline_missing() {
+        println!("\n{}", "!".repeat(60));
+        println!("[!] NO BASELINE: collect_baseline is off and no expected_displays are set");
+        println!("[!] Processes started during the interview and display or device changes");
+        println!("[!] will NOT be detected. Every report will carry baseline_missing.");
+        println!("{}\n", "!".repeat(60));
     }
+    detector.start_display_sampler();
+    detector.start_focus_tracking();
+
+    let mut event_triggers = match config.scan.trigger {
+        ScanTrigger::Interval => None,
+        ScanTrigger::Events => {
+            let started = trigger::start_sources(trigger::platform_sources());
+            if started.is_none() {
+                println!("[!] No event source available; falling back to interval scans");
+            }
+            started.map(|events| (events, TriggerDispatcher::new(&config.scan, Instant::now())))
+        }
+    };
 
     println!("\n{}", "=".repeat(60));
     println!("STARTING CONTINUOUS MONITORING");
-    println!("Scan interval: {} seconds", config.scan.interval_seconds);
+    if event_triggers.is_some() {
+        println!("Scan trigger: system events (keepalive every {} seconds)", config.scan.keepalive_seconds);
+    } else {
+        println!("Scan interval: {} seconds", config.scan.interval_seconds);
+    }
     println!("{}", "=".repeat(60));
 
+    let interactive = attended && cli.interactive && std::io::stdin().is_terminal();
+    if cli.interactive && !interactive {
+        println!("[!] --interactive requires a terminal on stdin; operator review disabled");
+    }
+
+    let checkpoint_path = run_dir.file(&config.session.checkpoint_file);
+    let checkpointing = config.session.session_id.is_some();
+    let mut verdict = match config.session.session_id {
+        Some(ref session_id) => match session::SessionVerdict::load_checkpoint(
+            run_dir.latest_session_file(&config.session.checkpoint_file),
+            session_id,
+        ) {
+            Ok(Some(verdict)) => {
+                // Checkpoints from before scan numbers were saved only have the total.
+                let last_scan = verdict.last_scan_number.max(verdict.total_scans);
+                println!("[+] Resuming session {} from checkpoint ({} earlier scans, continuing at scan #{})",
+                         session_id, verdict.total_scans, last_scan + 1);
+                detector.resume_scan_count(last_scan);
+                verdict
+            }
+            Ok(None) => session::SessionVerdict::new(),
+            Err(e) => {
+                println!("[!] {}; starting a fresh session verdict", e);
+                session::SessionVerdict::new()
+            }
+        },
+        None => session::SessionVerdict::new(),
+    };
+    let fingerprint = FingerprintInputs::collect().fingerprint();
+    println!("[*] Machine fingerprint: {}", fingerprint);
+    detector.set_machine_fingerprint(fingerprint, verdict.machine_fingerprint.clone());
+    let mut provenance = Provenance::collect();
+    if config.session.redact_os_username {
+        provenance = provenance.redacted();
+    }
+    let unknown = || "unknown".to_string();
+    println!("[*] Running as {} on {} ({} {})",
+             provenance.os_username.clone().unwrap_or_else(unknown),
+             provenance.hostname.clone().unwrap_or_else(unknown),
+             provenance.os_name.clone().unwrap_or_else(unknown),
+             provenance.os_version.clone().unwrap_or_default());
+    detector.set_provenance(provenance);
+    let mut pacer = pacing::ScanPacer::new(&config.scan);
+    let mut stream = ReportStream::new(&config.output);
+    let mut sinks = report_formats::sinks(&config.output.report_formats, &run_dir);
+    let mut console = ConsoleView::new(&config.output);
+    let mut previous_report: Option<DetectionReport> = None;
+    let mut notifications = NotificationRouter::from_config(&config);
+    let mut rng = if cli.deterministic {
+        StdRng::seed_from_u64(DETERMINISTIC_SEED)
+    } else {
+        StdRng::from_entropy()
+    };
+    tokio::pin!(shutdown);
+
+    if config.scan.startup_delay_seconds > 0
+        && !wait_startup_delay(Duration::from_secs(config.scan.startup_delay_seconds), shutdown.as_mut()).await
+    {
+        println!("\n[*] Monitoring stopped before the first scan");
+        return;
+    }
+
     loop {
-        let report = detector.scan();
-        print_report(&report, &config);
+        if definitions_refresh.is_some_and(|refresh| definitions_fetched_at.elapsed() >= refresh) {
+            definitions_fetched_at = Instant::now();
+            if let Some(definitions) = definitions::resolve(&config.definitions).await {
+                detector.set_definitions(&definitions);
+            }
+        }
 
-        let datetime: DateTime<Utc> = report.timestamp.into();
-        let filename = format!(
-            "detection_report_{}.json",
-            datetime.format("%Y%m%d_%H%M%S")
-        );
-        
-        if let Ok(json) = serde_json::to_string_pretty(&report) {
-            if let Err(e) = std::fs::write(&filename, json) {
-                println!("[!] Failed to write report to {}: {}", filename, e);
+        let mut report = detector.scan();
+        record_observation(&mut observation_log, ObservationPhase::Scan, report.scan_number, report.timestamp);
+
+        let interval = pacer.effective_interval();
+        let pacing = pacer.after_scan(Duration::from_millis(report.scan_duration_ms), &mut rng);
+        if pacing.overran {
+            println!("[!] Scan took {} ms, longer than the {} second interval",
+                     report.scan_duration_ms, interval.as_secs());
+        }
+        if let Some(ref adjustment) = pacing.adjustment {
+            println!("[*] Effective scan interval adjusted to {} seconds",
+                     adjustment.effective_interval_seconds);
+        }
+        report.interval_adjustment = pacing.adjustment;
+
+        if !cli.quiet {
+            let full = console.next_is_full();
+            match previous_report {
+                Some(ref previous) if !full => print_delta(previous, &report),
+                _ => print_report(&report, &config),
+            }
+        }
+        if report.exceeds_threshold {
+            println!("[!] ALERT: {}", alert::format_alert_line(&report, config.output.alert_line_max_chars));
+        }
+
+        if interactive {
+            review::review_report(&mut report, &mut std::io::stdin().lock(), &mut std::io::stdout(), detector.clock());
+        }
+        verdict.record(&report);
+        if let Some(ref mut out) = jsonl {
+            if let Err(e) = out.write_report(&report) {
+                println!("[!] {}; no longer writing reports to stdout", e);
+                jsonl = None;
+            }
+        }
+        if checkpointing {
+            if let Err(e) = verdict.save_checkpoint(&checkpoint_path) {
+                println!("[!] {}", e);
+            }
+        }
+        let newly_critical = report.severity == Severity::Critical
+            && previous_report.as_ref().is_none_or(|previous| previous.severity != Severity::Critical);
+        if config.output.bundle_on_critical && newly_critical {
+            bundle_critical_scan(&report, &config, &run_dir, detector.clock());
+        }
+
+        match stream.next(&report) {
+            Emission::Report => {
+                for sink in sinks.iter_mut() {
+                    if let Err(e) = sink.write_report(&report) {
+                        println!("[!] {}", e);
+                    }
+                }
+            }
+            Emission::Heartbeat(heartbeat) => {
+                for sink in sinks.iter_mut() {
+                    if let Err(e) = sink.write_heartbeat(&heartbeat) {
+                        println!("[!] {}", e);
+                    }
+                }
+                if config.output.system_log {
+                    if let Err(e) = event_log::write_event(&event_log::format_heartbeat(&heartbeat)) {
+                        println!("[!] {}", e);
+                    }
+                }
+            }
+            Emission::Skip => {}
+        }
+        // Notifications have their own severity, risk and throttle gates,
+        // independent of which reports are written.
+        for failure in notifications.dispatch(&report, Instant::now()) {
+            println!("[!] {}", failure);
+        }
+
+        previous_report = Some(report);
+
+        let Some((ref mut triggers, ref mut dispatcher)) = event_triggers else {
+            tokio::select! {
+                _ = tokio::time::sleep(pacing.sleep) => {}
+                _ = &mut shutdown => break,
+            }
+            continue;
+        };
+
+        // Event-driven: wait for the keepalive or a burst of events, but
+        // never scan again sooner than min_sleep_seconds.
+        dispatcher.scan_finished(Instant::now());
+        let stopped = loop {
+            tokio::select! {
+                _ = tokio::time::sleep_until(dispatcher.next_scan_at().into()) => break false,
+                Some(event) = triggers.events.recv() => dispatcher.record(event),
+                _ = &mut shutdown => break true,
             }
+        };
+        if stopped {
+            break;
         }
+        if let Trigger::Events(events) = dispatcher.take() {
+            let labels: Vec<&str> = events.iter().map(|e| e.label()).collect();
+            println!("[*] Scan triggered by {}", labels.join(", "));
+        }
+    }
+
+    println!("\n[*] Monitoring stopped after {} scans", verdict.total_scans);
+    println!("[*] Session verdict: {} (peak risk {:.2}, {} breached scans)",
+             if verdict.suspected_cheating { "SUSPECTED CHEATING" } else { "CLEAN" },
+             verdict.peak_risk_score,
+             verdict.breached_scans);
+
+    if let Ok(json) = serde_json::to_string_pretty(&verdict) {
+        if let Err(e) = std::fs::write(run_dir.file("session_verdict.json"), json) {
+            println!("[!] Failed to write session verdict: {}", e);
+        }
+    }
+    for sink in sinks.iter_mut() {
+        if let Err(e) = sink.finish(&verdict) {
+            println!("[!] {}", e);
+        }
+    }
+
+    if config.telemetry.enabled {
+        let summary = telemetry::TelemetrySummary::from_verdict(&verdict);
+        match telemetry::send(&config.telemetry, &summary).await {
+            Ok(()) => println!("[+] Sent anonymized telemetry summary"),
+            Err(e) => println!("[!] {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{self, Read};
+
+    /// A stdin that fails every read, like a handle closed under a service.
+    struct BrokenInput;
+
+    impl Read for BrokenInput {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "stdin closed"))
+        }
+    }
+
+    #[test]
+    fn test_wait_for_start_skips_prompt_without_terminal() {
+        wait_for_start(&mut io::BufReader::new(BrokenInput), false, false);
+    }
+
+    #[test]
+    fn test_wait_for_start_survives_closed_stdin() {
+        wait_for_start(&mut io::BufReader::new(BrokenInput), true, false);
+        wait_for_start(&mut io::empty(), true, false);
+    }
+
+    #[test]
+    fn test_wait_for_start_honors_auto_start() {
+        wait_for_start(&mut io::BufReader::new(BrokenInput), true, true);
+    }
+
+    #[tokio::test]
+    async fn test_first_scan_waits_for_startup_delay() {
+        let delay = Duration::from_millis(300);
+        let never = std::pin::pin!(std::future::pending::<()>());
+        let start = Instant::now();
+        assert!(wait_startup_delay(delay, never).await);
+        assert!(start.elapsed() >= delay);
 
-        tokio::time::sleep(Duration::from_secs(config.scan.interval_seconds)).await;
+        let stop = std::pin::pin!(async {});
+        assert!(!wait_startup_delay(Duration::from_secs(60), stop).await);
     }
 }
\ No newline at end of file
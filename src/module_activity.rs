@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+/// Tracks, per enabled module, whether it has ever produced output: a
+/// finding, or a sign it saw the machine at all (processes enumerated,
+/// displays read). A module that stays silent scan after scan is usually
+/// misconfigured or lacks a permission rather than finding nothing.
+#[derive(Debug, Default)]
+pub struct ModuleActivity {
+    modules: HashMap<&'static str, ModuleRecord>,
+}
+
+#[derive(Debug, Default)]
+struct ModuleRecord {
+    scans: usize,
+    produced_output: bool,
+    warned: bool,
+}
+
+impl ModuleActivity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records one scan of `module`. Failed runs aren't recorded; they are
+    /// already reported as module failures.
+    pub fn record(&mut self, module: &'static str, produced_output: bool) {
+        let record = self.modules.entry(module).or_default();
+        record.scans += 1;
+        record.produced_output |= produced_output;
+    }
+
+    /// Modules that have run `after_scans` times without any output, whether
+    /// or not they were already reported.
+    pub fn silent_count(&self, after_scans: usize) -> usize {
+        self.modules
+            .values()
+            .filter(|record| !record.produced_output && record.scans >= after_scans)
+            .count()
+    }
+
+    /// Modules that have run `after_scans` times without any output and
+    /// haven't been reported yet, sorted. Each module is returned once.
+    pub fn newly_silent(&mut self, after_scans: usize) -> Vec<&'static str> {
+        let mut silent: Vec<&'static str> = self
+            .modules
+            .iter_mut()
+            .filter(|(_, record)| !record.produced_output && !record.warned && record.scans >= after_scans)
+            .map(|(module, record)| {
+                record.warned = true;
+                *module
+            })
+            .collect();
+        silent.sort();
+        silent
+    }
+}
\ No newline at end of file
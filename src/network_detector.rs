@@ -0,0 +1,259 @@
+use std::net::{IpAddr, SocketAddr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpState {
+    Listen,
+    Established,
+    Other,
+}
+
+/// One row of the TCP connection table with the PID that owns the socket.
+/// `remote` is `None` for listening sockets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TcpConnection {
+    pub pid: u32,
+    pub local: SocketAddr,
+    pub remote: Option<SocketAddr>,
+    pub state: TcpState,
+}
+
+/// A process serving a localhost port that a browser process is connected
+/// to, the shape of an extension-backed answer injector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalProxyPair {
+    pub server_pid: u32,
+    pub client_pid: u32,
+    pub port: u16,
+}
+
+#[derive(Default)]
+pub struct NetworkDetector;
+
+impl NetworkDetector {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Reads the TCP table with owning PIDs. Without elevated rights some
+    /// platforms omit PIDs for other users' sockets; those rows are skipped.
+    pub fn tcp_connections(&self) -> Result<Vec<TcpConnection>, String> {
+        #[cfg(target_os = "windows")]
+        {
+            // `-p TCP` only lists IPv4 sockets.
+            let mut connections = parse_netstat(&run("netstat", &["-ano", "-p", "TCP"])?);
+            connections.extend(parse_netstat(&run("netstat", &["-ano", "-p", "TCPv6"])?));
+            Ok(connections)
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            Ok(parse_ss(&run("ss", &["-tanpH"])?))
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            Ok(parse_lsof(&run("lsof", &["-nP", "-iTCP"])?))
+        }
+    }
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    use crate::command::LimitedOutput;
+
+    let output = std::process::Command::new(program)
+        .args(args)
+        .limited_output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn is_local(ip: IpAddr) -> bool {
+    ip.is_loopback() || ip.is_unspecified()
+}
+
+/// Pairs listeners reachable on localhost with established loopback
+/// connections to the same port from a browser process. Browsers talking to
+/// themselves (e.g. a DevTools port) are ignored.
+pub fn find_local_proxy_pairs<F>(connections: &[TcpConnection], is_browser: F) -> Vec<LocalProxyPair>
+where
+    F: Fn(u32) -> bool,
+{
+    let mut pairs = Vec::new();
+
+    for listener in connections.iter().filter(|c| c.state == TcpState::Listen && is_local(c.local.ip())) {
+        if is_browser(listener.pid) {
+            continue;
+        }
+
+        for client in connections.iter().filter(|c| c.state == TcpState::Established && c.pid != listener.pid) {
+            let Some(remote) = client.remote else {
+                continue;
+            };
+            if remote.ip().is_loopback() && remote.port() == listener.local.port() && is_browser(client.pid) {
+                let pair = LocalProxyPair {
+                    server_pid: listener.pid,
+                    client_pid: client.pid,
+                    port: listener.local.port(),
+                };
+                if !pairs.contains(&pair) {
+                    pairs.push(pair);
+                }
+            }
+        }
+    }
+
+    pairs
+}
+
+fn parse_state(state: &str) -> TcpState {
+    match state.to_ascii_uppercase().as_str() {
+        "LISTEN" | "LISTENING" => TcpState::Listen,
+        "ESTAB" | "ESTABLISHED" => TcpState::Established,
+        _ => TcpState::Other,
+    }
+}
+
+/// Parses `127.0.0.1:80` and `[::1]:80`, plus the `*:80` form lsof uses for
+/// wildcard listeners. Wildcard ports (`0.0.0.0:*`) don't parse.
+fn parse_socket(addr: &str) -> Option<SocketAddr> {
+    match addr.strip_prefix("*:") {
+        Some(port) => format!("0.0.0.0:{}", port).parse().ok(),
+        None => addr.parse().ok(),
+    }
+}
+
+fn connection(pid: u32, local: &str, remote: Option<&str>, state: TcpState) -> Option<TcpConnection> {
+    Some(TcpConnection {
+        pid,
+        local: parse_socket(local)?,
+        remote: match state {
+            TcpState::Listen => None,
+            _ => remote.and_then(parse_socket),
+        },
+        state,
+    })
+}
+
+/// Parses Windows `netstat -ano` output:
+/// `TCP    127.0.0.1:5000    0.0.0.0:0    LISTENING    1234`.
+pub fn parse_netstat(output: &str) -> Vec<TcpConnection> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 5 || fields[0] != "TCP" {
+                return None;
+            }
+            let pid = fields[4].parse().ok()?;
+            connection(pid, fields[1], Some(fields[2]), parse_state(fields[3]))
+        })
+        .collect()
+}
+
+/// Parses Linux `ss -tanpH` output:
+/// `ESTAB 0 0 127.0.0.1:54000 127.0.0.1:5000 users:(("chrome",pid=5678,fd=30))`.
+pub fn parse_ss(output: &str) -> Vec<TcpConnection> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 6 {
+                return None;
+            }
+            let users = fields[5..].join(" ");
+            let pid = users
+                .split("pid=")
+                .nth(1)?
+                .split(|c: char| !c.is_ascii_digit())
+                .next()?
+                .parse()
+                .ok()?;
+            connection(pid, fields[3], Some(fields[4]), parse_state(fields[0]))
+        })
+        .collect()
+}
+
+/// Parses macOS `lsof -nP -iTCP` output, whose last columns are
+/// `127.0.0.1:54000->127.0.0.1:5000 (ESTABLISHED)` or `*:5000 (LISTEN)`.
+pub fn parse_lsof(output: &str) -> Vec<TcpConnection> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 3 {
+                return None;
+            }
+            let pid = fields[1].parse().ok()?;
+            let state = parse_state(fields[fields.len() - 1].trim_matches(|c| c == '(' || c == ')'));
+            let name = fields[fields.len() - 2];
+            let (local, remote) = match name.split_once("->") {
+                Some((local, remote)) => (local, Some(remote)),
+                None => (name, None),
+            };
+            connection(pid, local, remote, state)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(pid: u32, local: &str, remote: Option<&str>, state: TcpState) -> TcpConnection {
+        connection(pid, local, remote, state).unwrap()
+    }
+
+    #[test]
+    fn test_browser_connected_to_local_listener_is_paired() {
+        let browser = |pid: u32| pid == 500 || pid == 501;
+        let connections = vec![
+            row(100, "127.0.0.1:5000", None, TcpState::Listen),
+            row(500, "127.0.0.1:54000", Some("127.0.0.1:5000"), TcpState::Established),
+            row(100, "127.0.0.1:5000", Some("127.0.0.1:54000"), TcpState::Established),
+            // Unrelated listener nobody talks to, and a browser's own DevTools port.
+            row(200, "127.0.0.1:6000", None, TcpState::Listen),
+            row(501, "127.0.0.1:9222", None, TcpState::Listen),
+            row(500, "127.0.0.1:54001", Some("127.0.0.1:9222"), TcpState::Established),
+            // A non-browser client of a local server.
+            row(300, "127.0.0.1:54002", Some("127.0.0.1:6000"), TcpState::Established),
+        ];
+
+        assert_eq!(find_local_proxy_pairs(&connections, browser), vec![LocalProxyPair {
+            server_pid: 100,
+            client_pid: 500,
+            port: 5000,
+        }]);
+    }
+
+    #[test]
+    fn test_remote_connection_on_same_port_is_not_paired() {
+        let connections = vec![
+            row(100, "0.0.0.0:443", None, TcpState::Listen),
+            row(500, "192.168.1.20:54000", Some("142.250.1.1:443"), TcpState::Established),
+        ];
+        assert!(find_local_proxy_pairs(&connections, |pid| pid == 500).is_empty());
+    }
+
+    #[test]
+    fn test_parse_connection_tables() {
+        let ss = "LISTEN 0 4096 127.0.0.1:5000 0.0.0.0:* users:((\"node\",pid=1234,fd=20))\n\
+                  ESTAB 0 0 [::1]:54000 [::1]:5000 users:((\"chrome\",pid=5678,fd=30))\n";
+        let netstat = "  Proto  Local Address          Foreign Address        State           PID\n\
+                       \x20 TCP    127.0.0.1:5000         0.0.0.0:0              LISTENING       1234\n\
+                       \x20 TCP    127.0.0.1:54000        127.0.0.1:5000         ESTABLISHED     5678\n";
+        let lsof = "COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME\n\
+                    node 1234 me 20u IPv4 0x1 0t0 TCP *:5000 (LISTEN)\n\
+                    Google 5678 me 30u IPv4 0x2 0t0 TCP 127.0.0.1:54000->127.0.0.1:5000 (ESTABLISHED)\n";
+
+        for connections in [parse_ss(ss), parse_netstat(netstat), parse_lsof(lsof)] {
+            assert_eq!(connections.len(), 2);
+            assert_eq!(connections[0].pid, 1234);
+            assert_eq!(connections[0].state, TcpState::Listen);
+            assert_eq!(connections[0].local.port(), 5000);
+            assert_eq!(connections[1].pid, 5678);
+            assert_eq!(connections[1].remote.map(|r| r.port()), Some(5000));
+            assert_eq!(find_local_proxy_pairs(&connections, |pid| pid == 5678).len(), 1);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,288 @@
+use crate::config::{Config, NotificationConfig};
+use crate::{alert, event_log, DetectionReport, Severity};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Where a scan can be pushed besides the JSON report files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    Webhook,
+    Command,
+    Syslog,
+}
+
+impl Channel {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Channel::Webhook => "webhook",
+            Channel::Command => "command",
+            Channel::Syslog => "system log",
+        }
+    }
+
+    /// Push channels wake someone up, so they are throttled; the system log
+    /// records every scan it is gated to.
+    fn is_throttled(&self) -> bool {
+        !matches!(self, Channel::Syslog)
+    }
+}
+
+pub trait Notifier: Send {
+    fn channel(&self) -> Channel;
+    fn notify(&self, report: &DetectionReport) -> Result<(), String>;
+}
+
+/// Posts the report JSON. The request runs in the background so a slow
+/// endpoint never delays the next scan; failures are printed.
+#[cfg(feature = "network")]
+pub struct WebhookNotifier {
+    url: String,
+    timeout: Duration,
+}
+
+#[cfg(feature = "network")]
+impl Notifier for WebhookNotifier {
+    fn channel(&self) -> Channel {
+        Channel::Webhook
+    }
+
+    fn notify(&self, report: &DetectionReport) -> Result<(), String> {
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|_| "Webhook notifications need a running async runtime".to_string())?;
+        let body = serde_json::to_vec(report).map_err(|e| format!("Failed to serialize report: {}", e))?;
+        let client = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .build()
+            .map_err(|e| format!("Failed to build webhook client: {}", e))?;
+        let url = self.url.clone();
+
+        runtime.spawn(async move {
+            let result = client
+                .post(&url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body)
+                .send()
+                .await;
+            match result {
+                Ok(response) if !response.status().is_success() => {
+                    println!("[!] Webhook returned {}", response.status());
+                }
+                Ok(_) => {}
+                Err(e) => println!("[!] Failed to send webhook notification: {}", e),
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Runs `notifications.command` with the one-line alert as its last
+/// argument, without waiting for it to finish.
+pub struct CommandNotifier {
+    argv: Vec<String>,
+    alert_line_max_chars: usize,
+}
+
+impl Notifier for CommandNotifier {
+    fn channel(&self) -> Channel {
+        Channel::Command
+    }
+
+    fn notify(&self, report: &DetectionReport) -> Result<(), String> {
+        let (program, args) = self.argv.split_first().ok_or("notifications.command is empty")?;
+        let mut child = std::process::Command::new(program)
+            .args(args)
+            .arg(alert::format_alert_line(report, self.alert_line_max_chars))
+            .stdin(std::process::Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to run notification command {}: {}", program, e))?;
+
+        // Reap it off the scan thread.
+        std::thread::spawn(move || child.wait());
+        Ok(())
+    }
+}
+
+pub struct SyslogNotifier;
+
+impl Notifier for SyslogNotifier {
+    fn channel(&self) -> Channel {
+        Channel::Syslog
+    }
+
+    fn notify(&self, report: &DetectionReport) -> Result<(), String> {
+        event_log::write_event(&event_log::format_event(report))
+    }
+}
+
+/// Sends each scan to the channels whose minimum severity it reaches.
+/// Webhook and command notifications are also held back for scans below
+/// `min_risk_to_alert` or, with `only_on_threshold_exceeded`, scans that
+/// didn't breach, and throttled to one per `throttle_seconds`, except that
+/// a critical scan always goes out.
+pub struct NotificationRouter {
+    notifiers: Vec<Box<dyn Notifier>>,
+    min_severity: HashMap<Channel, Severity>,
+    only_on_threshold_exceeded: bool,
+    min_risk_to_alert: f64,
+    throttle: Duration,
+    last_sent: HashMap<Channel, Instant>,
+}
+
+impl NotificationRouter {
+    /// The channels `config` enables: `output.system_log` and the webhook and
+    /// command under `[notifications]`.
+    pub fn from_config(config: &Config) -> Self {
+        let notifications = &config.notifications;
+        let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+        // `Config::validate` rejects a webhook when the network feature is off.
+        #[cfg(feature = "network")]
+        if let Some(ref url) = notifications.webhook_url {
+            notifiers.push(Box::new(WebhookNotifier {
+                url: url.clone(),
+                timeout: Duration::from_secs(notifications.timeout_seconds),
+            }));
+        }
+        if !notifications.command.is_empty() {
+            notifiers.push(Box::new(CommandNotifier {
+                argv: notifications.command.clone(),
+                alert_line_max_chars: config.output.alert_line_max_chars,
+            }));
+        }
+        if config.output.system_log {
+            notifiers.push(Box::new(SyslogNotifier));
+        }
+        Self::with_notifiers(notifiers, notifications)
+    }
+
+    pub fn with_notifiers(notifiers: Vec<Box<dyn Notifier>>, config: &NotificationConfig) -> Self {
+        Self {
+            notifiers,
+            min_severity: HashMap::from([
+                (Channel::Webhook, config.webhook_min_severity),
+                (Channel::Command, config.command_min_severity),
+                (Channel::Syslog, config.syslog_min_severity),
+            ]),
+            only_on_threshold_exceeded: config.only_on_threshold_exceeded,
+            min_risk_to_alert: config.min_risk_to_alert,
+            throttle: Duration::from_secs(config.throttle_seconds),
+            last_sent: HashMap::new(),
+        }
+    }
+
+    /// Notifies every channel the report is routed to and returns the
+    /// failures, one message per channel.
+    pub fn dispatch(&mut self, report: &DetectionReport, now: Instant) -> Vec<String> {
+        let mut failures = Vec::new();
+        for index in 0..self.notifiers.len() {
+            let channel = self.notifiers[index].channel();
+            if !self.should_send(channel, report, now) {
+                continue;
+            }
+            if channel.is_throttled() {
+                self.last_sent.insert(channel, now);
+            }
+            if let Err(e) = self.notifiers[index].notify(report) {
+                failures.push(format!("{} notification failed: {}", channel.label(), e));
+            }
+        }
+        failures
+    }
+
+    fn should_send(&self, channel: Channel, report: &DetectionReport, now: Instant) -> bool {
+        // Observe mode never pages anyone; its reports only reach the log.
+        let severity = if report.observational { Severity::Low } else { report.severity };
+        if severity < self.min_severity.get(&channel).copied().unwrap_or(Severity::Low) {
+            return false;
+        }
+
+        if !channel.is_throttled() || severity == Severity::Critical {
+            return true;
+        }
+        let worth_pushing = (report.exceeds_threshold || !self.only_on_threshold_exceeded)
+            && report.overall_risk_score >= self.min_risk_to_alert;
+        let throttled = self.last_sent.get(&channel).is_some_and(|sent| now.duration_since(*sent) < self.throttle);
+        worth_pushing && !throttled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::report;
+    use std::sync::{Arc, Mutex};
+
+    struct RecordingNotifier {
+        channel: Channel,
+        sent: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl Notifier for RecordingNotifier {
+        fn channel(&self) -> Channel {
+            self.channel
+        }
+
+        fn notify(&self, report: &DetectionReport) -> Result<(), String> {
+            self.sent.lock().unwrap().push(report.scan_number);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_webhook_is_gated_by_severity_and_throttled() {
+        let webhook = Arc::new(Mutex::new(Vec::new()));
+        let syslog = Arc::new(Mutex::new(Vec::new()));
+        let config = NotificationConfig {
+            webhook_min_severity: Severity::High,
+            throttle_seconds: 60,
+            ..Default::default()
+        };
+        let mut router = NotificationRouter::with_notifiers(
+            vec![
+                Box::new(RecordingNotifier { channel: Channel::Webhook, sent: webhook.clone() }),
+                Box::new(RecordingNotifier { channel: Channel::Syslog, sent: syslog.clone() }),
+            ],
+            &config,
+        );
+        let scan = |number, severity| DetectionReport { severity, ..report(number, 0.5, true) };
+        let start = Instant::now();
+
+        router.dispatch(&scan(1, Severity::Medium), start);
+        router.dispatch(&scan(2, Severity::High), start);
+        router.dispatch(&scan(3, Severity::High), start + Duration::from_secs(10));
+        router.dispatch(&scan(4, Severity::Critical), start + Duration::from_secs(20));
+        router.dispatch(&scan(5, Severity::High), start + Duration::from_secs(90));
+
+        assert_eq!(*webhook.lock().unwrap(), vec![2, 4, 5]);
+        assert_eq!(*syslog.lock().unwrap(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_low_risk_scans_are_not_pushed() {
+        let command = Arc::new(Mutex::new(Vec::new()));
+        let syslog = Arc::new(Mutex::new(Vec::new()));
+        let config = NotificationConfig {
+            command_min_severity: Severity::Low,
+            throttle_seconds: 0,
+            only_on_threshold_exceeded: true,
+            min_risk_to_alert: 0.6,
+            ..Default::default()
+        };
+        let mut router = NotificationRouter::with_notifiers(
+            vec![
+                Box::new(RecordingNotifier { channel: Channel::Command, sent: command.clone() }),
+                Box::new(RecordingNotifier { channel: Channel::Syslog, sent: syslog.clone() }),
+            ],
+            &config,
+        );
+        let scan = |number, risk, breached, severity| DetectionReport { severity, ..report(number, risk, breached) };
+        let now = Instant::now();
+
+        router.dispatch(&scan(1, 0.7, false, Severity::High), now);
+        router.dispatch(&scan(2, 0.5, true, Severity::Medium), now);
+        router.dispatch(&scan(3, 0.7, true, Severity::High), now);
+        router.dispatch(&scan(4, 0.2, true, Severity::Critical), now);
+
+        assert_eq!(*command.lock().unwrap(), vec![3, 4]);
+        assert_eq!(*syslog.lock().unwrap(), vec![1, 2, 3, 4]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,493 @@
+use crate::audio_detector::{CaptureStreams, EndpointState};
+use crate::clock::{Clock, ClockSample};
+use crate::config::Config;
+use crate::focus_detector::ForegroundWindow;
+use crate::hardware_detector::{DisplayConfiguration, RemoteDesktopStatus};
+use crate::platform::PlatformProvider;
+use crate::process_monitor::{ProcessCapabilities, ProcessSource};
+use crate::system_load::SystemLoad;
+use crate::tcc::TccGrant;
+use crate::vm_detector::VmCheckResult;
+use crate::{DetectionReport, FairviewDetector, OverlayWindow, Process};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// Everything the baseline or one scan read from the platform, before any
+/// scoring: the process list and what each process could do, the display
+/// configuration, remote desktop and audio state, and candidate overlay
+/// windows. Replaying these through `rescore` rebuilds the reports under
+/// whatever config is current, so a disputed verdict can be re-adjudicated
+/// from what was on the machine rather than from how it was scored.
+///
+/// A field is `None` when nothing asked for it, e.g. because the module was
+/// disabled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Observation {
+    pub phase: ObservationPhase,
+    /// 0 for the baseline.
+    pub scan_number: usize,
+    #[serde(with = "crate::timestamp_format")]
+    pub timestamp: SystemTime,
+    pub processes: Vec<ObservedProcess>,
+    pub current_session: Option<u32>,
+    pub displays: Option<Result<DisplayConfiguration, String>>,
+    pub video_capture_devices: Option<Result<Vec<String>, String>>,
+    pub remote_desktop: Option<RemoteDesktopStatus>,
+    pub audio_monitoring: Option<bool>,
+    /// Only where the platform counts streams; elsewhere `audio_monitoring`
+    /// is recorded instead.
+    #[serde(default)]
+    pub audio_capture_streams: Option<CaptureStreams>,
+    pub audio_endpoints: Option<Result<Vec<EndpointState>, String>>,
+    pub overlays: Option<Vec<OverlayWindow>>,
+    #[serde(default)]
+    pub system_load: Option<SystemLoad>,
+}
+
+impl Default for Observation {
+    fn default() -> Self {
+        Self {
+            phase: ObservationPhase::default(),
+            scan_number: 0,
+            timestamp: SystemTime::UNIX_EPOCH,
+            processes: Vec::new(),
+            current_session: None,
+            displays: None,
+            video_capture_devices: None,
+            remote_desktop: None,
+            audio_monitoring: None,
+            audio_capture_streams: None,
+            audio_endpoints: None,
+            overlays: None,
+            system_load: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObservationPhase {
+    Baseline,
+    #[default]
+    Scan,
+}
+
+/// A listed process with whatever was looked up about it. Whitelisted
+/// processes are usually never inspected, so their details stay empty.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ObservedProcess {
+    #[serde(flatten)]
+    pub process: Process,
+    pub capabilities: Option<ProcessCapabilities>,
+    pub signature_verified: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub permission_grants: Vec<TccGrant>,
+}
+
+/// Passes every call through to `inner` and keeps the answers until `take`.
+/// A value read more than once between two `take`s keeps the latest answer.
+pub struct RecordingPlatform {
+    inner: Arc<dyn PlatformProvider>,
+    current: Mutex<Observation>,
+}
+
+impl RecordingPlatform {
+    pub fn new(inner: Arc<dyn PlatformProvider>) -> Self {
+        Self { inner, current: Mutex::new(Observation::default()) }
+    }
+
+    /// Returns what was read since the last call, labelled with `phase`,
+    /// `scan_number` and `timestamp`, and starts a new observation.
+    pub fn take(&self, phase: ObservationPhase, scan_number: usize, timestamp: SystemTime) -> Observation {
+        let observation = std::mem::take(&mut *self.lock());
+        Observation { phase, scan_number, timestamp, ..observation }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Observation> {
+        self.current.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn with_process<F: FnOnce(&mut ObservedProcess)>(&self, process: &Process, update: F) {
+        let mut current = self.lock();
+        let index = match current.processes.iter().position(|p| p.process.pid == process.pid) {
+            Some(index) => index,
+            None => {
+                current.processes.push(ObservedProcess { process: process.clone(), ..Default::default() });
+                current.processes.len() - 1
+            }
+        };
+        update(&mut current.processes[index]);
+    }
+}
+
+impl ProcessSource for RecordingPlatform {
+    fn processes(&self) -> Vec<Process> {
+        let processes = self.inner.processes();
+        let mut current = self.lock();
+        let previous = std::mem::take(&mut current.processes);
+        current.processes = processes
+            .iter()
+            .map(|process| {
+                // Keep details looked up earlier in the same scan.
+                previous
+                    .iter()
+                    .find(|p| p.process.pid == process.pid && p.process.name == process.name)
+                    .map(|p| ObservedProcess { process: process.clone(), ..p.clone() })
+                    .unwrap_or_else(|| ObservedProcess { process: process.clone(), ..Default::default() })
+            })
+            .collect();
+        processes
+    }
+
+    fn capabilities(&self, process: &Process) -> ProcessCapabilities {
+        let capabilities = self.inner.capabilities(process);
+        self.with_process(process, |observed| observed.capabilities = Some(capabilities.clone()));
+        capabilities
+    }
+
+    fn current_session(&self) -> Option<u32> {
+        let session = self.inner.current_session();
+        self.lock().current_session = session;
+        session
+    }
+
+    /// Only meaningful on the machine being recorded, so not part of an
+    /// observation.
+    fn own_pid(&self) -> Option<u32> {
+        self.inner.own_pid()
+    }
+
+    fn signature_verified(&self, process: &Process) -> Option<bool> {
+        let verified = self.inner.signature_verified(process);
+        self.with_process(process, |observed| observed.signature_verified = verified);
+        verified
+    }
+
+    fn permission_grants(&self, process: &Process) -> Vec<TccGrant> {
+        let grants = self.inner.permission_grants(process);
+        self.with_process(process, |observed| observed.permission_grants = grants.clone());
+        grants
+    }
+}
+
+impl PlatformProvider for RecordingPlatform {
+    fn displays(&self) -> Result<DisplayConfiguration, String> {
+        let displays = self.inner.displays();
+        self.lock().displays = Some(displays.clone());
+        displays
+    }
+
+    fn video_capture_devices(&self) -> Result<Vec<String>, String> {
+        let devices = self.inner.video_capture_devices();
+        self.lock().video_capture_devices = Some(devices.clone());
+        devices
+    }
+
+    fn remote_desktop(&self) -> RemoteDesktopStatus {
+        let status = self.inner.remote_desktop();
+        self.lock().remote_desktop = Some(status.clone());
+        status
+    }
+
+    fn audio_monitoring(&self) -> bool {
+        let detected = self.inner.audio_monitoring();
+        self.lock().audio_monitoring = Some(detected);
+        detected
+    }
+
+    fn audio_capture_streams(&self) -> Option<CaptureStreams> {
+        let streams = self.inner.audio_capture_streams();
+        self.lock().audio_capture_streams.clone_from(&streams);
+        streams
+    }
+
+    fn audio_endpoints(&self) -> Result<Vec<EndpointState>, String> {
+        let endpoints = self.inner.audio_endpoints();
+        self.lock().audio_endpoints = Some(endpoints.clone());
+        endpoints
+    }
+
+    fn overlays(&self) -> Vec<OverlayWindow> {
+        let overlays = self.inner.overlays();
+        self.lock().overlays = Some(overlays.clone());
+        overlays
+    }
+
+    /// Sampled continuously between scans, so not part of an observation.
+    fn foreground_process(&self) -> Option<u32> {
+        self.inner.foreground_process()
+    }
+
+    fn foreground_window(&self) -> Option<ForegroundWindow> {
+        self.inner.foreground_window()
+    }
+
+    fn cloud_clipboard_sync(&self) -> Option<bool> {
+        self.inner.cloud_clipboard_sync()
+    }
+
+    fn vm_signals(&self) -> VmCheckResult {
+        self.inner.vm_signals()
+    }
+
+    fn system_load(&self) -> Option<SystemLoad> {
+        let load = self.inner.system_load();
+        self.lock().system_load = load;
+        load
+    }
+
+    fn refresh_snapshot(&self) {
+        self.inner.refresh_snapshot()
+    }
+}
+
+/// Answers every call from one recorded observation.
+#[derive(Default)]
+pub struct ReplayPlatform {
+    observation: Mutex<Observation>,
+}
+
+impl ReplayPlatform {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, observation: Observation) {
+        *self.lock() = observation;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Observation> {
+        self.observation.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn find<T, F: FnOnce(&ObservedProcess) -> T>(&self, process: &Process, read: F) -> Option<T> {
+        self.lock().processes.iter().find(|p| p.process.pid == process.pid).map(read)
+    }
+}
+
+fn not_recorded<T>(what: &str) -> Result<T, String> {
+    Err(format!("{} was not recorded in this observation", what))
+}
+
+impl ProcessSource for ReplayPlatform {
+    fn processes(&self) -> Vec<Process> {
+        self.lock().processes.iter().map(|p| p.process.clone()).collect()
+    }
+
+    fn capabilities(&self, process: &Process) -> ProcessCapabilities {
+        self.find(process, |p| p.capabilities.clone()).flatten().unwrap_or_default()
+    }
+
+    fn current_session(&self) -> Option<u32> {
+        self.lock().current_session
+    }
+
+    fn signature_verified(&self, process: &Process) -> Option<bool> {
+        self.find(process, |p| p.signature_verified).flatten()
+    }
+
+    fn permission_grants(&self, process: &Process) -> Vec<TccGrant> {
+        self.find(process, |p| p.permission_grants.clone()).unwrap_or_default()
+    }
+}
+
+impl PlatformProvider for ReplayPlatform {
+    fn displays(&self) -> Result<DisplayConfiguration, String> {
+        self.lock().displays.clone().unwrap_or_else(|| not_recorded("The display configuration"))
+    }
+
+    fn video_capture_devices(&self) -> Result<Vec<String>, String> {
+        self.lock().video_capture_devices.clone().unwrap_or_else(|| not_recorded("The video capture device list"))
+    }
+
+    fn remote_desktop(&self) -> RemoteDesktopStatus {
+        self.lock().remote_desktop.clone().unwrap_or_default()
+    }
+
+    fn audio_monitoring(&self) -> bool {
+        self.lock().audio_monitoring.unwrap_or(false)
+    }
+
+    fn audio_capture_streams(&self) -> Option<CaptureStreams> {
+        self.lock().audio_capture_streams.clone()
+    }
+
+    fn audio_endpoints(&self) -> Result<Vec<EndpointState>, String> {
+        self.lock().audio_endpoints.clone().unwrap_or_else(|| not_recorded("The audio endpoint list"))
+    }
+
+    fn overlays(&self) -> Vec<OverlayWindow> {
+        self.lock().overlays.clone().unwrap_or_default()
+    }
+
+    fn foreground_process(&self) -> Option<u32> {
+        None
+    }
+
+    fn foreground_window(&self) -> Option<ForegroundWindow> {
+        None
+    }
+
+    fn cloud_clipboard_sync(&self) -> Option<bool> {
+        None
+    }
+
+    fn vm_signals(&self) -> VmCheckResult {
+        VmCheckResult::default()
+    }
+
+    fn system_load(&self) -> Option<SystemLoad> {
+        self.lock().system_load
+    }
+
+    fn refresh_snapshot(&self) {}
+}
+
+/// Reads the time from the observation being replayed, so reports carry the
+/// original timestamps.
+struct ReplayClock {
+    platform: Arc<ReplayPlatform>,
+    origin: SystemTime,
+}
+
+impl Clock for ReplayClock {
+    fn now(&self) -> ClockSample {
+        let wall = self.platform.lock().timestamp;
+        ClockSample {
+            wall,
+            monotonic: wall.duration_since(self.origin).unwrap_or(Duration::ZERO),
+        }
+    }
+}
+
+/// Scores recorded observations again under `config`. VM detection,
+/// network connections, virtual desktops, input devices and focus changes
+/// aren't part of the observation log, so those modules are off in the
+/// replay.
+pub fn rescore(mut config: Config, observations: &[Observation]) -> Vec<DetectionReport> {
+    config.monitoring.enable_vm_detection = false;
+    config.monitoring.enable_network_monitoring = false;
+    config.monitoring.enable_virtual_desktop_detection = false;
+    config.monitoring.enable_input_device_monitoring = false;
+    config.hardware.display_sample_seconds = None;
+    config.overlays.focus_sample_millis = None;
+    config.correlation.watch_dirs.clear();
+
+    let platform = Arc::new(ReplayPlatform::new());
+    let mut detector = FairviewDetector::with_platform(config, platform.clone());
+    let origin = observations.iter().map(|o| o.timestamp).min().unwrap_or(SystemTime::UNIX_EPOCH);
+    detector.set_clock(Box::new(ReplayClock { platform: platform.clone(), origin }));
+
+    let mut reports = Vec::new();
+    for observation in observations {
+        let phase = observation.phase;
+        let scan_number = observation.scan_number;
+        platform.set(observation.clone());
+        match phase {
+            ObservationPhase::Baseline => detector.collect_baseline(),
+            ObservationPhase::Scan => {
+                detector.resume_scan_count(scan_number.saturating_sub(1));
+                reports.push(detector.scan());
+            }
+        }
+    }
+    reports
+}
+
+/// Appends observations to a JSON lines file, one per baseline or scan.
+pub struct ObservationLog {
+    file: File,
+}
+
+impl ObservationLog {
+    pub fn create(path: &std::path::Path) -> Result<Self, String> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Failed to open observation log {}: {}", path.display(), e))?;
+        Ok(Self { file })
+    }
+
+    pub fn write(&mut self, observation: &Observation) -> Result<(), String> {
+        let mut line = serde_json::to_vec(observation).map_err(|e| format!("Failed to serialize observation: {}", e))?;
+        line.push(b'\n');
+        self.file
+            .write_all(&line)
+            .and_then(|()| self.file.flush())
+            .map_err(|e| format!("Failed to write observation log: {}", e))
+    }
+}
+
+/// Reads an observation log written by `ObservationLog`.
+pub fn read_log(path: &str) -> Result<Vec<Observation>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let mut observations = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let observation = serde_json::from_str(&line)
+            .map_err(|e| format!("Invalid observation on line {} of {}: {}", index + 1, path, e))?;
+        observations.push(observation);
+    }
+    Ok(observations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware_detector::ConnectionType;
+    use crate::test_support::{capabilities, display, process, MockPlatform};
+
+    #[test]
+    fn test_rescoring_recorded_observations_reproduces_reports() {
+        let platform = MockPlatform {
+            processes: vec![
+                (process(1, "explorer.exe", "C:\\Windows\\explorer.exe"), capabilities(false, false, false)),
+                (process(20, "helper.exe", "C:\\Users\\me\\helper.exe"), capabilities(true, true, true)),
+            ],
+            displays: vec![display("0", ConnectionType::HDMI), display("1", ConnectionType::Virtual)],
+            audio_monitoring: true,
+            ..Default::default()
+        };
+        let mut config = Config::default();
+        config.monitoring.enable_vm_detection = false;
+        config.monitoring.enable_network_monitoring = false;
+        config.monitoring.enable_virtual_desktop_detection = false;
+        config.monitoring.enable_input_device_monitoring = false;
+
+        let recorder = Arc::new(RecordingPlatform::new(Arc::new(platform)));
+        let mut detector = FairviewDetector::with_platform(config.clone(), recorder.clone());
+        let mut lines = Vec::new();
+        let mut originals = Vec::new();
+        for _ in 0..2 {
+            let report = detector.scan();
+            let observation = recorder.take(ObservationPhase::Scan, report.scan_number, report.timestamp);
+            lines.push(serde_json::to_string(&observation).unwrap());
+            originals.push(report);
+        }
+
+        let observations: Vec<Observation> = lines.iter().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(observations[1].processes.len(), 2);
+        assert!(observations[1].displays.as_ref().is_some_and(|d| d.as_ref().is_ok_and(|d| d.has_virtual_display)));
+
+        let rescored = rescore(config, &observations);
+        assert_eq!(rescored.len(), originals.len());
+        for (original, rescored) in originals.iter().zip(&rescored) {
+            assert_eq!(rescored.scan_number, original.scan_number);
+            assert_eq!(rescored.timestamp, original.timestamp);
+            assert_eq!(rescored.overall_risk_score, original.overall_risk_score);
+            assert_eq!(rescored.severity, original.severity);
+            let flagged = |report: &DetectionReport| -> Vec<u32> {
+                report.suspicious_processes.iter().map(|p| p.pid).collect()
+            };
+            assert_eq!(flagged(rescored), flagged(original));
+            assert!(!flagged(rescored).is_empty());
+        }
+    }
+}
\ No newline at end of file
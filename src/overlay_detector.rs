@@ -1,16 +1,207 @@
+use crate::config::OverlayConfig;
+use crate::platform::{PlatformProvider, SystemPlatform};
 use crate::OverlayWindow;
+use std::collections::HashMap;
+use std::sync::Arc;
 
-pub struct OverlayDetector;
+pub struct OverlayDetector {
+    platform: Arc<dyn PlatformProvider>,
+    /// First and last scan each overlay id was reported in.
+    sightings: HashMap<String, (usize, usize)>,
+}
+
+/// Raw attributes of a top-level window, gathered per platform and passed to
+/// `classify_window` so the decision can be tested without a desktop.
+#[derive(Debug, Clone, Default)]
+pub struct WindowAttributes {
+    pub width: u32,
+    pub height: u32,
+    pub is_layered: bool,
+    pub is_transparent: bool,
+    pub is_topmost: bool,
+    pub is_visible: bool,
+    /// Hidden by DWM (`DWMWA_CLOAKED`) but still composited, e.g. for thumbnails.
+    pub is_cloaked: bool,
+    /// Display affinity hides the window from screen capture
+    /// (`WDA_MONITOR` or `WDA_EXCLUDEFROMCAPTURE`).
+    pub excluded_from_capture: bool,
+    pub title: String,
+    pub class_name: String,
+    /// Constant alpha set with `SetLayeredWindowAttributes(LWA_ALPHA)`.
+    pub alpha: Option<u8>,
+}
+
+pub const WDA_MONITOR: u32 = 0x01;
+pub const WDA_EXCLUDEFROMCAPTURE: u32 = 0x11;
+
+/// True for display affinities that keep a window out of screen capture:
+/// `WDA_MONITOR` blacks it out, `WDA_EXCLUDEFROMCAPTURE` removes it entirely.
+pub fn affinity_excludes_capture(affinity: u32) -> bool {
+    affinity == WDA_MONITOR || affinity == WDA_EXCLUDEFROMCAPTURE
+}
+
+/// Decides whether a window should be reported as an overlay. A window that
+/// hides itself from screen capture is reported while it is visible or
+/// cloaked; a hidden one shows nothing to hide. Otherwise it has to be a
+/// layered, click-through or always-on-top window that is visible, topmost,
+/// or cloaked.
+pub fn classify_window(attributes: &WindowAttributes) -> bool {
+    if attributes.width <= 50 || attributes.height <= 50 {
+        return false;
+    }
+
+    if attributes.excluded_from_capture {
+        return attributes.is_visible || attributes.is_cloaked;
+    }
+
+    attributes.is_layered
+        && (attributes.is_transparent || attributes.is_topmost)
+        && (attributes.is_visible || attributes.is_topmost || attributes.is_cloaked)
+}
+
+/// Builds the reported overlay for a window that passes `classify_window`,
+/// carrying over its title and class name so a reviewer can tell what it is.
+pub fn overlay_from_attributes(
+    handle: usize,
+    position: (i32, i32),
+    owner_pid: u32,
+    attributes: WindowAttributes,
+) -> Option<OverlayWindow> {
+    if !classify_window(&attributes) {
+        return None;
+    }
+
+    Some(OverlayWindow {
+        id: String::new(),
+        handle,
+        position,
+        size: (attributes.width, attributes.height),
+        owner_pid,
+        is_transparent: attributes.is_transparent,
+        is_topmost: attributes.is_topmost,
+        is_cloaked: attributes.is_cloaked,
+        excluded_from_capture: attributes.excluded_from_capture,
+        title: attributes.title,
+        class_name: attributes.class_name,
+        owner_flagged: false,
+        alpha: attributes.alpha,
+        risk_score: 0.0,
+        first_seen_scan: 0,
+    })
+}
+
+/// True when the overlay's owning process or title is on the allowlist. An
+/// empty title never matches, so untitled windows are only allowed by owner.
+pub fn is_allowlisted(overlay: &OverlayWindow, owner_name: Option<&str>, allowlist: &OverlayConfig) -> bool {
+    let owner_allowed = owner_name.is_some_and(|name| {
+        allowlist
+            .allowed_processes
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(name))
+    });
+    if owner_allowed {
+        return true;
+    }
+
+    if overlay.title.is_empty() {
+        return false;
+    }
+    let title = overlay.title.to_lowercase();
+    allowlist
+        .allowed_titles
+        .iter()
+        .any(|allowed| title.contains(&allowed.to_lowercase()))
+}
+
+impl Default for OverlayDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl OverlayDetector {
     pub fn new() -> Self {
-        Self
+        Self::with_platform(Arc::new(SystemPlatform))
+    }
+
+    pub fn with_platform(platform: Arc<dyn PlatformProvider>) -> Self {
+        Self { platform, sightings: HashMap::new() }
+    }
+
+    /// Whether overlay detection is implemented on this OS; elsewhere
+    /// `find_hidden_overlays` always comes back empty.
+    pub fn is_supported() -> bool {
+        cfg!(target_os = "windows")
     }
 
     pub fn find_hidden_overlays(&self) -> Vec<OverlayWindow> {
+        self.platform.overlays()
+    }
+
+    /// Gives each overlay its `id` and `first_seen_scan` and sorts them by id,
+    /// so the same windows come out the same way whatever order the OS listed
+    /// them in. Windows that would share an id are told apart by handle, with
+    /// `-2`, `-3` and so on after the first. An id unseen for
+    /// `FORGET_AFTER_SCANS` scans counts as new when it comes back.
+    pub fn identify(&mut self, overlays: &mut Vec<OverlayWindow>, scan_number: usize) {
+        let mut keyed: Vec<(String, OverlayWindow)> =
+            overlays.drain(..).map(|overlay| (overlay_id(&overlay), overlay)).collect();
+        keyed.sort_by(|(a, left), (b, right)| a.cmp(b).then(left.handle.cmp(&right.handle)));
+
+        let mut previous: Option<String> = None;
+        let mut repeat = 1;
+        for (base, mut overlay) in keyed {
+            repeat = if previous.as_ref() == Some(&base) { repeat + 1 } else { 1 };
+            overlay.id = if repeat == 1 { base.clone() } else { format!("{}-{}", base, repeat) };
+            previous = Some(base);
+
+            let sighting = self.sightings.entry(overlay.id.clone()).or_insert((scan_number, scan_number));
+            if scan_number.saturating_sub(sighting.1) > FORGET_AFTER_SCANS {
+                sighting.0 = scan_number;
+            }
+            sighting.1 = scan_number;
+            overlay.first_seen_scan = sighting.0;
+            overlays.push(overlay);
+        }
+        overlays.sort_by(|a, b| a.id.cmp(&b.id));
+        self.sightings.retain(|_, (_, last)| scan_number.saturating_sub(*last) <= FORGET_AFTER_SCANS);
+    }
+}
+
+/// Pixels per position bucket in `overlay_id`, so a window nudged by a few
+/// pixels keeps its id.
+const POSITION_BUCKET: i32 = 100;
+
+/// Scans an overlay can go unreported before its id counts as new again.
+const FORGET_AFTER_SCANS: usize = 60;
+
+/// 16 hex digits of FNV-1a over the owner PID, title, class name and
+/// position bucket. The handle is left out: Windows reuses handles, and
+/// other platforms may not have one at all.
+pub fn overlay_id(overlay: &OverlayWindow) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |part: &str| {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    };
+    feed(&overlay.owner_pid.to_string());
+    feed(&overlay.title);
+    feed(&overlay.class_name);
+    feed(&format!(
+        "{},{}",
+        overlay.position.0.div_euclid(POSITION_BUCKET),
+        overlay.position.1.div_euclid(POSITION_BUCKET)
+    ));
+    format!("{:016x}", hash)
+}
+
+impl SystemPlatform {
+    pub(crate) fn current_overlays(&self) -> Vec<OverlayWindow> {
         #[cfg(target_os = "windows")]
         {
-            return self.find_windows_overlays();
+            self.find_windows_overlays()
         }
 
         #[cfg(not(target_os = "windows"))]
@@ -20,8 +211,12 @@ impl OverlayDetector {
     }
 }
 
+/// Longest window title read per window; longer captions are cut off.
 #[cfg(target_os = "windows")]
-impl OverlayDetector {
+const MAX_TITLE_CHARS: usize = 512;
+
+#[cfg(target_os = "windows")]
+impl SystemPlatform {
     fn find_windows_overlays(&self) -> Vec<OverlayWindow> {
         use std::sync::{Arc, Mutex};
         use windows::Win32::UI::WindowsAndMessaging::*;
@@ -44,46 +239,203 @@ impl OverlayDetector {
         lparam: windows::Win32::Foundation::LPARAM
     ) -> windows::Win32::Foundation::BOOL {
         use std::sync::Mutex;
+        use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_CLOAKED};
         use windows::Win32::UI::WindowsAndMessaging::*;
         use windows::Win32::Foundation::*;
 
         let overlays: &Mutex<Vec<OverlayWindow>> =
             &*(lparam.0 as *const Mutex<Vec<OverlayWindow>>);
 
+        let mut rect = RECT::default();
+        if GetWindowRect(hwnd, &mut rect).is_err() {
+            return BOOL(1);
+        }
+
         let ex_style = GetWindowLongW(hwnd, GWL_EXSTYLE) as u32;
+
+        let mut cloaked: u32 = 0;
+        let is_cloaked = DwmGetWindowAttribute(
+            hwnd,
+            DWMWA_CLOAKED,
+            &mut cloaked as *mut u32 as *mut _,
+            std::mem::size_of::<u32>() as u32,
+        )
+        .is_ok()
+            && cloaked != 0;
+
+        let mut affinity: u32 = 0;
+        let excluded_from_capture = GetWindowDisplayAffinity(hwnd, &mut affinity).is_ok()
+            && affinity_excludes_capture(affinity);
+
+        // For windows of other processes GetWindowTextW reads the cached
+        // caption instead of sending WM_GETTEXT, so a hung or protected
+        // window yields an empty title rather than blocking or failing.
+        let title_len = (GetWindowTextLengthW(hwnd).max(0) as usize).min(MAX_TITLE_CHARS);
+        let mut title_buf = vec![0u16; title_len + 1];
+        let title_len = GetWindowTextW(hwnd, &mut title_buf).max(0) as usize;
+
+        let mut class_buf = [0u16; 256];
+        let class_len = GetClassNameW(hwnd, &mut class_buf).max(0) as usize;
+
         let is_layered = (ex_style & WS_EX_LAYERED.0) != 0;
-        let is_transparent = (ex_style & WS_EX_TRANSPARENT.0) != 0;
-        let is_topmost = (ex_style & WS_EX_TOPMOST.0) != 0;
-
-        if is_layered && (is_transparent || is_topmost) {
-            let mut rect = RECT::default();
-
-            if GetWindowRect(hwnd, &mut rect).is_ok() {
-                let width = (rect.right - rect.left) as u32;
-                let height = (rect.bottom - rect.top) as u32;
-
-                if width > 50 && height > 50 {
-                    let mut pid: u32 = 0;
-                    GetWindowThreadProcessId(hwnd, Some(&mut pid));
-
-                    let is_visible = IsWindowVisible(hwnd).as_bool();
-
-                    if is_visible || is_topmost {
-                        if let Ok(mut overlays_guard) = overlays.lock() {
-                            overlays_guard.push(OverlayWindow {
-                                handle: hwnd.0 as usize,
-                                position: (rect.left, rect.top),
-                                size: (width, height),
-                                owner_pid: pid,
-                                is_transparent,
-                                is_topmost,
-                            });
-                        }
-                    }
-                }
+        let mut alpha: u8 = 255;
+        let mut layered_flags = LAYERED_WINDOW_ATTRIBUTES_FLAGS(0);
+        // Fails for windows drawn with UpdateLayeredWindow (per-pixel alpha).
+        let alpha = (is_layered
+            && GetLayeredWindowAttributes(hwnd, None, Some(&mut alpha), Some(&mut layered_flags)).is_ok()
+            && (layered_flags.0 & LWA_ALPHA.0) != 0)
+            .then_some(alpha);
+
+        let attributes = WindowAttributes {
+            width: (rect.right - rect.left).max(0) as u32,
+            height: (rect.bottom - rect.top).max(0) as u32,
+            is_layered,
+            is_transparent: (ex_style & WS_EX_TRANSPARENT.0) != 0,
+            is_topmost: (ex_style & WS_EX_TOPMOST.0) != 0,
+            is_visible: IsWindowVisible(hwnd).as_bool(),
+            is_cloaked,
+            excluded_from_capture,
+            title: String::from_utf16_lossy(&title_buf[..title_len]),
+            class_name: String::from_utf16_lossy(&class_buf[..class_len]),
+            alpha,
+        };
+
+        let mut pid: u32 = 0;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+
+        if let Some(overlay) = overlay_from_attributes(hwnd.0 as usize, (rect.left, rect.top), pid, attributes) {
+            if let Ok(mut overlays_guard) = overlays.lock() {
+                overlays_guard.push(overlay);
             }
         }
 
         BOOL(1)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window() -> WindowAttributes {
+        WindowAttributes {
+            width: 400,
+            height: 300,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_visible_layered_topmost_window_is_overlay() {
+        let attributes = WindowAttributes {
+            is_layered: true,
+            is_topmost: true,
+            is_visible: true,
+            ..window()
+        };
+        assert!(classify_window(&attributes));
+        assert!(!classify_window(&WindowAttributes { is_layered: false, ..attributes }));
+    }
+
+    #[test]
+    fn test_cloaked_transparent_window_is_overlay() {
+        let attributes = WindowAttributes {
+            is_layered: true,
+            is_transparent: true,
+            is_visible: false,
+            is_cloaked: true,
+            ..window()
+        };
+        assert!(classify_window(&attributes));
+        assert!(!classify_window(&WindowAttributes { is_cloaked: false, ..attributes }));
+    }
+
+    #[test]
+    fn test_affinity_classification() {
+        assert!(!affinity_excludes_capture(0x00));
+        assert!(affinity_excludes_capture(WDA_MONITOR));
+        assert!(affinity_excludes_capture(WDA_EXCLUDEFROMCAPTURE));
+        assert!(!affinity_excludes_capture(0x10));
+    }
+
+    fn overlay_titled(title: &str) -> OverlayWindow {
+        OverlayWindow {
+            id: String::new(),
+            handle: 1,
+            position: (0, 0),
+            size: (400, 300),
+            owner_pid: 42,
+            is_transparent: true,
+            is_topmost: true,
+            is_cloaked: false,
+            excluded_from_capture: false,
+            title: title.to_string(),
+            class_name: String::new(),
+            owner_flagged: false,
+            alpha: None,
+            risk_score: 0.0,
+            first_seen_scan: 0,
+        }
+    }
+
+    #[test]
+    fn test_overlay_carries_title_and_class_name() {
+        let attributes = WindowAttributes {
+            is_layered: true,
+            is_topmost: true,
+            is_visible: true,
+            title: "Interview Copilot".to_string(),
+            class_name: "Chrome_WidgetWin_1".to_string(),
+            ..window()
+        };
+
+        let overlay = overlay_from_attributes(7, (10, 20), 42, attributes.clone()).unwrap();
+        assert_eq!(overlay.title, "Interview Copilot");
+        assert_eq!(overlay.class_name, "Chrome_WidgetWin_1");
+        assert_eq!(overlay.size, (400, 300));
+        assert_eq!(overlay.owner_pid, 42);
+
+        assert!(overlay_from_attributes(7, (10, 20), 42, WindowAttributes { is_layered: false, ..attributes }).is_none());
+    }
+
+    #[test]
+    fn test_allowlisted_title_is_excluded() {
+        let allowlist = OverlayConfig::default();
+        assert!(is_allowlisted(&overlay_titled("Discord Overlay - #general"), None, &allowlist));
+        assert!(is_allowlisted(&overlay_titled("volume control"), None, &allowlist));
+        assert!(!is_allowlisted(&overlay_titled("Interview Copilot"), None, &allowlist));
+        assert!(!is_allowlisted(&overlay_titled(""), Some("helper.exe"), &allowlist));
+        assert!(is_allowlisted(&overlay_titled(""), Some("shellexperiencehost.exe"), &allowlist));
+    }
+
+    #[test]
+    fn test_same_window_keeps_its_id_across_scans() {
+        let panel = |handle, position| OverlayWindow { handle, position, owner_pid: 42, ..overlay_titled("Copilot") };
+        let mut detector = OverlayDetector::with_platform(Arc::new(crate::test_support::MockPlatform::default()));
+
+        let mut first = vec![panel(9, (310, 120)), overlay_titled("Notes"), panel(7, (10, 20))];
+        detector.identify(&mut first, 1);
+        // Listed in another order, with new handles and one panel moved a little.
+        let mut second = vec![panel(3, (40, 60)), overlay_titled("Notes"), panel(5, (330, 150))];
+        detector.identify(&mut second, 2);
+
+        let ids = |overlays: &[OverlayWindow]| overlays.iter().map(|o| o.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&first), ids(&second));
+        assert!(first.windows(2).all(|pair| pair[0].id < pair[1].id));
+        assert_eq!(second.iter().find(|o| o.position == (40, 60)).unwrap().id, overlay_id(&panel(7, (10, 20))));
+        assert!(second.iter().all(|overlay| overlay.first_seen_scan == 1));
+    }
+
+    #[test]
+    fn test_visible_capture_excluded_window_is_overlay() {
+        let attributes = WindowAttributes {
+            excluded_from_capture: true,
+            is_visible: true,
+            ..window()
+        };
+        assert!(classify_window(&attributes));
+        assert!(classify_window(&WindowAttributes { is_visible: false, is_cloaked: true, ..attributes.clone() }));
+        assert!(!classify_window(&WindowAttributes { is_visible: false, ..attributes.clone() }));
+        assert!(!classify_window(&WindowAttributes { width: 10, ..attributes }));
+    }
 }
\ No newline at end of file
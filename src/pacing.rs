@@ -0,0 +1,145 @@
+use crate::config::ScanConfig;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Decides how long to wait between scans. Normally the loop keeps a
+/// start-to-start cadence of `interval_seconds`; when a scan overruns that
+/// interval the pacer falls back to a jittered minimum sleep and, if enabled,
+/// doubles the effective interval up to `max_interval_seconds`.
+pub struct ScanPacer {
+    base: Duration,
+    effective: Duration,
+    floor: Duration,
+    cap: Duration,
+    auto_extend: bool,
+    jitter: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntervalAdjustment {
+    pub previous_interval_seconds: u64,
+    pub effective_interval_seconds: u64,
+    pub scan_duration_ms: u64,
+    pub reason: String,
+}
+
+#[derive(Debug)]
+pub struct PacingDecision {
+    pub sleep: Duration,
+    pub overran: bool,
+    pub adjustment: Option<IntervalAdjustment>,
+}
+
+impl ScanPacer {
+    pub fn new(config: &ScanConfig) -> Self {
+        let base = Duration::from_secs(config.interval_seconds);
+        Self {
+            base,
+            effective: base,
+            floor: Duration::from_secs(config.min_sleep_seconds),
+            cap: Duration::from_secs(config.max_interval_seconds).max(base),
+            auto_extend: config.auto_extend_interval,
+            jitter: config.backoff_jitter,
+        }
+    }
+
+    pub fn effective_interval(&self) -> Duration {
+        self.effective
+    }
+
+    pub fn after_scan<R: Rng>(&mut self, scan_duration: Duration, rng: &mut R) -> PacingDecision {
+        let previous = self.effective;
+        let overran = scan_duration >= self.effective;
+
+        if overran && self.auto_extend {
+            self.effective = (self.effective * 2).min(self.cap);
+        } else if !overran && self.effective > self.base && scan_duration < self.effective / 4 {
+            // Scans are comfortably fast again, so step back toward the configured interval.
+            self.effective = (self.effective / 2).max(self.base);
+        }
+
+        let adjustment = (self.effective != previous).then(|| IntervalAdjustment {
+            previous_interval_seconds: previous.as_secs(),
+            effective_interval_seconds: self.effective.as_secs(),
+            scan_duration_ms: scan_duration.as_millis() as u64,
+            reason: if overran {
+                "Scan overran the interval".to_string()
+            } else {
+                "Scan duration recovered".to_string()
+            },
+        });
+
+        let sleep = if overran {
+            self.floor.mul_f64(1.0 + rng.gen_range(0.0..=self.jitter))
+        } else {
+            (self.effective - scan_duration).max(self.floor)
+        };
+
+        PacingDecision {
+            sleep,
+            overran,
+            adjustment,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn pacer() -> ScanPacer {
+        let mut config = Config::default().scan;
+        config.interval_seconds = 30;
+        config.min_sleep_seconds = 5;
+        config.max_interval_seconds = 90;
+        config.backoff_jitter = 0.2;
+        ScanPacer::new(&config)
+    }
+
+    #[test]
+    fn test_normal_scan_keeps_cadence() {
+        let mut pacer = pacer();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let decision = pacer.after_scan(Duration::from_secs(2), &mut rng);
+        assert!(!decision.overran);
+        assert!(decision.adjustment.is_none());
+        assert_eq!(decision.sleep, Duration::from_secs(28));
+    }
+
+    #[test]
+    fn test_overrunning_scans_extend_up_to_cap() {
+        let mut pacer = pacer();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for expected in [60, 90, 90] {
+            let decision = pacer.after_scan(Duration::from_secs(120), &mut rng);
+            assert!(decision.overran);
+            assert!(decision.sleep >= Duration::from_secs(5));
+            assert!(decision.sleep <= Duration::from_secs(6));
+            assert_eq!(pacer.effective_interval(), Duration::from_secs(expected));
+        }
+
+        let recovered = pacer.after_scan(Duration::from_secs(1), &mut rng);
+        let adjustment = recovered.adjustment.unwrap();
+        assert_eq!(adjustment.previous_interval_seconds, 90);
+        assert_eq!(adjustment.effective_interval_seconds, 45);
+    }
+
+    #[test]
+    fn test_overrun_without_auto_extend_only_floors_sleep() {
+        let mut config = Config::default().scan;
+        config.auto_extend_interval = false;
+        let mut pacer = ScanPacer::new(&config);
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let decision = pacer.after_scan(Duration::from_secs(45), &mut rng);
+        assert!(decision.overran);
+        assert!(decision.adjustment.is_none());
+        assert!(decision.sleep >= Duration::from_secs(config.min_sleep_seconds));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,186 @@
+use crate::audio_detector::{CaptureStreams, EndpointState};
+use crate::focus_detector::ForegroundWindow;
+use crate::hardware_detector::{DisplayConfiguration, RemoteDesktopStatus};
+use crate::process_monitor::{ProcessCapabilities, ProcessSource, SystemProcessSource};
+use crate::system_load::SystemLoad;
+use crate::tcc::TccGrant;
+use crate::vm_detector::VmCheckResult;
+use crate::{OverlayWindow, Process};
+use std::sync::Arc;
+
+/// The OS primitives the process, hardware and audio detectors are built on.
+/// `SystemPlatform` calls the real APIs and commands for the current OS;
+/// tests plug in a mock so whole scans run deterministically on any host.
+pub trait PlatformProvider: ProcessSource + Sync {
+    fn displays(&self) -> Result<DisplayConfiguration, String>;
+
+    /// Friendly names of attached video capture devices, deduplicated.
+    fn video_capture_devices(&self) -> Result<Vec<String>, String>;
+
+    fn remote_desktop(&self) -> RemoteDesktopStatus;
+
+    /// Whether an app is capturing or processing audio in real time.
+    fn audio_monitoring(&self) -> bool;
+
+    /// Audio capture streams counted and attributed to applications. Only
+    /// read on Linux so far; `None` elsewhere, where `audio_monitoring` is
+    /// all there is.
+    fn audio_capture_streams(&self) -> Option<CaptureStreams>;
+
+    /// Audio endpoints and whether a stream is running on each. Only read on
+    /// Linux so far; other platforms report none.
+    fn audio_endpoints(&self) -> Result<Vec<EndpointState>, String>;
+
+    /// Windows that may be overlays, before the allowlist is applied. Only
+    /// enumerated on Windows so far; other platforms report none.
+    fn overlays(&self) -> Vec<OverlayWindow>;
+
+    /// PID owning the foreground window. Only read on Windows so far.
+    fn foreground_process(&self) -> Option<u32>;
+
+    /// The window with input focus and its owner, read once per scan. `None`
+    /// when nothing has focus or the platform can't tell (no EWMH window
+    /// manager on Linux, say).
+    fn foreground_window(&self) -> Option<ForegroundWindow>;
+
+    /// Whether Cloud Clipboard uploads copies to the user's other devices.
+    /// Only read on Windows; `None` elsewhere.
+    fn cloud_clipboard_sync(&self) -> Option<bool>;
+
+    /// This moment's VM signals, before smoothing across scans.
+    fn vm_signals(&self) -> VmCheckResult;
+
+    /// System-wide CPU and memory use, `None` where it can't be read.
+    fn system_load(&self) -> Option<SystemLoad>;
+
+    /// Takes this scan's snapshot of the machine, which process listing,
+    /// CPU-based audio detection, VM checks and `system_load` then read.
+    fn refresh_snapshot(&self);
+}
+
+/// The real OS. The platform-specific code lives next to the detector that
+/// interprets it.
+pub struct SystemPlatform;
+
+impl ProcessSource for SystemPlatform {
+    fn processes(&self) -> Vec<Process> {
+        SystemProcessSource.processes()
+    }
+
+    fn capabilities(&self, process: &Process) -> ProcessCapabilities {
+        SystemProcessSource.capabilities(process)
+    }
+
+    fn current_session(&self) -> Option<u32> {
+        SystemProcessSource.current_session()
+    }
+
+    fn own_pid(&self) -> Option<u32> {
+        SystemProcessSource.own_pid()
+    }
+
+    fn signature_verified(&self, process: &Process) -> Option<bool> {
+        SystemProcessSource.signature_verified(process)
+    }
+
+    fn permission_grants(&self, process: &Process) -> Vec<TccGrant> {
+        SystemProcessSource.permission_grants(process)
+    }
+}
+
+impl PlatformProvider for SystemPlatform {
+    fn displays(&self) -> Result<DisplayConfiguration, String> {
+        self.current_display_configuration()
+    }
+
+    fn video_capture_devices(&self) -> Result<Vec<String>, String> {
+        self.current_video_capture_devices()
+    }
+
+    fn remote_desktop(&self) -> RemoteDesktopStatus {
+        self.current_remote_desktop()
+    }
+
+    fn audio_monitoring(&self) -> bool {
+        self.detect_realtime_audio_processing()
+    }
+
+    fn audio_capture_streams(&self) -> Option<CaptureStreams> {
+        #[cfg(target_os = "linux")]
+        {
+            self.linux_capture_streams()
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            None
+        }
+    }
+
+    fn audio_endpoints(&self) -> Result<Vec<EndpointState>, String> {
+        #[cfg(target_os = "linux")]
+        {
+            self.linux_endpoint_states()
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            Ok(Vec::new())
+        }
+    }
+
+    fn overlays(&self) -> Vec<OverlayWindow> {
+        self.current_overlays()
+    }
+
+    fn foreground_process(&self) -> Option<u32> {
+        self.current_foreground_process()
+    }
+
+    fn foreground_window(&self) -> Option<ForegroundWindow> {
+        self.current_foreground_window()
+    }
+
+    fn cloud_clipboard_sync(&self) -> Option<bool> {
+        self.current_cloud_clipboard_sync()
+    }
+
+    fn vm_signals(&self) -> VmCheckResult {
+        self.current_vm_signals()
+    }
+
+    fn system_load(&self) -> Option<SystemLoad> {
+        self.current_system_load()
+    }
+
+    fn refresh_snapshot(&self) {
+        crate::snapshot::shared().refresh()
+    }
+}
+
+/// Lets the process monitor share the provider the other detectors use.
+impl ProcessSource for Arc<dyn PlatformProvider> {
+    fn processes(&self) -> Vec<Process> {
+        self.as_ref().processes()
+    }
+
+    fn capabilities(&self, process: &Process) -> ProcessCapabilities {
+        self.as_ref().capabilities(process)
+    }
+
+    fn current_session(&self) -> Option<u32> {
+        self.as_ref().current_session()
+    }
+
+    fn own_pid(&self) -> Option<u32> {
+        self.as_ref().own_pid()
+    }
+
+    fn signature_verified(&self, process: &Process) -> Option<bool> {
+        self.as_ref().signature_verified(process)
+    }
+
+    fn permission_grants(&self, process: &Process) -> Vec<TccGrant> {
+        self.as_ref().permission_grants(process)
+    }
+}
\ No newline at end of file
@@ -1,15 +1,246 @@
-use crate::Process;
-use crate::config::Config;
+use crate::{Process, ProcessStats, ReasonCode};
+use crate::config::{Config, HeuristicsConfig, WhitelistProfile};
+use crate::tcc::TccGrant;
+use serde::{Deserialize, Serialize};
 use sysinfo::System;
-use std::time::SystemTime;
-use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+use std::collections::{HashMap, HashSet};
+
+/// Where process listings and capability checks come from. The real OS is
+/// behind `SystemProcessSource`; tests and benchmarks plug in synthetic ones.
+pub trait ProcessSource: Send {
+    fn processes(&self) -> Vec<Process>;
+    fn capabilities(&self, process: &Process) -> ProcessCapabilities;
+
+    /// Session Fairview itself runs in, when the platform has sessions.
+    fn current_session(&self) -> Option<u32> {
+        None
+    }
+
+    /// Fairview's own PID, which a working listing of this machine always
+    /// includes. `None` where the listing isn't of the machine Fairview runs
+    /// on, as with replays and synthetic sources.
+    fn own_pid(&self) -> Option<u32> {
+        None
+    }
+
+    /// Whether the executable's code signature verifies. `None` when the
+    /// platform has no check or the file can't be read.
+    fn signature_verified(&self, _process: &Process) -> Option<bool> {
+        None
+    }
+
+    /// Privacy permission entries (macOS TCC) recorded for the process.
+    /// Empty where the platform keeps no such record.
+    fn permission_grants(&self, _process: &Process) -> Vec<TccGrant> {
+        Vec::new()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessCapabilities {
+    pub screen_capture: bool,
+    pub audio_capture: bool,
+    pub accessibility: bool,
+    /// Links the graphics stack (DXGI, Direct3D, DWM) without showing a
+    /// capture session. Nearly every GPU-accelerated app does, so this only
+    /// adds a small weight and is not counted as a capability.
+    pub screen_capture_hint: bool,
+    /// Loaded modules (file names only) behind each capability. Only the
+    /// Windows checks are module-based; elsewhere these stay empty.
+    pub screen_capture_modules: Vec<String>,
+    pub audio_capture_modules: Vec<String>,
+    pub accessibility_modules: Vec<String>,
+    /// The OS refused to let Fairview inspect the process (typically one
+    /// running with higher privileges), so the flags above are unknown rather
+    /// than false.
+    pub inspection_denied: bool,
+    /// The process exited between being listed and being inspected, so
+    /// there was nothing left to inspect.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub process_exited: bool,
+}
+
+/// Modules named per capability in a reason; the rest are counted.
+const MAX_EVIDENCE_MODULES: usize = 3;
+
+impl ProcessCapabilities {
+    /// The reason text for `code`, followed by the modules that triggered it
+    /// when known, e.g. "Has screen capture permission (screen capture:
+    /// graphicscapture.dll)".
+    pub fn reason(&self, code: ReasonCode) -> String {
+        let (label, modules) = match code {
+            ReasonCode::ScreenCapture => ("screen capture", &self.screen_capture_modules),
+            ReasonCode::ScreenCaptureHint => ("graphics", &self.screen_capture_modules),
+            ReasonCode::AudioCapture => ("audio capture", &self.audio_capture_modules),
+            ReasonCode::Accessibility => ("accessibility", &self.accessibility_modules),
+            _ => return code.description().to_string(),
+        };
+        if modules.is_empty() {
+            return code.description().to_string();
+        }
+
+        let mut listed = modules.iter().take(MAX_EVIDENCE_MODULES).cloned().collect::<Vec<_>>().join(", ");
+        if modules.len() > MAX_EVIDENCE_MODULES {
+            listed.push_str(&format!(", +{} more", modules.len() - MAX_EVIDENCE_MODULES));
+        }
+        format!("{} ({}: {})", code.description(), label, listed)
+    }
+
+    pub fn count(&self) -> usize {
+        [self.screen_capture, self.audio_capture, self.accessibility]
+            .iter()
+            .filter(|&&b| b)
+            .count()
+    }
+}
+
+pub struct SystemProcessSource;
+
+/// How strongly a Windows process's loaded modules point at screen capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenCaptureEvidence {
+    None,
+    /// Links DXGI or Direct3D, like any GPU app.
+    LinksGraphics,
+    /// Loaded a component that only capture sessions use.
+    CaptureSession,
+}
+
+/// Modules loaded only by processes holding a capture session: the
+/// Windows.Graphics.Capture runtime and wrappers around DXGI Desktop
+/// Duplication. Duplication itself lives in dxgi.dll, which is why linking
+/// DXGI alone is only a hint.
+const CAPTURE_SESSION_MODULES: &[&str] = &["graphicscapture.dll", "desktopduplication", "screen_capture_lite"];
+
+const GRAPHICS_MODULES: &[&str] = &["dxgi.dll", "dwmapi.dll", "d3d11.dll", "gdi32.dll"];
+
+/// Loaded modules whose name contains any of `patterns`, case-insensitively.
+pub fn matching_modules(loaded_modules: &[String], patterns: &[&str]) -> Vec<String> {
+    loaded_modules
+        .iter()
+        .filter(|m| {
+            let m = m.to_lowercase();
+            patterns.iter().any(|p| m.contains(p))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Grades the screen capture evidence and returns the modules behind it.
+#[cfg(target_os = "windows")]
+const AUDIO_MODULES: &[&str] = &["audioses.dll", "wasapi", "winmm.dll", "dsound.dll"];
+
+#[cfg(target_os = "windows")]
+const ACCESSIBILITY_MODULES: &[&str] = &["uiautomation", "oleacc.dll"];
+
+pub fn screen_capture_evidence(loaded_modules: &[String]) -> (ScreenCaptureEvidence, Vec<String>) {
+    let session = matching_modules(loaded_modules, CAPTURE_SESSION_MODULES);
+    if !session.is_empty() {
+        return (ScreenCaptureEvidence::CaptureSession, session);
+    }
+
+    let graphics = matching_modules(loaded_modules, GRAPHICS_MODULES);
+    if !graphics.is_empty() {
+        (ScreenCaptureEvidence::LinksGraphics, graphics)
+    } else {
+        (ScreenCaptureEvidence::None, Vec::new())
+    }
+}
+
+/// Extensions dropped from a process name to get its stem.
+const EXECUTABLE_EXTENSIONS: &[&str] = &[".exe", ".app"];
+
+/// Stored as the path of processes whose executable can't be resolved,
+/// usually for lack of permission.
+pub const UNKNOWN_PATH: &str = "Unknown";
+
+/// Canonical view of a process for whitelist and name matching: lowercased,
+/// with the executable extension split off and `/` as the path separator, so
+/// `OBS64.EXE`, `obs64.exe` and a Linux `obs64` all compare alike.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedProcess {
+    pub name: String,
+    pub stem: String,
+    pub path: String,
+}
+
+pub fn normalize_process(process: &Process) -> NormalizedProcess {
+    let name = normalize_name(&process.name);
+    NormalizedProcess {
+        stem: strip_executable_extension(&name).to_string(),
+        name,
+        path: normalize_path(&process.path),
+    }
+}
+
+pub fn normalize_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+pub fn normalize_path(path: &str) -> String {
+    path.trim().replace('\\', "/").to_lowercase().trim_end_matches('/').to_string()
+}
+
+fn strip_executable_extension(name: &str) -> &str {
+    EXECUTABLE_EXTENSIONS
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext))
+        .unwrap_or(name)
+}
+
+impl NormalizedProcess {
+    /// A pattern with an extension (`code.exe`) matches that executable, with
+    /// or without the extension on this platform, or any name containing it.
+    /// A bare pattern (`obs`, `visual studio`) matches any stem containing it.
+    pub fn name_matches(&self, pattern: &str) -> bool {
+        let pattern = normalize_name(pattern);
+        let pattern_stem = strip_executable_extension(&pattern);
+        if pattern_stem.is_empty() {
+            return false;
+        }
+
+        if pattern_stem.len() == pattern.len() {
+            self.stem.contains(pattern_stem)
+        } else {
+            self.stem == pattern_stem || self.name.contains(&pattern)
+        }
+    }
+
+    /// Exact executable match: `python`, `python.exe` and `PYTHON.EXE` all
+    /// name the `python` executable, `python3` doesn't.
+    pub fn stem_is(&self, name: &str) -> bool {
+        let name = normalize_name(name);
+        !name.is_empty() && self.stem == strip_executable_extension(&name)
+    }
+
+    /// Whether the executable path couldn't be resolved.
+    pub fn has_unknown_path(&self) -> bool {
+        self.path.is_empty() || self.path == normalize_path(UNKNOWN_PATH)
+    }
+
+    /// True when the executable lives in `directory` or below it. Never true
+    /// for an unknown path, which can't vouch for any directory.
+    pub fn is_under(&self, directory: &str) -> bool {
+        let directory = normalize_path(directory);
+        !directory.is_empty()
+            && !self.has_unknown_path()
+            && self.path.starts_with(&directory)
+            && matches!(self.path.as_bytes().get(directory.len()), None | Some(b'/'))
+    }
+}
 
 pub struct ProcessMonitor {
     baseline_processes: HashMap<u32, ProcessBaseline>,
     config: Config,
+    active_profile: Option<String>,
+    source: Box<dyn ProcessSource>,
+    /// This scan's listing, between `hold_listing` and `release_listing`.
+    held_listing: Option<Vec<Process>>,
 }
 
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 struct ProcessBaseline {
     name: String,
     path: String,
@@ -18,17 +249,59 @@ struct ProcessBaseline {
 
 impl ProcessMonitor {
     pub fn new(config: Config) -> Self {
+        Self::with_source(config, Box::new(SystemProcessSource))
+    }
+
+    pub fn with_source(config: Config, source: Box<dyn ProcessSource>) -> Self {
         Self {
             baseline_processes: HashMap::new(),
+            active_profile: config.whitelist.active_profile.clone(),
             config,
+            source,
+            held_listing: None,
+        }
+    }
+
+    /// Swaps in an updated config, keeping the baseline and active profile.
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
+    pub fn set_active_profile(&mut self, name: Option<&str>) -> Result<(), String> {
+        if let Some(name) = name {
+            if !self.config.whitelist.profiles.contains_key(name) {
+                return Err(format!("Unknown whitelist profile: {}", name));
+            }
         }
+        self.active_profile = name.map(|n| n.to_string());
+        Ok(())
+    }
+
+    pub fn active_profile_name(&self) -> Option<&str> {
+        self.active_profile.as_deref()
+    }
+
+    pub fn active_profile(&self) -> Option<&WhitelistProfile> {
+        self.active_profile
+            .as_ref()
+            .and_then(|name| self.config.whitelist.profiles.get(name))
     }
 
     pub fn collect_baseline(&mut self) {
         println!("[*] Collecting baseline processes...");
-        let processes = self.get_all_processes();
-        
-        for process in processes {
+        let count = self.rebaseline(&HashSet::new());
+        println!("[+] Baseline collected: {} processes", count);
+    }
+
+    /// Replaces the baseline with the processes running now and returns its
+    /// size. Processes in `carry_forward` keep their current standing: one
+    /// not in the old baseline stays out of the new one.
+    pub fn rebaseline(&mut self, carry_forward: &HashSet<u32>) -> usize {
+        let previous = std::mem::take(&mut self.baseline_processes);
+        for process in self.get_all_processes() {
+            if carry_forward.contains(&process.pid) && !previous.contains_key(&process.pid) {
+                continue;
+            }
             self.baseline_processes.insert(
                 process.pid,
                 ProcessBaseline {
@@ -38,45 +311,109 @@ impl ProcessMonitor {
                 },
             );
         }
-        
-        println!("[+] Baseline collected: {} processes", self.baseline_processes.len());
+        self.baseline_processes.len()
     }
 
+    /// Lists processes, keeping only those in the current session when
+    /// `current_session_only` is set. Processes whose session is unknown are
+    /// kept.
     pub fn get_all_processes(&self) -> Vec<Process> {
-        let mut system = System::new_all();
-        system.refresh_all();
-        
-        let mut processes = Vec::new();
-        
-        for (pid, process) in system.processes() {
-            processes.push(Process {
-                pid: pid.as_u32(),
-                name: process.name().to_string(),
-                path: process.exe()
-                    .map(|p| p.to_string_lossy().to_string())
-                    .unwrap_or_else(|| "Unknown".to_string()),
-            });
+        let processes = match self.held_listing {
+            Some(ref listing) => listing.clone(),
+            None => self.source.processes(),
+        };
+        if !self.config.monitoring.current_session_only {
+            return processes;
         }
-        
-        processes
+
+        match self.source.current_session() {
+            Some(session) => processes
+                .into_iter()
+                .filter(|p| p.session_id.unwrap_or(session) == session)
+                .collect(),
+            None => processes,
+        }
+    }
+
+    /// Lists the processes once and answers `get_all_processes` from that
+    /// listing until `release_listing`, so every module of a scan sees the
+    /// same processes without enumerating them again.
+    pub fn hold_listing(&mut self) {
+        self.held_listing = Some(self.source.processes());
+    }
+
+    pub fn release_listing(&mut self) {
+        self.held_listing = None;
+    }
+
+    pub fn capabilities(&self, process: &Process) -> ProcessCapabilities {
+        self.source.capabilities(process)
+    }
+
+    /// Whether `processes`, as listed by `get_all_processes`, look like a
+    /// listing that was blocked: nothing at all, or everything but Fairview
+    /// itself.
+    pub fn enumeration_blocked(&self, processes: &[Process]) -> bool {
+        processes.is_empty() || self.source.own_pid().is_some_and(|pid| processes.iter().all(|p| p.pid != pid))
+    }
+
+    pub fn signature_verified(&self, process: &Process) -> Option<bool> {
+        self.source.signature_verified(process)
+    }
+
+    pub fn permission_grants(&self, process: &Process) -> Vec<TccGrant> {
+        self.source.permission_grants(process)
+    }
+
+    /// Name and path of every process seen while collecting the baseline.
+    pub fn baseline_processes(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.baseline_processes
+            .values()
+            .map(|p| (p.name.as_str(), p.path.as_str()))
     }
 
     pub fn was_in_baseline(&self, pid: u32) -> bool {
         self.baseline_processes.contains_key(&pid)
     }
 
+    /// A low-footprint, recently started, windowless process holding screen or
+    /// audio capability: the profile of a background transcriber that stays
+    /// under the capability-count gate. Only Windows reports whether a process
+    /// has a window; elsewhere that is unknown and nothing matches.
+    pub fn matches_quiet_helper_profile(
+        process: &Process,
+        capabilities: &ProcessCapabilities,
+        heuristics: &HeuristicsConfig,
+    ) -> bool {
+        let stats = &process.stats;
+        heuristics.enable_quiet_helper
+            && (capabilities.screen_capture || capabilities.audio_capture)
+            && stats.memory_bytes <= heuristics.quiet_helper_max_memory_mb.saturating_mul(1024 * 1024)
+            && stats.cpu_usage <= heuristics.quiet_helper_max_cpu_percent
+            && stats.run_time_seconds <= heuristics.quiet_helper_max_age_seconds
+            && stats.has_visible_window == Some(false)
+    }
+
     pub fn is_whitelisted(&self, process: &Process) -> bool {
-        let name_lower = process.name.to_lowercase();
-        let path_lower = process.path.to_lowercase();
+        let normalized = normalize_process(process);
+        let profile = self.active_profile();
 
-        for whitelisted in &self.config.whitelist.processes {
-            if name_lower.contains(&whitelisted.to_lowercase()) {
+        if self.is_revoked(&normalized) {
+            return false;
+        }
+
+        let processes = self.config.whitelist.processes.iter()
+            .chain(profile.into_iter().flat_map(|p| p.processes.iter()));
+        for whitelisted in processes {
+            if normalized.name_matches(whitelisted) {
                 return true;
             }
         }
 
-        for whitelisted_dir in &self.config.whitelist.directories {
-            if path_lower.starts_with(&whitelisted_dir.to_lowercase()) {
+        let directories = self.config.whitelist.directories.iter()
+            .chain(profile.into_iter().flat_map(|p| p.directories.iter()));
+        for whitelisted_dir in directories {
+            if normalized.is_under(whitelisted_dir) {
                 return true;
             }
         }
@@ -84,66 +421,176 @@ impl ProcessMonitor {
         false
     }
 
-    pub fn has_screen_capture_permission(&self, process: &Process) -> bool {
+    /// Whether the active profile withdraws trust from this process,
+    /// overriding both the configured and the built-in whitelists.
+    pub fn is_revoked(&self, process: &NormalizedProcess) -> bool {
+        self.active_profile()
+            .map(|p| p.revoked_processes.iter().any(|r| process.name_matches(r)))
+            .unwrap_or(false)
+    }
+}
+
+impl SystemProcessSource {
+    fn list_processes(&self, system: &System) -> Vec<Process> {
+        #[cfg(target_os = "windows")]
+        let windowed_pids = Some(self.windowed_pids());
+        #[cfg(not(target_os = "windows"))]
+        let windowed_pids: Option<std::collections::HashSet<u32>> = None;
+
+        let mut drives = crate::drive::DriveResolver::load();
+        let mut processes = Vec::new();
+
+        // Capability and grant lookups for this listing share one read.
         #[cfg(target_os = "macos")]
-        {
-            self.check_macos_permission(process, "kTCCServiceScreenCapture")
+        crate::tcc::refresh();
+        
+        for (pid, process) in system.processes() {
+            let path = process.exe()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| UNKNOWN_PATH.to_string());
+            processes.push(Process {
+                pid: pid.as_u32(),
+                name: process.name().to_string(),
+                drive_type: drives.drive_type(&path),
+                parent_pid: process.parent().map(|parent| parent.as_u32()),
+                path,
+                stats: ProcessStats {
+                    memory_bytes: process.memory(),
+                    cpu_usage: process.cpu_usage(),
+                    run_time_seconds: process.run_time(),
+                    has_visible_window: windowed_pids.as_ref().map(|w| w.contains(&pid.as_u32())),
+                    gui_objects: gui_objects_of(pid.as_u32()),
+                },
+                session_id: session_of(pid.as_u32()),
+            });
         }
+        
+        processes
+    }
+}
+
+impl ProcessSource for SystemProcessSource {
+    /// Reads the shared snapshot, which `SystemPlatform::refresh_snapshot`
+    /// refreshes at the start of each scan.
+    fn processes(&self) -> Vec<Process> {
+        crate::snapshot::shared().read(|system| self.list_processes(system))
+    }
 
+    fn current_session(&self) -> Option<u32> {
+        session_of(std::process::id())
+    }
+
+    fn own_pid(&self) -> Option<u32> {
+        Some(std::process::id())
+    }
+
+    fn signature_verified(&self, process: &Process) -> Option<bool> {
         #[cfg(target_os = "windows")]
         {
-            self.check_windows_screen_capture(process)
+            authenticode_verified(&process.path)
         }
 
-        #[cfg(target_os = "linux")]
+        #[cfg(not(target_os = "windows"))]
         {
-            let name_lower = process.name.to_lowercase();
-            let known_apps = ["obs", "zoom", "teams", "discord", "slack", "chrome", "firefox"];
-            let suspicious = ["cluely", "interview", "assistant", "helper"];
-            
-            known_apps.iter().any(|&app| name_lower.contains(app)) ||
-            suspicious.iter().any(|&app| name_lower.contains(app))
+            let _ = process;
+            None
         }
     }
 
-    pub fn has_audio_capture_permission(&self, process: &Process) -> bool {
+    fn permission_grants(&self, process: &Process) -> Vec<TccGrant> {
         #[cfg(target_os = "macos")]
         {
-            self.check_macos_permission(process, "kTCCServiceMicrophone")
+            let Some(grants) = crate::tcc::current() else {
+                return Vec::new();
+            };
+            let bundle_id = crate::tcc::bundle_id(&process.path);
+            grants.of_client(&process.path, bundle_id.as_deref()).cloned().collect()
         }
 
-        #[cfg(target_os = "windows")]
+        #[cfg(not(target_os = "macos"))]
         {
-            self.check_windows_audio_capture(process)
-        }
-
-        #[cfg(target_os = "linux")]
-        {
-            self.check_linux_audio_capture(process)
+            let _ = process;
+            Vec::new()
         }
     }
 
-    pub fn has_accessibility_permission(&self, process: &Process) -> bool {
+    fn capabilities(&self, process: &Process) -> ProcessCapabilities {
         #[cfg(target_os = "macos")]
         {
-            self.check_macos_permission(process, "kTCCServiceAccessibility")
+            ProcessCapabilities {
+                screen_capture: self.check_macos_permission(process, crate::tcc::TccService::ScreenCapture),
+                audio_capture: self.check_macos_permission(process, crate::tcc::TccService::Microphone),
+                accessibility: self.check_macos_permission(process, crate::tcc::TccService::Accessibility),
+                ..Default::default()
+            }
         }
 
         #[cfg(target_os = "windows")]
         {
-            self.check_windows_accessibility(process)
+            use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_INVALID_PARAMETER};
+
+            let (loaded_modules, inspection_denied) = match self.get_loaded_modules(process.pid) {
+                Ok(modules) => (modules, false),
+                // The snapshot of a PID that no longer exists fails with
+                // an invalid parameter.
+                Err(e) if e.code() == ERROR_INVALID_PARAMETER.to_hresult() => {
+                    return ProcessCapabilities { process_exited: true, ..Default::default() };
+                }
+                Err(e) => (Vec::new(), e.code() == ERROR_ACCESS_DENIED.to_hresult()),
+            };
+            let (screen_capture, screen_capture_modules) = screen_capture_evidence(&loaded_modules);
+            let audio_capture_modules = matching_modules(&loaded_modules, AUDIO_MODULES);
+            let accessibility_modules = matching_modules(&loaded_modules, ACCESSIBILITY_MODULES);
+            ProcessCapabilities {
+                screen_capture: screen_capture == ScreenCaptureEvidence::CaptureSession,
+                audio_capture: !audio_capture_modules.is_empty(),
+                accessibility: !accessibility_modules.is_empty(),
+                screen_capture_hint: screen_capture == ScreenCaptureEvidence::LinksGraphics,
+                screen_capture_modules,
+                audio_capture_modules,
+                accessibility_modules,
+                inspection_denied,
+                process_exited: false,
+            }
         }
 
         #[cfg(target_os = "linux")]
         {
-            self.check_linux_accessibility(process)
+            let audio_capture = self.check_linux_audio_capture(process);
+            let accessibility = self.check_linux_accessibility(process);
+            // A `/proc` read that failed because the process is gone isn't
+            // a denial.
+            if (audio_capture.is_none() || accessibility.is_none())
+                && !std::path::Path::new(&format!("/proc/{}", process.pid)).exists()
+            {
+                return ProcessCapabilities { process_exited: true, ..Default::default() };
+            }
+            ProcessCapabilities {
+                screen_capture: self.check_linux_screen_capture(process),
+                audio_capture: audio_capture.unwrap_or(false),
+                accessibility: accessibility.unwrap_or(false),
+                inspection_denied: audio_capture.is_none() || accessibility.is_none(),
+                ..Default::default()
+            }
         }
     }
 }
 
 #[cfg(target_os = "macos")]
-impl ProcessMonitor {
-    fn check_macos_permission(&self, process: &Process, _service: &str) -> bool {
+impl SystemProcessSource {
+    /// Whether the process holds the TCC grant for `service`, looked up by
+    /// the bundle id of its app or, for command-line tools, its path.
+    fn check_macos_permission(&self, process: &Process, service: crate::tcc::TccService) -> bool {
+        let Some(grants) = crate::tcc::current() else {
+            // Without Full Disk Access, guess from the name as before;
+            // `tcc::refresh` has said so.
+            return Self::guess_macos_permission(process);
+        };
+        let bundle_id = crate::tcc::bundle_id(&process.path);
+        grants.allows(&process.path, bundle_id.as_deref(), service)
+    }
+
+    fn guess_macos_permission(process: &Process) -> bool {
         let name_lower = process.name.to_lowercase();
         let known_apps = ["obs", "zoom", "teams", "discord", "slack", "chrome", "firefox"];
         let suspicious = ["cluely", "interview", "assistant", "helper"];
@@ -154,107 +601,442 @@ impl ProcessMonitor {
 }
 
 #[cfg(target_os = "windows")]
-impl ProcessMonitor {
-    fn check_windows_screen_capture(&self, process: &Process) -> bool {
-        let loaded_modules = self.get_loaded_modules(process.pid);
-        
-        let screen_capture_dlls = vec!["dxgi.dll", "dwmapi.dll", "d3d11.dll", "gdi32.dll"];
+fn session_of(pid: u32) -> Option<u32> {
+    use windows::Win32::System::RemoteDesktop::ProcessIdToSessionId;
+
+    let mut session = 0;
+    unsafe { ProcessIdToSessionId(pid, &mut session) }.ok().map(|_| session)
+}
 
-        screen_capture_dlls.iter()
-            .any(|dll| loaded_modules.iter().any(|m| m.to_lowercase().contains(dll)))
+#[cfg(not(target_os = "windows"))]
+fn session_of(_pid: u32) -> Option<u32> {
+    None
+}
+
+/// Checks the file's Authenticode signature, embedded or, as for most of
+/// System32, through a system catalog. Revocation isn't checked, to stay
+/// offline.
+#[cfg(target_os = "windows")]
+fn authenticode_verified(path: &str) -> Option<bool> {
+    use std::mem::size_of;
+    use std::os::windows::io::AsRawHandle;
+    use windows::core::{w, HSTRING, PCWSTR};
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::Security::Cryptography::Catalog::*;
+    use windows::Win32::Security::WinTrust::*;
+
+    let file = std::fs::File::open(path).ok()?;
+    let handle = HANDLE(file.as_raw_handle() as isize);
+    let wide_path = HSTRING::from(path);
+
+    let mut file_info = WINTRUST_FILE_INFO {
+        cbStruct: size_of::<WINTRUST_FILE_INFO>() as u32,
+        pcwszFilePath: PCWSTR(wide_path.as_ptr()),
+        hFile: handle,
+        pgKnownSubject: std::ptr::null_mut(),
+    };
+    if unsafe { win_verify_trust(WTD_CHOICE_FILE, WINTRUST_DATA_0 { pFile: &mut file_info }) } {
+        return Some(true);
     }
 
-    fn check_windows_audio_capture(&self, process: &Process) -> bool {
-        let loaded_modules = self.get_loaded_modules(process.pid);
-        
-        let audio_dlls = vec!["audioses.dll", "wasapi", "winmm.dll", "dsound.dll"];
+    unsafe {
+        let mut admin = 0isize;
+        CryptCATAdminAcquireContext2(&mut admin, None, w!("SHA256"), None, 0).ok()?;
+        let verified = (|| {
+            let mut size = 0u32;
+            CryptCATAdminCalcHashFromFileHandle2(admin, handle, &mut size, None, 0).ok()?;
+            let mut hash = vec![0u8; size as usize];
+            CryptCATAdminCalcHashFromFileHandle2(admin, handle, &mut size, Some(hash.as_mut_ptr()), 0).ok()?;
 
-        audio_dlls.iter()
-            .any(|dll| loaded_modules.iter().any(|m| m.to_lowercase().contains(dll)))
+            let catalog = CryptCATAdminEnumCatalogFromHash(admin, &hash, 0, None);
+            if catalog == 0 {
+                return Some(false);
+            }
+            let mut info = CATALOG_INFO { cbStruct: size_of::<CATALOG_INFO>() as u32, wszCatalogFile: [0; 260] };
+            let verified = CryptCATCatalogInfoFromContext(catalog, &mut info, 0).is_ok() && {
+                let member_tag = HSTRING::from(hash.iter().map(|b| format!("{:02X}", b)).collect::<String>());
+                let mut catalog_info = WINTRUST_CATALOG_INFO {
+                    cbStruct: size_of::<WINTRUST_CATALOG_INFO>() as u32,
+                    pcwszCatalogFilePath: PCWSTR(info.wszCatalogFile.as_ptr()),
+                    pcwszMemberTag: PCWSTR(member_tag.as_ptr()),
+                    pcwszMemberFilePath: PCWSTR(wide_path.as_ptr()),
+                    hMemberFile: handle,
+                    pbCalculatedFileHash: hash.as_mut_ptr(),
+                    cbCalculatedFileHash: hash.len() as u32,
+                    hCatAdmin: admin,
+                    ..Default::default()
+                };
+                win_verify_trust(WTD_CHOICE_CATALOG, WINTRUST_DATA_0 { pCatalog: &mut catalog_info })
+            };
+            let _ = CryptCATAdminReleaseCatalogContext(admin, catalog, 0);
+            Some(verified)
+        })();
+        let _ = CryptCATAdminReleaseContext(admin, 0);
+        verified
     }
+}
 
-    fn check_windows_accessibility(&self, process: &Process) -> bool {
-        let loaded_modules = self.get_loaded_modules(process.pid);
-        
-        loaded_modules.iter().any(|m| {
-            let m_lower = m.to_lowercase();
-            m_lower.contains("uiautomation") || m_lower.contains("oleacc.dll")
-        })
+#[cfg(target_os = "windows")]
+unsafe fn win_verify_trust(
+    choice: windows::Win32::Security::WinTrust::WINTRUST_DATA_UNION_CHOICE,
+    subject: windows::Win32::Security::WinTrust::WINTRUST_DATA_0,
+) -> bool {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Security::WinTrust::*;
+
+    let mut data = WINTRUST_DATA {
+        cbStruct: std::mem::size_of::<WINTRUST_DATA>() as u32,
+        dwUIChoice: WTD_UI_NONE,
+        fdwRevocationChecks: WTD_REVOKE_NONE,
+        dwUnionChoice: choice,
+        Anonymous: subject,
+        dwStateAction: WTD_STATEACTION_VERIFY,
+        ..Default::default()
+    };
+    let mut action = WINTRUST_ACTION_GENERIC_VERIFY_V2;
+    let status = WinVerifyTrust(HWND(0), &mut action, &mut data as *mut _ as *mut std::ffi::c_void);
+
+    // Frees the state the verify call kept.
+    data.dwStateAction = WTD_STATEACTION_CLOSE;
+    WinVerifyTrust(HWND(0), &mut action, &mut data as *mut _ as *mut std::ffi::c_void);
+    status == 0
+}
+
+/// GDI plus USER objects the process holds.
+#[cfg(target_os = "windows")]
+fn gui_objects_of(pid: u32) -> Option<u32> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::*;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let count = GetGuiResources(handle, GR_GDIOBJECTS) + GetGuiResources(handle, GR_USEROBJECTS);
+        let _ = CloseHandle(handle);
+        Some(count)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn gui_objects_of(_pid: u32) -> Option<u32> {
+    None
+}
+
+/// Time since the executable at `path` was written, taking the later of its
+/// modification and creation times: a copy keeps the original mtime on some
+/// platforms but gets a new creation time. `None` when the metadata can't be
+/// read, in which case `now` isn't called. A timestamp later than `now` is
+/// ignored, so a forged future mtime doesn't hide the creation time.
+pub fn executable_age(path: &str, now: impl FnOnce() -> SystemTime) -> Option<Duration> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let now = now();
+    [metadata.modified().ok(), metadata.created().ok()]
+        .into_iter()
+        .flatten()
+        .filter_map(|written| now.duration_since(written).ok())
+        .min()
+}
+
+/// PIDs holding unusually many GUI objects for their size, the footprint of
+/// a tool repeatedly capturing or walking other windows. A process is an
+/// outlier when it holds at least `min_objects` and its objects per MB of
+/// memory reach `factor` times the median across processes holding any.
+pub fn gui_object_outliers(processes: &[Process], factor: f64, min_objects: u32) -> HashSet<u32> {
+    let density = |p: &Process| {
+        let memory_mb = (p.stats.memory_bytes as f64 / (1024.0 * 1024.0)).max(1.0);
+        p.stats.gui_objects.map(|objects| objects as f64 / memory_mb)
+    };
+
+    let mut densities: Vec<f64> = processes
+        .iter()
+        .filter(|p| p.stats.gui_objects.is_some_and(|n| n > 0))
+        .filter_map(density)
+        .collect();
+    if densities.is_empty() {
+        return HashSet::new();
+    }
+    densities.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let median = densities[densities.len() / 2];
+
+    processes
+        .iter()
+        .filter(|p| p.stats.gui_objects.is_some_and(|n| n >= min_objects))
+        .filter(|p| density(p).is_some_and(|d| d >= median * factor))
+        .map(|p| p.pid)
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+impl SystemProcessSource {
+    fn windowed_pids(&self) -> std::collections::HashSet<u32> {
+        use std::collections::HashSet;
+        use windows::Win32::Foundation::*;
+        use windows::Win32::UI::WindowsAndMessaging::*;
+
+        unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+            let pids = &mut *(lparam.0 as *mut HashSet<u32>);
+            if IsWindowVisible(hwnd).as_bool() {
+                let mut pid: u32 = 0;
+                GetWindowThreadProcessId(hwnd, Some(&mut pid));
+                pids.insert(pid);
+            }
+            BOOL(1)
+        }
+
+        let mut pids = HashSet::new();
+        unsafe {
+            let _ = EnumWindows(Some(collect), LPARAM(&mut pids as *mut HashSet<u32> as isize));
+        }
+        pids
     }
 
-    fn get_loaded_modules(&self, pid: u32) -> Vec<String> {
+    /// Module file names loaded by `pid`. Fails when the snapshot can't be
+    /// taken, e.g. with access denied for an elevated process.
+    fn get_loaded_modules(&self, pid: u32) -> windows::core::Result<Vec<String>> {
         use windows::Win32::System::Diagnostics::ToolHelp::*;
         use windows::Win32::Foundation::*;
         
         let mut modules = Vec::new();
         
         unsafe {
-            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPMODULE, pid);
-            
-            if let Ok(snapshot) = snapshot {
-                let mut module_entry = MODULEENTRY32W {
-                    dwSize: std::mem::size_of::<MODULEENTRY32W>() as u32,
-                    ..Default::default()
-                };
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPMODULE, pid)?;
 
-                if Module32FirstW(snapshot, &mut module_entry).is_ok() {
-                    loop {
-                        let module_name = String::from_utf16_lossy(
-                            &module_entry.szModule
-                                .iter()
-                                .take_while(|&&c| c != 0)
-                                .copied()
-                                .collect::<Vec<u16>>()
-                        );
-                        
-                        modules.push(module_name);
-
-                        if Module32NextW(snapshot, &mut module_entry).is_err() {
-                            break;
-                        }
+            let mut module_entry = MODULEENTRY32W {
+                dwSize: std::mem::size_of::<MODULEENTRY32W>() as u32,
+                ..Default::default()
+            };
+
+            if Module32FirstW(snapshot, &mut module_entry).is_ok() {
+                loop {
+                    let module_name = String::from_utf16_lossy(
+                        &module_entry.szModule
+                            .iter()
+                            .take_while(|&&c| c != 0)
+                            .copied()
+                            .collect::<Vec<u16>>()
+                    );
+                    
+                    modules.push(module_name);
+
+                    if Module32NextW(snapshot, &mut module_entry).is_err() {
+                        break;
                     }
                 }
-
-                let _ = CloseHandle(snapshot);
             }
+
+            let _ = CloseHandle(snapshot);
         }
         
-        modules
+        Ok(modules)
     }
 }
 
 #[cfg(target_os = "linux")]
-impl ProcessMonitor {
-    fn check_linux_audio_capture(&self, process: &Process) -> bool {
+impl SystemProcessSource {
+    fn check_linux_screen_capture(&self, process: &Process) -> bool {
+        let name_lower = process.name.to_lowercase();
+        let known_apps = ["obs", "zoom", "teams", "discord", "slack", "chrome", "firefox"];
+        let suspicious = ["cluely", "interview", "assistant", "helper"];
+        
+        known_apps.iter().any(|&app| name_lower.contains(app)) ||
+        suspicious.iter().any(|&app| name_lower.contains(app))
+    }
+
+    /// `None` when `/proc/<pid>/fd` can't be read for lack of permission.
+    fn check_linux_audio_capture(&self, process: &Process) -> Option<bool> {
         use std::fs;
         
         let fd_path = format!("/proc/{}/fd", process.pid);
         
-        if let Ok(entries) = fs::read_dir(&fd_path) {
-            for entry in entries.flatten() {
-                if let Ok(link) = fs::read_link(entry.path()) {
-                    let link_str = link.to_string_lossy();
-                    if link_str.contains("/dev/snd") || 
-                       link_str.contains("pulse") ||
-                       link_str.contains("pipewire") {
-                        return true;
+        match fs::read_dir(&fd_path) {
+            Ok(entries) => {
+                for entry in entries.flatten() {
+                    if let Ok(link) = fs::read_link(entry.path()) {
+                        let link_str = link.to_string_lossy();
+                        if link_str.contains("/dev/snd") || 
+                           link_str.contains("pulse") ||
+                           link_str.contains("pipewire") {
+                            return Some(true);
+                        }
                     }
                 }
             }
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => return None,
+            Err(_) => {}
         }
 
-        false
+        Some(false)
     }
 
-    fn check_linux_accessibility(&self, process: &Process) -> bool {
+    /// `None` when `/proc/<pid>/maps` can't be read for lack of permission.
+    fn check_linux_accessibility(&self, process: &Process) -> Option<bool> {
         use std::fs;
         
         let maps_path = format!("/proc/{}/maps", process.pid);
         
-        if let Ok(maps) = fs::read_to_string(maps_path) {
-            return maps.contains("at-spi") || maps.contains("atspi");
+        match fs::read_to_string(maps_path) {
+            Ok(maps) => Some(maps.contains("at-spi") || maps.contains("atspi")),
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => None,
+            Err(_) => Some(false),
         }
+    }
+}
 
-        false
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WhitelistProfile;
+
+    fn process(name: &str, path: &str) -> Process {
+        Process {
+            pid: 4242,
+            name: name.to_string(),
+            path: path.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_screen_capture_evidence_from_modules() {
+        let modules = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(screen_capture_evidence(&modules(&["KERNEL32.DLL", "user32.dll"])).0, ScreenCaptureEvidence::None);
+        assert_eq!(
+            screen_capture_evidence(&modules(&["dxgi.dll", "d3d11.dll"])),
+            (ScreenCaptureEvidence::LinksGraphics, modules(&["dxgi.dll", "d3d11.dll"]))
+        );
+        assert_eq!(
+            screen_capture_evidence(&modules(&["dxgi.dll", "d3d11.dll", "GraphicsCapture.dll"])),
+            (ScreenCaptureEvidence::CaptureSession, modules(&["GraphicsCapture.dll"]))
+        );
+    }
+
+    #[test]
+    fn test_reasons_name_matched_modules() {
+        let modules = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let capabilities = ProcessCapabilities {
+            screen_capture: true,
+            audio_capture: true,
+            screen_capture_modules: modules(&["GraphicsCapture.dll"]),
+            audio_capture_modules: matching_modules(
+                &modules(&["ntdll.dll", "AUDIOSES.DLL", "winmm.dll", "dsound.dll", "wasapi_shim.dll"]),
+                &["audioses.dll", "wasapi", "winmm.dll", "dsound.dll"],
+            ),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            capabilities.reason(ReasonCode::ScreenCapture),
+            "Has screen capture permission (screen capture: GraphicsCapture.dll)"
+        );
+        assert_eq!(
+            capabilities.reason(ReasonCode::AudioCapture),
+            "Has audio capture permission (audio capture: AUDIOSES.DLL, winmm.dll, dsound.dll, +1 more)"
+        );
+        assert_eq!(capabilities.reason(ReasonCode::Accessibility), "Has accessibility API access");
+        assert_eq!(capabilities.reason(ReasonCode::SuspiciousName), "Suspicious process name");
+    }
+
+    struct TwoSessionSource;
+
+    impl ProcessSource for TwoSessionSource {
+        fn processes(&self) -> Vec<Process> {
+            let in_session = |pid, session_id| Process { pid, session_id, ..process("notes.exe", "C:\\notes.exe") };
+            vec![in_session(1, Some(2)), in_session(2, Some(3)), in_session(3, None)]
+        }
+
+        fn capabilities(&self, _process: &Process) -> ProcessCapabilities {
+            ProcessCapabilities::default()
+        }
+
+        fn current_session(&self) -> Option<u32> {
+            Some(2)
+        }
+    }
+
+    #[test]
+    fn test_current_session_only_skips_other_sessions() {
+        let mut config = Config::default();
+        let pids = |config: &Config| {
+            ProcessMonitor::with_source(config.clone(), Box::new(TwoSessionSource))
+                .get_all_processes()
+                .iter()
+                .map(|p| p.pid)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(pids(&config), vec![1, 2, 3]);
+        config.monitoring.current_session_only = true;
+        assert_eq!(pids(&config), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_gui_object_outliers() {
+        let sized = |pid: u32, memory_mb: u64, gui_objects: Option<u32>| Process {
+            pid,
+            stats: ProcessStats { memory_bytes: memory_mb * 1024 * 1024, gui_objects, ..Default::default() },
+            ..process("app.exe", "C:\\app.exe")
+        };
+        // Ordinary apps hold a few GUI objects per MB.
+        let mut processes: Vec<Process> = (1..=20).map(|pid| sized(pid, 100 + pid as u64 * 10, Some(300 + pid * 20))).collect();
+        processes.push(sized(100, 20, Some(4000)));
+        processes.push(sized(101, 1, Some(60)));
+        processes.push(sized(102, 50, None));
+
+        assert_eq!(gui_object_outliers(&processes, 5.0, 500), HashSet::from([100]));
+        assert_eq!(gui_object_outliers(&processes, 5.0, 50), HashSet::from([100, 101]));
+        assert!(gui_object_outliers(&processes, 100.0, 500).is_empty());
+        assert!(gui_object_outliers(&processes[20..], 5.0, 500).is_empty());
+    }
+
+    #[test]
+    fn test_switching_profiles_changes_whitelist() {
+        let mut config = Config::default();
+        config.whitelist.profiles.insert(
+            "whiteboard".to_string(),
+            WhitelistProfile {
+                processes: vec!["excalidraw".to_string()],
+                revoked_processes: vec!["code.exe".to_string()],
+                ..Default::default()
+            },
+        );
+        let mut monitor = ProcessMonitor::new(config);
+
+        let drawing = process("excalidraw.exe", "C:\\Users\\candidate\\excalidraw.exe");
+        let ide = process("code.exe", "C:\\Users\\candidate\\AppData\\Local\\Programs\\code.exe");
+
+        assert!(!monitor.is_whitelisted(&drawing));
+        assert!(monitor.is_whitelisted(&ide));
+
+        monitor.set_active_profile(Some("whiteboard")).unwrap();
+        assert_eq!(monitor.active_profile_name(), Some("whiteboard"));
+        assert!(monitor.is_whitelisted(&drawing));
+        assert!(!monitor.is_whitelisted(&ide));
+
+        monitor.set_active_profile(None).unwrap();
+        assert!(!monitor.is_whitelisted(&drawing));
+        assert!(monitor.set_active_profile(Some("missing")).is_err());
+    }
+
+    #[test]
+    fn test_normalize_mixed_case_and_pathful_inputs() {
+        let normalized = normalize_process(&process("OBS64.EXE", "C:\\Program Files\\OBS-Studio\\bin\\OBS64.exe"));
+        assert_eq!(normalized, NormalizedProcess {
+            name: "obs64.exe".to_string(),
+            stem: "obs64".to_string(),
+            path: "c:/program files/obs-studio/bin/obs64.exe".to_string(),
+        });
+
+        assert!(normalized.name_matches("obs64.exe"));
+        assert!(normalized.name_matches("OBS"));
+        assert!(normalized.is_under("C:\\Program Files\\OBS-Studio\\"));
+        assert!(!normalized.is_under("C:\\Program Files\\OBS"));
+
+        let linux = normalize_process(&process("code", "/usr/share/code/code"));
+        assert!(linux.name_matches("code.exe"));
+        assert!(linux.is_under("/usr/share/code"));
+
+        let driver = normalize_process(&process("chromedriver.exe", "C:\\tools\\chromedriver.exe"));
+        assert!(!driver.name_matches("chrome.exe"));
+        assert!(driver.name_matches("chrome"));
     }
 }
\ No newline at end of file
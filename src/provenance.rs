@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use sysinfo::System;
+
+/// Stands in for a redacted value in reports and printed config.
+pub const REDACTED: &str = "<redacted>";
+
+/// The machine and OS account a report came from, read once at startup.
+/// Each part is `None` where the OS doesn't say.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub hostname: Option<String>,
+    pub os_username: Option<String>,
+    pub os_name: Option<String>,
+    pub os_version: Option<String>,
+}
+
+impl Provenance {
+    pub fn collect() -> Self {
+        Self {
+            hostname: System::host_name(),
+            os_username: current_username(),
+            os_name: System::name(),
+            os_version: System::os_version(),
+        }
+    }
+
+    /// The same with the username, which often names the candidate,
+    /// replaced by `REDACTED`.
+    pub fn redacted(self) -> Self {
+        Self {
+            os_username: self.os_username.map(|_| REDACTED.to_string()),
+            ..self
+        }
+    }
+}
+
+fn current_username() -> Option<String> {
+    let variable = if cfg!(target_os = "windows") { "USERNAME" } else { "USER" };
+    std::env::var(variable)
+        .ok()
+        .or_else(|| std::env::var("LOGNAME").ok())
+        .filter(|name| !name.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provenance_is_populated_and_username_redactable() {
+        let collected = Provenance::collect();
+        assert!(collected.hostname.is_some());
+        assert!(collected.os_name.is_some());
+
+        let provenance = Provenance {
+            os_username: Some("jdoe".to_string()),
+            ..collected
+        };
+        let redacted = provenance.clone().redacted();
+        assert_eq!(redacted.os_username.as_deref(), Some(REDACTED));
+        assert_eq!(redacted.hostname, provenance.hostname);
+        assert_eq!(redacted.os_version, provenance.os_version);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,269 @@
+use crate::alert::{format_alert_line, severity_label};
+use crate::config::{OutputSpec, ReportFormat};
+use crate::report_stream::Heartbeat;
+use crate::run_dir::RunDirectory;
+use crate::session::SessionVerdict;
+use crate::DetectionReport;
+use chrono::{DateTime, Utc};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Somewhere reports go. Every configured `output.report_formats` entry is
+/// one sink, and each emitted report is handed to all of them.
+pub trait ReportSink {
+    fn write_report(&mut self, report: &DetectionReport) -> Result<(), String>;
+
+    /// Change-only heartbeats. Only the per-report JSON files carry them.
+    fn write_heartbeat(&mut self, _heartbeat: &Heartbeat) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called once when monitoring stops, with the session's verdict.
+    fn finish(&mut self, _verdict: &SessionVerdict) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl ReportFormat {
+    /// Where the format writes inside the run directory when the spec names
+    /// no destination. `json` writes a file per report instead.
+    pub fn default_destination(self) -> Option<&'static str> {
+        match self {
+            ReportFormat::Json => None,
+            ReportFormat::Jsonl => Some("reports.jsonl"),
+            ReportFormat::Csv => Some("reports.csv"),
+            ReportFormat::Html => Some("session_report.html"),
+        }
+    }
+}
+
+impl OutputSpec {
+    /// The file this spec writes, `None` for per-report `json` files.
+    pub fn destination_file(&self) -> Option<&str> {
+        match self.format {
+            ReportFormat::Json => None,
+            format => self.destination.as_deref().or(format.default_destination()),
+        }
+    }
+}
+
+/// One sink per spec, writing inside `run_dir` (absolute destinations are
+/// kept as they are).
+pub fn sinks(specs: &[OutputSpec], run_dir: &RunDirectory) -> Vec<Box<dyn ReportSink>> {
+    specs
+        .iter()
+        .map(|spec| {
+            let path = || run_dir.file(spec.destination_file().unwrap_or_default());
+            let sink: Box<dyn ReportSink> = match spec.format {
+                ReportFormat::Json => Box::new(JsonFiles { run_dir: run_dir.clone() }),
+                ReportFormat::Jsonl => Box::new(JsonlFile { path: path() }),
+                ReportFormat::Csv => Box::new(CsvFile { path: path() }),
+                ReportFormat::Html => Box::new(HtmlPage { path: path(), rows: Vec::new() }),
+            };
+            sink
+        })
+        .collect()
+}
+
+/// A pretty-printed `detection_report_*.json` per report and a
+/// `detection_heartbeat_*.json` per heartbeat.
+struct JsonFiles {
+    run_dir: RunDirectory,
+}
+
+impl JsonFiles {
+    fn write(&self, filename: String, json: serde_json::Result<String>) -> Result<(), String> {
+        let json = json.map_err(|e| format!("Failed to serialize {}: {}", filename, e))?;
+        let path = self.run_dir.file(&filename);
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write report to {}: {}", path.display(), e))
+    }
+}
+
+impl ReportSink for JsonFiles {
+    fn write_report(&mut self, report: &DetectionReport) -> Result<(), String> {
+        let datetime: DateTime<Utc> = report.timestamp.into();
+        let filename =
+            format!("detection_report_{}_scan{}.json", datetime.format("%Y%m%d_%H%M%S"), report.scan_number);
+        self.write(filename, serde_json::to_string_pretty(report))
+    }
+
+    fn write_heartbeat(&mut self, heartbeat: &Heartbeat) -> Result<(), String> {
+        let datetime: DateTime<Utc> = heartbeat.timestamp.into();
+        let filename = format!("detection_heartbeat_{}.json", datetime.format("%Y%m%d_%H%M%S"));
+        self.write(filename, serde_json::to_string_pretty(heartbeat))
+    }
+}
+
+fn append(path: &PathBuf) -> Result<std::fs::File, String> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+/// Every report as one JSON line, appended to a single file.
+struct JsonlFile {
+    path: PathBuf,
+}
+
+impl ReportSink for JsonlFile {
+    fn write_report(&mut self, report: &DetectionReport) -> Result<(), String> {
+        crate::jsonl::write_line(&mut append(&self.path)?, report)
+            .map_err(|e| format!("{} ({})", e, self.path.display()))
+    }
+}
+
+/// One row per report for spreadsheets, with a header when the file is new.
+struct CsvFile {
+    path: PathBuf,
+}
+
+pub const CSV_HEADER: &str =
+    "timestamp,scan_number,overall_risk_score,severity,exceeds_threshold,suspicious_processes,hidden_overlays,summary";
+
+/// The CSV row for `report`, matching `CSV_HEADER`.
+pub fn csv_row(report: &DetectionReport) -> String {
+    [
+        crate::timestamp_format::format(&report.timestamp),
+        report.scan_number.to_string(),
+        format!("{:.2}", report.overall_risk_score),
+        severity_label(report.severity).to_string(),
+        report.exceeds_threshold.to_string(),
+        report.suspicious_processes.len().to_string(),
+        report.hidden_overlays.len().to_string(),
+        format_alert_line(report, usize::MAX),
+    ]
+    .iter()
+    .map(|field| csv_field(field))
+    .collect::<Vec<_>>()
+    .join(",")
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+impl ReportSink for CsvFile {
+    fn write_report(&mut self, report: &DetectionReport) -> Result<(), String> {
+        let new_file = std::fs::metadata(&self.path).map(|m| m.len() == 0).unwrap_or(true);
+        let mut out = append(&self.path)?;
+        let mut text = String::new();
+        if new_file {
+            text.push_str(CSV_HEADER);
+            text.push('\n');
+        }
+        text.push_str(&csv_row(report));
+        text.push('\n');
+        out.write_all(text.as_bytes()).map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))
+    }
+}
+
+/// A self-contained page listing every scan, rewritten after each report and
+/// topped with the session verdict once monitoring stops.
+struct HtmlPage {
+    path: PathBuf,
+    rows: Vec<String>,
+}
+
+impl HtmlPage {
+    fn render(&self, verdict: Option<&SessionVerdict>) -> String {
+        let status = match verdict {
+            Some(verdict) => format!(
+                "<h2 class=\"{}\">{}</h2>\n<p>{} scans, {} breached, peak risk {:.2} ({})</p>",
+                if verdict.suspected_cheating { "breach" } else { "clean" },
+                if verdict.suspected_cheating { "SUSPECTED CHEATING" } else { "CLEAN" },
+                verdict.total_scans,
+                verdict.breached_scans,
+                verdict.peak_risk_score,
+                severity_label(verdict.peak_severity),
+            ),
+            None => "<h2>Session in progress</h2>".to_string(),
+        };
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Fairview session report</title>\n\
+             <style>body{{font-family:sans-serif}}td,th{{padding:2px 8px;text-align:left}}\
+             .breach{{color:#b00}}.clean{{color:#070}}</style>\n</head>\n<body>\n\
+             <h1>Fairview session report</h1>\n{}\n<table>\n\
+             <tr><th>Scan</th><th>Time</th><th>Risk</th><th>Severity</th><th>Summary</th></tr>\n{}</table>\n</body>\n</html>\n",
+            status,
+            self.rows.concat()
+        )
+    }
+
+    fn write(&self, verdict: Option<&SessionVerdict>) -> Result<(), String> {
+        std::fs::write(&self.path, self.render(verdict))
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+impl ReportSink for HtmlPage {
+    fn write_report(&mut self, report: &DetectionReport) -> Result<(), String> {
+        self.rows.push(format!(
+            "<tr{}><td>{}</td><td>{}</td><td>{:.2}</td><td>{}</td><td>{}</td></tr>\n",
+            if report.exceeds_threshold { " class=\"breach\"" } else { "" },
+            report.scan_number,
+            crate::timestamp_format::format(&report.timestamp),
+            report.overall_risk_score,
+            severity_label(report.severity),
+            escape_html(&format_alert_line(report, usize::MAX)),
+        ));
+        self.write(None)
+    }
+
+    fn finish(&mut self, verdict: &SessionVerdict) -> Result<(), String> {
+        self.write(Some(verdict))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputConfig;
+    use crate::test_support::report;
+
+    #[test]
+    fn test_every_configured_format_receives_each_report() {
+        let output = OutputConfig {
+            output_dir: std::env::temp_dir()
+                .join(format!("fairview_formats_{}", std::process::id()))
+                .to_string_lossy()
+                .into_owned(),
+            ..Default::default()
+        };
+        let run_dir = RunDirectory::create(&output, None, Utc::now()).unwrap();
+        let specs = [
+            OutputSpec { format: ReportFormat::Jsonl, destination: None },
+            OutputSpec { format: ReportFormat::Csv, destination: Some("scans.csv".to_string()) },
+        ];
+
+        let mut sinks = sinks(&specs, &run_dir);
+        for scan in 1..=2 {
+            for sink in sinks.iter_mut() {
+                sink.write_report(&report(scan, 0.1 * scan as f64, false)).unwrap();
+            }
+        }
+        let jsonl = std::fs::read_to_string(run_dir.file("reports.jsonl")).unwrap();
+        let csv = std::fs::read_to_string(run_dir.file("scans.csv")).unwrap();
+        std::fs::remove_dir_all(run_dir.path()).ok();
+
+        let scans: Vec<usize> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str::<DetectionReport>(line).unwrap().scan_number)
+            .collect();
+        assert_eq!(scans, vec![1, 2]);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], CSV_HEADER);
+        assert!(rows[2].contains(",2,0.20,LOW,false,0,0,[LOW] Scan 2: risk 0.20 — no findings"));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,521 @@
+use crate::config::{ConsoleMode, OutputConfig};
+use crate::{alert, DetectionReport, PolicyBreach, ReasonCode, Severity};
+use serde::Serialize;
+use std::time::{Duration, SystemTime};
+
+/// What to write for a scan.
+#[derive(Debug)]
+pub enum Emission {
+    Report,
+    Heartbeat(Heartbeat),
+    Skip,
+}
+
+/// Minimal liveness record written, in change-only mode or while frozen after
+/// a breach, when nothing has been emitted for `heartbeat_seconds`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Heartbeat {
+    pub heartbeat: bool,
+    #[serde(with = "crate::timestamp_format")]
+    pub timestamp: SystemTime,
+    pub scan_number: usize,
+}
+
+/// The parts of a report that make it worth emitting again. Scores and
+/// timings drift every scan and are left out.
+#[derive(Debug, Clone, PartialEq)]
+struct Findings {
+    exceeds_threshold: bool,
+    severity: Severity,
+    policy_breach: Option<PolicyBreach>,
+    processes: Vec<(u32, Vec<ReasonCode>)>,
+    /// By `OverlayWindow::id`, which survives handle reuse.
+    overlays: Vec<String>,
+    parked_windows: Vec<usize>,
+    audio_monitoring: bool,
+    audio_loops: usize,
+    new_audio_inputs: usize,
+    hardware_flags: Vec<String>,
+    new_input_devices: usize,
+    cloud_clipboard_sync: bool,
+    /// Only whether the foreground app is a flagged one; ordinary focus
+    /// changes aren't findings.
+    suspicious_foreground: bool,
+    is_vm: bool,
+    module_failures: Vec<String>,
+}
+
+impl Findings {
+    fn of(report: &DetectionReport) -> Self {
+        let mut processes: Vec<_> = report
+            .suspicious_processes
+            .iter()
+            .map(|p| {
+                let mut codes = p.reason_codes.clone();
+                codes.sort();
+                (p.pid, codes)
+            })
+            .collect();
+        processes.sort();
+        let mut overlays: Vec<_> = report.hidden_overlays.iter().map(|o| o.id.clone()).collect();
+        overlays.sort();
+        let mut parked_windows: Vec<_> = report.parked_windows.iter().map(|w| w.handle).collect();
+        parked_windows.sort();
+
+        Self {
+            exceeds_threshold: report.exceeds_threshold,
+            severity: report.severity,
+            policy_breach: report.policy_breach,
+            processes,
+            overlays,
+            parked_windows,
+            audio_monitoring: report.audio_monitoring_detected,
+            audio_loops: report.audio_loops.len(),
+            new_audio_inputs: report.new_audio_inputs.len(),
+            hardware_flags: report
+                .hardware_suspicion
+                .as_ref()
+                .map(|h| h.flags.clone())
+                .unwrap_or_default(),
+            new_input_devices: report.new_input_devices.len(),
+            cloud_clipboard_sync: report.cloud_clipboard_sync,
+            suspicious_foreground: report.foreground_app.as_ref().is_some_and(|app| app.is_suspicious()),
+            is_vm: report.vm_detection.as_ref().is_some_and(|vm| vm.is_vm),
+            module_failures: report.module_failures.clone(),
+        }
+    }
+}
+
+/// Decides per scan whether to write the full report, a heartbeat or
+/// nothing. Without `changes_only` every report is written.
+pub struct ReportStream {
+    changes_only: bool,
+    heartbeat_interval: Option<Duration>,
+    freeze_on_breach: bool,
+    /// Severity of the last report written since `freeze_on_breach` froze
+    /// the stream.
+    frozen_at: Option<Severity>,
+    last_findings: Option<Findings>,
+    last_emitted: Option<SystemTime>,
+}
+
+impl ReportStream {
+    pub fn new(config: &OutputConfig) -> Self {
+        Self {
+            changes_only: config.changes_only,
+            heartbeat_interval: (config.heartbeat_seconds > 0)
+                .then(|| Duration::from_secs(config.heartbeat_seconds)),
+            freeze_on_breach: config.freeze_reports_on_breach,
+            frozen_at: None,
+            last_findings: None,
+            last_emitted: None,
+        }
+    }
+
+    pub fn next(&mut self, report: &DetectionReport) -> Emission {
+        if let Some(frozen_at) = self.frozen_at {
+            // A frozen stream still shows it is alive.
+            if report.severity <= frozen_at {
+                return self.heartbeat_if_due(report);
+            }
+            println!("[!] Severity escalated to {}; writing scan {}", alert::severity_label(report.severity), report.scan_number);
+            self.frozen_at = Some(report.severity);
+            return self.emit(report);
+        }
+        if self.freeze_on_breach && report.exceeds_threshold {
+            println!("[*] Scan {} breached; further reports are only written if the severity escalates", report.scan_number);
+            self.frozen_at = Some(report.severity);
+            return self.emit(report);
+        }
+
+        if !self.changes_only {
+            return self.emit(report);
+        }
+
+        let findings = Findings::of(report);
+        if self.last_findings.as_ref() != Some(&findings) {
+            self.last_findings = Some(findings);
+            return self.emit(report);
+        }
+
+        self.heartbeat_if_due(report)
+    }
+
+    fn emit(&mut self, report: &DetectionReport) -> Emission {
+        self.last_emitted = Some(report.timestamp);
+        Emission::Report
+    }
+
+    /// A heartbeat once nothing has been written for `heartbeat_interval`.
+    fn heartbeat_if_due(&mut self, report: &DetectionReport) -> Emission {
+        let Some(interval) = self.heartbeat_interval else {
+            return Emission::Skip;
+        };
+        let due = match self.last_emitted {
+            Some(last) => report.timestamp.duration_since(last).unwrap_or_default() >= interval,
+            None => true,
+        };
+        if !due {
+            return Emission::Skip;
+        }
+
+        self.last_emitted = Some(report.timestamp);
+        Emission::Heartbeat(Heartbeat {
+            heartbeat: true,
+            timestamp: report.timestamp,
+            scan_number: report.scan_number,
+        })
+    }
+}
+
+/// Smallest change in the overall risk score worth a delta line; smaller
+/// moves are scan-to-scan drift.
+const RISK_DELTA_MIN: f64 = 0.05;
+
+/// What changed between two consecutive scans, one console line per change,
+/// e.g. `+ flagged chrome.exe (PID 4120)`, `- overlay gone: Notes` or
+/// `risk 0.30→0.50`. Built from the same findings change-only output
+/// compares, so an empty result means change-only mode would skip the scan
+/// (apart from risk drift).
+pub fn render_delta(previous: &DetectionReport, current: &DetectionReport) -> Vec<String> {
+    let before = Findings::of(previous);
+    let after = Findings::of(current);
+    let mut lines = Vec::new();
+
+    if (current.overall_risk_score - previous.overall_risk_score).abs() >= RISK_DELTA_MIN {
+        lines.push(format!("risk {:.2}→{:.2}", previous.overall_risk_score, current.overall_risk_score));
+    }
+    if before.severity != after.severity {
+        lines.push(format!(
+            "severity {}→{}",
+            alert::severity_label(before.severity),
+            alert::severity_label(after.severity)
+        ));
+    }
+    match (before.exceeds_threshold, after.exceeds_threshold) {
+        (false, true) => lines.push("+ risk threshold exceeded".to_string()),
+        (true, false) => lines.push("- back within the risk threshold".to_string()),
+        _ => {}
+    }
+    if before.policy_breach != after.policy_breach {
+        if let Some(breach) = after.policy_breach {
+            lines.push(format!("+ policy breach: {}", breach_label(breach)));
+        } else if let Some(breach) = before.policy_breach {
+            lines.push(format!("- policy breach cleared: {}", breach_label(breach)));
+        }
+    }
+    if !before.is_vm && after.is_vm {
+        lines.push("+ virtual machine detected".to_string());
+    } else if before.is_vm && !after.is_vm {
+        lines.push("- virtual machine no longer detected".to_string());
+    }
+
+    for process in &current.suspicious_processes {
+        let label = format!("{} (PID {})", process.name, process.pid);
+        let Some((_, old_codes)) = before.processes.iter().find(|(pid, _)| *pid == process.pid) else {
+            lines.push(format!("+ flagged {}", label));
+            continue;
+        };
+        let added: Vec<&str> = process
+            .reason_codes
+            .iter()
+            .filter(|code| !old_codes.contains(code))
+            .map(|code| code.description())
+            .collect();
+        if !added.is_empty() {
+            lines.push(format!("~ {}: {}", label, added.join(", ")));
+        }
+    }
+    for process in &previous.suspicious_processes {
+        if !after.processes.iter().any(|(pid, _)| *pid == process.pid) {
+            lines.push(format!("- {} (PID {}) no longer flagged", process.name, process.pid));
+        }
+    }
+
+    for overlay in &current.hidden_overlays {
+        if !before.overlays.contains(&overlay.id) {
+            lines.push(format!("+ overlay {}", window_label(&overlay.title, overlay.handle)));
+        }
+    }
+    for overlay in &previous.hidden_overlays {
+        if !after.overlays.contains(&overlay.id) {
+            lines.push(format!("- overlay gone: {}", window_label(&overlay.title, overlay.handle)));
+        }
+    }
+    for window in &current.parked_windows {
+        if !before.parked_windows.contains(&window.handle) {
+            lines.push(format!(
+                "+ window parked on another desktop: {} ({})",
+                window_label(&window.title, window.handle),
+                window.owner_name
+            ));
+        }
+    }
+    for window in &previous.parked_windows {
+        if !after.parked_windows.contains(&window.handle) {
+            lines.push(format!("- parked window gone: {}", window_label(&window.title, window.handle)));
+        }
+    }
+
+    match (before.audio_monitoring, after.audio_monitoring) {
+        (false, true) => lines.push("+ audio monitoring detected".to_string()),
+        (true, false) => lines.push("- audio monitoring stopped".to_string()),
+        _ => {}
+    }
+    if before.audio_loops != after.audio_loops {
+        lines.push(format!("audio loops {}→{}", before.audio_loops, after.audio_loops));
+    }
+    if before.new_audio_inputs != after.new_audio_inputs {
+        lines.push(format!("new audio inputs {}→{}", before.new_audio_inputs, after.new_audio_inputs));
+    }
+    for flag in after.hardware_flags.iter().filter(|flag| !before.hardware_flags.contains(flag)) {
+        lines.push(format!("+ hardware: {}", flag));
+    }
+    for flag in before.hardware_flags.iter().filter(|flag| !after.hardware_flags.contains(flag)) {
+        lines.push(format!("- hardware cleared: {}", flag));
+    }
+    if before.new_input_devices != after.new_input_devices {
+        lines.push(format!("new input devices {}→{}", before.new_input_devices, after.new_input_devices));
+    }
+    match (before.cloud_clipboard_sync, after.cloud_clipboard_sync) {
+        (false, true) => lines.push("+ cloud clipboard sync turned on".to_string()),
+        (true, false) => lines.push("- cloud clipboard sync turned off".to_string()),
+        _ => {}
+    }
+    match (before.suspicious_foreground, after.suspicious_foreground) {
+        (false, true) => lines.push("+ flagged app in the foreground".to_string()),
+        (true, false) => lines.push("- flagged app left the foreground".to_string()),
+        _ => {}
+    }
+    for failure in after.module_failures.iter().filter(|failure| !before.module_failures.contains(failure)) {
+        lines.push(format!("+ module failure: {}", failure));
+    }
+    for failure in before.module_failures.iter().filter(|failure| !after.module_failures.contains(failure)) {
+        lines.push(format!("- module recovered: {}", failure));
+    }
+    lines
+}
+
+fn breach_label(breach: PolicyBreach) -> &'static str {
+    match breach {
+        PolicyBreach::VmHardFail => "virtual machine (vm.hard_fail)",
+        PolicyBreach::VirtualDisplayHardFail => "virtual display (hardware.virtual_display_hard_fail)",
+        PolicyBreach::WirelessDisplayHardFail => "wireless display (hardware.wireless_display_hard_fail)",
+        PolicyBreach::CustomRule => "custom rule",
+        PolicyBreach::MachineChanged => "machine changed",
+        PolicyBreach::Denylisted => "denylisted process",
+    }
+}
+
+fn window_label(title: &str, handle: usize) -> String {
+    if title.is_empty() {
+        format!("handle {}", handle)
+    } else {
+        title.to_string()
+    }
+}
+
+/// Decides per scan whether the console gets the full report or only the
+/// delta from the previous scan. The first scan is always printed in full,
+/// and in delta mode every `console_full_every`th one after it.
+pub struct ConsoleView {
+    delta: bool,
+    full_every: usize,
+    /// Scans printed since the last full report; `None` before the first.
+    since_full: Option<usize>,
+}
+
+impl ConsoleView {
+    pub fn new(config: &OutputConfig) -> Self {
+        Self {
+            delta: config.console == ConsoleMode::Delta,
+            full_every: config.console_full_every,
+            since_full: None,
+        }
+    }
+
+    pub fn next_is_full(&mut self) -> bool {
+        let full = match self.since_full {
+            None => true,
+            Some(since_full) => !self.delta || (self.full_every > 0 && since_full + 1 >= self.full_every),
+        };
+        self.since_full = Some(if full { 0 } else { self.since_full.unwrap_or(0) + 1 });
+        full
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{process, report};
+    use crate::{OverlayWindow, SuspiciousProcess};
+
+    fn config(changes_only: bool, heartbeat_seconds: u64) -> OutputConfig {
+        OutputConfig {
+            changes_only,
+            heartbeat_seconds,
+            ..OutputConfig::default()
+        }
+    }
+
+    /// Runs `scans` unchanged clean scans 30 seconds apart and returns what
+    /// each one emitted: `R` for a report, `H` for a heartbeat, `.` for none.
+    fn emissions(stream: &mut ReportStream, scans: usize) -> String {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        (1..=scans)
+            .map(|n| {
+                let mut scan = report(n, 0.1, false);
+                scan.timestamp = start + Duration::from_secs(30 * n as u64);
+                match stream.next(&scan) {
+                    Emission::Report => 'R',
+                    Emission::Heartbeat(heartbeat) => {
+                        assert_eq!(heartbeat.scan_number, n);
+                        'H'
+                    }
+                    Emission::Skip => '.',
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_heartbeat_cadence_when_nothing_changes() {
+        let mut stream = ReportStream::new(&config(true, 120));
+        assert_eq!(emissions(&mut stream, 10), "R...H...H.");
+
+        let mut stream = ReportStream::new(&config(true, 0));
+        assert_eq!(emissions(&mut stream, 10), "R.........");
+
+        let mut stream = ReportStream::new(&config(false, 120));
+        assert_eq!(emissions(&mut stream, 3), "RRR");
+    }
+
+    #[test]
+    fn test_changed_findings_are_emitted() {
+        let mut stream = ReportStream::new(&config(true, 120));
+        assert!(matches!(stream.next(&report(1, 0.1, false)), Emission::Report));
+        assert!(matches!(stream.next(&report(2, 0.2, false)), Emission::Skip));
+        assert!(matches!(stream.next(&report(3, 0.9, true)), Emission::Report));
+        assert!(matches!(stream.next(&report(4, 0.9, true)), Emission::Skip));
+    }
+
+    #[test]
+    fn test_reports_freeze_after_breach_until_escalation() {
+        let mut stream = ReportStream::new(&OutputConfig {
+            freeze_reports_on_breach: true,
+            ..OutputConfig::default()
+        });
+        let emitted: String = [(0.1, false), (0.6, true), (0.1, false), (0.7, true), (0.9, true), (0.9, true)]
+            .iter()
+            .enumerate()
+            .map(|(n, &(risk, breach))| match stream.next(&report(n + 1, risk, breach)) {
+                Emission::Report => 'R',
+                Emission::Heartbeat(_) => 'H',
+                Emission::Skip => '.',
+            })
+            .collect();
+        assert_eq!(emitted, "RR..R.");
+    }
+
+    #[test]
+    fn test_frozen_stream_still_sends_heartbeats() {
+        let mut stream = ReportStream::new(&OutputConfig {
+            freeze_reports_on_breach: true,
+            ..config(false, 120)
+        });
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let emitted: String = (1..=10)
+            .map(|n| {
+                let mut scan = report(n, 0.9, n > 1);
+                scan.timestamp = start + Duration::from_secs(30 * n as u64);
+                match stream.next(&scan) {
+                    Emission::Report => 'R',
+                    Emission::Heartbeat(_) => 'H',
+                    Emission::Skip => '.',
+                }
+            })
+            .collect();
+        assert_eq!(emitted, "RR...H...H");
+    }
+
+    fn flagged(pid: u32, name: &str, reason_codes: Vec<ReasonCode>) -> SuspiciousProcess {
+        let process = process(pid, name, "/tmp/x");
+        SuspiciousProcess {
+            pid,
+            name: process.name,
+            path: process.path,
+            risk_score: 0.5,
+            reasons: Vec::new(),
+            reason_codes,
+            started_during_interview: false,
+            is_whitelisted: false,
+            drive_type: Default::default(),
+        }
+    }
+
+    fn overlay(handle: usize, title: &str) -> OverlayWindow {
+        let mut overlay = OverlayWindow {
+            id: String::new(),
+            handle,
+            position: (0, 0),
+            size: (400, 300),
+            owner_pid: 42,
+            is_transparent: true,
+            is_topmost: true,
+            is_cloaked: false,
+            excluded_from_capture: false,
+            title: title.to_string(),
+            class_name: String::new(),
+            owner_flagged: false,
+            alpha: None,
+            risk_score: 0.5,
+            first_seen_scan: 0,
+        };
+        overlay.id = crate::overlay_detector::overlay_id(&overlay);
+        overlay
+    }
+
+    #[test]
+    fn test_render_delta() {
+        let quiet = report(1, 0.3, false);
+        assert!(render_delta(&quiet, &report(2, 0.32, false)).is_empty());
+
+        let mut before = report(1, 0.3, false);
+        before.suspicious_processes = vec![flagged(7, "obs64.exe", vec![ReasonCode::ScreenCapture])];
+        before.hidden_overlays = vec![overlay(11, "Notes")];
+        let mut after = report(2, 0.5, false);
+        after.suspicious_processes = vec![
+            flagged(7, "obs64.exe", vec![ReasonCode::ScreenCapture, ReasonCode::StartedDuringInterview]),
+            flagged(9, "chrome.exe", vec![ReasonCode::ScreenCapture]),
+        ];
+        after.module_failures = vec!["audio: device busy".to_string()];
+
+        assert_eq!(
+            render_delta(&before, &after),
+            vec![
+                "risk 0.30→0.50".to_string(),
+                "~ obs64.exe (PID 7): Started during interview".to_string(),
+                "+ flagged chrome.exe (PID 9)".to_string(),
+                "- overlay gone: Notes".to_string(),
+                "+ module failure: audio: device busy".to_string(),
+            ]
+        );
+
+        let breached = report(3, 0.9, true);
+        let lines = render_delta(&after, &breached);
+        assert_eq!(&lines[..3], ["risk 0.50→0.90", "severity MEDIUM→CRITICAL", "+ risk threshold exceeded"]);
+        assert!(lines.contains(&"- chrome.exe (PID 9) no longer flagged".to_string()));
+        assert!(lines.contains(&"- module recovered: audio: device busy".to_string()));
+    }
+
+    #[test]
+    fn test_console_full_report_cadence() {
+        let output = OutputConfig { console: ConsoleMode::Delta, console_full_every: 3, ..OutputConfig::default() };
+        let mut view = ConsoleView::new(&output);
+        let shown: Vec<bool> = (0..7).map(|_| view.next_is_full()).collect();
+        assert_eq!(shown, [true, false, false, true, false, false, true]);
+
+        let mut view = ConsoleView::new(&OutputConfig::default());
+        assert!((0..3).all(|_| view.next_is_full()));
+    }
+}
\ No newline at end of file
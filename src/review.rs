@@ -0,0 +1,125 @@
+use crate::clock::Clock;
+use crate::DetectionReport;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewDecision {
+    Confirmed,
+    FalsePositive,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperatorAnnotation {
+    pub decision: ReviewDecision,
+    pub note: Option<String>,
+    #[serde(with = "crate::timestamp_format")]
+    pub annotated_at: SystemTime,
+}
+
+/// Pauses on a breaching report so the operator can annotate it. Reports
+/// within the threshold are left untouched.
+pub fn review_report<R: BufRead, W: Write>(
+    report: &mut DetectionReport,
+    input: &mut R,
+    output: &mut W,
+    clock: &dyn Clock,
+) {
+    if report.exceeds_threshold {
+        report.operator_annotation = prompt_for_annotation(input, output, report.scan_number, clock);
+    }
+}
+
+/// Asks the operator to confirm or dismiss a breaching scan. Returns `None`
+/// when the operator skips the review or input is closed. The answer is
+/// stamped from `clock`.
+pub fn prompt_for_annotation<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    scan_number: usize,
+    clock: &dyn Clock,
+) -> Option<OperatorAnnotation> {
+    let _ = writeln!(
+        output,
+        "[?] Scan #{} exceeded the risk threshold. Mark as (c)onfirmed, (f)alse positive, or press Enter to skip:",
+        scan_number
+    );
+
+    let decision = loop {
+        let line = read_trimmed_line(input)?;
+        match line.to_lowercase().as_str() {
+            "" => return None,
+            "c" | "confirm" | "confirmed" => break ReviewDecision::Confirmed,
+            "f" | "fp" | "false positive" | "false-positive" => break ReviewDecision::FalsePositive,
+            other => {
+                let _ = writeln!(output, "[!] Unrecognized response '{}', enter c, f, or nothing to skip:", other);
+            }
+        }
+    };
+
+    let _ = writeln!(output, "[?] Optional note (press Enter to skip):");
+    let note = read_trimmed_line(input).filter(|n| !n.is_empty());
+
+    Some(OperatorAnnotation {
+        decision,
+        note,
+        annotated_at: clock.now().wall,
+    })
+}
+
+fn read_trimmed_line<R: BufRead>(input: &mut R) -> Option<String> {
+    let mut line = String::new();
+    match input.read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::test_support::report;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_prompt_records_decision_and_note() {
+        let mut input = Cursor::new("maybe\nf\nCandidate was screen sharing with us\n");
+        let mut output = Vec::new();
+
+        let clock = FixedClock::deterministic();
+        let annotation = prompt_for_annotation(&mut input, &mut output, 4, &clock).unwrap();
+        assert_eq!(annotation.decision, ReviewDecision::FalsePositive);
+        assert_eq!(annotation.note.as_deref(), Some("Candidate was screen sharing with us"));
+        assert_eq!(annotation.annotated_at, FixedClock::deterministic().now().wall);
+    }
+
+    #[test]
+    fn test_prompt_skip_and_closed_input() {
+        let mut output = Vec::new();
+        let clock = FixedClock::deterministic();
+        assert!(prompt_for_annotation(&mut Cursor::new("\n"), &mut output, 1, &clock).is_none());
+        assert!(prompt_for_annotation(&mut Cursor::new(""), &mut output, 1, &clock).is_none());
+    }
+
+    #[test]
+    fn test_annotation_attached_to_breaching_report() {
+        let mut breaching = report(3, 0.8, true);
+        let mut output = Vec::new();
+        let clock = FixedClock::deterministic();
+        review_report(&mut breaching, &mut Cursor::new("c\nSecond monitor visible\n"), &mut output, &clock);
+
+        let annotation = breaching.operator_annotation.as_ref().unwrap();
+        assert_eq!(annotation.decision, ReviewDecision::Confirmed);
+
+        let json = serde_json::to_value(&breaching).unwrap();
+        assert_eq!(json["operator_annotation"]["decision"], "confirmed");
+        assert_eq!(json["operator_annotation"]["note"], "Second monitor visible");
+
+        let mut clean = report(4, 0.1, false);
+        review_report(&mut clean, &mut Cursor::new("c\n"), &mut output, &clock);
+        assert!(clean.operator_annotation.is_none());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,94 @@
+use crate::config::{RuleCapability, RuleConfig};
+use crate::process_monitor::{normalize_path, NormalizedProcess, ProcessCapabilities};
+use crate::{ReasonCode, Severity};
+
+/// Combined effect of every `[[rules]]` entry matching one process.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuleOutcome {
+    /// Names of the matching rules, in config order.
+    pub matched: Vec<String>,
+    pub risk_delta: f64,
+    pub suppress: bool,
+    pub forced_severity: Option<Severity>,
+}
+
+/// True when every condition the rule sets holds for the process. Reason
+/// codes are those the built-in checks already gave it.
+pub fn matches(
+    rule: &RuleConfig,
+    process: &NormalizedProcess,
+    capabilities: &ProcessCapabilities,
+    reason_codes: &[ReasonCode],
+) -> bool {
+    if let Some(ref pattern) = rule.process_name {
+        if !process.name_matches(pattern) {
+            return false;
+        }
+    }
+
+    if let Some(ref fragment) = rule.path_contains {
+        if !process.path.contains(&normalize_path(fragment)) {
+            return false;
+        }
+    }
+
+    let has_capability = |capability: &RuleCapability| match capability {
+        RuleCapability::ScreenCapture => capabilities.screen_capture,
+        RuleCapability::AudioCapture => capabilities.audio_capture,
+        RuleCapability::Accessibility => capabilities.accessibility,
+    };
+
+    rule.capabilities.iter().all(has_capability)
+        && rule.reason_codes.iter().all(|code| reason_codes.contains(code))
+}
+
+pub fn evaluate(
+    rules: &[RuleConfig],
+    process: &NormalizedProcess,
+    capabilities: &ProcessCapabilities,
+    reason_codes: &[ReasonCode],
+) -> RuleOutcome {
+    let mut outcome = RuleOutcome::default();
+
+    for rule in rules.iter().filter(|r| matches(r, process, capabilities, reason_codes)) {
+        outcome.matched.push(rule.name.clone());
+        outcome.risk_delta += rule.risk_delta;
+        outcome.suppress |= rule.suppress;
+        outcome.forced_severity = outcome.forced_severity.max(rule.force_severity);
+    }
+
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_monitor::normalize_process;
+    use crate::test_support::process;
+
+    #[test]
+    fn test_rule_needs_every_condition() {
+        let rule = RuleConfig {
+            name: "appdata audio helper".to_string(),
+            process_name: Some("helper".to_string()),
+            path_contains: Some("AppData\\Local".to_string()),
+            capabilities: vec![RuleCapability::AudioCapture],
+            reason_codes: vec![ReasonCode::StartedDuringInterview],
+            risk_delta: 0.5,
+            ..Default::default()
+        };
+        let helper = normalize_process(&process(1, "Helper.exe", "C:\\Users\\me\\AppData\\Local\\Helper.exe"));
+        let audio = ProcessCapabilities { audio_capture: true, ..Default::default() };
+        let started = [ReasonCode::AudioCapture, ReasonCode::StartedDuringInterview];
+
+        assert!(matches(&rule, &helper, &audio, &started));
+        assert!(!matches(&rule, &helper, &ProcessCapabilities::default(), &started));
+        assert!(!matches(&rule, &helper, &audio, &started[..1]));
+        let elsewhere = normalize_process(&process(1, "helper.exe", "C:\\Program Files\\helper.exe"));
+        assert!(!matches(&rule, &elsewhere, &audio, &started));
+
+        let outcome = evaluate(&[rule.clone(), rule], &helper, &audio, &started);
+        assert_eq!(outcome.matched.len(), 2);
+        assert!((outcome.risk_delta - 1.0).abs() < 1e-9);
+    }
+}
\ No newline at end of file
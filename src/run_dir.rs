@@ -0,0 +1,153 @@
+use crate::config::OutputConfig;
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Where one run writes its reports, heartbeats, checkpoint and verdict.
+/// With `output.per_run_directory` each launch gets its own subdirectory of
+/// `output.output_dir`, named from the session id and launch time, so
+/// retries and back-to-back candidates on one machine don't intermix.
+#[derive(Debug, Clone)]
+pub struct RunDirectory {
+    path: PathBuf,
+    /// Directory name prefix shared by every run of the same session.
+    session_prefix: Option<String>,
+}
+
+impl RunDirectory {
+    pub fn create(output: &OutputConfig, session_id: Option<&str>, launched: DateTime<Utc>) -> Result<Self, String> {
+        let root = Path::new(&output.output_dir);
+        fs::create_dir_all(root)
+            .map_err(|e| format!("Failed to create output directory {}: {}", root.display(), e))?;
+
+        if !output.per_run_directory {
+            return Ok(Self { path: root.to_path_buf(), session_prefix: None });
+        }
+
+        let session_prefix = session_id.map(|id| format!("{}_", sanitize(id)));
+        let base = format!(
+            "{}{}",
+            session_prefix.as_deref().unwrap_or("run_"),
+            launched.format("%Y%m%d_%H%M%S")
+        );
+
+        // Two launches within the same second get numbered suffixes.
+        for attempt in 1u32.. {
+            let name = if attempt == 1 { base.clone() } else { format!("{}-{}", base, attempt) };
+            let path = root.join(name);
+            match fs::create_dir(&path) {
+                Ok(()) => return Ok(Self { path, session_prefix }),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(format!("Failed to create run directory {}: {}", path.display(), e)),
+            }
+        }
+        unreachable!("run directory suffixes exhausted")
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// `name` inside this run's directory. Absolute paths are kept as is.
+    pub fn file<P: AsRef<Path>>(&self, name: P) -> PathBuf {
+        self.path.join(name)
+    }
+
+    /// The newest copy of `name` this session wrote: this run's own, or else
+    /// the one in its latest earlier run directory. Lets a restarted session
+    /// find the checkpoint its previous run left behind.
+    pub fn latest_session_file<P: AsRef<Path>>(&self, name: P) -> PathBuf {
+        let own = self.file(&name);
+        let (Some(prefix), Some(root)) = (&self.session_prefix, self.path.parent()) else {
+            return own;
+        };
+        if own.exists() {
+            return own;
+        }
+
+        let mut earlier: Vec<PathBuf> = fs::read_dir(root)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path != &self.path && is_run_of(path, prefix))
+                    .collect()
+            })
+            .unwrap_or_default();
+        earlier.sort_by_key(|path| run_order(path));
+        earlier
+            .into_iter()
+            .rev()
+            .map(|dir| dir.join(&name))
+            .find(|path| path.exists())
+            .unwrap_or(own)
+    }
+}
+
+/// Whether `path` is named `<prefix><YYYYmmdd_HHMMSS>[-n]`, so session `a`
+/// doesn't pick up the runs of session `a_b`.
+fn is_run_of(path: &Path, prefix: &str) -> bool {
+    let Some(name) = path.file_name().map(|n| n.to_string_lossy()) else {
+        return false;
+    };
+    let Some(stamp) = name.strip_prefix(prefix) else {
+        return false;
+    };
+    let stamp = stamp.as_bytes();
+    stamp.len() >= 15
+        && stamp[..8].iter().all(u8::is_ascii_digit)
+        && stamp[8] == b'_'
+        && stamp[9..15].iter().all(u8::is_ascii_digit)
+}
+
+/// Sort key for run directories: launch time, then the same-second suffix.
+fn run_order(path: &Path) -> (String, u32) {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    if let Some((base, suffix)) = name.rsplit_once('-') {
+        if let Ok(attempt) = suffix.parse() {
+            return (base.to_string(), attempt);
+        }
+    }
+    (name, 1)
+}
+
+/// Session ids are opaque; keep only characters safe in a directory name.
+fn sanitize(session_id: &str) -> String {
+    session_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_each_run_gets_its_own_directory() {
+        let root = std::env::temp_dir().join(format!("fairview_run_dir_test_{}", std::process::id()));
+        let output = OutputConfig {
+            output_dir: root.to_string_lossy().into_owned(),
+            per_run_directory: true,
+            ..Default::default()
+        };
+        let launched = Utc.with_ymd_and_hms(2024, 11, 18, 15, 30, 45).unwrap();
+
+        let first = RunDirectory::create(&output, Some("int/42"), launched).unwrap();
+        let second = RunDirectory::create(&output, Some("int/42"), launched).unwrap();
+        assert_ne!(first.path(), second.path());
+        assert!(first.path().ends_with("int_42_20241118_153045"));
+        assert!(second.path().ends_with("int_42_20241118_153045-2"));
+
+        fs::write(first.file("checkpoint.json"), "{}").unwrap();
+        assert_eq!(second.latest_session_file("checkpoint.json"), first.file("checkpoint.json"));
+        let other_session = RunDirectory::create(&output, Some("int"), launched).unwrap();
+        assert_eq!(other_session.latest_session_file("checkpoint.json"), other_session.file("checkpoint.json"));
+
+        let anonymous = RunDirectory::create(&output, None, launched).unwrap();
+        assert!(anonymous.path().ends_with("run_20241118_153045"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
\ No newline at end of file
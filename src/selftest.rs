@@ -0,0 +1,241 @@
+use crate::config::Config;
+use crate::platform::PlatformProvider;
+use crate::FairviewDetector;
+use std::path::Path;
+use std::sync::Arc;
+
+/// One item of `fairview selftest`. A failed critical check fails the
+/// self-test; a failed non-critical one is only a warning.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub name: String,
+    pub passed: bool,
+    pub critical: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), passed: true, critical: true, detail: detail.into() }
+    }
+
+    fn fail(name: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), passed: false, critical: true, detail: detail.into() }
+    }
+
+    fn warn_unless(passed: bool, name: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), passed, critical: false, detail: detail.into() }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SelfTest {
+    pub checks: Vec<Check>,
+}
+
+impl SelfTest {
+    pub fn passed(&self) -> bool {
+        self.critical_failures() == 0
+    }
+
+    pub fn critical_failures(&self) -> usize {
+        self.checks.iter().filter(|check| check.critical && !check.passed).count()
+    }
+
+    /// One line per check, then the verdict.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for check in &self.checks {
+            let status = match (check.passed, check.critical) {
+                (true, _) => "[+] PASS",
+                (false, true) => "[!] FAIL",
+                (false, false) => "[!] WARN",
+            };
+            out.push_str(&format!("{}  {}: {}\n", status, check.name, check.detail));
+        }
+        if self.passed() {
+            out.push_str("[+] Self-test passed: Fairview is ready to monitor this machine\n");
+        } else {
+            out.push_str(&format!("[!] Self-test failed: {} critical checks failed\n", self.critical_failures()));
+        }
+        out
+    }
+}
+
+/// Checks the config, the output directory, the platform tools the
+/// detectors shell out to, and one scan of the machine as it is now. An
+/// invalid config fails its check and the rest run under the defaults.
+pub fn run(config: Result<Config, String>, platform: Arc<dyn PlatformProvider>) -> SelfTest {
+    let mut checks = Vec::new();
+    let config = match config {
+        Ok(config) => {
+            checks.push(Check::pass("Config", "loads and validates"));
+            config
+        }
+        Err(e) => {
+            checks.push(Check::fail("Config", format!("{} (remaining checks use the defaults)", e)));
+            Config::default()
+        }
+    };
+
+    checks.push(output_dir_check(&config.output.output_dir));
+    checks.extend(tool_checks());
+    checks.extend(scan_checks(config, platform));
+    SelfTest { checks }
+}
+
+fn output_dir_check(dir: &str) -> Check {
+    let probe = Path::new(dir).join(format!(".fairview_selftest_{}", std::process::id()));
+    let written = std::fs::create_dir_all(dir).and_then(|()| std::fs::write(&probe, b"selftest"));
+    std::fs::remove_file(&probe).ok();
+    match written {
+        Ok(()) => Check::pass("Output directory", format!("{} is writable", dir)),
+        Err(e) => Check::fail("Output directory", format!("{} is not writable: {}", dir, e)),
+    }
+}
+
+/// Commands the detectors run on this platform, any one of each group
+/// being enough, with what goes without them.
+#[cfg(target_os = "linux")]
+const PLATFORM_TOOLS: &[(&[&str], &str)] = &[
+    (&["pactl", "pw-cli"], "audio capture stream counting"),
+    (&["xrandr"], "display detection"),
+    (&["netstat"], "remote desktop detection"),
+    (&["xprop"], "foreground app capture"),
+];
+
+#[cfg(target_os = "macos")]
+const PLATFORM_TOOLS: &[(&[&str], &str)] = &[
+    (&["system_profiler"], "display and audio detection"),
+    (&["ioreg"], "input device detection"),
+    (&["lsof"], "screen sharing detection"),
+    (&["sqlite3"], "privacy permission grants"),
+    (&["lsappinfo"], "foreground app capture"),
+];
+
+#[cfg(target_os = "windows")]
+const PLATFORM_TOOLS: &[(&[&str], &str)] = &[
+    (&["qwinsta"], "remote desktop detection"),
+    (&["powershell"], "machine fingerprint"),
+    (&["reg"], "Cloud Clipboard detection"),
+];
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+const PLATFORM_TOOLS: &[(&[&str], &str)] = &[];
+
+fn tool_checks() -> Vec<Check> {
+    PLATFORM_TOOLS
+        .iter()
+        .map(|(tools, purpose)| {
+            let found = tools.iter().find(|tool| on_path(tool));
+            let detail = match found {
+                Some(tool) => format!("{} found", tool),
+                None => format!("{} not found; {} is unavailable", tools.join(" or "), purpose),
+            };
+            Check::warn_unless(found.is_some(), "Platform tools", detail)
+        })
+        .collect()
+}
+
+fn on_path(tool: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    let names = if cfg!(target_os = "windows") { vec![format!("{}.exe", tool)] } else { vec![tool.to_string()] };
+    std::env::split_paths(&path).any(|dir| names.iter().any(|name| dir.join(name).is_file()))
+}
+
+/// Scans once and checks every enabled module ran, the score is in range
+/// and every process could be inspected. A clean machine that already
+/// breaches is worth knowing before the interview, but isn't a failure.
+fn scan_checks(config: Config, platform: Arc<dyn PlatformProvider>) -> Vec<Check> {
+    let mut detector = FairviewDetector::with_platform(config, platform);
+    let report = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| detector.scan())) {
+        Ok(report) => report,
+        Err(_) => return vec![Check::fail("Scan", "the scan itself crashed")],
+    };
+
+    let mut checks: Vec<Check> =
+        report.module_failures.iter().map(|failure| Check::fail("Module", failure.clone())).collect();
+    if checks.is_empty() {
+        checks.push(Check::pass("Modules", format!("{} enabled modules ran", report.uncertainty.modules_run)));
+    }
+
+    let score = report.overall_risk_score;
+    checks.push(if score.is_finite() && (0.0..=1.0).contains(&score) {
+        Check::pass("Risk score", format!("{:.2} on a clean scan", score))
+    } else {
+        Check::fail("Risk score", format!("{} is out of range", score))
+    });
+    checks.push(Check::warn_unless(
+        !report.exceeds_threshold,
+        "Clean scan",
+        if report.exceeds_threshold {
+            "already exceeds the risk threshold; review the whitelist before the interview"
+        } else {
+            "below the risk threshold"
+        },
+    ));
+    if report.baseline_missing {
+        checks.push(Check::warn_unless(
+            false,
+            "Baseline",
+            "collect_baseline is off in enforce mode; new processes and display changes won't be detected",
+        ));
+    }
+    checks.push(Check::warn_unless(
+        report.inaccessible_processes == 0,
+        "Privileges",
+        if report.inaccessible_processes == 0 {
+            "every process could be inspected".to_string()
+        } else {
+            format!("{} processes could not be inspected; run elevated", report.inaccessible_processes)
+        },
+    ));
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{capabilities, process, MockPlatform};
+
+    fn quiet_config(output_dir: &str) -> Config {
+        let mut config = Config::default();
+        config.monitoring.enable_vm_detection = false;
+        config.monitoring.enable_input_device_monitoring = false;
+        config.monitoring.enable_network_monitoring = false;
+        config.monitoring.enable_virtual_desktop_detection = false;
+        config.output.output_dir = output_dir.to_string();
+        config
+    }
+
+    /// A machine whose process listing works.
+    fn listing() -> MockPlatform {
+        let shell = (process(1, "explorer.exe", "C:\\Windows\\explorer.exe"), capabilities(false, false, false));
+        MockPlatform { processes: vec![shell], ..Default::default() }
+    }
+
+    #[test]
+    fn test_selftest_fails_on_critical_checks_only() {
+        let writable = std::env::temp_dir().join(format!("fairview_selftest_{}", std::process::id()));
+        let writable = writable.to_string_lossy().into_owned();
+        let clean = run(Ok(quiet_config(&writable)), Arc::new(listing()));
+        std::fs::remove_dir_all(&writable).ok();
+        assert!(clean.passed(), "{}", clean.render());
+
+        // A file where the output directory should be, and a module that crashes.
+        let blocked = std::env::temp_dir().join(format!("fairview_selftest_file_{}", std::process::id()));
+        std::fs::write(&blocked, b"not a directory").unwrap();
+        let broken = MockPlatform { broken: true, ..listing() };
+        let failing = run(Ok(quiet_config(&blocked.join("reports").to_string_lossy())), Arc::new(broken));
+        std::fs::remove_file(&blocked).ok();
+
+        let failed: Vec<&str> = failing.checks.iter().filter(|c| !c.passed && c.critical).map(|c| c.name.as_str()).collect();
+        assert_eq!(failed, vec!["Output directory", "Module"]);
+        assert!(failing.render().ends_with("[!] Self-test failed: 2 critical checks failed\n"));
+
+        let warned = SelfTest { checks: vec![Check::pass("Config", "ok"), Check::warn_unless(false, "Privileges", "denied")] };
+        assert!(warned.passed());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,174 @@
+use crate::review::ReviewDecision;
+use crate::{DetectionReport, ReasonCode, Severity};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Aggregates across every scan of a session. Checkpointed to disk so a
+/// restarted monitor can carry on with the same totals.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SessionVerdict {
+    pub session_id: Option<String>,
+    pub candidate_ref: Option<String>,
+    /// Fingerprint of the machine the session's first scan ran on.
+    #[serde(default)]
+    pub machine_fingerprint: Option<String>,
+    /// Host and OS account of the session's first scan.
+    #[serde(default)]
+    pub provenance: Option<crate::provenance::Provenance>,
+    pub total_scans: usize,
+    /// Highest `scan_number` recorded, so a restarted monitor continues the
+    /// numbering instead of starting again at 1.
+    #[serde(default)]
+    pub last_scan_number: usize,
+    pub breached_scans: usize,
+    pub peak_risk_score: f64,
+    /// Highest uncapped `raw_risk_score`, which still separates sessions
+    /// that all peaked at 1.0.
+    #[serde(default)]
+    pub peak_raw_risk_score: f64,
+    pub peak_severity: Severity,
+    /// How often each reason was raised across the reported processes. Those
+    /// left out by `min_report_confidence` or `max_reported_processes` aren't
+    /// counted.
+    pub reason_counts: BTreeMap<ReasonCode, usize>,
+    pub confirmed_by_operator: usize,
+    pub dismissed_by_operator: usize,
+    pub operator_notes: Vec<String>,
+    pub clock_tampering_scans: usize,
+    pub suspected_cheating: bool,
+}
+
+impl SessionVerdict {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes the aggregates as JSON, via a temporary file so a crash mid-write
+    /// leaves the previous checkpoint intact.
+    pub fn save_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize checkpoint: {}", e))?;
+
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, json).map_err(|e| format!("Failed to write checkpoint: {}", e))?;
+        fs::rename(&tmp, path).map_err(|e| format!("Failed to write checkpoint: {}", e))
+    }
+
+    /// Loads a checkpoint written for `session_id`. A missing file or one
+    /// from a different session yields `None`, so a new interview never
+    /// inherits another's totals.
+    pub fn load_checkpoint<P: AsRef<Path>>(path: P, session_id: &str) -> Result<Option<Self>, String> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Failed to read checkpoint: {}", e)),
+        };
+
+        let verdict: SessionVerdict = serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse checkpoint: {}", e))?;
+
+        Ok((verdict.session_id.as_deref() == Some(session_id)).then_some(verdict))
+    }
+
+    pub fn record(&mut self, report: &DetectionReport) {
+        self.total_scans += 1;
+        self.last_scan_number = self.last_scan_number.max(report.scan_number);
+        self.session_id.clone_from(&report.session_id);
+        self.candidate_ref.clone_from(&report.candidate_ref);
+        if self.machine_fingerprint.is_none() {
+            self.machine_fingerprint.clone_from(&report.machine_fingerprint);
+        }
+        if self.provenance.is_none() {
+            self.provenance.clone_from(&report.provenance);
+        }
+        self.peak_risk_score = self.peak_risk_score.max(report.overall_risk_score);
+        self.peak_raw_risk_score = self.peak_raw_risk_score.max(report.raw_risk_score);
+        self.peak_severity = self.peak_severity.max(report.severity);
+
+        for process in &report.suspicious_processes {
+            for code in &process.reason_codes {
+                *self.reason_counts.entry(*code).or_insert(0) += 1;
+            }
+        }
+
+        if report.exceeds_threshold {
+            self.breached_scans += 1;
+        }
+
+        if report.clock_tampering_suspected {
+            self.clock_tampering_scans += 1;
+        }
+
+        if let Some(ref annotation) = report.operator_annotation {
+            match annotation.decision {
+                ReviewDecision::Confirmed => self.confirmed_by_operator += 1,
+                ReviewDecision::FalsePositive => self.dismissed_by_operator += 1,
+            }
+            if let Some(ref note) = annotation.note {
+                self.operator_notes.push(format!("Scan #{}: {}", report.scan_number, note));
+            }
+        }
+
+        // Breaches the operator dismissed as false positives don't count against the candidate.
+        self.suspected_cheating = self.confirmed_by_operator > 0
+            || self.breached_scans > self.dismissed_by_operator;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review::OperatorAnnotation;
+    use crate::test_support::report;
+    use std::time::SystemTime;
+
+    #[test]
+    fn test_dismissed_breach_clears_verdict() {
+        let mut verdict = SessionVerdict::new();
+        let mut breach = report(1, 0.9, true);
+        breach.operator_annotation = Some(OperatorAnnotation {
+            decision: ReviewDecision::FalsePositive,
+            note: Some("Interviewer-approved screen share".to_string()),
+            annotated_at: SystemTime::now(),
+        });
+
+        verdict.record(&breach);
+        verdict.record(&report(2, 0.2, false));
+
+        assert_eq!(verdict.breached_scans, 1);
+        assert_eq!(verdict.dismissed_by_operator, 1);
+        assert!(!verdict.suspected_cheating);
+        assert_eq!(verdict.operator_notes, vec!["Scan #1: Interviewer-approved screen share"]);
+
+        verdict.record(&report(3, 0.7, true));
+        assert!(verdict.suspected_cheating);
+    }
+
+    #[test]
+    fn test_aggregates_survive_restart_via_checkpoint() {
+        let path = std::env::temp_dir().join(format!("fairview_checkpoint_test_{}.json", std::process::id()));
+
+        let mut breach = report(1, 0.9, true);
+        breach.session_id = Some("int-2041".to_string());
+        let mut verdict = SessionVerdict::new();
+        verdict.record(&breach);
+        verdict.save_checkpoint(&path).unwrap();
+
+        assert!(SessionVerdict::load_checkpoint(&path, "int-9999").unwrap().is_none());
+        let mut resumed = SessionVerdict::load_checkpoint(&path, "int-2041").unwrap().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let mut after_restart = report(1, 0.4, false);
+        after_restart.session_id = Some("int-2041".to_string());
+        resumed.record(&after_restart);
+
+        assert_eq!(resumed.total_scans, 2);
+        assert_eq!(resumed.breached_scans, 1);
+        assert_eq!(resumed.peak_risk_score, 0.9);
+        assert!(resumed.suspected_cheating);
+        assert!(SessionVerdict::load_checkpoint(&path, "int-2041").unwrap().is_none());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,85 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use sysinfo::{ProcessRefreshKind, System};
+
+/// One `sysinfo::System` refreshed once per scan, which process listing,
+/// CPU-based audio detection, VM checks and system load all read instead
+/// of each enumerating the machine themselves. Kept between scans so CPU
+/// use is measured over the time since the previous refresh.
+pub struct SystemSnapshot {
+    system: Mutex<System>,
+    refreshes: AtomicUsize,
+}
+
+impl Default for SystemSnapshot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SystemSnapshot {
+    /// Empty until the first refresh.
+    pub fn new() -> Self {
+        Self { system: Mutex::new(System::new()), refreshes: AtomicUsize::new(0) }
+    }
+
+    /// Re-reads the process table, CPU and memory. The first refresh takes
+    /// two CPU samples `MINIMUM_CPU_UPDATE_INTERVAL` apart, since a single
+    /// sample reads every process as idle.
+    pub fn refresh(&self) {
+        let mut system = self.lock();
+        if self.refreshes.load(Ordering::SeqCst) == 0 {
+            system.refresh_cpu_usage();
+            system.refresh_processes_specifics(ProcessRefreshKind::new().with_cpu());
+            std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        }
+        system.refresh_cpu_usage();
+        system.refresh_memory();
+        system.refresh_processes_specifics(ProcessRefreshKind::everything());
+        self.refreshes.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Runs `read` against the latest refresh, taking one first if there
+    /// hasn't been any, as when a process listing is asked for outside a scan.
+    pub fn read<R>(&self, read: impl FnOnce(&System) -> R) -> R {
+        if self.refreshes() == 0 {
+            self.refresh();
+        }
+        read(&self.lock())
+    }
+
+    /// How many times the machine has been enumerated.
+    pub fn refreshes(&self) -> usize {
+        self.refreshes.load(Ordering::SeqCst)
+    }
+
+    /// A reader that panicked under `catch_unwind` leaves the `System`
+    /// intact, so a poisoned lock is still usable.
+    fn lock(&self) -> MutexGuard<'_, System> {
+        self.system.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The snapshot `SystemPlatform` refreshes at the start of every scan.
+pub fn shared() -> &'static SystemSnapshot {
+    static SHARED: OnceLock<SystemSnapshot> = OnceLock::new();
+    SHARED.get_or_init(SystemSnapshot::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_share_one_enumeration_until_refreshed() {
+        let snapshot = SystemSnapshot::new();
+        let first = snapshot.read(|system| system.processes().len());
+        let second = snapshot.read(|system| system.processes().len());
+        assert_eq!(snapshot.refreshes(), 1);
+        assert!(first > 0 && first == second);
+
+        snapshot.refresh();
+        assert_eq!(snapshot.refreshes(), 2);
+        assert!(snapshot.read(|system| system.total_memory()) > 0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,50 @@
+use crate::platform::{PlatformProvider, SystemPlatform};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// System-wide CPU and memory use at scan time, so a score raised by
+/// CPU-based heuristics can be read against how busy the machine was.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SystemLoad {
+    /// Average over all cores since the previous reading, 0-100.
+    pub cpu_percent: f32,
+    pub memory_percent: f32,
+    /// CPU use reached `heuristics.saturated_cpu_percent`; set by the
+    /// detector, not the platform.
+    #[serde(default)]
+    pub saturated: bool,
+}
+
+/// Reads the machine's load once per scan.
+pub struct SystemLoadMonitor {
+    platform: Arc<dyn PlatformProvider>,
+}
+
+impl SystemLoadMonitor {
+    pub fn with_platform(platform: Arc<dyn PlatformProvider>) -> Self {
+        Self { platform }
+    }
+
+    /// `None` where the platform can't tell.
+    pub fn read(&self, saturated_cpu_percent: f32) -> Option<SystemLoad> {
+        let load = self.platform.system_load()?;
+        Some(SystemLoad { saturated: load.cpu_percent >= saturated_cpu_percent, ..load })
+    }
+}
+
+impl SystemPlatform {
+    /// Read from the shared snapshot, so CPU use covers the time since the
+    /// previous scan's refresh.
+    pub(crate) fn current_system_load(&self) -> Option<SystemLoad> {
+        crate::snapshot::shared().read(|system| {
+            if system.cpus().is_empty() || system.total_memory() == 0 {
+                return None;
+            }
+            Some(SystemLoad {
+                cpu_percent: system.global_cpu_info().cpu_usage(),
+                memory_percent: (system.used_memory() as f64 / system.total_memory() as f64 * 100.0) as f32,
+                saturated: false,
+            })
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,313 @@
+use crate::command::LimitedOutput;
+use crate::lru::LruCache;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Privacy services read from the macOS TCC (Transparency, Consent and
+/// Control) database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TccService {
+    ScreenCapture,
+    Microphone,
+    Accessibility,
+}
+
+impl TccService {
+    pub const ALL: [TccService; 3] = [TccService::ScreenCapture, TccService::Microphone, TccService::Accessibility];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            TccService::ScreenCapture => "kTCCServiceScreenCapture",
+            TccService::Microphone => "kTCCServiceMicrophone",
+            TccService::Accessibility => "kTCCServiceAccessibility",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|service| service.key() == key)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TccService::ScreenCapture => "screen recording",
+            TccService::Microphone => "microphone",
+            TccService::Accessibility => "accessibility",
+        }
+    }
+}
+
+/// One row of the TCC `access` table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TccGrant {
+    pub service: TccService,
+    /// Bundle id of an app, or the executable path of a command-line tool.
+    pub client: String,
+    pub client_is_path: bool,
+    pub allowed: bool,
+    /// When the entry last changed, usually when the user answered the
+    /// prompt or ticked the box in System Settings.
+    #[serde(with = "crate::timestamp_format")]
+    pub last_modified: SystemTime,
+}
+
+/// macOS 11 replaced the `allowed` column with `auth_value` (0 denied,
+/// 2 allowed, 3 limited); older databases only answer the second query.
+const TCC_QUERIES: [&str; 2] = [
+    "SELECT service, client, client_type, auth_value, last_modified FROM access",
+    "SELECT service, client, client_type, allowed * 2, last_modified FROM access",
+];
+
+/// Microphone grants live in the per-user database, screen recording and
+/// accessibility in the system one.
+pub const SYSTEM_DATABASE: &str = "/Library/Application Support/com.apple.TCC/TCC.db";
+const USER_DATABASE: &str = "Library/Application Support/com.apple.TCC/TCC.db";
+
+/// Parses `sqlite3 -separator '|'` output of one of `TCC_QUERIES`. Rows for
+/// other services and malformed lines are skipped.
+pub fn parse_tcc_rows(output: &str) -> Vec<TccGrant> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (service, rest) = line.split_once('|')?;
+            // Paths may contain the separator, so the numeric columns are
+            // taken from the right.
+            let mut fields = rest.rsplitn(4, '|');
+            let last_modified = UNIX_EPOCH + Duration::from_secs(fields.next()?.trim().parse().ok()?);
+            let auth_value: i64 = fields.next()?.trim().parse().ok()?;
+            let client_type: i64 = fields.next()?.trim().parse().ok()?;
+            let client = fields.next()?;
+            Some(TccGrant {
+                service: TccService::from_key(service)?,
+                client: client.to_string(),
+                client_is_path: client_type == 1,
+                allowed: auth_value >= 2,
+                last_modified,
+            })
+        })
+        .collect()
+}
+
+/// Reads the grant rows of one database with the `sqlite3` shipped with
+/// macOS.
+pub fn read_database(path: &Path) -> Result<Vec<TccGrant>, String> {
+    let mut error = String::new();
+    for query in TCC_QUERIES {
+        let output = Command::new("sqlite3")
+            .args(["-readonly", "-separator", "|"])
+            .arg(path)
+            .arg(query)
+            .limited_output()
+            .map_err(|e| format!("Failed to run sqlite3: {}", e))?;
+        if output.status.success() {
+            return Ok(parse_tcc_rows(&String::from_utf8_lossy(&output.stdout)));
+        }
+        error = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if !error.contains("no such column") {
+            break;
+        }
+    }
+    Err(format!("Failed to read {}: {}", path.display(), error))
+}
+
+/// The grants recorded in both TCC databases.
+#[derive(Debug, Clone, Default)]
+pub struct TccGrants {
+    grants: Vec<TccGrant>,
+}
+
+impl TccGrants {
+    pub fn new(grants: Vec<TccGrant>) -> Self {
+        Self { grants }
+    }
+
+    /// Reads the user and the system database. SIP keeps both unreadable
+    /// unless the app running Fairview has Full Disk Access, so this fails
+    /// when neither can be read.
+    pub fn load() -> Result<Self, String> {
+        let mut databases = vec![PathBuf::from(SYSTEM_DATABASE)];
+        if let Some(home) = std::env::var_os("HOME") {
+            databases.push(Path::new(&home).join(USER_DATABASE));
+        }
+
+        let mut grants = Vec::new();
+        let mut errors = Vec::new();
+        for database in &databases {
+            match read_database(database) {
+                Ok(rows) => grants.extend(rows),
+                Err(e) => errors.push(e),
+            }
+        }
+        if errors.len() == databases.len() {
+            return Err(format!(
+                "{} (grant Full Disk Access to the app running Fairview)",
+                errors.join("; ")
+            ));
+        }
+        Ok(Self { grants })
+    }
+
+    /// Entries for the executable at `path`, matched by its path or by the
+    /// bundle id of the app it belongs to.
+    pub fn of_client<'a>(&'a self, path: &'a str, bundle_id: Option<&'a str>) -> impl Iterator<Item = &'a TccGrant> {
+        self.grants.iter().filter(move |grant| {
+            if grant.client_is_path {
+                grant.client == path
+            } else {
+                bundle_id == Some(grant.client.as_str())
+            }
+        })
+    }
+
+    /// Whether the executable currently holds `service`. A denied entry or
+    /// none at all both mean no.
+    pub fn allows(&self, path: &str, bundle_id: Option<&str>, service: TccService) -> bool {
+        self.of_client(path, bundle_id).any(|grant| grant.service == service && grant.allowed)
+    }
+}
+
+/// Services among `grants` that were allowed after `since`, each listed once.
+pub fn granted_since(grants: &[TccGrant], since: SystemTime) -> Vec<TccService> {
+    let mut services: Vec<TccService> = grants
+        .iter()
+        .filter(|grant| grant.allowed && grant.last_modified > since)
+        .map(|grant| grant.service)
+        .collect();
+    services.sort();
+    services.dedup();
+    services
+}
+
+/// Grants as of the last `refresh`, shared by every capability check in a
+/// scan so the databases are read once per scan.
+struct Snapshot {
+    grants: Option<Arc<TccGrants>>,
+    warned: bool,
+}
+
+fn snapshot() -> &'static Mutex<Snapshot> {
+    static SNAPSHOT: OnceLock<Mutex<Snapshot>> = OnceLock::new();
+    SNAPSHOT.get_or_init(|| Mutex::new(Snapshot { grants: None, warned: false }))
+}
+
+/// Rereads the databases. The first failure is printed; later ones only
+/// leave the snapshot empty.
+pub fn refresh() {
+    let loaded = TccGrants::load();
+    let mut snapshot = snapshot().lock().unwrap_or_else(|e| e.into_inner());
+    match loaded {
+        Ok(grants) => snapshot.grants = Some(Arc::new(grants)),
+        Err(e) => {
+            if !snapshot.warned {
+                println!("[!] Cannot read the TCC database, guessing macOS capabilities from process names: {}", e);
+                snapshot.warned = true;
+            }
+            snapshot.grants = None;
+        }
+    }
+}
+
+/// The grants read by the last `refresh`, or `None` when TCC was unreadable.
+pub fn current() -> Option<Arc<TccGrants>> {
+    snapshot().lock().unwrap_or_else(|e| e.into_inner()).grants.clone()
+}
+
+/// The outermost `.app` bundle containing `path`. Helpers nested inside an
+/// app resolve to the app, which is what TCC attributes their access to.
+pub fn app_bundle(path: &str) -> Option<&str> {
+    path.find(".app/").map(|end| &path[..end + ".app".len()])
+}
+
+/// Bundle identifiers kept between scans.
+const BUNDLE_ID_CACHE_SIZE: usize = 1024;
+
+/// `CFBundleIdentifier` of the app `path` belongs to, looked up once per
+/// bundle while it stays among the `BUNDLE_ID_CACHE_SIZE` most recently used.
+pub fn bundle_id(path: &str) -> Option<String> {
+    let bundle = app_bundle(path)?;
+    let mut cache = bundle_id_cache().lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(id) = cache.get(bundle) {
+        return id.clone();
+    }
+    let id = read_bundle_id(bundle);
+    cache.insert(bundle, id.clone());
+    id
+}
+
+/// Forgets the looked-up bundle identifiers.
+pub fn clear_bundle_ids() {
+    bundle_id_cache().lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+fn bundle_id_cache() -> &'static Mutex<LruCache<Option<String>>> {
+    static CACHE: OnceLock<Mutex<LruCache<Option<String>>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(LruCache::new(BUNDLE_ID_CACHE_SIZE)))
+}
+
+fn read_bundle_id(bundle: &str) -> Option<String> {
+    let output = Command::new("/usr/libexec/PlistBuddy")
+        .args(["-c", "Print :CFBundleIdentifier"])
+        .arg(Path::new(bundle).join("Contents/Info.plist"))
+        .limited_output()
+        .ok()?;
+    let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !id.is_empty()).then_some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `sqlite3 -separator '|'` output of the first query, with a command-line
+    /// tool whose path contains the separator and rows to skip.
+    const FIXTURE: &str = "\
+kTCCServiceScreenCapture|us.zoom.xos|0|2|1700000000
+kTCCServiceAccessibility|com.cluely.app|0|2|1731943800
+kTCCServiceScreenCapture|com.cluely.app|0|0|1731943790
+kTCCServiceMicrophone|/usr/local/bin/rec|tool|1|2|1731943900
+kTCCServiceListenEvent|com.example.keys|0|2|1731943950
+kTCCServiceAccessibility|broken row
+";
+
+    #[test]
+    fn test_parse_tcc_rows() {
+        let grants = TccGrants::new(parse_tcc_rows(FIXTURE));
+        assert_eq!(grants.grants.len(), 4);
+        assert_eq!(
+            grants.grants[3],
+            TccGrant {
+                service: TccService::Microphone,
+                client: "/usr/local/bin/rec|tool".to_string(),
+                client_is_path: true,
+                allowed: true,
+                last_modified: UNIX_EPOCH + Duration::from_secs(1731943900),
+            }
+        );
+
+        let app = "/Applications/Cluely.app/Contents/MacOS/Cluely";
+        assert_eq!(app_bundle(app), Some("/Applications/Cluely.app"));
+        let cluely: Vec<TccGrant> = grants.of_client(app, Some("com.cluely.app")).cloned().collect();
+        assert_eq!(cluely.len(), 2);
+        let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+        assert_eq!(granted_since(&cluely, at(1731943000)), vec![TccService::Accessibility]);
+        assert!(granted_since(&cluely, at(1731943800)).is_empty());
+        assert_eq!(grants.of_client("/usr/local/bin/rec|tool", None).count(), 1);
+    }
+
+    #[test]
+    fn test_allows_checks_the_queried_service() {
+        let grants = TccGrants::new(parse_tcc_rows(FIXTURE));
+        let app = "/Applications/Cluely.app/Contents/MacOS/Cluely";
+
+        assert!(grants.allows(app, Some("com.cluely.app"), TccService::Accessibility));
+        // Denied in the fixture.
+        assert!(!grants.allows(app, Some("com.cluely.app"), TccService::ScreenCapture));
+        assert!(!grants.allows(app, Some("com.cluely.app"), TccService::Microphone));
+        // Without the bundle id the app's rows can't be matched.
+        assert!(!grants.allows(app, None, TccService::Accessibility));
+        assert!(grants.allows("/usr/local/bin/rec|tool", None, TccService::Microphone));
+        assert!(grants.allows("/Applications/zoom.us.app/Contents/MacOS/zoom.us", Some("us.zoom.xos"), TccService::ScreenCapture));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,126 @@
+use crate::config::TelemetryConfig;
+use crate::session::SessionVerdict;
+use crate::{ReasonCode, Severity};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Anonymized end-of-session summary for tuning defaults across a fleet. It is
+/// built only from aggregate counts, so process names, paths, PIDs, operator
+/// notes and hostnames never leave the machine.
+#[derive(Debug, Serialize)]
+pub struct TelemetrySummary {
+    pub fairview_version: &'static str,
+    pub platform: &'static str,
+    pub total_scans: usize,
+    pub breached_scans: usize,
+    pub peak_severity: Severity,
+    pub reason_counts: BTreeMap<ReasonCode, usize>,
+    pub confirmed_by_operator: usize,
+    pub dismissed_by_operator: usize,
+}
+
+impl TelemetrySummary {
+    pub fn from_verdict(verdict: &SessionVerdict) -> Self {
+        Self {
+            fairview_version: env!("CARGO_PKG_VERSION"),
+            platform: std::env::consts::OS,
+            total_scans: verdict.total_scans,
+            breached_scans: verdict.breached_scans,
+            peak_severity: verdict.peak_severity,
+            reason_counts: verdict.reason_counts.clone(),
+            confirmed_by_operator: verdict.confirmed_by_operator,
+            dismissed_by_operator: verdict.dismissed_by_operator,
+        }
+    }
+}
+
+/// Posts the summary as JSON. Does nothing unless telemetry is enabled.
+#[cfg(feature = "network")]
+pub async fn send(config: &TelemetryConfig, summary: &TelemetrySummary) -> Result<(), String> {
+    let endpoint = match (config.enabled, config.endpoint.as_deref()) {
+        (true, Some(endpoint)) => endpoint,
+        _ => return Ok(()),
+    };
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(config.timeout_seconds))
+        .build()
+        .map_err(|e| format!("Failed to build telemetry client: {}", e))?;
+
+    let response = client
+        .post(endpoint)
+        .json(summary)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send telemetry: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Telemetry endpoint returned {}", response.status()));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "network"))]
+pub async fn send(config: &TelemetryConfig, _summary: &TelemetrySummary) -> Result<(), String> {
+    if config.enabled {
+        return Err("Telemetry needs Fairview built with the network feature".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review::{OperatorAnnotation, ReviewDecision};
+    use crate::test_support::report;
+    use crate::SuspiciousProcess;
+    use std::time::SystemTime;
+
+    #[test]
+    fn test_summary_contains_no_raw_identifiers() {
+        let mut breach = report(1, 0.9, true);
+        breach.suspicious_processes.push(SuspiciousProcess {
+            pid: 4242,
+            name: "interview-ai.exe".to_string(),
+            path: "C:\\Users\\jdoe\\AppData\\interview-ai.exe".to_string(),
+            risk_score: 0.9,
+            reasons: vec![ReasonCode::SuspiciousName.description().to_string()],
+            reason_codes: vec![ReasonCode::SuspiciousName, ReasonCode::ScreenCapture],
+            started_during_interview: true,
+            is_whitelisted: false,
+            drive_type: Default::default(),
+        });
+        breach.whitelist_profile = Some("whiteboard".to_string());
+        breach.operator_annotation = Some(OperatorAnnotation {
+            decision: ReviewDecision::Confirmed,
+            note: Some("Candidate jdoe alt-tabbed".to_string()),
+            annotated_at: SystemTime::now(),
+        });
+
+        let mut verdict = SessionVerdict::new();
+        verdict.record(&breach);
+        verdict.record(&report(2, 0.1, false));
+
+        let summary = TelemetrySummary::from_verdict(&verdict);
+        let json = serde_json::to_string(&summary).unwrap();
+
+        for identifier in ["interview-ai", "jdoe", "AppData", "4242", "whiteboard", "alt-tabbed"] {
+            assert!(!json.contains(identifier), "telemetry leaked {}: {}", identifier, json);
+        }
+        assert!(json.contains("\"suspicious_name\":1"));
+        assert!(json.contains("\"peak_severity\":\"critical\""));
+        assert!(json.contains("\"total_scans\":2"));
+    }
+
+    #[tokio::test]
+    async fn test_send_is_noop_when_disabled() {
+        let config = TelemetryConfig {
+            enabled: false,
+            endpoint: Some("http://127.0.0.1:9/unreachable".to_string()),
+            timeout_seconds: 1,
+        };
+        let summary = TelemetrySummary::from_verdict(&SessionVerdict::new());
+        assert!(send(&config, &summary).await.is_ok());
+    }
+}
\ No newline at end of file
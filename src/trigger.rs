@@ -0,0 +1,496 @@
+use crate::config::ScanConfig;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// A system change worth scanning for straight away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemEvent {
+    ProcessStarted,
+    DisplayChanged,
+}
+
+impl SystemEvent {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SystemEvent::ProcessStarted => "process start",
+            SystemEvent::DisplayChanged => "display change",
+        }
+    }
+}
+
+/// A platform facility that reports system events as they happen.
+pub trait EventSource {
+    fn name(&self) -> &'static str;
+
+    /// Starts delivering events from a background thread. Fails when the
+    /// facility is missing or Fairview lacks the rights to use it.
+    fn start(&mut self, events: UnboundedSender<SystemEvent>) -> Result<(), String>;
+
+    /// Releases anything that outlives the process, such as a trace session.
+    fn stop(&mut self) {}
+}
+
+/// The sources available on this platform.
+pub fn platform_sources() -> Vec<Box<dyn EventSource>> {
+    #[allow(unused_mut)]
+    let mut sources: Vec<Box<dyn EventSource>> = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    {
+        sources.push(Box::new(windows_events::ProcessStartTrace));
+        sources.push(Box::new(windows_events::DisplayWatcher));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        sources.push(Box::new(linux_events::ProcConnector));
+    }
+
+    sources
+}
+
+/// Event sources that started, and the channel they report on. Dropping it
+/// stops the sources.
+pub struct EventTriggers {
+    pub events: UnboundedReceiver<SystemEvent>,
+    sources: Vec<Box<dyn EventSource>>,
+}
+
+impl Drop for EventTriggers {
+    fn drop(&mut self) {
+        for source in &mut self.sources {
+            source.stop();
+        }
+    }
+}
+
+/// Starts every source that can run here. Returns None when none could, in
+/// which case the caller should fall back to interval scans.
+pub fn start_sources(sources: Vec<Box<dyn EventSource>>) -> Option<EventTriggers> {
+    let (sender, events) = mpsc::unbounded_channel();
+    let mut started = Vec::new();
+
+    for mut source in sources {
+        match source.start(sender.clone()) {
+            Ok(()) => {
+                println!("[+] Scanning on {} events", source.name());
+                started.push(source);
+            }
+            Err(e) => println!("[!] {} events unavailable: {}", source.name(), e),
+        }
+    }
+
+    if started.is_empty() {
+        None
+    } else {
+        Some(EventTriggers { events, sources: started })
+    }
+}
+
+/// Why the next scan is running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trigger {
+    Keepalive,
+    Events(Vec<SystemEvent>),
+}
+
+/// Decides when the next event-driven scan is due. Events arriving in a burst
+/// are coalesced into one scan, held back until `min_sleep_seconds` after the
+/// last scan finished; without events a scan still runs every
+/// `keepalive_seconds`.
+pub struct TriggerDispatcher {
+    keepalive: Duration,
+    min_gap: Duration,
+    last_scan: Instant,
+    pending: Vec<SystemEvent>,
+}
+
+impl TriggerDispatcher {
+    pub fn new(config: &ScanConfig, now: Instant) -> Self {
+        Self {
+            keepalive: Duration::from_secs(config.keepalive_seconds),
+            min_gap: Duration::from_secs(config.min_sleep_seconds),
+            last_scan: now,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, event: SystemEvent) {
+        if !self.pending.contains(&event) {
+            self.pending.push(event);
+        }
+    }
+
+    pub fn scan_finished(&mut self, now: Instant) {
+        self.last_scan = now;
+    }
+
+    pub fn next_scan_at(&self) -> Instant {
+        if self.pending.is_empty() {
+            self.last_scan + self.keepalive
+        } else {
+            self.last_scan + self.min_gap
+        }
+    }
+
+    /// Takes the reason for the scan about to run.
+    pub fn take(&mut self) -> Trigger {
+        if self.pending.is_empty() {
+            Trigger::Keepalive
+        } else {
+            Trigger::Events(std::mem::take(&mut self.pending))
+        }
+    }
+}
+
+/// Process starts from the kernel's proc connector. Joining its multicast
+/// group needs CAP_NET_ADMIN, so this only starts when run as root.
+#[cfg(target_os = "linux")]
+mod linux_events {
+    use super::{EventSource, SystemEvent};
+    use std::io;
+    use tokio::sync::mpsc::UnboundedSender;
+
+    const NETLINK_CONNECTOR: libc::c_int = 11;
+    const CN_IDX_PROC: u32 = 1;
+    const CN_VAL_PROC: u32 = 1;
+    const PROC_CN_MCAST_LISTEN: u32 = 1;
+    const PROC_EVENT_EXEC: u32 = 0x2;
+
+    const NLMSG_HEADER_LEN: usize = 16;
+    const CN_MSG_LEN: usize = 20;
+
+    pub struct ProcConnector;
+
+    impl EventSource for ProcConnector {
+        fn name(&self) -> &'static str {
+            "netlink process"
+        }
+
+        fn start(&mut self, events: UnboundedSender<SystemEvent>) -> Result<(), String> {
+            let socket = open_socket().map_err(|e| format!("proc connector: {}", e))?;
+            std::thread::spawn(move || {
+                receive(socket, &events);
+                unsafe { libc::close(socket) };
+            });
+            Ok(())
+        }
+    }
+
+    fn open_socket() -> io::Result<libc::c_int> {
+        unsafe {
+            let socket = libc::socket(libc::AF_NETLINK, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, NETLINK_CONNECTOR);
+            if socket < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut address: libc::sockaddr_nl = std::mem::zeroed();
+            address.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            address.nl_groups = CN_IDX_PROC;
+            let bound = libc::bind(
+                socket,
+                &address as *const libc::sockaddr_nl as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            );
+
+            let listen = listen_message();
+            if bound < 0 || libc::send(socket, listen.as_ptr() as *const libc::c_void, listen.len(), 0) < 0 {
+                let error = io::Error::last_os_error();
+                libc::close(socket);
+                return Err(error);
+            }
+            Ok(socket)
+        }
+    }
+
+    /// nlmsghdr + cn_msg + the PROC_CN_MCAST_LISTEN operation.
+    fn listen_message() -> Vec<u8> {
+        let total = NLMSG_HEADER_LEN + CN_MSG_LEN + 4;
+        let mut message = Vec::with_capacity(total);
+        message.extend_from_slice(&(total as u32).to_ne_bytes());
+        message.extend_from_slice(&(libc::NLMSG_DONE as u16).to_ne_bytes());
+        message.extend_from_slice(&0u16.to_ne_bytes());
+        message.extend_from_slice(&0u32.to_ne_bytes());
+        message.extend_from_slice(&std::process::id().to_ne_bytes());
+        message.extend_from_slice(&CN_IDX_PROC.to_ne_bytes());
+        message.extend_from_slice(&CN_VAL_PROC.to_ne_bytes());
+        message.extend_from_slice(&0u32.to_ne_bytes());
+        message.extend_from_slice(&0u32.to_ne_bytes());
+        message.extend_from_slice(&4u16.to_ne_bytes());
+        message.extend_from_slice(&0u16.to_ne_bytes());
+        message.extend_from_slice(&PROC_CN_MCAST_LISTEN.to_ne_bytes());
+        message
+    }
+
+    fn receive(socket: libc::c_int, events: &UnboundedSender<SystemEvent>) {
+        let mut buffer = [0u8; 4096];
+        loop {
+            let read = unsafe { libc::recv(socket, buffer.as_mut_ptr() as *mut libc::c_void, buffer.len(), 0) };
+            if read < 0 {
+                if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return;
+            }
+
+            // The proc_event payload starts with its `what` field.
+            let what = NLMSG_HEADER_LEN + CN_MSG_LEN;
+            if (read as usize) < what + 4 {
+                continue;
+            }
+            let kind = u32::from_ne_bytes([buffer[what], buffer[what + 1], buffer[what + 2], buffer[what + 3]]);
+            if kind == PROC_EVENT_EXEC && events.send(SystemEvent::ProcessStarted).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Process starts from an ETW session on the Microsoft-Windows-Kernel-Process
+/// provider (needs administrator rights), and display changes from
+/// `WM_DISPLAYCHANGE` sent to a hidden window.
+#[cfg(target_os = "windows")]
+mod windows_events {
+    use super::{EventSource, SystemEvent};
+    use std::sync::OnceLock;
+    use tokio::sync::mpsc::UnboundedSender;
+    use windows::core::{GUID, HSTRING, PCWSTR, PWSTR};
+    use windows::Win32::Foundation::*;
+    use windows::Win32::System::Diagnostics::Etw::*;
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    const SESSION_NAME: &str = "Fairview Process Trace";
+    const KERNEL_PROCESS_PROVIDER: GUID = GUID::from_u128(0x22fb2cd6_0e7b_422b_a0c7_2fad1fd0e716);
+    const WINEVENT_KEYWORD_PROCESS: u64 = 0x10;
+    const PROCESS_START_EVENT_ID: u16 = 1;
+
+    static PROCESS_EVENTS: OnceLock<UnboundedSender<SystemEvent>> = OnceLock::new();
+    static DISPLAY_EVENTS: OnceLock<UnboundedSender<SystemEvent>> = OnceLock::new();
+
+    pub struct ProcessStartTrace;
+
+    impl EventSource for ProcessStartTrace {
+        fn name(&self) -> &'static str {
+            "ETW process"
+        }
+
+        fn start(&mut self, events: UnboundedSender<SystemEvent>) -> Result<(), String> {
+            let _ = PROCESS_EVENTS.set(events);
+
+            unsafe {
+                // A session left behind by a run that didn't shut down cleanly
+                // would make StartTraceW fail.
+                control_session(EVENT_TRACE_CONTROL_STOP);
+
+                let mut properties = session_properties();
+                let mut session = CONTROLTRACE_HANDLE::default();
+                StartTraceW(&mut session, &HSTRING::from(SESSION_NAME), properties.as_mut_ptr() as *mut _)
+                    .map_err(|e| format!("could not start trace session: {}", e))?;
+
+                if let Err(e) = EnableTraceEx2(
+                    session,
+                    &KERNEL_PROCESS_PROVIDER,
+                    EVENT_CONTROL_CODE_ENABLE_PROVIDER.0,
+                    TRACE_LEVEL_INFORMATION as u8,
+                    WINEVENT_KEYWORD_PROCESS,
+                    0,
+                    0,
+                    None,
+                ) {
+                    control_session(EVENT_TRACE_CONTROL_STOP);
+                    return Err(format!("could not enable the kernel process provider: {}", e));
+                }
+
+                let mut logger_name: Vec<u16> = SESSION_NAME.encode_utf16().chain(Some(0)).collect();
+                let mut logfile = EVENT_TRACE_LOGFILEW {
+                    LoggerName: PWSTR(logger_name.as_mut_ptr()),
+                    ..Default::default()
+                };
+                logfile.Anonymous1.ProcessTraceMode = PROCESS_TRACE_MODE_REAL_TIME | PROCESS_TRACE_MODE_EVENT_RECORD;
+                logfile.Anonymous2.EventRecordCallback = Some(on_process_event);
+
+                let trace = OpenTraceW(&mut logfile);
+                if trace.Value == u64::MAX {
+                    control_session(EVENT_TRACE_CONTROL_STOP);
+                    return Err(format!("could not open trace session: {}", windows::core::Error::from_win32()));
+                }
+
+                std::thread::spawn(move || {
+                    let _ = ProcessTrace(&[trace], None, None);
+                    let _ = CloseTrace(trace);
+                });
+            }
+            Ok(())
+        }
+
+        fn stop(&mut self) {
+            unsafe { control_session(EVENT_TRACE_CONTROL_STOP) };
+        }
+    }
+
+    /// EVENT_TRACE_PROPERTIES followed by room for the session name, as
+    /// StartTraceW and ControlTraceW expect.
+    fn session_properties() -> Vec<u64> {
+        let header = std::mem::size_of::<EVENT_TRACE_PROPERTIES>();
+        let size = header + (SESSION_NAME.len() + 1) * 2;
+        let mut buffer = vec![0u64; size / 8 + 1];
+        let properties = buffer.as_mut_ptr() as *mut EVENT_TRACE_PROPERTIES;
+        unsafe {
+            (*properties).Wnode.BufferSize = size as u32;
+            (*properties).Wnode.Flags = WNODE_FLAG_TRACED_GUID;
+            (*properties).Wnode.ClientContext = 1;
+            (*properties).LogFileMode = EVENT_TRACE_REAL_TIME_MODE;
+            (*properties).LoggerNameOffset = header as u32;
+        }
+        buffer
+    }
+
+    unsafe fn control_session(code: EVENT_TRACE_CONTROL) {
+        let mut properties = session_properties();
+        let _ = ControlTraceW(
+            CONTROLTRACE_HANDLE::default(),
+            &HSTRING::from(SESSION_NAME),
+            properties.as_mut_ptr() as *mut _,
+            code,
+        );
+    }
+
+    unsafe extern "system" fn on_process_event(record: *mut EVENT_RECORD) {
+        if (*record).EventHeader.EventDescriptor.Id == PROCESS_START_EVENT_ID {
+            if let Some(events) = PROCESS_EVENTS.get() {
+                let _ = events.send(SystemEvent::ProcessStarted);
+            }
+        }
+    }
+
+    pub struct DisplayWatcher;
+
+    impl EventSource for DisplayWatcher {
+        fn name(&self) -> &'static str {
+            "display change"
+        }
+
+        fn start(&mut self, events: UnboundedSender<SystemEvent>) -> Result<(), String> {
+            let _ = DISPLAY_EVENTS.set(events);
+            let (ready, created) = std::sync::mpsc::sync_channel(1);
+
+            // The window has to be created on the thread that pumps its messages.
+            std::thread::spawn(move || unsafe {
+                let class_name = HSTRING::from("FairviewDisplayWatcher");
+                let class = WNDCLASSW {
+                    lpfnWndProc: Some(display_window_proc),
+                    lpszClassName: PCWSTR(class_name.as_ptr()),
+                    ..Default::default()
+                };
+                RegisterClassW(&class);
+
+                // Top-level but never shown: message-only windows don't get
+                // broadcasts like WM_DISPLAYCHANGE.
+                let window = CreateWindowExW(
+                    WINDOW_EX_STYLE::default(),
+                    &class_name,
+                    &class_name,
+                    WINDOW_STYLE::default(),
+                    0,
+                    0,
+                    0,
+                    0,
+                    None,
+                    None,
+                    None,
+                    None,
+                );
+                if window.0 == 0 {
+                    let _ = ready.send(Err(windows::core::Error::from_win32().to_string()));
+                    return;
+                }
+                let _ = ready.send(Ok(()));
+
+                let mut message = MSG::default();
+                while GetMessageW(&mut message, None, 0, 0).as_bool() {
+                    let _ = TranslateMessage(&message);
+                    DispatchMessageW(&message);
+                }
+            });
+
+            created
+                .recv()
+                .map_err(|_| "display watcher thread exited".to_string())?
+                .map_err(|e| format!("could not create window: {}", e))
+        }
+    }
+
+    unsafe extern "system" fn display_window_proc(window: HWND, message: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if message == WM_DISPLAYCHANGE {
+            if let Some(events) = DISPLAY_EVENTS.get() {
+                let _ = events.send(SystemEvent::DisplayChanged);
+            }
+        }
+        DefWindowProcW(window, message, wparam, lparam)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    /// Sends a scripted list of events when started, or fails to start.
+    struct MockEventSource {
+        script: Option<Vec<SystemEvent>>,
+    }
+
+    impl EventSource for MockEventSource {
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+
+        fn start(&mut self, events: UnboundedSender<SystemEvent>) -> Result<(), String> {
+            let script = self.script.take().ok_or_else(|| "not supported here".to_string())?;
+            for event in script {
+                let _ = events.send(event);
+            }
+            Ok(())
+        }
+    }
+
+    fn dispatcher(now: Instant) -> TriggerDispatcher {
+        let mut config = Config::default();
+        config.scan.min_sleep_seconds = 5;
+        config.scan.keepalive_seconds = 300;
+        TriggerDispatcher::new(&config.scan, now)
+    }
+
+    #[test]
+    fn test_no_available_source_falls_back_to_interval() {
+        assert!(start_sources(vec![Box::new(MockEventSource { script: None })]).is_none());
+    }
+
+    #[test]
+    fn test_event_bursts_trigger_one_scan() {
+        let script = vec![SystemEvent::ProcessStarted, SystemEvent::DisplayChanged, SystemEvent::ProcessStarted];
+        let sources: Vec<Box<dyn EventSource>> = vec![
+            Box::new(MockEventSource { script: None }),
+            Box::new(MockEventSource { script: Some(script) }),
+        ];
+        let mut triggers = start_sources(sources).expect("one source started");
+
+        let start = Instant::now();
+        let mut dispatcher = dispatcher(start);
+        assert_eq!(dispatcher.next_scan_at(), start + Duration::from_secs(300));
+
+        while let Ok(event) = triggers.events.try_recv() {
+            dispatcher.record(event);
+        }
+        assert_eq!(dispatcher.next_scan_at(), start + Duration::from_secs(5));
+        assert_eq!(
+            dispatcher.take(),
+            Trigger::Events(vec![SystemEvent::ProcessStarted, SystemEvent::DisplayChanged])
+        );
+
+        let finished = start + Duration::from_secs(7);
+        dispatcher.scan_finished(finished);
+        assert_eq!(dispatcher.next_scan_at(), finished + Duration::from_secs(300));
+        assert_eq!(dispatcher.take(), Trigger::Keepalive);
+    }
+}
\ No newline at end of file
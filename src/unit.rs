@@ -0,0 +1,242 @@
+use std::path::{Path, PathBuf};
+
+const SYSTEMD_DESCRIPTION: &str = "Fairview Interview Monitor";
+const LAUNCHD_LABEL: &str = "com.fairview.agent";
+const LOG_FILE: &str = "fairview.log";
+
+/// Everything a generated unit needs to start Fairview unattended. Paths should
+/// be absolute since neither systemd nor launchd start in the caller's directory.
+#[derive(Debug, Clone)]
+pub struct UnitOptions {
+    pub executable: PathBuf,
+    pub config_path: PathBuf,
+    pub working_dir: PathBuf,
+    pub log_path: PathBuf,
+}
+
+impl UnitOptions {
+    /// Resolves `config_path` against the current directory and keeps reports and
+    /// the log next to the config file.
+    pub fn for_config(config_path: &str) -> Result<Self, String> {
+        let executable = std::env::current_exe()
+            .map_err(|e| format!("Failed to locate fairview executable: {}", e))?;
+        let current_dir = std::env::current_dir()
+            .map_err(|e| format!("Failed to read current directory: {}", e))?;
+
+        let config_path = current_dir.join(config_path);
+        let working_dir = config_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or(current_dir);
+        let log_path = working_dir.join(LOG_FILE);
+
+        Ok(Self {
+            executable,
+            config_path,
+            working_dir,
+            log_path,
+        })
+    }
+
+    fn arguments(&self) -> Vec<String> {
+        vec![
+            self.executable.display().to_string(),
+            "--quiet".to_string(),
+            "--config".to_string(),
+            self.config_path.display().to_string(),
+        ]
+    }
+}
+
+pub fn systemd_unit(options: &UnitOptions) -> String {
+    let exec_start = options
+        .arguments()
+        .iter()
+        .map(|arg| quote_systemd(arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let log = escape_specifiers(&options.log_path.display().to_string());
+
+    format!(
+        "[Unit]\n\
+         Description={}\n\
+         After=network.target\n\
+         \n\
+         [Service]\n\
+         Type=simple\n\
+         ExecStart={}\n\
+         WorkingDirectory={}\n\
+         Restart=on-failure\n\
+         RestartSec=5\n\
+         StandardInput=null\n\
+         StandardOutput=append:{}\n\
+         StandardError=append:{}\n\
+         \n\
+         [Install]\n\
+         WantedBy=multi-user.target\n",
+        SYSTEMD_DESCRIPTION,
+        exec_start,
+        escape_specifiers(&options.working_dir.display().to_string()),
+        log,
+        log,
+    )
+}
+
+pub fn launchd_plist(options: &UnitOptions) -> String {
+    let arguments: String = options
+        .arguments()
+        .iter()
+        .map(|arg| format!("        <string>{}</string>\n", escape_xml(arg)))
+        .collect();
+    let log = escape_xml(&options.log_path.display().to_string());
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n\
+         \x20   <key>Label</key>\n\
+         \x20   <string>{}</string>\n\
+         \x20   <key>ProgramArguments</key>\n\
+         \x20   <array>\n\
+         {}\
+         \x20   </array>\n\
+         \x20   <key>WorkingDirectory</key>\n\
+         \x20   <string>{}</string>\n\
+         \x20   <key>RunAtLoad</key>\n\
+         \x20   <true/>\n\
+         \x20   <key>KeepAlive</key>\n\
+         \x20   <dict>\n\
+         \x20       <key>SuccessfulExit</key>\n\
+         \x20       <false/>\n\
+         \x20   </dict>\n\
+         \x20   <key>StandardOutPath</key>\n\
+         \x20   <string>{}</string>\n\
+         \x20   <key>StandardErrorPath</key>\n\
+         \x20   <string>{}</string>\n\
+         </dict>\n\
+         </plist>\n",
+        LAUNCHD_LABEL,
+        arguments,
+        escape_xml(&options.working_dir.display().to_string()),
+        log,
+        log,
+    )
+}
+
+/// Picks the unit format for the host: a systemd unit on Linux, a launchd
+/// plist on macOS.
+pub fn for_current_platform(options: &UnitOptions) -> Result<String, String> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(systemd_unit(options))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Ok(launchd_plist(options))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let _ = options;
+        Err("generate-unit supports Linux and macOS; use install-service on Windows".to_string())
+    }
+}
+
+/// Hint printed after writing a unit. On macOS the plist is a per-user
+/// agent, since window, overlay and privacy checks need the candidate's login
+/// session, which a system daemon doesn't run in. The systemd unit needs root.
+pub fn install_hint(unit_path: &str) -> String {
+    if cfg!(target_os = "macos") {
+        format!(
+            "cp {} ~/Library/LaunchAgents/{}.plist && launchctl load ~/Library/LaunchAgents/{}.plist",
+            unit_path, LAUNCHD_LABEL, LAUNCHD_LABEL
+        )
+    } else {
+        format!(
+            "sudo cp {} /etc/systemd/system/fairview.service && sudo systemctl enable --now fairview",
+            unit_path
+        )
+    }
+}
+
+fn quote_systemd(arg: &str) -> String {
+    let arg = escape_specifiers(arg);
+    if arg.contains(char::is_whitespace) || arg.contains('"') {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        arg
+    }
+}
+
+/// Doubles `%` so systemd doesn't expand it as a specifier such as `%h`.
+fn escape_specifiers(value: &str) -> String {
+    value.replace('%', "%%")
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> UnitOptions {
+        UnitOptions {
+            executable: PathBuf::from("/opt/fairview/fairview"),
+            config_path: PathBuf::from("/etc/fairview/fairview_config.toml"),
+            working_dir: PathBuf::from("/etc/fairview"),
+            log_path: PathBuf::from("/etc/fairview/fairview.log"),
+        }
+    }
+
+    #[test]
+    fn test_systemd_unit_runs_quiet_with_config() {
+        let unit = systemd_unit(&options());
+        assert!(unit.contains(
+            "ExecStart=/opt/fairview/fairview --quiet --config /etc/fairview/fairview_config.toml\n"
+        ));
+        assert!(unit.contains("Restart=on-failure\n"));
+        assert!(unit.contains("StandardOutput=append:/etc/fairview/fairview.log\n"));
+    }
+
+    #[test]
+    fn test_systemd_unit_quotes_paths_with_spaces() {
+        let mut options = options();
+        options.config_path = PathBuf::from("/srv/my interviews/fairview.toml");
+        assert!(systemd_unit(&options).contains("--config \"/srv/my interviews/fairview.toml\""));
+    }
+
+    #[test]
+    fn test_systemd_unit_escapes_percent_signs() {
+        let mut options = options();
+        options.config_path = PathBuf::from("/srv/100%h/fairview.toml");
+        options.working_dir = PathBuf::from("/srv/100%h");
+        options.log_path = PathBuf::from("/srv/100%h/fairview.log");
+
+        let unit = systemd_unit(&options);
+        assert!(unit.contains("--config /srv/100%%h/fairview.toml\n"));
+        assert!(unit.contains("WorkingDirectory=/srv/100%%h\n"));
+        assert!(unit.contains("StandardError=append:/srv/100%%h/fairview.log\n"));
+    }
+
+    #[test]
+    fn test_launchd_plist_program_arguments() {
+        let plist = launchd_plist(&options());
+        assert!(plist.contains(
+            "    <key>ProgramArguments</key>\n    <array>\n\
+             \x20       <string>/opt/fairview/fairview</string>\n\
+             \x20       <string>--quiet</string>\n\
+             \x20       <string>--config</string>\n\
+             \x20       <string>/etc/fairview/fairview_config.toml</string>\n\
+             \x20   </array>\n"
+        ));
+        assert!(plist.contains("<key>StandardOutPath</key>\n    <string>/etc/fairview/fairview.log</string>"));
+    }
+}
\ No newline at end of file
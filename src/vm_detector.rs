@@ -1,22 +1,112 @@
+use crate::platform::{PlatformProvider, SystemPlatform};
 use sysinfo::{System, Networks};
+use std::path::Path;
 use raw_cpuid::CpuId;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-pub struct VmDetector;
+/// Smoothed confidence above which the machine is reported as a VM.
+const VM_CONFIDENCE_THRESHOLD: f64 = 0.7;
 
-#[derive(Debug, Clone, Serialize)]
+/// Guest tools only run inside a VM, so on their own they are enough to
+/// cross the threshold.
+const GUEST_TOOLS_CONFIDENCE: f64 = 0.8;
+const PARAVIRTUAL_DRIVER_CONFIDENCE: f64 = 0.5;
+
+/// Balloon and paravirtualized device drivers, by file or module name,
+/// with the hypervisor they belong to. Hyper-V's `vmbus` is left out since
+/// hosts load it too.
+const PARAVIRTUAL_DRIVERS: &[(&str, &str)] = &[
+    ("balloon", "KVM/QEMU"), ("virtio_balloon", "KVM/QEMU"), ("vioscsi", "KVM/QEMU"),
+    ("viostor", "KVM/QEMU"), ("netkvm", "KVM/QEMU"),
+    ("vmmemctl", "VMware"), ("vmw_balloon", "VMware"), ("vmhgfs", "VMware"),
+    ("vboxguest", "VirtualBox"), ("vboxsf", "VirtualBox"),
+    ("xen_balloon", "Xen"), ("xenbus", "Xen"), ("hv_balloon", "Hyper-V"),
+    ("prl_fs", "Parallels"),
+];
+
+/// Where installed Windows drivers live, checked for `PARAVIRTUAL_DRIVERS`.
+const WINDOWS_DRIVER_DIR: &str = "C:\\Windows\\System32\\drivers";
+
+/// Keeps a moving average of the VM confidence so that timing-based or
+/// ambiguous Hyper-V signals in a single scan don't flip the verdict.
+pub struct VmDetector {
+    platform: Arc<dyn PlatformProvider>,
+    smoothing_factor: f64,
+    smoothed_confidence: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct VmCheckResult {
     pub is_vm: bool,
     pub reasons: Vec<String>,
+    /// Moving average across scans; this is what `is_vm` is based on.
     pub confidence_score: f64,
+    /// This scan's confidence before smoothing.
+    pub raw_confidence_score: f64,
+}
+
+impl Default for VmDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Blends `sample` into the running average. The first scan seeds the average,
+/// so a steady signal such as a VM MAC address is reported straight away and
+/// only a later spike has to repeat before it is.
+pub fn smooth_confidence(previous: Option<f64>, sample: f64, smoothing_factor: f64) -> f64 {
+    let previous = previous.unwrap_or(sample);
+    smoothing_factor * sample + (1.0 - smoothing_factor) * previous
 }
 
 impl VmDetector {
     pub fn new() -> Self {
-        Self
+        Self::with_platform(Arc::new(SystemPlatform))
     }
 
-    pub fn detect(&self) -> VmCheckResult {
+    pub fn with_platform(platform: Arc<dyn PlatformProvider>) -> Self {
+        Self {
+            platform,
+            smoothing_factor: 1.0,
+            smoothed_confidence: None,
+        }
+    }
+
+    /// Guest-tools processes, by executable name without extension, with the
+    /// hypervisor they ship with. They keep running in a nested VM whose
+    /// CPUID and MAC addresses have been spoofed.
+    pub const GUEST_TOOL_PROCESSES: &'static [(&'static str, &'static str)] = &[
+        ("vmtoolsd", "VMware"), ("vmwaretray", "VMware"), ("vmwareuser", "VMware"),
+        ("vboxservice", "VirtualBox"), ("vboxtray", "VirtualBox"), ("vboxclient", "VirtualBox"),
+        ("qemu-ga", "QEMU"), ("spice-vdagent", "QEMU/SPICE"),
+        ("prl_tools", "Parallels"), ("prl_tools_service", "Parallels"), ("prl_cc", "Parallels"),
+        ("xenservice", "Xen"), ("xe-daemon", "Xen"),
+        ("hv_kvp_daemon", "Hyper-V"), ("hv_vss_daemon", "Hyper-V"),
+    ];
+
+    pub fn set_smoothing_factor(&mut self, smoothing_factor: f64) {
+        self.smoothing_factor = smoothing_factor;
+    }
+
+    /// Samples the VM signals and folds them into the smoothed verdict.
+    pub fn detect(&mut self) -> VmCheckResult {
+        let sample = self.platform.vm_signals();
+        self.apply_smoothing(sample)
+    }
+
+    fn apply_smoothing(&mut self, mut result: VmCheckResult) -> VmCheckResult {
+        let smoothed = smooth_confidence(self.smoothed_confidence, result.raw_confidence_score, self.smoothing_factor);
+        self.smoothed_confidence = Some(smoothed);
+        result.confidence_score = smoothed;
+        result.is_vm = smoothed > VM_CONFIDENCE_THRESHOLD;
+        result
+    }
+
+}
+
+impl SystemPlatform {
+    pub(crate) fn current_vm_signals(&self) -> VmCheckResult {
         let mut reasons = Vec::new();
         let mut confidence: f64 = 0.0;
 
@@ -67,12 +157,18 @@ impl VmDetector {
             reasons.extend(mac_reasons);
         }
 
-        let is_vm = confidence > 0.7; 
+        let process_names = crate::snapshot::shared().read(process_names);
+        let (guest_confidence, guest_reasons) = guest_evidence(&process_names, &installed_drivers());
+        confidence += guest_confidence;
+        reasons.extend(guest_reasons);
+
+        let confidence = confidence.min(1.0);
 
         VmCheckResult {
-            is_vm,
+            is_vm: confidence > VM_CONFIDENCE_THRESHOLD,
             reasons,
-            confidence_score: confidence.min(1.0),
+            confidence_score: confidence,
+            raw_confidence_score: confidence,
         }
     }
 
@@ -108,4 +204,140 @@ impl VmDetector {
         }
         detected
     }
+}
+
+fn process_names(system: &System) -> Vec<String> {
+    system.processes().values().map(|p| p.name().to_string()).collect()
+}
+
+/// Confidence and reasons from guest-tools processes and paravirtualized
+/// drivers, the signs of a guest that survive spoofed CPUID and MAC
+/// addresses.
+fn guest_evidence(process_names: &[String], drivers: &[String]) -> (f64, Vec<String>) {
+    let mut confidence = 0.0;
+    let mut reasons = Vec::new();
+
+    let guest_tools: Vec<String> = process_names
+        .iter()
+        .filter_map(|name| {
+            let stem = file_stem(name);
+            VmDetector::GUEST_TOOL_PROCESSES
+                .iter()
+                .find(|(tool, _)| *tool == stem)
+                .map(|(_, vendor)| format!("{} ({})", name, vendor))
+        })
+        .collect();
+    if !guest_tools.is_empty() {
+        confidence += GUEST_TOOLS_CONFIDENCE;
+        reasons.push(format!("Guest tools running: {}", guest_tools.join(", ")));
+    }
+
+    let paravirtual: Vec<String> = drivers
+        .iter()
+        .filter_map(|driver| {
+            let stem = file_stem(driver);
+            PARAVIRTUAL_DRIVERS
+                .iter()
+                .find(|(known, _)| *known == stem)
+                .map(|(_, vendor)| format!("{} ({})", driver, vendor))
+        })
+        .collect();
+    if !paravirtual.is_empty() {
+        confidence += PARAVIRTUAL_DRIVER_CONFIDENCE;
+        reasons.push(format!("Paravirtualized drivers present: {}", paravirtual.join(", ")));
+    }
+
+    (confidence, reasons)
+}
+
+fn file_stem(name: &str) -> String {
+    let name = name.to_lowercase();
+    match name.rsplit_once('.') {
+        Some((stem, "exe" | "sys" | "ko")) => stem.to_string(),
+        _ => name,
+    }
+}
+
+/// Loaded kernel modules on Linux, installed drivers on Windows.
+fn installed_drivers() -> Vec<String> {
+    if cfg!(target_os = "windows") {
+        return std::fs::read_dir(Path::new(WINDOWS_DRIVER_DIR))
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+    }
+
+    std::fs::read_to_string("/proc/modules")
+        .map(|modules| {
+            modules
+                .lines()
+                .filter_map(|line| line.split_whitespace().next())
+                .map(|module| module.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(confidence: f64) -> VmCheckResult {
+        VmCheckResult {
+            is_vm: confidence > VM_CONFIDENCE_THRESHOLD,
+            reasons: Vec::new(),
+            confidence_score: confidence,
+            raw_confidence_score: confidence,
+        }
+    }
+
+    #[test]
+    fn test_single_spike_does_not_trip() {
+        let mut detector = VmDetector::new();
+        detector.set_smoothing_factor(0.5);
+
+        assert!(!detector.apply_smoothing(sample(0.1)).is_vm);
+        let spike = detector.apply_smoothing(sample(1.0));
+        assert!(!spike.is_vm);
+        assert_eq!(spike.raw_confidence_score, 1.0);
+        assert!(!detector.apply_smoothing(sample(0.1)).is_vm);
+    }
+
+    #[test]
+    fn test_sustained_signal_trips() {
+        let mut detector = VmDetector::new();
+        detector.set_smoothing_factor(0.5);
+
+        assert!(!detector.apply_smoothing(sample(0.1)).is_vm);
+        assert!(!detector.apply_smoothing(sample(1.0)).is_vm);
+        let third = detector.apply_smoothing(sample(1.0));
+        assert!(third.is_vm);
+        assert!((third.confidence_score - 0.775).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_stable_signal_is_reported_on_first_scan() {
+        let mut detector = VmDetector::new();
+        detector.set_smoothing_factor(0.5);
+
+        let first = detector.apply_smoothing(sample(0.8));
+        assert!(first.is_vm);
+        assert_eq!(first.confidence_score, 0.8);
+    }
+
+    #[test]
+    fn test_guest_tools_raise_confidence_above_threshold() {
+        let processes = vec!["explorer.exe".to_string(), "VBoxService.exe".to_string()];
+        let (confidence, reasons) = guest_evidence(&processes, &[]);
+        assert!(confidence > VM_CONFIDENCE_THRESHOLD);
+        assert_eq!(reasons, vec!["Guest tools running: VBoxService.exe (VirtualBox)".to_string()]);
+
+        let (with_drivers, _) = guest_evidence(&processes, &["vmmemctl.sys".to_string()]);
+        assert!(with_drivers > confidence);
+        assert_eq!(guest_evidence(&["explorer.exe".to_string()], &["vmbus.sys".to_string()]).0, 0.0);
+    }
 }
\ No newline at end of file
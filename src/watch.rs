@@ -0,0 +1,127 @@
+use crate::DetectionReport;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+/// Follows a JSON-lines report file, such as `--stdout-jsonl` redirected
+/// to disk or `rescore --output`, and hands back each report once its line
+/// is complete. When the file shrinks or is replaced, reading starts over
+/// from the top of the new file.
+#[derive(Debug)]
+pub struct ReportTail {
+    path: PathBuf,
+    offset: u64,
+    identity: Option<u64>,
+    partial: Vec<u8>,
+    line_number: usize,
+}
+
+impl ReportTail {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), offset: 0, identity: None, partial: Vec::new(), line_number: 0 }
+    }
+
+    /// Reports appended since the last poll, in file order. A line that
+    /// isn't a report comes back as an error naming it; a missing file
+    /// (between a rotation and the next write, say) yields nothing.
+    pub fn poll(&mut self) -> Vec<Result<DetectionReport, String>> {
+        let Ok(mut file) = File::open(&self.path) else {
+            return Vec::new();
+        };
+        let Ok(metadata) = file.metadata() else {
+            return Vec::new();
+        };
+
+        let identity = file_identity(&metadata);
+        if metadata.len() < self.offset || (self.identity.is_some() && identity != self.identity) {
+            println!("[*] {} was truncated or replaced; reading from the start", self.path.display());
+            self.offset = 0;
+            self.partial.clear();
+            self.line_number = 0;
+        }
+        self.identity = identity;
+
+        let mut appended = Vec::new();
+        if file.seek(SeekFrom::Start(self.offset)).is_err() || file.read_to_end(&mut appended).is_err() {
+            return Vec::new();
+        }
+        self.offset += appended.len() as u64;
+        self.partial.extend_from_slice(&appended);
+
+        let mut reports = Vec::new();
+        while let Some(end) = self.partial.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=end).collect();
+            self.line_number += 1;
+            let line = String::from_utf8_lossy(&line);
+            if line.trim().is_empty() {
+                continue;
+            }
+            reports.push(
+                serde_json::from_str(&line)
+                    .map_err(|e| format!("Line {} of {} is not a report: {}", self.line_number, self.path.display(), e)),
+            );
+        }
+        reports
+    }
+}
+
+/// Something that changes when the path is pointed at a different file:
+/// the inode on Unix, the creation time on Windows.
+#[cfg(unix)]
+fn file_identity(metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ino())
+}
+
+#[cfg(windows)]
+fn file_identity(metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::windows::fs::MetadataExt;
+    Some(metadata.creation_time())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn file_identity(_metadata: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alert::format_alert_line;
+    use crate::jsonl::write_line;
+    use crate::test_support::report;
+    use std::io::Write;
+
+    fn rendered(tail: &mut ReportTail) -> Vec<String> {
+        tail.poll().into_iter().map(|report| format_alert_line(&report.unwrap(), 160)).collect()
+    }
+
+    #[test]
+    fn test_appended_reports_are_rendered_in_order() {
+        let path = std::env::temp_dir().join(format!("fairview_watch_test_{}.jsonl", std::process::id()));
+        let mut file = File::create(&path).unwrap();
+        write_line(&mut file, &report(1, 0.05, false)).unwrap();
+        write_line(&mut file, &report(2, 0.10, false)).unwrap();
+
+        let mut tail = ReportTail::new(&path);
+        assert_eq!(rendered(&mut tail), vec![
+            "[LOW] Scan 1: risk 0.05 — no findings",
+            "[LOW] Scan 2: risk 0.10 — no findings",
+        ]);
+
+        // Half a line waits for the rest.
+        let line = serde_json::to_string(&report(3, 0.15, false)).unwrap();
+        let (head, rest) = line.split_at(line.len() / 2);
+        file.write_all(head.as_bytes()).unwrap();
+        assert!(tail.poll().is_empty());
+        writeln!(file, "{}", rest).unwrap();
+        assert_eq!(rendered(&mut tail), vec!["[LOW] Scan 3: risk 0.15 — no findings"]);
+
+        // Truncated and rewritten: start over from the new first line.
+        let mut file = File::create(&path).unwrap();
+        write_line(&mut file, &report(1, 0.20, false)).unwrap();
+        let rendered = rendered(&mut tail);
+        std::fs::remove_file(&path).ok();
+        assert_eq!(rendered, vec!["[LOW] Scan 1: risk 0.20 — no findings"]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,35 @@
+use std::process::{Command, Output};
+
+fn check_config(fixture: &str, extra: &[&str]) -> Output {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), fixture);
+    Command::new(env!("CARGO_BIN_EXE_fairview"))
+        .args(["check-config", "--config", &path])
+        .args(extra)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_valid_config_prints_effective_values() {
+    let output = check_config("valid_config.toml", &["--session-id", "int-2041"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("[*] Override applied: session.session_id = int-2041 (--session-id)"));
+    assert!(stdout.contains("normalized: process 0.350"));
+    assert!(stdout.contains("interval_seconds = 15"));
+    assert!(stdout.contains("session_id = \"int-2041\""));
+    assert!(stdout.contains("[+] Configuration is valid"));
+    assert!(!stdout.contains("Starting scan"));
+}
+
+#[test]
+fn test_invalid_config_fails_without_scanning() {
+    let output = check_config("invalid_config.toml", &[]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(!output.status.success());
+    assert!(stdout.contains("Weights must sum to 1.0, got 1.25"), "{}", stdout);
+    assert!(!stdout.contains("Configuration is valid"));
+    assert!(!stdout.contains("Starting scan"));
+}
\ No newline at end of file
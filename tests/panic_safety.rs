@@ -0,0 +1,184 @@
+use fairview::audio_detector::{CaptureStreams, EndpointState};
+use fairview::config::Config;
+use fairview::focus_detector::ForegroundWindow;
+use fairview::hardware_detector::{ConnectionType, DisplayConfiguration, DisplayInfo, RemoteDesktopStatus};
+use fairview::platform::PlatformProvider;
+use fairview::process_monitor::{ProcessCapabilities, ProcessSource};
+use fairview::system_load::SystemLoad;
+use fairview::vm_detector::VmCheckResult;
+use fairview::{DetectionReport, FairviewDetector, OverlayWindow, Process};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Module {
+    Vm,
+    Process,
+    Overlay,
+    Audio,
+    Hardware,
+}
+
+/// A quiet machine with one suspicious process, where every call that feeds
+/// `panics_in` panics instead of answering.
+struct PanickingPlatform {
+    panics_in: Option<Module>,
+}
+
+impl PanickingPlatform {
+    fn check(&self, module: Module) {
+        if self.panics_in == Some(module) {
+            panic!("{:?} module blew up", module);
+        }
+    }
+}
+
+impl ProcessSource for PanickingPlatform {
+    fn processes(&self) -> Vec<Process> {
+        self.check(Module::Process);
+        vec![Process {
+            pid: 10,
+            name: "interview-ai.exe".to_string(),
+            path: "C:\\Users\\candidate\\interview-ai.exe".to_string(),
+            ..Default::default()
+        }]
+    }
+
+    fn capabilities(&self, _process: &Process) -> ProcessCapabilities {
+        ProcessCapabilities { screen_capture: true, ..Default::default() }
+    }
+}
+
+impl PlatformProvider for PanickingPlatform {
+    fn displays(&self) -> Result<DisplayConfiguration, String> {
+        self.check(Module::Hardware);
+        Ok(DisplayConfiguration {
+            display_count: 1,
+            displays: vec![DisplayInfo {
+                id: "0".to_string(),
+                name: "Built-in".to_string(),
+                width: 1920,
+                height: 1080,
+                is_primary: true,
+                connection_type: ConnectionType::Unknown,
+            }],
+            has_virtual_display: false,
+            has_hdmi_splitter_signature: false,
+        })
+    }
+
+    fn video_capture_devices(&self) -> Result<Vec<String>, String> {
+        self.check(Module::Hardware);
+        Ok(Vec::new())
+    }
+
+    fn remote_desktop(&self) -> RemoteDesktopStatus {
+        RemoteDesktopStatus::default()
+    }
+
+    fn audio_monitoring(&self) -> bool {
+        self.check(Module::Audio);
+        false
+    }
+
+    fn audio_capture_streams(&self) -> Option<CaptureStreams> {
+        None
+    }
+
+    fn audio_endpoints(&self) -> Result<Vec<EndpointState>, String> {
+        Ok(Vec::new())
+    }
+
+    fn overlays(&self) -> Vec<OverlayWindow> {
+        self.check(Module::Overlay);
+        Vec::new()
+    }
+
+    fn foreground_process(&self) -> Option<u32> {
+        None
+    }
+
+    fn foreground_window(&self) -> Option<ForegroundWindow> {
+        None
+    }
+
+    fn cloud_clipboard_sync(&self) -> Option<bool> {
+        None
+    }
+
+    fn vm_signals(&self) -> VmCheckResult {
+        self.check(Module::Vm);
+        VmCheckResult::default()
+    }
+
+    fn system_load(&self) -> Option<SystemLoad> {
+        None
+    }
+
+    fn refresh_snapshot(&self) {}
+}
+
+/// Scans once with the five built-in modules on and only those that shell
+/// out to the OS off.
+fn scan(panics_in: Option<Module>) -> DetectionReport {
+    let mut config = Config::default();
+    config.monitoring.enable_vm_detection = true;
+    config.monitoring.enable_process_monitoring = true;
+    config.monitoring.enable_overlay_monitoring = true;
+    config.monitoring.enable_audio_monitoring = true;
+    config.monitoring.enable_hardware_monitoring = true;
+    config.monitoring.enable_input_device_monitoring = false;
+    config.monitoring.enable_network_monitoring = false;
+    config.monitoring.enable_virtual_desktop_detection = false;
+    config.monitoring.enable_audio_loop_detection = false;
+
+    let platform: Arc<dyn PlatformProvider> = Arc::new(PanickingPlatform { panics_in });
+    let mut detector = FairviewDetector::with_platform(config, platform);
+    let report = detector.scan();
+    serde_json::to_string(&report).expect("report serializes");
+    report
+}
+
+/// The scan survives a panic in `module`, names it in `module_failures`,
+/// and still has the output of every other module.
+fn assert_contained(module: Module, failure: &str) {
+    let report = scan(Some(module));
+
+    assert_eq!(report.module_failures, vec![failure.to_string()]);
+    assert_eq!(report.uncertainty.failed_modules, 1);
+    assert_eq!(report.vm_detection.is_some(), module != Module::Vm);
+    assert_eq!(report.suspicious_processes.len(), usize::from(module != Module::Process));
+    assert_eq!(report.hardware_suspicion.is_some(), module != Module::Hardware);
+}
+
+#[test]
+fn test_clean_scan_has_no_failures() {
+    let report = scan(None);
+    assert!(report.module_failures.is_empty());
+    assert!(report.vm_detection.is_some() && report.hardware_suspicion.is_some());
+    assert_eq!(report.suspicious_processes.len(), 1);
+}
+
+#[test]
+fn test_vm_detection_panic_is_contained() {
+    assert_contained(Module::Vm, "VM detection module failed");
+}
+
+#[test]
+fn test_process_monitoring_panic_is_contained() {
+    assert_contained(Module::Process, "Process monitoring module failed");
+}
+
+#[test]
+fn test_overlay_detection_panic_is_contained() {
+    assert_contained(Module::Overlay, "Overlay detection module failed");
+}
+
+#[test]
+fn test_audio_detection_panic_is_contained() {
+    assert_contained(Module::Audio, "Audio detection module failed");
+}
+
+#[test]
+fn test_hardware_detection_panic_is_contained() {
+    assert_contained(Module::Hardware, "Hardware detection module failed");
+}
\ No newline at end of file
@@ -0,0 +1,56 @@
+use fairview::config::Config;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+
+/// Runs the binary with `--stdout-jsonl` until it has written two reports,
+/// then checks stdout holds nothing but JSON lines and the console output
+/// went to stderr.
+#[test]
+fn test_stdout_jsonl_keeps_diagnostics_on_stderr() {
+    let output_dir = std::env::temp_dir().join(format!("fairview-jsonl-{}", std::process::id()));
+    let mut config = Config::default();
+    config.scan.interval_seconds = 1;
+    config.scan.min_sleep_seconds = 0;
+    config.monitoring.collect_baseline = false;
+    config.output.output_dir = output_dir.display().to_string();
+    let config = toml::to_string(&config).unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fairview"))
+        .args(["--stdout-jsonl", "--config-inline", &config])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut stderr = child.stderr.take().unwrap();
+    // Drain stderr alongside so a chatty scan can't fill the pipe.
+    let diagnostics = std::thread::spawn(move || {
+        let mut text = String::new();
+        stderr.read_to_string(&mut text).unwrap();
+        text
+    });
+
+    let mut lines = Vec::new();
+    while lines.len() < 2 {
+        let mut line = String::new();
+        assert!(stdout.read_line(&mut line).unwrap() > 0, "fairview exited early");
+        lines.push(line);
+    }
+    child.kill().unwrap();
+    child.wait().unwrap();
+    let mut rest = String::new();
+    stdout.read_to_string(&mut rest).unwrap();
+    lines.extend(rest.lines().map(|line| format!("{}\n", line)));
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    for (index, line) in lines.iter().enumerate() {
+        assert!(line.ends_with('\n') && !line.trim_end().contains('\n'));
+        let report: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(report["scan_number"], index as u64 + 1);
+        assert!(report["overall_risk_score"].is_number());
+    }
+    let diagnostics = diagnostics.join().unwrap();
+    assert!(diagnostics.contains("Fairview v0.1.0"));
+    assert!(diagnostics.contains("[*] Starting scan #1"));
+}
\ No newline at end of file